@@ -0,0 +1,186 @@
+//! CODEOWNERS files: finding the one a repository uses and matching paths against it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where GitHub and GitLab look for the file, in order.
+const LOCATIONS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// Ownership rules of a CODEOWNERS file; the last matching rule wins.
+pub struct CodeOwners {
+    /// The file the rules were read from.
+    pub path: PathBuf,
+    rules: Vec<Rule>,
+}
+
+struct Rule {
+    /// Pattern segments, starting with `**` when the pattern may match at any depth.
+    segments: Vec<String>,
+    /// A trailing `/`: matches only directories, that is, everything below them.
+    dir_only: bool,
+    owners: Vec<String>,
+}
+
+impl CodeOwners {
+    /// The first CODEOWNERS file found in `repo`.
+    pub fn load(repo: &Path) -> Option<Self> {
+        LOCATIONS.iter().find_map(|location| {
+            let path = repo.join(location);
+            let text = fs::read_to_string(&path).ok()?;
+            Some(Self {
+                path,
+                rules: text.lines().filter_map(parse_rule).collect(),
+            })
+        })
+    }
+
+    /// Owners of `path` (relative to the repository root); empty when nobody owns it.
+    pub fn owners(&self, path: &str) -> &[String] {
+        let parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                // A pattern naming a directory covers everything below it, except that
+                // `dir/*` stops at the files directly inside, as on GitHub.
+                let first = if rule.segments.last().is_some_and(|s| s == "*") {
+                    parts.len()
+                } else {
+                    1
+                };
+                let last = if rule.dir_only {
+                    parts.len().saturating_sub(1)
+                } else {
+                    parts.len()
+                };
+                (first..=last).any(|end| match_segments(&rule.segments, &parts[..end]))
+            })
+            .map_or(&[], |rule| rule.owners.as_slice())
+    }
+}
+
+fn parse_rule(line: &str) -> Option<Rule> {
+    let line = line.trim();
+    // GitLab `[Section]` headers carry no pattern.
+    if line.is_empty() || line.starts_with(['#', '[', '^']) {
+        return None;
+    }
+    let mut fields = line.split_whitespace();
+    let pattern = fields.next()?;
+    let owners = fields
+        .take_while(|f| !f.starts_with('#'))
+        .map(str::to_string)
+        .collect();
+    let dir_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    // A slash anywhere but the end anchors the pattern to the repository root.
+    let anchored = trimmed.contains('/');
+    let mut segments: Vec<String> = trimmed
+        .trim_start_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+    if segments.is_empty() {
+        // `/` or `*`-style catch-alls.
+        segments.push("**".into());
+    } else if !anchored {
+        segments.insert(0, "**".into());
+    }
+    Some(Rule {
+        segments,
+        dir_only,
+        owners,
+    })
+}
+
+fn match_segments(pattern: &[String], parts: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => parts.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=parts.len()).any(|skip| match_segments(rest, &parts[skip..]))
+        }
+        Some((first, rest)) => parts
+            .split_first()
+            .is_some_and(|(part, tail)| match_glob(first, part) && match_segments(rest, tail)),
+    }
+}
+
+/// `*` and `?` within one path segment.
+fn match_glob(pattern: &str, text: &str) -> bool {
+    let (p, t): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_like_github() {
+        let text = "\
+# comment
+*            @everyone
+*.js         @js
+/docs/       @docs
+docs/*       @docs-top
+apps/**/x    @x  # trailing comment
+/vendor/
+";
+        let codeowners = CodeOwners {
+            path: PathBuf::new(),
+            rules: text.lines().filter_map(parse_rule).collect(),
+        };
+        let cases = [
+            ("README.md", "@everyone"),
+            ("src/app.js", "@js"),
+            ("docs", "@everyone"),
+            ("docs/guide.md", "@docs-top"),
+            ("docs/app.js", "@docs-top"),
+            ("docs/deep/guide.md", "@docs"),
+            ("src/docs/guide.md", "@everyone"),
+            ("apps/x", "@x"),
+            ("apps/a/b/x", "@x"),
+            ("apps/x/file.txt", "@x"),
+            ("apps/y", "@everyone"),
+            ("vendor/lib.js", ""),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(codeowners.owners(path).join(" "), expected, "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn matches_globs_within_a_segment() {
+        let cases = [
+            ("*", "anything", true),
+            ("*", "", true),
+            ("*.js", "app.js", true),
+            ("*.js", "app.jsx", false),
+            ("a?c", "abc", true),
+            ("a?c", "ac", false),
+            ("*test*", "my_test_file", true),
+            ("été*", "été.md", true),
+        ];
+        for (pattern, text, matches) in cases {
+            assert_eq!(match_glob(pattern, text), matches, "{:?} {:?}", pattern, text);
+        }
+    }
+}
@@ -0,0 +1,701 @@
+//! Unified diffs parsed into lines, files and hunks for navigation and display.
+
+use std::ops::Range;
+
+/// What a line of a unified diff is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineKind {
+    /// `diff --git`, `---` and `+++` lines.
+    FileHeader,
+    /// An `@@` line.
+    HunkHeader,
+    /// A `+` line.
+    Added,
+    /// A `-` line.
+    Removed,
+    /// A line both sides have.
+    Context,
+    /// Anything else: `index`, mode changes, commit headers.
+    Meta,
+}
+
+impl LineKind {
+    /// Whether the line was added or removed.
+    pub fn is_change(self) -> bool {
+        matches!(self, LineKind::Added | LineKind::Removed)
+    }
+}
+
+/// A line of the diff with where it sits in the old and new file.
+#[derive(Clone, Debug)]
+pub struct DiffLine {
+    /// What the line is.
+    pub kind: LineKind,
+    /// The line as git printed it, without color codes.
+    pub text: String,
+    /// Byte ranges of `text` that differ from the paired line on the other side.
+    pub emphasis: Vec<Range<usize>>,
+    /// Line number in the old file, for context and removed lines.
+    pub old_no: Option<u32>,
+    /// Line number in the new file, for context and added lines.
+    pub new_no: Option<u32>,
+    /// Index into `ParsedDiff::files` of the file this line belongs to.
+    pub file: Option<usize>,
+    /// Index into `ParsedDiff::hunks` of the enclosing hunk.
+    pub hunk: Option<usize>,
+}
+
+/// An `@@` section of the diff.
+#[derive(Clone, Debug)]
+pub struct Hunk {
+    /// Line index of the `@@` header.
+    pub line: usize,
+    /// Function/struct context git printed after the header (may be empty).
+    pub context: String,
+}
+
+/// One cell of the minimap: what the lines it stands for change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapCell {
+    /// No added or removed lines.
+    Empty,
+    /// Added lines only.
+    Added,
+    /// Removed lines only.
+    Removed,
+    /// Both added and removed lines.
+    Mixed,
+}
+
+/// One row of the side-by-side view, as indices into `ParsedDiff::lines`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitRow {
+    /// Headers and other lines that belong to both sides.
+    Full(usize),
+    /// The old line on the left and the new one on the right; a context line is both.
+    Pair(Option<usize>, Option<usize>),
+}
+
+impl SplitRow {
+    /// Last line of the diff shown on this row.
+    pub fn last_line(self) -> usize {
+        match self {
+            SplitRow::Full(i) => i,
+            SplitRow::Pair(old, new) => old.max(new).unwrap_or(0),
+        }
+    }
+}
+
+/// A unified diff, line for line, with its files and hunks indexed.
+#[derive(Clone, Debug, Default)]
+pub struct ParsedDiff {
+    /// Every line of the output, headers included.
+    pub lines: Vec<DiffLine>,
+    /// Paths of the files the diff touches, under their new names.
+    pub files: Vec<String>,
+    /// Hunks in the order they appear.
+    pub hunks: Vec<Hunk>,
+}
+
+impl ParsedDiff {
+    /// Parses LOG lines as a unified diff. Returns `None` when the text has no hunks.
+    pub fn parse(raw: &[String]) -> Option<Self> {
+        let mut lines = Vec::with_capacity(raw.len());
+        let mut files: Vec<String> = Vec::new();
+        let mut file: Option<usize> = None;
+        let mut hunks: Vec<Hunk> = Vec::new();
+        let mut hunk: Option<usize> = None;
+        let mut old_path: Option<String> = None;
+        let mut in_hunk = false;
+        let mut has_hunk = false;
+        let (mut old_no, mut new_no) = (0u32, 0u32);
+
+        for line in raw {
+            let text = strip_ansi(line);
+            let mut numbers = (None, None);
+            let kind = if text.starts_with("diff --git ") || text.starts_with("diff --cc ") {
+                in_hunk = false;
+                hunk = None;
+                old_path = None;
+                if let Some(path) = text.rsplit_once(" b/").map(|(_, p)| p.to_string()) {
+                    files.push(path);
+                    file = Some(files.len() - 1);
+                }
+                LineKind::FileHeader
+            } else if text.starts_with("@@") {
+                in_hunk = true;
+                has_hunk = true;
+                if let Some((old, new)) = parse_hunk_start(&text) {
+                    old_no = old;
+                    new_no = new;
+                }
+                hunks.push(Hunk {
+                    line: lines.len(),
+                    context: hunk_context(&text).to_string(),
+                });
+                hunk = Some(hunks.len() - 1);
+                LineKind::HunkHeader
+            } else if !in_hunk {
+                if let Some(path) = text.strip_prefix("--- ") {
+                    old_path = strip_diff_prefix(path);
+                    LineKind::FileHeader
+                } else if let Some(path) = text.strip_prefix("+++ ") {
+                    if let Some(path) = strip_diff_prefix(path).or(old_path.take()) {
+                        if file.is_none_or(|i| files[i] != path) {
+                            files.push(path);
+                        }
+                        file = Some(files.len() - 1);
+                    }
+                    LineKind::FileHeader
+                } else {
+                    LineKind::Meta
+                }
+            } else if text.starts_with('+') {
+                numbers = (None, Some(new_no));
+                new_no += 1;
+                LineKind::Added
+            } else if text.starts_with('-') {
+                numbers = (Some(old_no), None);
+                old_no += 1;
+                LineKind::Removed
+            } else if text.starts_with(' ') || text.is_empty() {
+                numbers = (Some(old_no), Some(new_no));
+                old_no += 1;
+                new_no += 1;
+                LineKind::Context
+            } else {
+                LineKind::Meta
+            };
+            lines.push(DiffLine {
+                kind,
+                text,
+                emphasis: Vec::new(),
+                old_no: numbers.0,
+                new_no: numbers.1,
+                file,
+                hunk,
+            });
+        }
+
+        if !has_hunk {
+            return None;
+        }
+        pair_changed_lines(&mut lines);
+        Some(Self {
+            lines,
+            files,
+            hunks,
+        })
+    }
+
+    /// The hunk enclosing line `top` when its `@@` header has scrolled out of view.
+    pub fn sticky_hunk(&self, top: usize) -> Option<&Hunk> {
+        let hunk = self.hunks.get(self.lines.get(top)?.hunk?)?;
+        (hunk.line < top).then_some(hunk)
+    }
+
+    /// `path › context` of `hunk`, or its `@@` header when git printed no context.
+    pub fn hunk_label(&self, hunk: &Hunk) -> String {
+        let header = &self.lines[hunk.line];
+        let path = header
+            .file
+            .and_then(|i| self.files.get(i))
+            .map(|p| p.as_str())
+            .unwrap_or("?");
+        if hunk.context.is_empty() {
+            format!("{} {}", path, header.text)
+        } else {
+            format!("{} \u{203a} {}", path, hunk.context)
+        }
+    }
+
+    /// Resolves the file and new-side line number an editor should open for `index`.
+    /// Removed and header lines fall back to the nearest new-side line of the same file,
+    /// following first, and to line 1 when the file has none (a deleted file).
+    pub fn location(&self, index: usize) -> Option<(&str, u32)> {
+        let line = self.lines.get(index)?;
+        let path = self.files.get(line.file?)?;
+        let number = self.lines[index..]
+            .iter()
+            .take_while(|l| l.file == line.file)
+            .find_map(|l| l.new_no)
+            .or_else(|| {
+                self.lines[..index]
+                    .iter()
+                    .rev()
+                    .take_while(|l| l.file == line.file)
+                    .find_map(|l| l.new_no)
+            })
+            .unwrap_or(1);
+        Some((path, number))
+    }
+
+    /// Highest line number on either side, to size the gutter.
+    pub fn max_line_number(&self) -> u32 {
+        self.lines
+            .iter()
+            .filter_map(|l| l.old_no.max(l.new_no))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Rows of the side-by-side view: each run of removed lines next to the added lines
+    /// that follow it.
+    pub fn split_rows(&self) -> Vec<SplitRow> {
+        fn flush(rows: &mut Vec<SplitRow>, removed: &mut Vec<usize>, added: &mut Vec<usize>) {
+            for i in 0..removed.len().max(added.len()) {
+                rows.push(SplitRow::Pair(
+                    removed.get(i).copied(),
+                    added.get(i).copied(),
+                ));
+            }
+            removed.clear();
+            added.clear();
+        }
+        let mut rows = Vec::with_capacity(self.lines.len());
+        let (mut removed, mut added) = (Vec::new(), Vec::new());
+        for (i, line) in self.lines.iter().enumerate() {
+            match line.kind {
+                LineKind::Removed => {
+                    if !added.is_empty() {
+                        flush(&mut rows, &mut removed, &mut added);
+                    }
+                    removed.push(i);
+                }
+                LineKind::Added => added.push(i),
+                LineKind::Context => {
+                    flush(&mut rows, &mut removed, &mut added);
+                    rows.push(SplitRow::Pair(Some(i), Some(i)));
+                }
+                _ => {
+                    flush(&mut rows, &mut removed, &mut added);
+                    rows.push(SplitRow::Full(i));
+                }
+            }
+        }
+        flush(&mut rows, &mut removed, &mut added);
+        rows
+    }
+
+    /// Number of lines, the same as the raw output the diff was parsed from.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Whether the diff has no lines.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// A patch of hunk `hunk` alone, under its file's headers, for `git apply`.
+    pub fn hunk_patch(&self, hunk: usize) -> Option<String> {
+        let header_line = self.hunks.get(hunk)?.line;
+        let file = self.lines[header_line].file;
+        // The file headers run from `diff --git` up to the first hunk of the file.
+        let start = self.lines[..header_line]
+            .iter()
+            .rposition(|l| l.file == file && l.hunk.is_none() && l.text.starts_with("diff "))
+            .or_else(|| {
+                self.lines[..header_line]
+                    .iter()
+                    .rposition(|l| l.file == file && l.text.starts_with("--- "))
+            })?;
+        let mut patch = String::new();
+        for line in &self.lines[start..] {
+            match line.hunk {
+                None if line.file == file => {}
+                Some(h) if h == hunk => {}
+                Some(h) if h < hunk => continue,
+                _ => break,
+            }
+            patch.push_str(&line.text);
+            patch.push('\n');
+        }
+        Some(patch)
+    }
+
+    /// Buckets the diff into `rows` cells, one per minimap row.
+    pub fn minimap(&self, rows: usize) -> Vec<MapCell> {
+        if rows == 0 || self.lines.is_empty() {
+            return Vec::new();
+        }
+        let total = self.lines.len();
+        (0..rows)
+            .map(|row| {
+                let start = row * total / rows;
+                let end = ((row + 1) * total / rows).max(start + 1).min(total);
+                let mut added = false;
+                let mut removed = false;
+                for line in &self.lines[start..end] {
+                    match line.kind {
+                        LineKind::Added => added = true,
+                        LineKind::Removed => removed = true,
+                        _ => {}
+                    }
+                }
+                match (added, removed) {
+                    (true, true) => MapCell::Mixed,
+                    (true, false) => MapCell::Added,
+                    (false, true) => MapCell::Removed,
+                    (false, false) => MapCell::Empty,
+                }
+            })
+            .collect()
+    }
+
+    /// Start indices of contiguous runs of added/removed lines.
+    pub fn change_blocks(&self) -> Vec<usize> {
+        let mut starts = Vec::new();
+        let mut prev_change = false;
+        for (i, line) in self.lines.iter().enumerate() {
+            let is_change = line.kind.is_change();
+            if is_change && !prev_change {
+                starts.push(i);
+            }
+            prev_change = is_change;
+        }
+        starts
+    }
+
+    /// First line of the next run of changed lines after `from`.
+    pub fn next_change_block(&self, from: usize) -> Option<usize> {
+        self.change_blocks().into_iter().find(|&i| i > from)
+    }
+
+    /// First line of the run of changed lines before `from`.
+    pub fn prev_change_block(&self, from: usize) -> Option<usize> {
+        self.change_blocks().into_iter().rev().find(|&i| i < from)
+    }
+}
+
+fn parse_hunk_start(header: &str) -> Option<(u32, u32)> {
+    let mut parts = header.split_whitespace().skip(1);
+    let old = parts.next()?.strip_prefix('-')?;
+    let new = parts.next()?.strip_prefix('+')?;
+    let start = |s: &str| s.split(',').next().and_then(|n| n.parse().ok());
+    Some((start(old)?, start(new)?))
+}
+
+fn hunk_context(header: &str) -> &str {
+    header
+        .get(2..)
+        .and_then(|rest| rest.split_once("@@"))
+        .map(|(_, ctx)| ctx.trim())
+        .unwrap_or("")
+}
+
+fn strip_diff_prefix(path: &str) -> Option<String> {
+    let path = path.trim_end_matches('\t').trim_matches('"');
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
+
+const MAX_WORD_DIFF_TOKENS: usize = 400;
+
+/// Pairs each run of removed lines with the run of added lines that follows it and
+/// marks the tokens that differ within each pair.
+fn pair_changed_lines(lines: &mut [DiffLine]) {
+    let mut i = 0;
+    while i < lines.len() {
+        if lines[i].kind != LineKind::Removed {
+            i += 1;
+            continue;
+        }
+        let removed_start = i;
+        while i < lines.len() && lines[i].kind == LineKind::Removed {
+            i += 1;
+        }
+        let added_start = i;
+        while i < lines.len() && lines[i].kind == LineKind::Added {
+            i += 1;
+        }
+        let pairs = (added_start - removed_start).min(i - added_start);
+        for n in 0..pairs {
+            let (old, new) = word_diff(
+                &lines[removed_start + n].text[1..],
+                &lines[added_start + n].text[1..],
+            );
+            lines[removed_start + n].emphasis = shift(old, 1);
+            lines[added_start + n].emphasis = shift(new, 1);
+        }
+    }
+}
+
+fn shift(ranges: Vec<Range<usize>>, by: usize) -> Vec<Range<usize>> {
+    ranges
+        .into_iter()
+        .map(|r| r.start + by..r.end + by)
+        .collect()
+}
+
+/// Splits a line into word, whitespace and single punctuation tokens (byte ranges).
+fn tokenize(s: &str) -> Vec<Range<usize>> {
+    let mut tokens = Vec::new();
+    let mut start: Option<(usize, u8)> = None;
+    for (i, c) in s.char_indices() {
+        let class = if c.is_alphanumeric() || c == '_' {
+            1
+        } else if c.is_whitespace() {
+            2
+        } else {
+            0
+        };
+        match start {
+            Some((_, prev)) if prev == class && class != 0 => {}
+            Some((begin, _)) => {
+                tokens.push(begin..i);
+                start = Some((i, class));
+            }
+            None => start = Some((i, class)),
+        }
+    }
+    if let Some((begin, _)) = start {
+        tokens.push(begin..s.len());
+    }
+    tokens
+}
+
+/// Word-level LCS between two lines. Returns the changed byte ranges of each side,
+/// or nothing when the lines are too long to compare cheaply.
+pub fn word_diff(old: &str, new: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let a = tokenize(old);
+    let b = tokenize(new);
+    if a.len() > MAX_WORD_DIFF_TOKENS || b.len() > MAX_WORD_DIFF_TOKENS {
+        return (Vec::new(), Vec::new());
+    }
+
+    let tok_a: Vec<&str> = a.iter().map(|r| &old[r.clone()]).collect();
+    let tok_b: Vec<&str> = b.iter().map(|r| &new[r.clone()]).collect();
+
+    let (n, m) = (tok_a.len(), tok_b.len());
+    let mut table = vec![vec![0u16; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i][j] = if tok_a[i] == tok_b[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    let mut keep_a = vec![false; n];
+    let mut keep_b = vec![false; m];
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if tok_a[i] == tok_b[j] {
+            keep_a[i] = true;
+            keep_b[j] = true;
+            i += 1;
+            j += 1;
+        } else if table[i + 1][j] >= table[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    (changed_ranges(&a, &keep_a), changed_ranges(&b, &keep_b))
+}
+
+fn changed_ranges(tokens: &[Range<usize>], keep: &[bool]) -> Vec<Range<usize>> {
+    let mut out: Vec<Range<usize>> = Vec::new();
+    for (tok, &kept) in tokens.iter().zip(keep) {
+        if kept {
+            continue;
+        }
+        match out.last_mut() {
+            Some(last) if last.end == tok.start => last.end = tok.end,
+            _ => out.push(tok.clone()),
+        }
+    }
+    out
+}
+
+/// Removes ANSI escape sequences (CSI and OSC) from a line.
+pub fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                for n in chars.by_ref() {
+                    if ('@'..='~').contains(&n) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(n) = chars.next() {
+                    if n == '\x07' {
+                        break;
+                    }
+                    if n == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    const TWO_FILES: &str = "\
+diff --git a/src/a.rs b/src/a.rs
+index 1111111..2222222 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,3 +1,3 @@ fn main() {
+ let a = 1;
+-let b = 2;
++let b = 3;
+ let c = 4;
+diff --git a/new file.txt b/new file.txt
+new file mode 100644
+--- /dev/null
++++ b/new file.txt
+@@ -0,0 +1 @@
++hello";
+
+    #[test]
+    fn parses_files_hunks_and_line_numbers() {
+        let diff = ParsedDiff::parse(&lines(TWO_FILES)).unwrap();
+        assert_eq!(diff.files, ["src/a.rs", "new file.txt"]);
+        assert_eq!(diff.hunks.len(), 2);
+        assert_eq!(diff.hunks[0].context, "fn main() {");
+        assert_eq!(diff.hunks[1].context, "");
+
+        let kinds: Vec<LineKind> = diff.lines[..9].iter().map(|l| l.kind).collect();
+        use LineKind::*;
+        assert_eq!(
+            kinds,
+            [
+                FileHeader, Meta, FileHeader, FileHeader, HunkHeader, Context, Removed, Added,
+                Context
+            ]
+        );
+        let numbers: Vec<(Option<u32>, Option<u32>)> = diff.lines[5..9]
+            .iter()
+            .map(|l| (l.old_no, l.new_no))
+            .collect();
+        assert_eq!(
+            numbers,
+            [
+                (Some(1), Some(1)),
+                (Some(2), None),
+                (None, Some(2)),
+                (Some(3), Some(3))
+            ]
+        );
+        let hello = diff.lines.last().unwrap();
+        assert_eq!(
+            (hello.file, hello.hunk, hello.new_no),
+            (Some(1), Some(1), Some(1))
+        );
+        assert_eq!(diff.location(6), Some(("src/a.rs", 2)));
+        assert_eq!(diff.max_line_number(), 3);
+    }
+
+    #[test]
+    fn locations_stay_in_their_file() {
+        let diff = ParsedDiff::parse(&lines(
+            "diff --git a/kept.rs b/kept.rs
+--- a/kept.rs
++++ b/kept.rs
+@@ -8,3 +8,1 @@
+ keep
+-tail one
+-tail two
+diff --git a/gone.rs b/gone.rs
+deleted file mode 100644
+--- a/gone.rs
++++ /dev/null
+@@ -1,2 +0,0 @@
+-first
+-second",
+        ))
+        .unwrap();
+        assert_eq!(diff.files, ["kept.rs", "gone.rs"]);
+        // A trailing removed block opens at the last line the file still has.
+        assert_eq!(diff.location(6), Some(("kept.rs", 8)));
+        // A deleted file has no new-side lines; the previous file's are not borrowed.
+        assert_eq!(diff.location(13), Some(("gone.rs", 1)));
+        assert_eq!(diff.location(12), Some(("gone.rs", 1)));
+    }
+
+    #[test]
+    fn text_without_hunks_is_no_diff() {
+        assert!(
+            ParsedDiff::parse(&lines("commit 1a2b3c\nAuthor: A <a@b>\n\n    subject")).is_none()
+        );
+        assert!(ParsedDiff::parse(&[]).is_none());
+    }
+
+    #[test]
+    fn marks_changed_words() {
+        let diff = ParsedDiff::parse(&lines(TWO_FILES)).unwrap();
+        let removed = &diff.lines[6];
+        let added = &diff.lines[7];
+        assert_eq!(
+            removed
+                .emphasis
+                .iter()
+                .map(|r| &removed.text[r.clone()])
+                .collect::<Vec<_>>(),
+            ["2"]
+        );
+        assert_eq!(
+            added
+                .emphasis
+                .iter()
+                .map(|r| &added.text[r.clone()])
+                .collect::<Vec<_>>(),
+            ["3"]
+        );
+    }
+
+    #[test]
+    fn cuts_a_hunk_into_its_own_patch() {
+        let diff = ParsedDiff::parse(&lines(TWO_FILES)).unwrap();
+        assert_eq!(
+            diff.hunk_patch(1).unwrap(),
+            "diff --git a/new file.txt b/new file.txt\nnew file mode 100644\n--- /dev/null\n\
+             +++ b/new file.txt\n@@ -0,0 +1 @@\n+hello\n"
+        );
+    }
+
+    #[test]
+    fn finds_change_blocks() {
+        let diff = ParsedDiff::parse(&lines(TWO_FILES)).unwrap();
+        assert_eq!(diff.change_blocks(), [6, 14]);
+        assert_eq!(diff.next_change_block(6), Some(14));
+        assert_eq!(diff.prev_change_block(14), Some(6));
+        assert_eq!(diff.prev_change_block(6), None);
+    }
+
+    #[test]
+    fn strips_color_codes() {
+        assert_eq!(strip_ansi("\x1b[32m+added\x1b[m"), "+added");
+        assert_eq!(strip_ansi("\x1b]8;;https://x\x07link\x1b]8;;\x07"), "link");
+    }
+}
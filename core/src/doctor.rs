@@ -0,0 +1,718 @@
+//! Checks of the repository and the git setup, each with a fix where one is known.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::git::{
+    CommandResult, git_command, git_stdout, lfs_version, quote_arg, run_step, step_label,
+};
+use crate::jobs::CancelToken;
+use crate::preview::format_size;
+use crate::shell::hide_console;
+
+/// Keys expiring sooner than this are reported as a warning.
+const EXPIRY_WARNING_DAYS: u64 = 30;
+
+/// Untracked files above this size are probably build output or assets meant for LFS.
+const LARGE_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// git's own `gc.auto` default for loose objects.
+const GC_LOOSE_OBJECTS: u64 = 6700;
+
+/// `gc.autoPackLimit` default.
+const GC_PACKS: u64 = 50;
+
+/// How bad the outcome of a check is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Severity {
+    /// Nothing to do.
+    Ok,
+    /// Works for now, but worth a look.
+    Warning,
+    /// Something is broken or about to break.
+    Problem,
+}
+
+/// The outcome of one diagnostic.
+#[derive(Clone, Debug)]
+pub struct Check {
+    /// What was checked, shown as the row's title.
+    pub name: &'static str,
+    /// How the check turned out.
+    pub severity: Severity,
+    /// What was found.
+    pub detail: String,
+    /// What to do about a warning or problem.
+    pub hint: Option<String>,
+    /// A remedy that can be applied from the report.
+    pub fix: Option<Fix>,
+}
+
+/// An automatic remedy for a failed check.
+#[derive(Clone, Debug)]
+pub enum Fix {
+    /// A git command, or a shell command prefixed with `!`.
+    Step(String),
+    /// Files to delete, such as stale lock files.
+    RemoveFiles(Vec<PathBuf>),
+}
+
+impl Fix {
+    /// What the fix does, for the report.
+    pub fn label(&self) -> String {
+        match self {
+            Fix::Step(step) => step_label(step),
+            Fix::RemoveFiles(paths) => format!(
+                "remove {}",
+                paths
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+        }
+    }
+}
+
+/// Applies `fix`, appending what happened to `res`; returns whether it succeeded. A step
+/// is stopped once `cancel` is set.
+pub fn apply_fix(
+    git: &str,
+    repo: &Path,
+    fix: &Fix,
+    cancel: &CancelToken,
+    res: &mut CommandResult,
+) -> bool {
+    match fix {
+        Fix::Step(step) => run_step(git, step, repo, cancel, res),
+        Fix::RemoveFiles(paths) => paths.iter().all(|path| match fs::remove_file(path) {
+            Ok(()) => {
+                res.result_lines.push(format!("removed {}", path.display()));
+                true
+            }
+            Err(e) => {
+                res.result_lines
+                    .push(format!("ERROR: cannot remove {}: {}", path.display(), e));
+                false
+            }
+        }),
+    }
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            severity: Severity::Ok,
+            detail: detail.into(),
+            hint: None,
+            fix: None,
+        }
+    }
+
+    fn warning(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            severity: Severity::Warning,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+            fix: None,
+        }
+    }
+
+    fn problem(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            severity: Severity::Problem,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+            fix: None,
+        }
+    }
+
+    fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+/// Runs every check against `repo`. Slow (the upstream check goes over the network), so call
+/// it off the UI thread.
+pub fn run_checks(git: &str, repo: &Path) -> Vec<Check> {
+    vec![
+        detached_head(git, repo),
+        diverged_upstream(git, repo),
+        stale_locks(git, repo),
+        signing_key(git, repo),
+        hooks_executable(git, repo),
+        lfs_installed(git, repo),
+        missing_lfs_objects(git, repo),
+        large_untracked_files(git, repo),
+        gc_needed(git, repo),
+        upstream_reachable(git, repo),
+    ]
+}
+
+fn config(git: &str, repo: &Path, key: &str) -> Option<String> {
+    git_stdout(git, repo, &["config", "--get", key]).filter(|v| !v.is_empty())
+}
+
+/// The configured signing key exists, has not expired and belongs to `user.email`.
+pub fn signing_key(git: &str, repo: &Path) -> Check {
+    const NAME: &str = "signing key";
+    let key = config(git, repo, "user.signingkey");
+    let signs = config(git, repo, "commit.gpgsign").is_some_and(|v| v == "true");
+    if key.is_none() && !signs {
+        return Check::ok(NAME, "commit signing is not configured");
+    }
+    let email = config(git, repo, "user.email");
+    let format = config(git, repo, "gpg.format").unwrap_or_else(|| "openpgp".into());
+    match format.as_str() {
+        "ssh" => ssh_signing_key(git, repo, key, email),
+        "x509" => Check::ok(NAME, "x509 keys (gpgsm) are not checked"),
+        _ => {
+            let program = config(git, repo, "gpg.openpgp.program")
+                .or_else(|| config(git, repo, "gpg.program"))
+                .unwrap_or_else(|| "gpg".into());
+            // Without user.signingkey, git asks gpg for a key matching the committer.
+            let Some(spec) = key.or_else(|| email.clone()) else {
+                return Check::problem(
+                    NAME,
+                    "commit.gpgsign is on but neither user.signingkey nor user.email is set",
+                    "git config user.signingkey <key id>",
+                );
+            };
+            gpg_signing_key(&program, &spec, email.as_deref())
+        }
+    }
+}
+
+fn gpg_signing_key(program: &str, spec: &str, email: Option<&str>) -> Check {
+    const NAME: &str = "signing key";
+    let mut cmd = Command::new(program);
+    hide_console(&mut cmd);
+    let output = match cmd
+        .args(["--batch", "--with-colons", "--list-secret-keys", spec])
+        .stdin(Stdio::null())
+        .output()
+    {
+        Ok(o) => o,
+        Err(e) => {
+            return Check::problem(
+                NAME,
+                format!("cannot run {}: {}", program, e),
+                "install GnuPG or point gpg.program at it",
+            );
+        }
+    };
+    let listing = String::from_utf8_lossy(&output.stdout);
+    let mut validity = None;
+    let mut expires = None;
+    let mut fingerprint = None;
+    let mut uids = Vec::new();
+    for fields in listing.lines().map(|l| l.split(':').collect::<Vec<_>>()) {
+        match fields[0] {
+            "sec" if validity.is_none() => {
+                validity = fields.get(1).map(|v| v.to_string());
+                expires = fields.get(6).and_then(|e| e.parse::<u64>().ok());
+            }
+            "fpr" if fingerprint.is_none() => fingerprint = fields.get(9).map(|f| f.to_string()),
+            "uid" => uids.extend(fields.get(9).map(|u| u.to_string())),
+            _ => {}
+        }
+    }
+    let Some(validity) = validity else {
+        return Check::problem(
+            NAME,
+            format!("no secret key found for {}", spec),
+            "create one with `gpg --full-generate-key` or fix user.signingkey",
+        );
+    };
+    let fingerprint = fingerprint.unwrap_or_else(|| spec.to_string());
+    if validity == "r" {
+        return Check::problem(
+            NAME,
+            format!("key {} is revoked", fingerprint),
+            "generate a new key and update user.signingkey",
+        );
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let days_left = expires.map(|e| e.saturating_sub(now) / 86_400);
+    if validity == "e" || expires.is_some_and(|e| e <= now) {
+        return Check::problem(
+            NAME,
+            format!("key {} has expired", fingerprint),
+            format!("gpg --quick-set-expire {} 1y", fingerprint),
+        );
+    }
+    if let Some(email) = email
+        && !uids.iter().any(|u| {
+            u.to_lowercase()
+                .contains(&format!("<{}>", email.to_lowercase()))
+        })
+    {
+        return Check::warning(
+            NAME,
+            format!("key {} has no uid for user.email <{}>", fingerprint, email),
+            format!("gpg --quick-add-uid {} \"<{}>\"", fingerprint, email),
+        );
+    }
+    match days_left {
+        Some(days) if days < EXPIRY_WARNING_DAYS => Check::warning(
+            NAME,
+            format!("key {} expires in {} day(s)", fingerprint, days),
+            format!("gpg --quick-set-expire {} 1y", fingerprint),
+        ),
+        Some(days) => Check::ok(
+            NAME,
+            format!("key {} expires in {} days", fingerprint, days),
+        ),
+        None => Check::ok(NAME, format!("key {} does not expire", fingerprint)),
+    }
+}
+
+fn ssh_signing_key(git: &str, repo: &Path, key: Option<String>, email: Option<String>) -> Check {
+    const NAME: &str = "signing key";
+    let Some(key) = key else {
+        return Check::problem(
+            NAME,
+            "gpg.format is ssh but user.signingkey is not set",
+            "git config user.signingkey ~/.ssh/id_ed25519.pub",
+        );
+    };
+    // The key can be given inline instead of as a file.
+    if !key.starts_with("key::") && !key.starts_with("ssh-") {
+        let path = expand_home(&key);
+        if !path.is_file() {
+            return Check::problem(
+                NAME,
+                format!("key file {} does not exist", path.display()),
+                "git config user.signingkey <path to public key>",
+            );
+        }
+    }
+    if let (Some(signers), Some(email)) = (config(git, repo, "gpg.ssh.allowedSignersFile"), email) {
+        let listed = fs::read_to_string(expand_home(&signers)).is_ok_and(|text| {
+            text.lines()
+                .any(|l| l.split_whitespace().next() == Some(email.as_str()))
+        });
+        if !listed {
+            return Check::warning(
+                NAME,
+                format!("<{}> is not in the allowed signers file {}", email, signers),
+                "add `<email> <public key>` to the allowed signers file",
+            );
+        }
+    }
+    Check::ok(NAME, format!("ssh key {} (ssh keys do not expire)", key))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(|home| PathBuf::from(home).join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Hooks git would skip because they lack the executable bit.
+pub fn hooks_executable(git: &str, repo: &Path) -> Check {
+    const NAME: &str = "hooks";
+    // Honours core.hooksPath.
+    let Some(dir) = git_stdout(git, repo, &["rev-parse", "--git-path", "hooks"]) else {
+        return Check::ok(NAME, "not a git repository");
+    };
+    let dir = repo.join(dir);
+    let hooks: Vec<PathBuf> = fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.is_file() && p.extension().is_none_or(|e| e != "sample"))
+                .collect()
+        })
+        .unwrap_or_default();
+    if hooks.is_empty() {
+        return Check::ok(NAME, "no hooks installed");
+    }
+    let stuck: Vec<&PathBuf> = hooks.iter().filter(|p| !is_executable(p)).collect();
+    if stuck.is_empty() {
+        return Check::ok(NAME, format!("{} hook(s), all executable", hooks.len()));
+    }
+    let names: Vec<String> = stuck
+        .iter()
+        .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .collect();
+    let chmod = format!(
+        "chmod +x {}",
+        stuck
+            .iter()
+            .map(|p| quote_arg(&p.display().to_string()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    Check::warning(
+        NAME,
+        format!("not executable, so git skips them: {}", names.join(", ")),
+        &chmod,
+    )
+    .with_fix(Fix::Step(format!("!{}", chmod)))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+/// Windows runs hooks through sh regardless of permissions.
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+/// git-lfs is installed, and its filters are configured, when the repository uses LFS.
+pub fn lfs_installed(git: &str, repo: &Path) -> Check {
+    const NAME: &str = "lfs";
+    let uses_lfs = uses_lfs(repo);
+    match lfs_version(git) {
+        Some(_) if uses_lfs && config(git, repo, "filter.lfs.clean").is_none() => Check::warning(
+            NAME,
+            "git-lfs is installed but its filters are not configured",
+            "git lfs install",
+        )
+        .with_fix(Fix::Step("lfs install".into())),
+        Some(version) => Check::ok(NAME, version),
+        None if uses_lfs => Check::problem(
+            NAME,
+            ".gitattributes uses LFS but git-lfs is not installed",
+            "install git-lfs from https://git-lfs.com, then run `git lfs install`",
+        ),
+        None => Check::ok(NAME, "git-lfs not installed (not used by this repository)"),
+    }
+}
+
+/// The upstream of the current branch answers and still has the branch.
+pub fn upstream_reachable(git: &str, repo: &Path) -> Check {
+    const NAME: &str = "upstream";
+    let Some(branch) = git_stdout(git, repo, &["symbolic-ref", "--short", "-q", "HEAD"]) else {
+        return Check::ok(NAME, "detached HEAD, no upstream to check");
+    };
+    let remote = config(git, repo, &format!("branch.{}.remote", branch));
+    let merge = config(git, repo, &format!("branch.{}.merge", branch));
+    let (Some(remote), Some(merge)) = (remote, merge) else {
+        return Check::warning(
+            NAME,
+            format!("{} has no upstream", branch),
+            format!("git push -u origin {}", branch),
+        )
+        .with_fix(Fix::Step(format!("push -u origin {}", branch)));
+    };
+    let mut cmd = git_command(git);
+    // Fail instead of prompting for credentials the TUI cannot show.
+    cmd.env("GIT_TERMINAL_PROMPT", "0");
+    if std::env::var_os("GIT_SSH_COMMAND").is_none()
+        && config(git, repo, "core.sshCommand").is_none()
+    {
+        cmd.env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes");
+    }
+    let output = cmd
+        .args(["ls-remote", "--exit-code", &remote, &merge])
+        .current_dir(repo)
+        .stdin(Stdio::null())
+        .output();
+    let short = merge.trim_start_matches("refs/heads/");
+    match output {
+        Ok(o) if o.status.success() => {
+            Check::ok(NAME, format!("{} is reachable and has {}", remote, short))
+        }
+        // --exit-code reports a missing ref with status 2.
+        Ok(o) if o.status.code() == Some(2) => Check::warning(
+            NAME,
+            format!("{} no longer has {}", remote, short),
+            format!("git branch --unset-upstream {}", branch),
+        )
+        .with_fix(Fix::Step(format!("branch --unset-upstream {}", branch))),
+        Ok(o) => Check::problem(
+            NAME,
+            format!(
+                "cannot reach {}: {}",
+                remote,
+                String::from_utf8_lossy(&o.stderr)
+                    .lines()
+                    .find(|l| !l.trim().is_empty())
+                    .unwrap_or("unknown error")
+            ),
+            "check the network, the remote URL and your credentials",
+        ),
+        Err(e) => Check::problem(
+            NAME,
+            format!("cannot run git: {}", e),
+            "check git_path in config.toml",
+        ),
+    }
+}
+
+/// Work on a detached HEAD is lost once something else is checked out.
+pub fn detached_head(git: &str, repo: &Path) -> Check {
+    const NAME: &str = "HEAD";
+    if let Some(branch) = git_stdout(git, repo, &["symbolic-ref", "--short", "-q", "HEAD"]) {
+        return Check::ok(NAME, format!("on branch {}", branch));
+    }
+    let Some(head) = git_stdout(git, repo, &["rev-parse", "--short", "HEAD"]) else {
+        return Check::ok(NAME, "no commits yet");
+    };
+    let rebasing = ["rebase-merge", "rebase-apply"].iter().any(|dir| {
+        git_stdout(git, repo, &["rev-parse", "--git-path", dir])
+            .is_some_and(|p| repo.join(p).exists())
+    });
+    if rebasing {
+        return Check::ok(
+            NAME,
+            format!("detached at {} by a rebase in progress", head),
+        );
+    }
+    Check::warning(
+        NAME,
+        format!("detached at {}; new commits belong to no branch", head),
+        "create a branch to keep them",
+    )
+    .with_fix(Fix::Step(format!("switch -c detached-{}", head)))
+}
+
+/// Compares the current branch with its upstream as of the last fetch.
+pub fn diverged_upstream(git: &str, repo: &Path) -> Check {
+    const NAME: &str = "divergence";
+    let Some(upstream) = git_stdout(git, repo, &["rev-parse", "--abbrev-ref", "@{upstream}"])
+    else {
+        return Check::ok(NAME, "no upstream to compare with");
+    };
+    let counts = git_stdout(
+        git,
+        repo,
+        &["rev-list", "--left-right", "--count", "HEAD...@{upstream}"],
+    )
+    .unwrap_or_default();
+    let mut counts = counts.split_whitespace().map(|n| n.parse().unwrap_or(0u64));
+    let (ahead, behind) = (counts.next().unwrap_or(0), counts.next().unwrap_or(0));
+    match (ahead, behind) {
+        (_, 0) if ahead > 0 => {
+            Check::ok(NAME, format!("{} commit(s) ahead of {}", ahead, upstream))
+        }
+        (_, 0) => Check::ok(NAME, format!("up to date with {}", upstream)),
+        (0, _) => Check::warning(
+            NAME,
+            format!("{} commit(s) behind {}", behind, upstream),
+            "git pull --ff-only",
+        )
+        .with_fix(Fix::Step("pull --ff-only".into())),
+        _ => Check::warning(
+            NAME,
+            format!(
+                "diverged from {}: {} ahead, {} behind",
+                upstream, ahead, behind
+            ),
+            "rebase onto the upstream or merge it",
+        )
+        .with_fix(Fix::Step("pull --rebase".into())),
+    }
+}
+
+/// Lock files git leaves behind when it crashes or is killed.
+pub fn stale_locks(git: &str, repo: &Path) -> Check {
+    const NAME: &str = "locks";
+    let locks = lock_files(git, repo);
+    if locks.is_empty() {
+        return Check::ok(NAME, "no lock files");
+    }
+    let described: Vec<String> = locks
+        .iter()
+        .map(|p| {
+            let age = p
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.elapsed().ok())
+                .unwrap_or(Duration::ZERO);
+            format!("{} ({} min old)", p.display(), age.as_secs() / 60)
+        })
+        .collect();
+    if git_process_running() {
+        return Check::warning(
+            NAME,
+            format!("{} (another git process is running)", described.join(", ")),
+            "wait for the other git process to finish",
+        );
+    }
+    Check::problem(
+        NAME,
+        format!("stale: {}", described.join(", ")),
+        "no git process is running, so the locks can be removed",
+    )
+    .with_fix(Fix::RemoveFiles(locks))
+}
+
+/// `*.lock` files in the git directory and under `refs/`.
+pub fn lock_files(git: &str, repo: &Path) -> Vec<PathBuf> {
+    let Some(git_dir) = git_stdout(git, repo, &["rev-parse", "--git-dir"]) else {
+        return Vec::new();
+    };
+    let git_dir = repo.join(git_dir);
+    let entries = |dir: &Path| -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .collect()
+    };
+    let is_lock = |p: &PathBuf| p.is_file() && p.extension().is_some_and(|e| e == "lock");
+    let mut locks: Vec<PathBuf> = entries(&git_dir).into_iter().filter(is_lock).collect();
+    let mut dirs = vec![git_dir.join("refs")];
+    while let Some(dir) = dirs.pop() {
+        for path in entries(&dir) {
+            if path.is_dir() {
+                dirs.push(path);
+            } else if is_lock(&path) {
+                locks.push(path);
+            }
+        }
+    }
+    locks.sort();
+    locks
+}
+
+/// The lock file named by git's `Unable to create '<path>.lock': File exists.` error.
+pub fn lock_conflict(repo: &Path, output: &[String]) -> Option<PathBuf> {
+    output.iter().find_map(|line| {
+        let rest = line.split_once("Unable to create '")?.1;
+        let (path, tail) = rest.split_once('\'')?;
+        (path.ends_with(".lock") && tail.starts_with(": File exists")).then(|| repo.join(path))
+    })
+}
+
+/// Whether any git process is running, which would make its lock files legitimate.
+#[cfg(target_os = "linux")]
+pub fn git_process_running() -> bool {
+    fs::read_dir("/proc")
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+        })
+        .any(|e| fs::read_to_string(e.path().join("comm")).is_ok_and(|c| c.trim() == "git"))
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn git_process_running() -> bool {
+    Command::new("pgrep")
+        .args(["-x", "git"])
+        .stdout(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+#[cfg(windows)]
+pub fn git_process_running() -> bool {
+    let mut cmd = Command::new("tasklist");
+    hide_console(&mut cmd);
+    cmd.args(["/FI", "IMAGENAME eq git.exe", "/NH"])
+        .output()
+        .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).contains("git.exe"))
+}
+
+fn uses_lfs(repo: &Path) -> bool {
+    fs::read_to_string(repo.join(".gitattributes")).is_ok_and(|text| text.contains("filter=lfs"))
+}
+
+/// LFS files still checked out as pointers, e.g. after cloning with `GIT_LFS_SKIP_SMUDGE`.
+pub fn missing_lfs_objects(git: &str, repo: &Path) -> Check {
+    const NAME: &str = "lfs objects";
+    if !uses_lfs(repo) || lfs_version(git).is_none() {
+        return Check::ok(NAME, "not checked (no LFS)");
+    }
+    // Lines read `<oid> * <path>` when downloaded and `<oid> - <path>` for pointers.
+    let listing = git_stdout(git, repo, &["lfs", "ls-files"]).unwrap_or_default();
+    let total = listing.lines().count();
+    let missing = listing
+        .lines()
+        .filter(|l| l.split_whitespace().nth(1) == Some("-"))
+        .count();
+    if missing == 0 {
+        return Check::ok(NAME, format!("all {} LFS file(s) downloaded", total));
+    }
+    Check::warning(
+        NAME,
+        format!("{} of {} LFS file(s) are pointers only", missing, total),
+        "git lfs pull",
+    )
+    .with_fix(Fix::Step("lfs pull".into()))
+}
+
+/// Untracked files big enough that committing them by accident would hurt.
+pub fn large_untracked_files(git: &str, repo: &Path) -> Check {
+    const NAME: &str = "large files";
+    let listing = git_stdout(
+        git,
+        repo,
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+    )
+    .unwrap_or_default();
+    let large: Vec<(&str, u64)> = listing
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .filter_map(|p| Some((p, repo.join(p).metadata().ok()?.len())))
+        .filter(|(_, size)| *size > LARGE_FILE_BYTES)
+        .collect();
+    if large.is_empty() {
+        return Check::ok(
+            NAME,
+            format!("no untracked files over {}", format_size(LARGE_FILE_BYTES)),
+        );
+    }
+    let check = Check::warning(
+        NAME,
+        format!(
+            "untracked: {}",
+            large
+                .iter()
+                .map(|(p, size)| format!("{} ({})", p, format_size(*size)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        "add them to .gitignore, or track them with git lfs",
+    );
+    if lfs_version(git).is_none() {
+        return check;
+    }
+    let paths: Vec<String> = large.iter().map(|(p, _)| quote_arg(p)).collect();
+    check.with_fix(Fix::Step(format!("lfs track {}", paths.join(" "))))
+}
+
+/// Too many loose objects or packs slow every command down.
+pub fn gc_needed(git: &str, repo: &Path) -> Check {
+    const NAME: &str = "gc";
+    let counts = git_stdout(git, repo, &["count-objects", "-v"]).unwrap_or_default();
+    let field = |key: &str| {
+        counts
+            .lines()
+            .find_map(|l| l.strip_prefix(key)?.strip_prefix(": ")?.parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    let (loose, packs) = (field("count"), field("packs"));
+    let detail = format!("{} loose object(s), {} pack(s)", loose, packs);
+    if loose > GC_LOOSE_OBJECTS || packs > GC_PACKS {
+        return Check::warning(NAME, detail, "git gc").with_fix(Fix::Step("gc".into()));
+    }
+    Check::ok(NAME, detail)
+}
@@ -0,0 +1,2583 @@
+//! Running git and parsing what it prints.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ExitStatus, Output, Stdio};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::diff::{LineKind, ParsedDiff};
+use crate::jobs::CancelToken;
+use crate::shell::{hide_console, shell_command};
+use std::process::Command;
+use std::sync::mpsc;
+use std::thread;
+
+/// Git LFS step to run after a command succeeds or fails.
+#[derive(Clone)]
+pub enum LfsMode {
+    /// No LFS step.
+    None,
+    /// `git lfs fetch`.
+    Fetch(LfsOptions),
+    /// `git lfs pull`.
+    Pull(LfsOptions),
+    /// `git lfs push`.
+    Push(LfsOptions),
+}
+
+/// Where the LFS step transfers objects from or to, and which paths it covers.
+#[derive(Clone, Default)]
+pub struct LfsOptions {
+    /// Defaults to the remote git-lfs picks itself (`origin` for push).
+    pub remote: Option<String>,
+    /// Ref to fetch or push; fetch covers all refs and push `HEAD` when unset.
+    pub refspec: Option<String>,
+    /// Comma-separated path patterns passed as `--include` (fetch and pull only).
+    pub include: Option<String>,
+    /// Comma-separated path patterns passed as `--exclude` (fetch and pull only).
+    pub exclude: Option<String>,
+}
+
+impl LfsMode {
+    /// Arguments of the `git lfs` step run after the main command, if any.
+    pub fn follow_up(&self) -> Option<String> {
+        let (subcmd, opts) = match self {
+            LfsMode::None => return None,
+            LfsMode::Fetch(opts) => ("fetch", opts),
+            LfsMode::Pull(opts) => ("pull", opts),
+            LfsMode::Push(opts) => ("push", opts),
+        };
+        let mut args = vec!["lfs".to_string(), subcmd.to_string()];
+        if subcmd == "fetch" && opts.refspec.is_none() {
+            args.push("--all".into());
+        }
+        if subcmd != "push" {
+            for (flag, patterns) in [("--include", &opts.include), ("--exclude", &opts.exclude)] {
+                if let Some(patterns) = patterns {
+                    args.push(flag.into());
+                    args.push(quote_arg(patterns));
+                }
+            }
+        }
+        // A ref is positional after the remote, so naming one needs a remote too.
+        let remote = match (&opts.remote, &opts.refspec) {
+            (Some(remote), _) => Some(remote.as_str()),
+            (None, Some(_)) => Some("origin"),
+            (None, None) if subcmd == "push" => Some("origin"),
+            (None, None) => None,
+        };
+        args.extend(remote.map(quote_arg));
+        if subcmd != "pull" {
+            match &opts.refspec {
+                Some(refspec) => args.push(quote_arg(refspec)),
+                None if subcmd == "push" => args.push("HEAD".into()),
+                None => {}
+            }
+        }
+        Some(args.join(" "))
+    }
+}
+
+/// One entry of `git status --porcelain`.
+#[derive(Clone, Debug)]
+pub struct RepoFile {
+    /// Two-letter status as in `git status --short`, e.g. `M `, ` D` or `??`.
+    pub status: String,
+    /// Path relative to the repository root, as is: never quoted or escaped.
+    pub path: String,
+    /// Where a renamed or copied file came from.
+    pub orig_path: Option<String>,
+}
+
+impl RepoFile {
+    /// Paths to pass to git; both sides of a rename.
+    pub fn operands(&self) -> Vec<String> {
+        self.orig_path.iter().chain([&self.path]).cloned().collect()
+    }
+
+    /// File name of the entry, without directories.
+    pub fn display_label(&self) -> String {
+        self.path
+            .rsplit('/')
+            .find(|s| !s.is_empty())
+            .unwrap_or(&self.path)
+            .to_string()
+    }
+
+    /// `old -> new` for a rename or copy, else the path.
+    pub fn display_path(&self) -> String {
+        match &self.orig_path {
+            Some(orig) => format!("{} -> {}", orig, self.path),
+            None => self.path.clone(),
+        }
+    }
+
+    /// Left unmerged by a merge, rebase, cherry-pick or revert: `U` on either side, `AA`
+    /// or `DD`.
+    pub fn is_conflicted(&self) -> bool {
+        self.status.contains('U') || self.status == "AA" || self.status == "DD"
+    }
+}
+
+/// Which version of a conflicted file to keep.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictSide {
+    /// The branch being merged into, or rebased onto.
+    Ours,
+    /// The branch being merged, or the commit being replayed.
+    Theirs,
+}
+
+impl ConflictSide {
+    /// `ours` or `theirs`, as `git checkout` takes it.
+    pub fn name(self) -> &'static str {
+        match self {
+            ConflictSide::Ours => "ours",
+            ConflictSide::Theirs => "theirs",
+        }
+    }
+}
+
+/// The git command keeping `side` of the conflicted `file`: its version is checked out,
+/// or the file removed (and so resolved) when that side deleted it.
+pub fn take_side_command(file: &RepoFile, side: ConflictSide) -> String {
+    let path = quote_arg(&file.path);
+    let deleted = match side {
+        ConflictSide::Ours => file.status.starts_with('D'),
+        ConflictSide::Theirs => file.status.ends_with('D'),
+    };
+    if deleted {
+        format!("rm -- {}", path)
+    } else {
+        format!("checkout --{} -- {}", side.name(), path)
+    }
+}
+
+/// The git command marking the conflicted `file` resolved as it is in the worktree:
+/// staged, or its removal staged when it is gone.
+pub fn mark_resolved_command(file: &RepoFile, repo: &Path) -> String {
+    let path = quote_arg(&file.path);
+    if repo.join(&file.path).exists() {
+        format!("add -- {}", path)
+    } else {
+        format!("rm -- {}", path)
+    }
+}
+
+/// The combined diff of a conflicted file against both sides (`git diff --cc`): one
+/// column of `+`/`-` for ours and one for theirs.
+pub fn three_way_diff_args(file: &RepoFile) -> Vec<String> {
+    vec!["diff".into(), "--cc".into(), "--".into(), file.path.clone()]
+}
+
+/// The state of the worktree as `git status` reports it.
+#[derive(Clone, Debug, Default)]
+pub struct RepoStatus {
+    /// The checked-out branch, or `HEAD` when detached.
+    pub branch: String,
+    /// The branch it tracks, if any.
+    pub upstream: Option<Upstream>,
+    /// Files with staged changes.
+    pub staged: usize,
+    /// Tracked files with changes not staged.
+    pub unstaged: usize,
+    /// Untracked files.
+    pub untracked: usize,
+    /// Every changed, conflicted or untracked file.
+    pub files: Vec<RepoFile>,
+    /// A cherry-pick or revert of several commits stopped partway.
+    pub sequence: Option<PickSequence>,
+    /// The merge, rebase or other operation waiting to be continued, if any.
+    pub operation: Option<Operation>,
+}
+
+/// The branch the checked-out one tracks.
+#[derive(Clone, Debug)]
+pub struct Upstream {
+    /// `origin/main` and so on.
+    pub name: String,
+    /// Commits the branch has that the upstream lacks.
+    pub ahead: usize,
+    /// Commits the upstream has that the branch lacks.
+    pub behind: usize,
+}
+
+impl RepoStatus {
+    /// `[branch...upstream ↑ahead ↓behind] +staged ~unstaged ?untracked`.
+    pub fn summary(&self) -> String {
+        let tracking = match &self.upstream {
+            Some(u) => format!("...{} ↑{} ↓{}", u.name, u.ahead, u.behind),
+            None => String::new(),
+        };
+        format!(
+            "[{}{}] +{} ~{} ?{}",
+            self.branch, tracking, self.staged, self.unstaged, self.untracked
+        )
+    }
+}
+
+/// Progress of a multi-commit cherry-pick or revert, read from git's `sequencer/` state,
+/// which lists the commits still to apply and where the sequence started.
+#[derive(Clone, Debug)]
+pub struct PickSequence {
+    /// `cherry-pick` or `revert`.
+    pub command: &'static str,
+    /// Commits applied so far.
+    pub applied: usize,
+    /// Commits in the whole sequence.
+    pub total: usize,
+    /// `<hash> <subject>` of the commit waiting for its conflicts to be resolved.
+    pub stopped: Option<String>,
+}
+
+impl PickSequence {
+    /// Commits `--continue` would still apply, the stopped one included.
+    pub fn remaining(&self) -> usize {
+        self.total - self.applied
+    }
+}
+
+fn pick_sequence(git: &str, repo: &Path, git_dir: &Path) -> Option<PickSequence> {
+    let sequencer = git_dir.join("sequencer");
+    let todo = fs::read_to_string(sequencer.join("todo")).ok()?;
+    let todo: Vec<&str> = todo
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .collect();
+    let command = match todo.first()?.split_whitespace().next()? {
+        "pick" | "p" => "cherry-pick",
+        "revert" => "revert",
+        _ => return None,
+    };
+    let start = fs::read_to_string(sequencer.join("head")).ok()?;
+    let applied: usize = git_stdout(
+        git,
+        repo,
+        &["rev-list", "--count", &format!("{}..HEAD", start.trim())],
+    )?
+    .parse()
+    .ok()?;
+    // The first todo entry stays listed after its resolution is committed by hand.
+    let pending = ["CHERRY_PICK_HEAD", "REVERT_HEAD"]
+        .iter()
+        .any(|f| git_dir.join(f).exists());
+    let stopped = pending.then(|| {
+        let mut words = todo[0].splitn(3, ' ').skip(1);
+        let hash = words.next().unwrap_or_default();
+        format!("{} {}", hash, words.next().unwrap_or_default())
+    });
+    Some(PickSequence {
+        command,
+        applied,
+        total: applied + todo.len() - usize::from(!pending),
+        stopped,
+    })
+}
+
+/// A merge, rebase, cherry-pick, revert or `git am` stopped until it is continued or
+/// aborted, as told by the state files git leaves in the git directory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// `git merge`.
+    Merge,
+    /// The step the rebase stopped at and how many it has, when git recorded them.
+    Rebase(Option<(usize, usize)>),
+    /// `git cherry-pick` of a single commit.
+    CherryPick,
+    /// `git revert` of a single commit.
+    Revert,
+    /// The patch `git am` stopped at and how many there are.
+    Am(Option<(usize, usize)>),
+}
+
+impl Operation {
+    /// The git command taking `--continue`, `--abort` and `--skip` for it.
+    pub fn command(self) -> &'static str {
+        match self {
+            Operation::Merge => "merge",
+            Operation::Rebase(_) => "rebase",
+            Operation::CherryPick => "cherry-pick",
+            Operation::Revert => "revert",
+            Operation::Am(_) => "am",
+        }
+    }
+
+    /// `MERGING`, `REBASING 3/7` and so on, for the status bar.
+    pub fn label(self) -> String {
+        let (verb, progress) = match self {
+            Operation::Merge => ("MERGING", None),
+            Operation::Rebase(progress) => ("REBASING", progress),
+            Operation::CherryPick => ("CHERRY-PICKING", None),
+            Operation::Revert => ("REVERTING", None),
+            Operation::Am(progress) => ("APPLYING PATCHES", progress),
+        };
+        match progress {
+            Some((step, total)) => format!("{} {}/{}", verb, step, total),
+            None => verb.to_string(),
+        }
+    }
+}
+
+fn repo_operation(git_dir: &Path) -> Option<Operation> {
+    let progress = |dir: &Path, step: &str, total: &str| {
+        let read = |name: &str| fs::read_to_string(dir.join(name)).ok()?.trim().parse().ok();
+        Some((read(step)?, read(total)?))
+    };
+    let rebase_merge = git_dir.join("rebase-merge");
+    if rebase_merge.is_dir() {
+        return Some(Operation::Rebase(progress(&rebase_merge, "msgnum", "end")));
+    }
+    let rebase_apply = git_dir.join("rebase-apply");
+    if rebase_apply.is_dir() {
+        let progress = progress(&rebase_apply, "next", "last");
+        return Some(if rebase_apply.join("applying").exists() {
+            Operation::Am(progress)
+        } else {
+            Operation::Rebase(progress)
+        });
+    }
+    [
+        ("MERGE_HEAD", Operation::Merge),
+        ("CHERRY_PICK_HEAD", Operation::CherryPick),
+        ("REVERT_HEAD", Operation::Revert),
+    ]
+    .into_iter()
+    .find_map(|(file, operation)| git_dir.join(file).exists().then_some(operation))
+}
+
+/// Branch and upstream info reused across refreshes while HEAD and the refs are untouched.
+#[derive(Default)]
+pub struct RefCache {
+    /// `(git dir, common dir)`; they differ inside linked worktrees.
+    dirs: Option<(PathBuf, PathBuf)>,
+    key: Option<Vec<Option<SystemTime>>>,
+    branch: String,
+    upstream: Option<Upstream>,
+    /// Full ref of the upstream, e.g. `refs/remotes/origin/main`.
+    upstream_ref: Option<String>,
+}
+
+impl RefCache {
+    /// Modification times of every file that can change the branch, its upstream or the
+    /// ahead/behind counts; `push -u` and `branch -u` only touch the config.
+    fn key(&mut self, git: &str, repo: &Path) -> Option<Vec<Option<SystemTime>>> {
+        if self.dirs.is_none() {
+            let out = git_stdout(git, repo, &["rev-parse", "--git-dir", "--git-common-dir"])?;
+            let mut lines = out.lines().map(|l| repo.join(l.trim()));
+            self.dirs = Some((lines.next()?, lines.next()?));
+        }
+        let (git_dir, common_dir) = self.dirs.as_ref()?;
+        let mtime = |path: PathBuf| fs::metadata(path).and_then(|m| m.modified()).ok();
+
+        let head = git_dir.join("HEAD");
+        let mut key = vec![
+            mtime(head.clone()),
+            mtime(common_dir.join("packed-refs")),
+            mtime(common_dir.join("config")),
+            mtime(git_dir.join("config.worktree")),
+        ];
+        if let Some(head_ref) = fs::read_to_string(&head)
+            .ok()
+            .and_then(|h| h.strip_prefix("ref:").map(|r| r.trim().to_string()))
+        {
+            key.push(mtime(common_dir.join(head_ref)));
+        }
+        if let Some(upstream_ref) = &self.upstream_ref {
+            key.push(mtime(common_dir.join(upstream_ref)));
+        }
+        Some(key)
+    }
+
+    fn refresh(&mut self, git: &str, repo: &Path) {
+        let key = self.key(git, repo);
+        if key.is_some() && key == self.key {
+            return;
+        }
+
+        // Independent lookups, so run them side by side.
+        let (branch, upstream_ref, counts) = thread::scope(|s| {
+            let branch = s.spawn(|| git_stdout(git, repo, &["rev-parse", "--abbrev-ref", "HEAD"]));
+            let upstream_ref = s.spawn(|| {
+                git_stdout(
+                    git,
+                    repo,
+                    &["rev-parse", "--symbolic-full-name", "@{upstream}"],
+                )
+            });
+            let counts = git_stdout(
+                git,
+                repo,
+                &["rev-list", "--left-right", "--count", "HEAD...@{upstream}"],
+            );
+            (
+                branch.join().ok().flatten(),
+                upstream_ref.join().ok().flatten(),
+                counts,
+            )
+        });
+
+        self.branch = branch.unwrap_or_else(|| "?".into());
+        self.upstream = upstream_ref
+            .as_deref()
+            .zip(counts)
+            .and_then(|(full, counts)| {
+                let name = full
+                    .strip_prefix("refs/remotes/")
+                    .or_else(|| full.strip_prefix("refs/heads/"))
+                    .unwrap_or(full)
+                    .to_string();
+                let mut counts = counts.split_whitespace().map(|n| n.parse().unwrap_or(0));
+                Some(Upstream {
+                    name,
+                    ahead: counts.next()?,
+                    behind: counts.next()?,
+                })
+            });
+        self.upstream_ref = upstream_ref;
+        // The key depends on the upstream, so take it again now that it is known.
+        self.key = self.key(git, repo);
+    }
+
+    fn git_dir(&self) -> Option<&Path> {
+        self.dirs.as_ref().map(|(git_dir, _)| git_dir.as_path())
+    }
+}
+
+/// A commit of the COMMITS list.
+#[derive(Clone, Debug)]
+pub struct Commit {
+    /// Full hash.
+    pub hash: String,
+    /// Abbreviated hash.
+    pub short: String,
+    /// Author name.
+    pub author: String,
+    /// Author date, in the format the log was asked for.
+    pub date: String,
+    /// First line of the message.
+    pub subject: String,
+    /// Branches, tags and remote heads pointing at the commit.
+    pub refs: Vec<CommitRef>,
+    /// Full hashes of the parents, first parent first.
+    pub parents: Vec<String>,
+}
+
+/// What a ref pointing at a commit is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefKind {
+    /// The checked-out branch, or `HEAD` itself when detached.
+    Head,
+    /// A local branch.
+    Branch,
+    /// A remote-tracking branch.
+    Remote,
+    /// A tag.
+    Tag,
+}
+
+/// A ref pointing at a commit.
+#[derive(Clone, Debug)]
+pub struct CommitRef {
+    /// What the ref is.
+    pub kind: RefKind,
+    /// Short name: `main`, `origin/main`, `v1.2.0`.
+    pub name: String,
+}
+
+const COMMIT_FORMAT: &str = "--format=%H%x1f%h%x1f%an%x1f%ad%x1f%D%x1f%P%x1f%s";
+
+/// Parses `%D` under `--decorate=full`, e.g. `HEAD -> refs/heads/main, tag: refs/tags/v1`.
+/// Refs other than branches, remotes and tags (the stash, notes) are left out.
+fn parse_decorations(decorations: &str) -> Vec<CommitRef> {
+    decorations
+        .split(", ")
+        .filter_map(|item| {
+            let (kind, name) = if let Some(branch) = item.strip_prefix("HEAD -> ") {
+                (
+                    RefKind::Head,
+                    branch.strip_prefix("refs/heads/").unwrap_or(branch),
+                )
+            } else if item == "HEAD" {
+                (RefKind::Head, item)
+            } else if let Some(tag) = item.strip_prefix("tag: ") {
+                (RefKind::Tag, tag.strip_prefix("refs/tags/").unwrap_or(tag))
+            } else if let Some(branch) = item.strip_prefix("refs/heads/") {
+                (RefKind::Branch, branch)
+            } else if let Some(remote) = item.strip_prefix("refs/remotes/") {
+                (RefKind::Remote, remote)
+            } else {
+                return None;
+            };
+            Some(CommitRef {
+                kind,
+                name: name.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// One `git stash list` entry.
+#[derive(Clone, Debug)]
+pub struct Stash {
+    /// `stash@{0}` and so on.
+    pub name: String,
+    /// Relative age, e.g. `2 days ago`.
+    pub age: String,
+    /// `WIP on main: 1a2b3c subject` or the message given to `git stash push -m`.
+    pub subject: String,
+}
+
+/// Stashes as `git stash list` lists them, newest first.
+pub fn stash_list(git: &str, repo: &Path) -> Vec<Stash> {
+    git_stdout(git, repo, &["stash", "list", "--format=%gd%x1f%cr%x1f%gs"])
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            Some(Stash {
+                name: fields.next()?.to_string(),
+                age: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// A branch of the BRANCHES list.
+pub struct Branch {
+    /// `main`, or `origin/main` for a remote-tracking branch.
+    pub name: String,
+    /// Whether it is a remote-tracking branch.
+    pub remote: bool,
+    /// The branch HEAD points at.
+    pub current: bool,
+    /// The branch it tracks, e.g. `origin/main`.
+    pub upstream: Option<String>,
+    /// Commits it has that its upstream lacks.
+    pub ahead: u32,
+    /// Commits its upstream has that it lacks.
+    pub behind: u32,
+    /// The upstream is configured but no longer exists.
+    pub gone: bool,
+    /// Subject of the commit it points at.
+    pub subject: String,
+}
+
+/// Local branches, then remote-tracking ones, each sorted by name. The `origin/HEAD`
+/// aliases are left out.
+pub fn load_branches(git: &str, repo: &Path) -> Vec<Branch> {
+    git_stdout(
+        git,
+        repo,
+        &[
+            "for-each-ref",
+            "--format=%(HEAD)%1f%(refname)%1f%(refname:short)%1f%(upstream:short)%1f%(upstream:track,nobracket)%1f%(subject)",
+            "refs/heads",
+            "refs/remotes",
+        ],
+    )
+    .unwrap_or_default()
+    .lines()
+    .filter_map(|line| {
+        let mut fields = line.split('\x1f');
+        let head = fields.next()?;
+        let refname = fields.next()?;
+        let name = fields.next()?;
+        let upstream = fields.next()?;
+        let track = fields.next()?;
+        let subject = fields.next().unwrap_or("");
+        if refname.starts_with("refs/remotes/") && refname.ends_with("/HEAD") {
+            return None;
+        }
+        let count = |word: &str| {
+            track
+                .split(", ")
+                .find_map(|part| part.strip_prefix(word)?.trim().parse().ok())
+                .unwrap_or(0)
+        };
+        Some(Branch {
+            name: name.to_string(),
+            remote: refname.starts_with("refs/remotes/"),
+            current: head == "*",
+            upstream: (!upstream.is_empty()).then(|| upstream.to_string()),
+            ahead: count("ahead"),
+            behind: count("behind"),
+            gone: track == "gone",
+            subject: subject.to_string(),
+        })
+    })
+    .collect()
+}
+
+/// A configured remote.
+pub struct Remote {
+    /// `origin` and so on.
+    pub name: String,
+    /// Where it fetches from.
+    pub fetch_url: String,
+    /// Differs from `fetch_url` only when `remote.<name>.pushurl` is set.
+    pub push_url: String,
+}
+
+/// Remotes in the order `git remote -v` lists them.
+pub fn remote_list(git: &str, repo: &Path) -> Vec<Remote> {
+    let mut remotes: Vec<Remote> = Vec::new();
+    for line in git_stdout(git, repo, &["remote", "-v"])
+        .unwrap_or_default()
+        .lines()
+    {
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(url), Some(kind)) = (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let index = match remotes.iter().position(|r| r.name == name) {
+            Some(i) => i,
+            None => {
+                remotes.push(Remote {
+                    name: name.to_string(),
+                    fetch_url: url.to_string(),
+                    push_url: url.to_string(),
+                });
+                remotes.len() - 1
+            }
+        };
+        if kind == "(push)" {
+            remotes[index].push_url = url.to_string();
+        } else {
+            remotes[index].fetch_url = url.to_string();
+        }
+    }
+    remotes
+}
+
+/// A worktree of the repository.
+pub struct Worktree {
+    /// Its directory.
+    pub path: PathBuf,
+    /// The checked-out branch; `None` when HEAD is detached or the worktree is bare.
+    pub branch: Option<String>,
+    /// The commit it has checked out; empty for a bare repository.
+    pub head: String,
+    /// Whether it is the bare repository itself.
+    pub bare: bool,
+    /// Whether `git worktree lock` keeps it from being pruned or removed.
+    pub locked: bool,
+    /// Its directory is gone; `git worktree prune` drops it.
+    pub prunable: bool,
+}
+
+/// Worktrees as `git worktree list --porcelain` lists them, the main one first.
+pub fn worktree_list(git: &str, repo: &Path) -> Vec<Worktree> {
+    parse_worktrees(
+        &git_stdout(git, repo, &["worktree", "list", "--porcelain"]).unwrap_or_default(),
+    )
+}
+
+fn parse_worktrees(text: &str) -> Vec<Worktree> {
+    let mut worktrees: Vec<Worktree> = Vec::new();
+    for line in text.lines() {
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        if key == "worktree" {
+            worktrees.push(Worktree {
+                path: PathBuf::from(value),
+                branch: None,
+                head: String::new(),
+                bare: false,
+                locked: false,
+                prunable: false,
+            });
+            continue;
+        }
+        let Some(worktree) = worktrees.last_mut() else {
+            continue;
+        };
+        match key {
+            "HEAD" => worktree.head = value.to_string(),
+            "branch" => worktree.branch = Some(value.trim_start_matches("refs/heads/").to_string()),
+            "bare" => worktree.bare = true,
+            "locked" => worktree.locked = true,
+            "prunable" => worktree.prunable = true,
+            _ => {}
+        }
+    }
+    worktrees
+}
+
+/// Files `stash` changes relative to the commit it was made on.
+pub fn stash_paths(git: &str, repo: &Path, stash: &str) -> Vec<String> {
+    git_stdout(
+        git,
+        repo,
+        &["diff", "--name-only", &format!("{}^1", stash), stash],
+    )
+    .map(|out| out.lines().map(str::to_string).collect())
+    .unwrap_or_default()
+}
+
+/// The name `path` had in each commit `git log --follow` lists for it, newest first,
+/// so commits from before a rename can be shown by the name they used.
+pub fn followed_paths(git: &str, repo: &Path, path: &str) -> Vec<(String, String)> {
+    git_stdout(
+        git,
+        repo,
+        &[
+            "log",
+            "--follow",
+            "--name-only",
+            "--format=%x1e%H",
+            "--",
+            path,
+        ],
+    )
+    .unwrap_or_default()
+    .split('\x1e')
+    .filter_map(|entry| {
+        let mut lines = entry.lines().map(str::trim).filter(|l| !l.is_empty());
+        Some((lines.next()?.to_string(), lines.next_back()?.to_string()))
+    })
+    .collect()
+}
+
+/// Runs `git log` with `args` appended and parses one `Commit` per line.
+pub fn load_commits(git: &str, repo: &Path, args: &[String]) -> Result<Vec<Commit>, String> {
+    let output = git_command(git)
+        .arg("log")
+        .arg(COMMIT_FORMAT)
+        .arg("--date=short")
+        .arg("--decorate=full")
+        .args(args)
+        .current_dir(repo)
+        .output()
+        .map_err(|e| format!("failed to run git log: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.trim().to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            Some(Commit {
+                hash: fields.next()?.to_string(),
+                short: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                refs: parse_decorations(fields.next()?),
+                parents: fields
+                    .next()?
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect(),
+                subject: fields.next().unwrap_or("").to_string(),
+            })
+        })
+        .collect())
+}
+
+/// The signature of one commit as `git log` reports it with `%G?`.
+#[derive(Clone, Debug)]
+pub struct SignatureCheck {
+    /// Abbreviated hash.
+    pub short: String,
+    /// Author name.
+    pub author: String,
+    /// First line of the message.
+    pub subject: String,
+    /// `%G?`: `G` good, `U` good but the key is not trusted, `N` none, and `B`, `X`, `Y`,
+    /// `R`, `E` for bad, expired, made by an expired or revoked key, or uncheckable.
+    pub status: char,
+    /// `%GS`, empty when there is no signature.
+    pub signer: String,
+}
+
+impl SignatureCheck {
+    /// What `status` means, for the report.
+    pub fn describe(&self) -> &'static str {
+        match self.status {
+            'G' => "good",
+            'U' => "good, untrusted key",
+            'N' => "unsigned",
+            'B' => "BAD signature",
+            'X' => "expired signature",
+            'Y' => "signed with an expired key",
+            'R' => "signed with a revoked key",
+            'E' => "cannot be checked (missing key)",
+            _ => "unknown",
+        }
+    }
+}
+
+/// Checks the signature of every commit `git log <args>` lists, verifying with gpg or ssh.
+pub fn verify_signatures(
+    git: &str,
+    repo: &Path,
+    args: &[String],
+) -> Result<Vec<SignatureCheck>, String> {
+    let output = git_command(git)
+        .arg("log")
+        .arg("--format=%h%x1f%an%x1f%G?%x1f%GS%x1f%s")
+        .args(args)
+        .current_dir(repo)
+        .output()
+        .map_err(|e| format!("failed to run git log: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            Some(SignatureCheck {
+                short: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                status: fields.next()?.chars().next()?,
+                signer: fields.next()?.to_string(),
+                subject: fields.next().unwrap_or("").to_string(),
+            })
+        })
+        .collect())
+}
+
+/// A line `git grep` matched.
+#[derive(Clone, Debug)]
+pub struct GrepMatch {
+    /// Path of the file, relative to the repository root.
+    pub path: String,
+    /// Line number, from 1.
+    pub line: u32,
+    /// The matching line.
+    pub text: String,
+}
+
+/// The pattern of `git grep` arguments, as far as it can be highlighted.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GrepPattern {
+    /// The first `-e` pattern, or else the first argument that is no option or option
+    /// value; `None` when the patterns come from a `-f` file.
+    pub text: Option<String>,
+    /// `-F`: the pattern is a literal string, not a regex.
+    pub fixed: bool,
+}
+
+/// Short `git grep` options that take a value, attached or as the next argument.
+const GREP_SHORT_VALUES: &str = "ABCefm";
+
+/// Long `git grep` options that take a value as the next argument unless given `=value`.
+const GREP_LONG_VALUES: [&str; 7] = [
+    "--after-context",
+    "--before-context",
+    "--context",
+    "--max-depth",
+    "--max-count",
+    "--threads",
+    "--file",
+];
+
+/// Finds the pattern in the arguments of `git grep` before `--`; `None` when there is
+/// none, neither as an argument nor through `-e` or `-f`.
+pub fn grep_pattern(args: &[String]) -> Option<GrepPattern> {
+    let mut positional = None;
+    let mut expression = None;
+    let mut from_file = false;
+    let mut fixed = false;
+    let mut args = args.iter().take_while(|a| *a != "--");
+    while let Some(arg) = args.next() {
+        if let Some(long) = arg.strip_prefix("--") {
+            let (name, attached) = match long.split_once('=') {
+                Some((name, _)) => (name, true),
+                None => (long, false),
+            };
+            match name {
+                "fixed-strings" => fixed = true,
+                "basic-regexp" | "extended-regexp" | "perl-regexp" => fixed = false,
+                "file" => from_file = true,
+                _ => {}
+            }
+            if !attached && GREP_LONG_VALUES.contains(&arg.as_str()) {
+                args.next();
+            }
+        } else if let Some(flags) = arg.strip_prefix('-').filter(|f| !f.is_empty()) {
+            for (i, flag) in flags.char_indices() {
+                match flag {
+                    'F' => fixed = true,
+                    'G' | 'E' | 'P' => fixed = false,
+                    _ => {}
+                }
+                if GREP_SHORT_VALUES.contains(flag) {
+                    let rest = &flags[i + 1..];
+                    let value = if rest.is_empty() {
+                        args.next().cloned()
+                    } else {
+                        Some(rest.to_string())
+                    };
+                    match flag {
+                        'e' if expression.is_none() => expression = value,
+                        'f' => from_file = true,
+                        _ => {}
+                    }
+                    break;
+                }
+            }
+        } else if positional.is_none() {
+            positional = Some(arg.clone());
+        }
+    }
+    let text = if expression.is_some() || from_file {
+        expression
+    } else {
+        Some(positional?)
+    };
+    Some(GrepPattern { text, fixed })
+}
+
+/// Runs `git grep -n` over the worktree. No matches is an empty list, not an error.
+pub fn grep_worktree(git: &str, repo: &Path, args: &[String]) -> Result<Vec<GrepMatch>, String> {
+    let output = git_command(git)
+        .arg("grep")
+        .arg("-n")
+        .arg("-z")
+        .arg("-I")
+        .arg("--no-color")
+        .args(args)
+        .current_dir(repo)
+        .output()
+        .map_err(|e| format!("failed to run git grep: {}", e))?;
+
+    match output.status.code() {
+        Some(0) => {}
+        Some(1) => return Ok(Vec::new()),
+        _ => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(stderr.trim().to_string());
+        }
+    }
+
+    Ok(parse_grep_output(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parses the `path NUL line NUL text` lines of `git grep -n -z`. The path ends at its NUL
+/// even when it holds a `:` or a newline; `--` separators and lines without a number,
+/// such as those of `--count`, are skipped.
+fn parse_grep_output(output: &str) -> Vec<GrepMatch> {
+    let mut matches = Vec::new();
+    let mut rest = output;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("--\n") {
+            rest = after;
+            continue;
+        }
+        let Some((path, after)) = rest.split_once('\0') else {
+            break;
+        };
+        let (line, after) = after.split_once('\n').unwrap_or((after, ""));
+        rest = after;
+        if let Some((number, text)) = line.split_once('\0')
+            && let Ok(number) = number.parse()
+        {
+            matches.push(GrepMatch {
+                path: path.to_string(),
+                line: number,
+                text: text.to_string(),
+            });
+        }
+    }
+    matches
+}
+
+/// Markers reported by `introduced_todos`.
+pub const TODO_MARKERS: [&str; 3] = ["TODO", "FIXME", "HACK"];
+
+/// Whether `text` has one of `TODO_MARKERS` as a whole word, as `git grep -w` matches
+/// them: `HACK:` counts, `HACKATHON` does not.
+pub fn has_todo_marker(text: &str) -> bool {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    TODO_MARKERS.iter().any(|marker| {
+        text.match_indices(marker).any(|(i, _)| {
+            !text[..i].chars().next_back().is_some_and(is_word)
+                && !text[i + marker.len()..].chars().next().is_some_and(is_word)
+        })
+    })
+}
+
+pub(crate) fn git_stdout(git: &str, repo: &Path, args: &[&str]) -> Option<String> {
+    git_command(git)
+        .args(args)
+        .current_dir(repo)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+}
+
+/// Whether the repository requires DCO sign-off on commits, set per repository with
+/// `git config simple-git-tui.requireSignoff true`.
+pub fn signoff_required(git: &str, repo: &Path) -> bool {
+    git_stdout(
+        git,
+        repo,
+        &["config", "--bool", "simple-git-tui.requireSignoff"],
+    )
+    .is_some_and(|v| v == "true")
+}
+
+/// The trailer `git commit --signoff` adds for the active committer identity.
+pub fn signoff_trailer(git: &str, repo: &Path) -> Option<String> {
+    // `Name <email> 1700000000 +0000`
+    let ident = git_stdout(git, repo, &["var", "GIT_COMMITTER_IDENT"])?;
+    let end = ident.rfind('>')?;
+    Some(format!("Signed-off-by: {}", &ident[..=end]))
+}
+
+/// Fetch URL of `remote`, e.g. `git@github.com:owner/repo.git`.
+pub fn remote_url(git: &str, repo: &Path, remote: &str) -> Option<String> {
+    git_stdout(git, repo, &["remote", "get-url", remote]).filter(|url| !url.is_empty())
+}
+
+/// `<short hash> <subject>` of HEAD.
+pub fn head_summary(git: &str, repo: &Path) -> Option<String> {
+    git_stdout(git, repo, &["log", "-1", "--format=%h %s"]).filter(|s| !s.is_empty())
+}
+
+/// Full hash of HEAD; `None` in a repository without commits.
+pub fn head_commit(git: &str, repo: &Path) -> Option<String> {
+    git_stdout(git, repo, &["rev-parse", "HEAD"])
+}
+
+/// Full message of the `HEAD` commit, for amending it.
+pub fn head_message(git: &str, repo: &Path) -> Option<String> {
+    git_stdout(git, repo, &["log", "-1", "--format=%B"])
+}
+
+/// Writes a commit message into the git dir for `git commit -F`, returning its path
+/// relative to `repo`.
+pub fn write_commit_message(git: &str, repo: &Path, message: &str) -> io::Result<String> {
+    let path = git_stdout(
+        git,
+        repo,
+        &["rev-parse", "--git-path", "SIMPLE_GIT_TUI_EDITMSG"],
+    )
+    .ok_or_else(|| io::Error::other("not a git repository"))?;
+    fs::write(repo.join(&path), message)?;
+    Ok(path)
+}
+
+/// Records uncommitted tracked changes as a dangling stash commit without touching the worktree.
+pub fn snapshot_worktree(git: &str, repo: &Path) -> Option<String> {
+    git_stdout(git, repo, &["stash", "create"]).filter(|sha| !sha.is_empty())
+}
+
+/// Name of the remote's default branch, e.g. `main`: where `origin/HEAD` points, else
+/// `init.defaultBranch` when a local branch of that name exists.
+pub fn default_branch(git: &str, repo: &Path) -> Option<String> {
+    if let Some(head) = git_stdout(
+        git,
+        repo,
+        &[
+            "symbolic-ref",
+            "--quiet",
+            "--short",
+            "refs/remotes/origin/HEAD",
+        ],
+    ) {
+        return head.strip_prefix("origin/").map(str::to_string);
+    }
+    let name = git_stdout(git, repo, &["config", "--get", "init.defaultBranch"])?;
+    git_stdout(
+        git,
+        repo,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{}", name),
+        ],
+    )?;
+    Some(name)
+}
+
+/// Every file tracked at `HEAD` plus staged additions, as repository-relative paths.
+pub fn tracked_files(git: &str, repo: &Path) -> Vec<String> {
+    git_stdout(git, repo, &["ls-files", "-z"])
+        .map(|out| {
+            out.split('\0')
+                .filter(|p| !p.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Where the current branch forked from `base` (`origin/<base>` when that exists), falling
+/// back to the upstream: the start of what a pull request would show.
+pub fn branch_fork_point(git: &str, repo: &Path, base: Option<&str>) -> Option<String> {
+    base.into_iter()
+        .flat_map(|b| [format!("origin/{}", b), b.to_string()])
+        .chain(["@{upstream}".to_string()])
+        .find_map(|rev| git_stdout(git, repo, &["merge-base", "HEAD", &rev]))
+}
+
+/// Files changed since the branch forked from `base`; see [`branch_fork_point`].
+pub fn branch_changed_files(git: &str, repo: &Path, base: Option<&str>) -> Option<Vec<String>> {
+    let fork = branch_fork_point(git, repo, base)?;
+    let names = git_stdout(git, repo, &["diff", "--name-only", &fork, "HEAD"])?;
+    Some(names.lines().map(str::to_string).collect())
+}
+
+/// Full messages of the commits since the branch forked from `base`, oldest first.
+pub fn branch_commit_messages(git: &str, repo: &Path, base: Option<&str>) -> Option<Vec<String>> {
+    let fork = branch_fork_point(git, repo, base)?;
+    let log = git_stdout(
+        git,
+        repo,
+        &[
+            "log",
+            "--reverse",
+            "--format=%B%x00",
+            &format!("{}..HEAD", fork),
+        ],
+    )?;
+    Some(
+        log.split('\0')
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty())
+            .collect(),
+    )
+}
+
+/// What merging a branch into `HEAD` would do, worked out without touching the worktree.
+#[derive(Clone, Debug)]
+pub struct MergePreview {
+    /// Paths that would conflict.
+    pub conflicts: Vec<String>,
+    /// `Auto-merging ...` and `CONFLICT ...` lines.
+    pub messages: Vec<String>,
+    /// `git diff` from `HEAD` to the merge result, conflict markers included.
+    pub diff: Vec<String>,
+}
+
+/// Merges `branch` into `HEAD` in the object database only, with `merge-tree --write-tree`
+/// (git 2.38 or newer), and diffs the result against `HEAD`.
+pub fn merge_preview(git: &str, repo: &Path, branch: &str) -> Result<MergePreview, String> {
+    let output = git_command(git)
+        .args(["merge-tree", "--write-tree", "--name-only", "HEAD", branch])
+        .current_dir(repo)
+        .output()
+        .map_err(|e| format!("failed to run git merge-tree: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Exit code 1 with a tree on stdout means conflicts; anything else is an error.
+    let tree = stdout
+        .lines()
+        .next()
+        .filter(|t| !t.is_empty() && t.bytes().all(|b| b.is_ascii_hexdigit()));
+    let Some(tree) = tree.filter(|_| matches!(output.status.code(), Some(0 | 1))) else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(if output.status.code() == Some(129) {
+            "merge preview needs git 2.38 or newer (merge-tree --write-tree)".to_string()
+        } else {
+            stderr.trim().to_string()
+        });
+    };
+    let mut sections = stdout.lines().skip(1);
+    let conflicts: Vec<String> = sections
+        .by_ref()
+        .take_while(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect();
+    let messages: Vec<String> = sections.map(str::to_string).collect();
+    let diff = git_command(git)
+        .args(["diff", "HEAD", tree])
+        .current_dir(repo)
+        .output()
+        .map_err(|e| format!("failed to run git diff: {}", e))?;
+    Ok(MergePreview {
+        conflicts,
+        messages,
+        diff: String::from_utf8_lossy(&diff.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect(),
+    })
+}
+
+/// Whether `name` is allowed as a branch name, per `git check-ref-format --branch`.
+pub fn valid_branch_name(git: &str, repo: &Path, name: &str) -> bool {
+    git_stdout(git, repo, &["check-ref-format", "--branch", name]).is_some()
+}
+
+/// Whether the local branch `name` exists.
+pub fn local_branch_exists(git: &str, repo: &Path, name: &str) -> bool {
+    git_stdout(
+        git,
+        repo,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{}", name),
+        ],
+    )
+    .is_some()
+}
+
+/// Picks the commit the current branch diverged from: its upstream, then the default branch.
+pub fn branch_base(git: &str, repo: &Path) -> Option<String> {
+    ["@{upstream}".to_string(), "origin/HEAD".to_string()]
+        .into_iter()
+        .chain(default_branch(git, repo))
+        .find_map(|rev| git_stdout(git, repo, &["merge-base", "HEAD", &rev]))
+}
+
+/// Lists TODO/FIXME/HACK markers on lines added since `base`, including uncommitted edits
+/// and untracked files that are not ignored.
+pub fn introduced_todos(git: &str, repo: &Path, base: &str) -> Result<Vec<GrepMatch>, String> {
+    let output = git_command(git)
+        .arg("diff")
+        .arg("--no-color")
+        .arg("-U0")
+        .arg(base)
+        .current_dir(repo)
+        .output()
+        .map_err(|e| format!("failed to run git diff: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.trim().to_string());
+    }
+
+    let raw: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|s| s.to_owned())
+        .collect();
+    let mut todos: Vec<GrepMatch> = match ParsedDiff::parse(&raw) {
+        Some(diff) => diff
+            .lines
+            .iter()
+            .filter(|l| l.kind == LineKind::Added && has_todo_marker(&l.text[1..]))
+            .filter_map(|l| {
+                Some(GrepMatch {
+                    path: diff.files.get(l.file?)?.clone(),
+                    line: l.new_no?,
+                    text: l.text[1..].to_string(),
+                })
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    // `git diff` leaves untracked files out; all of their lines are new.
+    let untracked = git_stdout(
+        git,
+        repo,
+        &["ls-files", "-z", "--others", "--exclude-standard"],
+    )
+    .unwrap_or_default();
+    for path in untracked.split('\0').filter(|p| !p.is_empty()) {
+        let Ok(bytes) = fs::read(repo.join(path)) else {
+            continue;
+        };
+        // git's own test for binary files.
+        if bytes.iter().take(8000).any(|&b| b == 0) {
+            continue;
+        }
+        todos.extend(
+            String::from_utf8_lossy(&bytes)
+                .lines()
+                .zip(1..)
+                .filter(|(text, _)| has_todo_marker(text))
+                .map(|(text, line)| GrepMatch {
+                    path: path.to_string(),
+                    line,
+                    text: text.to_string(),
+                }),
+        );
+    }
+    Ok(todos)
+}
+
+/// One line of `git diff --numstat`.
+#[derive(Clone, Debug)]
+pub struct NumstatEntry {
+    /// `None` for binary files.
+    pub added: Option<u32>,
+    /// `None` for binary files.
+    pub deleted: Option<u32>,
+    /// Path of the file, the new one for a rename.
+    pub path: String,
+}
+
+/// `git diff --numstat` of what a commit would record: the index, or with `all`
+/// every tracked change against HEAD.
+pub fn commit_numstat(git: &str, repo: &Path, all: bool) -> Result<Vec<NumstatEntry>, String> {
+    let base = if all { "HEAD" } else { "--cached" };
+    let output = git_command(git)
+        .args(["diff", base, "--numstat"])
+        .current_dir(repo)
+        .output()
+        .map_err(|e| format!("failed to run git diff --numstat: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.trim().to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            let added = parts.next()?.parse().ok();
+            let deleted = parts.next()?.parse().ok();
+            Some(NumstatEntry {
+                added,
+                deleted,
+                path: parts.next()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// The patch a commit would record, parsed; see `commit_numstat` for `all`.
+pub fn commit_patch(git: &str, repo: &Path, all: bool) -> Option<ParsedDiff> {
+    let base = if all { "HEAD" } else { "--cached" };
+    let output = git_command(git)
+        .args(["diff", base, "--no-color", "-U0"])
+        .current_dir(repo)
+        .output()
+        .ok()?;
+    let raw: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|s| s.to_owned())
+        .collect();
+    ParsedDiff::parse(&raw)
+}
+
+/// `git diff` of `path` against the index, or with `staged` of the index against HEAD,
+/// uncolored so its hunks can be applied back.
+pub fn file_patch(git: &str, repo: &Path, path: &str, staged: bool) -> Result<Vec<String>, String> {
+    let output = git_command(git)
+        .arg("diff")
+        .args(staged.then_some("--cached"))
+        .args(["--no-color", "--no-ext-diff", "--", path])
+        .current_dir(repo)
+        .output()
+        .map_err(|e| format!("failed to run git diff: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+/// Applies `patch` to the index alone, the way `git add -p` stages the hunks it is given;
+/// `reverse` takes it back out of the index.
+pub fn apply_to_index(git: &str, repo: &Path, patch: &str, reverse: bool) -> Result<(), String> {
+    let mut child = git_command(git)
+        .args(["apply", "--cached"])
+        .args(reverse.then_some("--reverse"))
+        .arg("-")
+        .current_dir(repo)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run git apply: {}", e))?;
+    // git apply reads the whole patch before writing anything, so this cannot block.
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(patch.as_bytes())
+        .map_err(|e| format!("cannot send the patch to git apply: {}", e))?;
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("failed to run git apply: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Reads the `lfs` setting of a command; unknown values mean no LFS step.
+pub fn parse_lfs_mode(opt: Option<&String>, options: LfsOptions) -> LfsMode {
+    match opt.map(|s| s.as_str()) {
+        Some("fetch") => LfsMode::Fetch(options),
+        Some("pull") => LfsMode::Pull(options),
+        Some("push") => LfsMode::Push(options),
+        _ => LfsMode::None,
+    }
+}
+
+/// Where one submodule stands, from `git submodule status --recursive` and its own status.
+#[derive(Clone, Debug)]
+pub struct SubmoduleState {
+    /// Path of the submodule, relative to the superproject's root.
+    pub path: String,
+    /// Abbreviated commit checked out in the submodule.
+    pub head: String,
+    /// Whether `git submodule update --init` has checked it out.
+    pub initialized: bool,
+    /// The checked-out commit differs from the one the superproject records.
+    pub moved: bool,
+    /// Changed and untracked files inside the submodule.
+    pub changes: usize,
+    /// Commits its branch has that its upstream lacks.
+    pub ahead: usize,
+    /// Commits its upstream has that its branch lacks.
+    pub behind: usize,
+}
+
+impl SubmoduleState {
+    /// Whether it has changed or untracked files.
+    pub fn is_dirty(&self) -> bool {
+        self.changes > 0
+    }
+}
+
+/// States of all submodules, nested ones included. Runs one `git status` per submodule,
+/// so it belongs on a worker thread.
+pub fn submodule_states(git: &str, repo: &Path) -> Vec<SubmoduleState> {
+    let Some(listing) = git_stdout(git, repo, &["submodule", "status", "--recursive"]) else {
+        return Vec::new();
+    };
+    listing
+        .lines()
+        .filter_map(|line| {
+            // `<flag><sha> <path> (<describe>)`; the flag is ' ', '-', '+' or 'U'.
+            // The first line's ' ' is lost when the output is trimmed.
+            let (flag, rest) = match line.chars().next()? {
+                c @ ('-' | '+' | 'U' | ' ') => (c, &line[1..]),
+                _ => (' ', line),
+            };
+            let mut fields = rest.split_whitespace();
+            let sha = fields.next()?;
+            let path = fields.next()?.to_string();
+            let mut state = SubmoduleState {
+                head: sha.chars().take(7).collect(),
+                initialized: flag != '-',
+                moved: flag == '+',
+                changes: 0,
+                ahead: 0,
+                behind: 0,
+                path,
+            };
+            if state.initialized {
+                let status = git_stdout(
+                    git,
+                    &repo.join(&state.path),
+                    &["status", "--porcelain=v2", "--branch"],
+                )
+                .unwrap_or_default();
+                for line in status.lines() {
+                    if let Some(ab) = line.strip_prefix("# branch.ab ") {
+                        let mut counts = ab.split_whitespace().map(|n| n[1..].parse().unwrap_or(0));
+                        state.ahead = counts.next().unwrap_or(0);
+                        state.behind = counts.next().unwrap_or(0);
+                    } else if !line.starts_with('#') {
+                        state.changes += 1;
+                    }
+                }
+            }
+            Some(state)
+        })
+        .collect()
+}
+
+/// Branch, upstream and changed files of `repo`, limited to `scope` when set.
+pub fn load_repo_status(
+    git: &str,
+    repo: &Path,
+    scope: Option<&str>,
+    cache: &mut RefCache,
+) -> RepoStatus {
+    // Ref lookups run next to `git status`; the result is assembled once both finish.
+    let output = thread::scope(|s| {
+        s.spawn(|| cache.refresh(git, repo));
+        git_command(git)
+            .args(["status", "--porcelain=v2", "-z"])
+            .args(scope.map(|s| ["--", s]).into_iter().flatten())
+            .current_dir(repo)
+            .output()
+    });
+    let mut status = RepoStatus {
+        branch: cache.branch.clone(),
+        upstream: cache.upstream.clone(),
+        ..RepoStatus::default()
+    };
+
+    if let Ok(o) = output
+        && o.status.success()
+    {
+        status.files = parse_porcelain_v2(&String::from_utf8_lossy(&o.stdout));
+        for file in &status.files {
+            if file.status == "??" {
+                status.untracked += 1;
+                continue;
+            }
+            let mut chars = file.status.chars();
+            if chars.next() != Some(' ') {
+                status.staged += 1;
+            }
+            if chars.next() != Some(' ') {
+                status.unstaged += 1;
+            }
+        }
+    }
+    status.sequence = cache
+        .git_dir()
+        .and_then(|dir| pick_sequence(git, repo, dir));
+    status.operation = cache.git_dir().and_then(repo_operation);
+
+    status
+}
+
+/// Entries of `git status --porcelain=v2 -z`. Paths come NUL-terminated and unquoted;
+/// statuses are turned into the two letters of the short format.
+fn parse_porcelain_v2(text: &str) -> Vec<RepoFile> {
+    let mut files = Vec::new();
+    let mut records = text.split('\0');
+    while let Some(record) = records.next() {
+        let (kind, rest) = record.split_once(' ').unwrap_or((record, ""));
+        // Fields before the path: XY, submodule state, modes and object names, and for
+        // renames the similarity score.
+        let fields = match kind {
+            "1" => 7,
+            "2" => 8,
+            "u" => 9,
+            "?" => {
+                files.push(RepoFile {
+                    status: "??".into(),
+                    path: rest.to_string(),
+                    orig_path: None,
+                });
+                continue;
+            }
+            _ => continue,
+        };
+        let mut parts = rest.splitn(fields + 1, ' ');
+        let xy = parts.next().unwrap_or_default().replace('.', " ");
+        let Some(path) = parts.nth(fields - 1) else {
+            continue;
+        };
+        let orig_path = if kind == "2" {
+            records.next().map(String::from)
+        } else {
+            None
+        };
+        files.push(RepoFile {
+            status: xy,
+            path: path.to_string(),
+            orig_path,
+        });
+    }
+    files
+}
+
+/// Splits a command line into arguments, honouring double quotes and backslash escapes.
+/// `""` is an empty argument.
+pub fn parse_args_line(s: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut quoted = false;
+    let mut escape = false;
+
+    for c in s.chars() {
+        if escape {
+            current.push(c);
+            escape = false;
+        } else if c == '\\' && in_quotes {
+            escape = true;
+        } else if c == '"' {
+            in_quotes = !in_quotes;
+            quoted = true;
+        } else if c.is_whitespace() && !in_quotes {
+            if !current.is_empty() || quoted {
+                args.push(std::mem::take(&mut current));
+            }
+            quoted = false;
+        } else {
+            current.push(c);
+        }
+    }
+
+    if !current.is_empty() || quoted {
+        args.push(current);
+    }
+
+    args
+}
+
+/// Output of a command run through `run_git_with_lfs` or `run_step`.
+pub struct CommandResult {
+    /// What the command printed to stdout, for LOG.
+    pub log_lines: Vec<String>,
+    /// Exit codes, stderr and notes, for R.
+    pub result_lines: Vec<String>,
+    /// The command and its LFS step (if any) all exited with status 0.
+    pub success: bool,
+}
+
+/// `Command` for a background git process; see `hide_console`.
+pub fn git_command(git_path: &str) -> Command {
+    let mut cmd = Command::new(git_path);
+    hide_console(&mut cmd);
+    cmd
+}
+
+/// On Windows a bare `git` is looked up with `where`, preferring `git.exe` over `.cmd`/`.bat`
+/// shims found earlier on PATH. Any other path is returned unchanged.
+pub fn resolve_git_path(git_path: &str) -> String {
+    if !cfg!(windows) || git_path != "git" {
+        return git_path.to_string();
+    }
+    hide_console(&mut Command::new("where"))
+        .arg("git")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .lines()
+                .map(str::trim)
+                .find(|line| line.to_ascii_lowercase().ends_with(".exe"))
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| git_path.to_string())
+}
+
+/// Output of `git --version`, or why git could not be run.
+pub fn git_version(git_path: &str) -> Result<String, String> {
+    match git_command(git_path).arg("--version").output() {
+        Ok(o) if o.status.success() => Ok(String::from_utf8_lossy(&o.stdout).trim().to_string()),
+        Ok(o) => Err(format!("`{} --version` failed ({})", git_path, o.status)),
+        Err(e) => Err(format!("cannot run git at \"{}\": {}", git_path, e)),
+    }
+}
+
+/// First line of `git lfs version`; `None` when git-lfs is not installed.
+pub fn lfs_version(git_path: &str) -> Option<String> {
+    let output = git_command(git_path)
+        .args(["lfs", "version"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|l| l.trim().to_string())
+}
+
+/// Bytes held in the repository's local LFS object store.
+pub fn lfs_storage_size(git: &str, repo: &Path) -> u64 {
+    fn dir_size(dir: &Path) -> u64 {
+        fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .map(|e| match e.file_type() {
+                Ok(t) if t.is_dir() => dir_size(&e.path()),
+                Ok(_) => e.metadata().map(|m| m.len()).unwrap_or(0),
+                Err(_) => 0,
+            })
+            .sum()
+    }
+    git_stdout(git, repo, &["rev-parse", "--git-path", "lfs/objects"])
+        .map(|dir| dir_size(&repo.join(dir)))
+        .unwrap_or(0)
+}
+
+/// Whether checkouts leave LFS files as pointers, via `GIT_LFS_SKIP_SMUDGE=1` or
+/// `git lfs install --skip-smudge`.
+pub fn lfs_skip_smudge(git: &str, repo: &Path) -> bool {
+    std::env::var("GIT_LFS_SKIP_SMUDGE").is_ok_and(|v| v == "1" || v == "true")
+        || git_stdout(git, repo, &["config", "filter.lfs.smudge"])
+            .is_some_and(|s| s.contains("--skip"))
+}
+
+/// LFS-tracked files whose content has not been downloaded into the work tree.
+pub fn lfs_pointer_files(git: &str, repo: &Path) -> Vec<String> {
+    // `<oid> * <path>` when the object is checked out, `<oid> - <path>` for a pointer.
+    git_stdout(git, repo, &["lfs", "ls-files"])
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|l| l.split_once(' ')?.1.strip_prefix("- "))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Subcommands that talk to a remote.
+const NETWORK_SUBCOMMANDS: [&str; 5] = ["clone", "fetch", "pull", "push", "ls-remote"];
+
+/// Whether `git <args_str>` or its LFS step talks to a remote.
+pub fn is_network_command(args_str: &str, lfs_mode: &LfsMode) -> bool {
+    if lfs_mode.follow_up().is_some() {
+        return true;
+    }
+    let args = parse_args_line(args_str);
+    match args.first().map(String::as_str) {
+        Some("lfs") => args
+            .get(1)
+            .is_some_and(|a| matches!(a.as_str(), "fetch" | "pull" | "push")),
+        Some("remote") => args.get(1).is_some_and(|a| a == "update"),
+        Some(sub) => NETWORK_SUBCOMMANDS.contains(&sub),
+        None => false,
+    }
+}
+
+/// Subcommands that only read the repository, whatever their arguments.
+const READ_ONLY_SUBCOMMANDS: [&str; 24] = [
+    "status",
+    "log",
+    "show",
+    "diff",
+    "blame",
+    "annotate",
+    "grep",
+    "shortlog",
+    "describe",
+    "whatchanged",
+    "range-diff",
+    "cherry",
+    "ls-files",
+    "ls-tree",
+    "ls-remote",
+    "rev-parse",
+    "rev-list",
+    "cat-file",
+    "show-ref",
+    "for-each-ref",
+    "name-rev",
+    "check-ignore",
+    "check-attr",
+    "count-objects",
+];
+
+/// Whether `git <args_str>` and its LFS step leave the repository and remotes untouched.
+/// Commands that only list with no arguments, such as `branch` or `stash list`, count too;
+/// `--output` makes the diff family write a file, so those do not.
+pub fn is_read_only_command(args_str: &str, lfs_mode: &LfsMode) -> bool {
+    if lfs_mode.follow_up().is_some() {
+        return false;
+    }
+    let args = parse_args_line(args_str);
+    let Some(sub) = args.first().map(String::as_str) else {
+        return false;
+    };
+    let rest = &args[1..];
+    let only = |flags: &[&str]| rest.iter().all(|a| flags.contains(&a.as_str()));
+    let first = rest.first().map(String::as_str);
+    match sub {
+        "branch" => only(&[
+            "-a",
+            "--all",
+            "-r",
+            "--remotes",
+            "-v",
+            "-vv",
+            "--list",
+            "--show-current",
+            "--merged",
+            "--no-merged",
+        ]),
+        "tag" => only(&["-l", "--list", "-n"]),
+        "remote" => matches!(first, None | Some("-v" | "show" | "get-url")),
+        "stash" | "worktree" => matches!(first, Some("list" | "show")),
+        "reflog" => matches!(first, None | Some("show")),
+        "notes" => matches!(first, None | Some("list" | "show")),
+        "submodule" => matches!(first, None | Some("status" | "summary")),
+        "config" => rest.iter().any(|a| {
+            matches!(
+                a.as_str(),
+                "-l" | "--list" | "--get" | "--get-all" | "--get-regexp"
+            )
+        }),
+        "lfs" => {
+            matches!(
+                first,
+                Some("ls-files" | "status" | "env" | "version" | "track")
+            ) && (first != Some("track") || rest.len() == 1)
+        }
+        "diff" | "log" | "show" | "whatchanged" | "range-diff" => !rest
+            .iter()
+            .take_while(|a| *a != "--")
+            .any(|a| a == "--output" || a.starts_with("--output=") || a.starts_with("-o")),
+        _ => READ_ONLY_SUBCOMMANDS.contains(&sub),
+    }
+}
+
+/// Stderr of a command that could not reach the remote at all, as opposed to one the
+/// remote answered with an error such as a rejected push.
+const NETWORK_ERRORS: [&str; 11] = [
+    "Could not resolve host",
+    "Temporary failure in name resolution",
+    "Name or service not known",
+    "nodename nor servname provided",
+    "No such host is known",
+    "timed out",
+    "Network is unreachable",
+    "No route to host",
+    "Connection refused",
+    "Connection reset by peer",
+    "Failed to connect to",
+];
+
+/// Whether `lines` has one of the messages git prints when it cannot reach a remote.
+pub fn is_network_error(lines: &[String]) -> bool {
+    lines
+        .iter()
+        .any(|l| NETWORK_ERRORS.iter().any(|e| l.contains(e)))
+}
+
+/// Whether running `git <args_str>` with `lfs_mode` requires git-lfs.
+pub fn needs_lfs(args_str: &str, lfs_mode: &LfsMode) -> bool {
+    lfs_mode.follow_up().is_some()
+        || parse_args_line(args_str)
+            .first()
+            .is_some_and(|a| a == "lfs")
+}
+
+/// Top level of the work tree containing `cwd`, or `cwd` itself outside a repository.
+pub fn repo_root(git_path: &str, cwd: &Path) -> PathBuf {
+    git_command(git_path)
+        .arg("rev-parse")
+        .arg("--show-toplevel")
+        .current_dir(cwd)
+        .output()
+        .ok()
+        .and_then(|o| {
+            if o.status.success() {
+                Some(PathBuf::from(String::from_utf8_lossy(&o.stdout).trim()))
+            } else {
+                None
+            }
+        })
+        .unwrap_or_else(|| cwd.to_path_buf())
+}
+
+/// Result lines a stage of `run_git_with_lfs` added, reported as soon as that stage
+/// exits; its log lines are streamed as git prints them.
+pub struct StageOutput {
+    /// Lines added since the last stage.
+    pub result_lines: Vec<String>,
+    /// Stages finished, this one included.
+    pub done: usize,
+    /// Stages the command has: 2 with an LFS step, 1 without.
+    pub total: usize,
+}
+
+/// A sideband progress line such as `Receiving objects:  42% (42/100), 1.2 MiB | 3 MiB/s`.
+#[derive(Clone)]
+pub struct TransferProgress {
+    /// `Receiving objects`, `Resolving deltas` and so on.
+    pub phase: String,
+    /// The percentage, 0 to 100.
+    pub percent: u16,
+    /// Counts and throughput following the percentage.
+    pub detail: String,
+}
+
+/// Subcommands that only print transfer progress to a terminal unless asked to.
+const PROGRESS_SUBCOMMANDS: [&str; 4] = ["clone", "fetch", "pull", "push"];
+
+/// Recognizes git and git-lfs progress lines; `None` for any other output.
+pub fn parse_transfer_progress(line: &str) -> Option<TransferProgress> {
+    let (phase, rest) = line.trim().rsplit_once(": ")?;
+    let rest = rest.trim_start();
+    let (percent, detail) = rest.split_once('%')?;
+    let percent: u16 = percent.parse().ok()?;
+    Some(TransferProgress {
+        phase: phase.trim().to_string(),
+        percent: percent.min(100),
+        detail: detail.trim().trim_end_matches(", done.").to_string(),
+    })
+}
+
+/// Final progress phases of a transfer and how the summary words them.
+const TRANSFER_PHASES: [(&str, &str); 4] = [
+    ("Receiving objects", "received"),
+    ("Writing objects", "sent"),
+    ("Downloading LFS objects", "LFS downloaded"),
+    ("Uploading LFS objects", "LFS uploaded"),
+];
+
+/// One line summing up the transfers in a command's stderr, e.g.
+/// `sent 3 object(s), 290 bytes at 290.00 KiB/s`; `None` when nothing was transferred.
+pub fn transfer_summary(lines: &[String]) -> Option<String> {
+    let parts: Vec<String> = TRANSFER_PHASES
+        .iter()
+        .filter_map(|(phase, verb)| {
+            let progress = lines
+                .iter()
+                .rev()
+                .filter_map(|l| parse_transfer_progress(l))
+                .find(|p| p.phase == *phase && p.percent == 100)?;
+            // `(100/100), 1.00 MiB | 2.00 MiB/s`; small transfers stop after the counts.
+            let (counts, rest) = progress.detail.split_once(')')?;
+            let objects = counts.rsplit('/').next()?;
+            let mut part = format!("{} {} object(s)", verb, objects);
+            let rest = rest.trim_start_matches(',').trim();
+            match rest.split_once(" | ") {
+                Some((bytes, rate)) => part.push_str(&format!(", {} at {}", bytes, rate)),
+                None if !rest.is_empty() => part.push_str(&format!(", {}", rest)),
+                None => {}
+            }
+            Some(part)
+        })
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("; "))
+}
+
+/// How often a running command checks whether it was canceled.
+const CANCEL_POLL: Duration = Duration::from_millis(50);
+/// How long a canceled command gets to exit on SIGTERM before it is killed.
+const TERMINATE_GRACE: Duration = Duration::from_secs(2);
+
+/// What the reader threads of `output_with_progress` pass on.
+enum Piped {
+    /// A stdout line, without its newline.
+    Stdout(Vec<u8>),
+    /// A stderr segment and the `\r` or `\n` ending it, or `None` at the end of stderr.
+    Stderr(Vec<u8>, Option<u8>),
+}
+
+/// Like `Command::output`, but hands each stdout line to `on_line` and reports progress
+/// lines from stderr while the command runs. Lines redrawn with `\r` are only reported;
+/// the final state of each is kept in stderr. Once `cancel` is set the command is
+/// terminated and the output read so far returned.
+fn output_with_progress(
+    cmd: &mut Command,
+    cancel: &CancelToken,
+    on_transfer: &mut dyn FnMut(TransferProgress),
+    on_line: &mut dyn FnMut(&str),
+) -> io::Result<Output> {
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(cmd, 0);
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let (piped, received) = mpsc::channel();
+    let child_stdout = child.stdout.take().expect("stdout is piped");
+    let stdout_piped = piped.clone();
+    thread::spawn(move || {
+        for line in BufReader::new(child_stdout).split(b'\n') {
+            let Ok(line) = line else { break };
+            if stdout_piped.send(Piped::Stdout(line)).is_err() {
+                break;
+            }
+        }
+    });
+    let child_stderr = child.stderr.take().expect("stderr is piped");
+    thread::spawn(move || {
+        let mut segment = Vec::new();
+        for byte in BufReader::new(child_stderr).bytes() {
+            let Ok(byte) = byte else { break };
+            if byte == b'\r' || byte == b'\n' {
+                let _ = piped.send(Piped::Stderr(std::mem::take(&mut segment), Some(byte)));
+            } else {
+                segment.push(byte);
+            }
+        }
+        let _ = piped.send(Piped::Stderr(segment, None));
+    });
+    let cancel = cancel.clone();
+    let waiter = thread::spawn(move || {
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(status);
+            }
+            if cancel.is_canceled() {
+                return terminate(child);
+            }
+            thread::sleep(CANCEL_POLL);
+        }
+    });
+
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let mut report = |segment: &[u8]| {
+        if let Some(progress) = parse_transfer_progress(&String::from_utf8_lossy(segment)) {
+            on_transfer(progress);
+        }
+    };
+    // Ends once both reader threads are done with their pipes.
+    for message in received {
+        match message {
+            Piped::Stdout(mut line) => {
+                on_line(String::from_utf8_lossy(&line).trim_end_matches('\r'));
+                stdout.append(&mut line);
+                stdout.push(b'\n');
+            }
+            Piped::Stderr(segment, Some(b'\r')) => report(&segment),
+            Piped::Stderr(mut segment, Some(_)) => {
+                report(&segment);
+                stderr.append(&mut segment);
+                stderr.push(b'\n');
+            }
+            Piped::Stderr(mut segment, None) => stderr.append(&mut segment),
+        }
+    }
+
+    let status = waiter
+        .join()
+        .unwrap_or_else(|_| Err(io::Error::other("waiting for git panicked")))?;
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+/// Asks `child` to stop with SIGTERM, sent to its process group so helpers it started
+/// (ssh, git-remote-https, git-lfs) stop too, and kills it if it has not exited after
+/// a grace period. Elsewhere `kill` is `TerminateProcess`.
+fn terminate(mut child: Child) -> io::Result<ExitStatus> {
+    #[cfg(unix)]
+    {
+        let group = format!("-{}", child.id());
+        let signaled = Command::new("kill")
+            .args(["-TERM", "--", &group])
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success());
+        if signaled {
+            let until = Instant::now() + TERMINATE_GRACE;
+            while Instant::now() < until {
+                if let Some(status) = child.try_wait()? {
+                    return Ok(status);
+                }
+                thread::sleep(CANCEL_POLL);
+            }
+        }
+    }
+    let _ = child.kill();
+    child.wait()
+}
+
+/// Runs `git <args_str>` in `repo_path`, then the LFS step of `lfs_mode` unless canceled.
+/// Stdout goes to the log lines, each also passed to `on_log_line` as soon as git prints
+/// it; exit codes and stderr go to the result lines.
+#[allow(clippy::too_many_arguments)]
+pub fn run_git_with_lfs(
+    git_path: String,
+    args_str: String,
+    lfs_mode: LfsMode,
+    cancel: &CancelToken,
+    repo_path: PathBuf,
+    report: &mut dyn FnMut(StageOutput),
+    on_transfer: &mut dyn FnMut(TransferProgress),
+    on_log_line: &mut dyn FnMut(&str),
+) -> CommandResult {
+    let mut log_lines = Vec::new();
+    let mut result_lines = Vec::new();
+    let total = if lfs_mode.follow_up().is_some() { 2 } else { 1 };
+    let mut reported = 0;
+    let mut report_stage = |result_lines: &[String], done: usize| {
+        report(StageOutput {
+            result_lines: result_lines[reported..].to_vec(),
+            done,
+            total,
+        });
+        reported = result_lines.len();
+    };
+
+    result_lines.push(format!("$ git {}", args_str));
+
+    let mut parts = parse_args_line(&args_str);
+
+    if parts.is_empty() {
+        result_lines.push("ERROR: empty git command".into());
+        return CommandResult {
+            log_lines,
+            result_lines,
+            success: false,
+        };
+    }
+
+    let subcmd = parts.remove(0);
+    let mut success;
+    if PROGRESS_SUBCOMMANDS.contains(&subcmd.as_str())
+        && !parts
+            .iter()
+            .any(|p| p == "--progress" || p == "--no-progress" || p == "-q")
+    {
+        parts.insert(0, "--progress".into());
+    }
+
+    let main_output = output_with_progress(
+        git_command(&git_path)
+            .arg(&subcmd)
+            .args(&parts)
+            .env("GIT_LFS_FORCE_PROGRESS", "1")
+            .current_dir(&repo_path),
+        cancel,
+        on_transfer,
+        on_log_line,
+    );
+
+    match main_output {
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+            if stdout.is_empty() {
+                log_lines.push("<no stdout from git>".into());
+                on_log_line("<no stdout from git>");
+            } else {
+                log_lines.extend(stdout.lines().map(|s| s.to_owned()));
+            }
+
+            success = output.status.success();
+            result_lines.push(format!(
+                "git exit code: {}",
+                output.status.code().unwrap_or(-1)
+            ));
+            if !stderr.is_empty() {
+                result_lines.push("--- git stderr ---".into());
+                result_lines.extend(stderr.lines().map(|s| s.to_owned()));
+            }
+        }
+        Err(e) => {
+            result_lines.push(format!("ERROR: failed to run git: {}", e));
+            return CommandResult {
+                log_lines,
+                result_lines,
+                success: false,
+            };
+        }
+    }
+
+    if cancel.is_canceled() {
+        result_lines.push("<canceled: git was stopped, the output above is partial>".into());
+        return CommandResult {
+            log_lines,
+            result_lines,
+            success: false,
+        };
+    }
+
+    report_stage(&result_lines, 1);
+
+    if cancel.is_canceled() {
+        result_lines.push("<canceled before LFS stage>".into());
+        return CommandResult {
+            log_lines,
+            result_lines,
+            success: false,
+        };
+    }
+
+    if let Some(follow_up) = lfs_mode.follow_up() {
+        let label = format!("git {}", follow_up);
+        let subcmd = parse_args_line(&follow_up)
+            .into_iter()
+            .take(2)
+            .collect::<Vec<_>>()
+            .join(" ");
+        result_lines.push(String::new());
+        result_lines.push(format!("== {} ==", label));
+
+        let header = format!("--- {} ---", label);
+        let mut headed = false;
+        let lfs_output = output_with_progress(
+            git_command(&git_path)
+                .args(parse_args_line(&follow_up))
+                .env("GIT_LFS_FORCE_PROGRESS", "1")
+                .current_dir(&repo_path),
+            cancel,
+            on_transfer,
+            &mut |line| {
+                if !std::mem::replace(&mut headed, true) {
+                    on_log_line("");
+                    on_log_line(&header);
+                }
+                on_log_line(line);
+            },
+        );
+
+        match lfs_output {
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+                if !stdout.is_empty() {
+                    log_lines.push(String::new());
+                    log_lines.push(header);
+                    log_lines.extend(stdout.lines().map(|s| s.to_owned()));
+                }
+
+                success &= output.status.success();
+                result_lines.push(format!(
+                    "git {} exit code: {}",
+                    subcmd,
+                    output.status.code().unwrap_or(-1)
+                ));
+                if !stderr.is_empty() {
+                    result_lines.push("--- git lfs stderr ---".into());
+                    result_lines.extend(stderr.lines().map(|s| s.to_owned()));
+                }
+            }
+            Err(e) => {
+                success = false;
+                result_lines.push(format!("ERROR: failed to run git {}: {}", subcmd, e));
+            }
+        }
+    }
+    if total > 1 {
+        report_stage(&result_lines, 2);
+    }
+
+    CommandResult {
+        log_lines,
+        result_lines,
+        success,
+    }
+}
+
+/// Display form of a hook or flow step: `!` runs a shell command, anything else git.
+pub fn step_label(step: &str) -> String {
+    match step.strip_prefix('!') {
+        Some(line) => line.trim().to_string(),
+        None => format!("git {}", step),
+    }
+}
+
+/// Runs a hook or flow step, appending its output to `res`; returns whether it exited with 0.
+/// Once `cancel` is set the step is terminated like a command run by `run_git_with_lfs`.
+pub fn run_step(
+    git_path: &str,
+    step: &str,
+    repo_path: &Path,
+    cancel: &CancelToken,
+    res: &mut CommandResult,
+) -> bool {
+    let label = step_label(step);
+    res.result_lines.push(String::new());
+    res.result_lines.push(format!("== {} ==", label));
+    if cancel.is_canceled() {
+        res.result_lines.push("<canceled before it started>".into());
+        return false;
+    }
+
+    let mut cmd = match step.strip_prefix('!') {
+        Some(line) => shell_command(line.trim()),
+        None => {
+            let mut cmd = git_command(git_path);
+            cmd.args(parse_args_line(step));
+            cmd
+        }
+    };
+    let output = output_with_progress(cmd.current_dir(repo_path), cancel, &mut |_| {}, &mut |_| {});
+    match output {
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+            if !stdout.is_empty() {
+                res.log_lines.push(String::new());
+                res.log_lines.push(format!("--- {} ---", label));
+                res.log_lines.extend(stdout.lines().map(|s| s.to_owned()));
+            }
+            res.result_lines
+                .push(format!("exit code: {}", output.status.code().unwrap_or(-1)));
+            if !stderr.is_empty() {
+                res.result_lines.push("--- stderr ---".into());
+                res.result_lines
+                    .extend(stderr.lines().map(|s| s.to_owned()));
+            }
+            if cancel.is_canceled() {
+                res.result_lines.push(format!(
+                    "<canceled: {} was stopped, the output above is partial>",
+                    label
+                ));
+                return false;
+            }
+            output.status.success()
+        }
+        Err(e) => {
+            res.result_lines
+                .push(format!("ERROR: failed to run {}: {}", label, e));
+            false
+        }
+    }
+}
+
+/// Quotes `arg` for `parse_args_line` when it contains whitespace or quotes.
+pub fn quote_arg(arg: &str) -> String {
+    if arg.is_empty() || arg.contains(char::is_whitespace) || arg.contains('"') {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        arg.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_argument_lines() {
+        let cases: [(&str, &[&str]); 6] = [
+            ("status -sb", &["status", "-sb"]),
+            (r#"commit -m """#, &["commit", "-m", ""]),
+            ("  log   --oneline  ", &["log", "--oneline"]),
+            (r#"commit -m "two words""#, &["commit", "-m", "two words"]),
+            (r#"add "a \"quoted\" name""#, &["add", r#"a "quoted" name"#]),
+            (r#"add C:\dir\file"#, &["add", r#"C:\dir\file"#]),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(parse_args_line(line), expected, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn quoted_arguments_split_back() {
+        for arg in [
+            "plain",
+            "two words",
+            "",
+            r#"say "hi""#,
+            r"back\slash two",
+            "日本語 名前",
+        ] {
+            let line = format!("add -- {}", quote_arg(arg));
+            assert_eq!(parse_args_line(&line), ["add", "--", arg], "{:?}", arg);
+        }
+    }
+
+    const H1: &str = "1111111111111111111111111111111111111111";
+    const H2: &str = "2222222222222222222222222222222222222222";
+
+    #[test]
+    fn parses_porcelain_v2_records() {
+        let records = [
+            format!("1 .M N... 100644 100644 100644 {H1} {H1} src/main.rs"),
+            format!("1 A. N... 000000 100644 100644 {H1} {H2} dir with spaces/a b.txt"),
+            format!("1 MD N... 100644 100644 000000 {H1} {H2} 日本語/ファイル.md"),
+            format!("2 R. N... 100644 100644 100644 {H1} {H1} R100 new name.rs"),
+            "old name.rs".to_string(),
+            format!("2 RM N... 100644 100644 100644 {H1} {H2} C75 docs/copy é.md"),
+            "docs/orig é.md".to_string(),
+            format!("u UU N... 100644 100644 100644 100644 {H1} {H2} {H1} conflicted file.txt"),
+            "? untracked dir/notes .txt".to_string(),
+            "? ünïcode".to_string(),
+            "! ignored.log".to_string(),
+        ];
+        let text = records.join("\0") + "\0";
+        let parsed = parse_porcelain_v2(&text);
+        let parsed: Vec<(&str, &str, Option<&str>)> = parsed
+            .iter()
+            .map(|f| (f.status.as_str(), f.path.as_str(), f.orig_path.as_deref()))
+            .collect();
+        assert_eq!(
+            parsed,
+            [
+                (" M", "src/main.rs", None),
+                ("A ", "dir with spaces/a b.txt", None),
+                ("MD", "日本語/ファイル.md", None),
+                ("R ", "new name.rs", Some("old name.rs")),
+                ("RM", "docs/copy é.md", Some("docs/orig é.md")),
+                ("UU", "conflicted file.txt", None),
+                ("??", "untracked dir/notes .txt", None),
+                ("??", "ünïcode", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn porcelain_paths_keep_leading_and_repeated_spaces() {
+        let text = format!(
+            "1 .M N... 100644 100644 100644 {H1} {H1}  lead  and  double\0\
+             2 R. N... 100644 100644 100644 {H1} {H1} R090 to\0 from \0"
+        );
+        let parsed = parse_porcelain_v2(&text);
+        assert_eq!(parsed[0].path, " lead  and  double");
+        assert_eq!(parsed[1].path, "to");
+        assert_eq!(parsed[1].orig_path.as_deref(), Some(" from "));
+        assert_eq!(parsed.len(), 2);
+        assert!(parse_porcelain_v2("").is_empty());
+    }
+
+    #[test]
+    fn classifies_read_only_commands() {
+        let cases = [
+            ("status -sb", true),
+            ("log --oneline --output-indicator-new=+", true),
+            ("diff HEAD -- -odd.txt", true),
+            ("diff --output=patch.diff", false),
+            ("log --output patch.txt", false),
+            ("show -o out HEAD", false),
+            ("branch -a", true),
+            ("branch topic", false),
+            ("stash list", true),
+            ("stash", false),
+            ("config --get user.name", true),
+            ("commit -m x", false),
+        ];
+        for (args, read_only) in cases {
+            assert_eq!(is_read_only_command(args, &LfsMode::None), read_only, "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn parses_decorations() {
+        let refs = parse_decorations(
+            "HEAD -> refs/heads/main, tag: refs/tags/v1.2.0, refs/remotes/origin/main, refs/stash",
+        );
+        let refs: Vec<(RefKind, &str)> = refs.iter().map(|r| (r.kind, r.name.as_str())).collect();
+        assert_eq!(
+            refs,
+            [
+                (RefKind::Head, "main"),
+                (RefKind::Tag, "v1.2.0"),
+                (RefKind::Remote, "origin/main"),
+            ]
+        );
+        let detached = parse_decorations("HEAD, refs/heads/topic");
+        assert_eq!(detached[0].kind, RefKind::Head);
+        assert_eq!(detached[1].kind, RefKind::Branch);
+        assert!(parse_decorations("").is_empty());
+    }
+
+    #[test]
+    fn matches_todo_markers_as_words() {
+        let cases = [
+            ("// TODO: later", true),
+            ("# FIXME(me) broken", true),
+            ("HACK", true),
+            ("/*TODO*/", true),
+            ("HACKATHON prize", false),
+            ("TODOS", false),
+            ("MY_TODO", false),
+            ("todo lowercase", false),
+            ("ÉTODO", true),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(has_todo_marker(text), expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn finds_grep_patterns() {
+        let pattern = |text: &str, fixed| {
+            Some(GrepPattern {
+                text: Some(text.to_string()),
+                fixed,
+            })
+        };
+        let cases = [
+            ("foo", pattern("foo", false)),
+            ("-i foo -- src", pattern("foo", false)),
+            ("-A 3 foo", pattern("foo", false)),
+            (
+                "-A3 -B 2 --context 1 --max-depth=2 foo",
+                pattern("foo", false),
+            ),
+            ("-n -e -dash -e other", pattern("-dash", false)),
+            ("-ie foo", pattern("foo", false)),
+            ("-F a.b", pattern("a.b", true)),
+            ("-wiF a.b", pattern("a.b", true)),
+            ("--fixed-strings a.b", pattern("a.b", true)),
+            ("-F -E a.b", pattern("a.b", false)),
+            ("-A F x", pattern("x", false)),
+            ("-eF x", pattern("F", false)),
+            (
+                "-f patterns.txt src",
+                Some(GrepPattern {
+                    text: None,
+                    fixed: false,
+                }),
+            ),
+            ("-i", None),
+            ("-A 3", None),
+            ("-- foo", None),
+        ];
+        for (args, expected) in cases {
+            assert_eq!(grep_pattern(&parse_args_line(args)), expected, "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn parses_grep_output() {
+        let matches = parse_grep_output(
+            "src/main.rs\x0012\0    let x: u8 = 1;\n\
+             dir:with:colons/a b.txt\x003\0a:b\0c\n\
+             --\n\
+             line\nbreak.txt\x007\0\n\
+             counted.txt\x002\n",
+        );
+        let matches: Vec<(&str, u32, &str)> = matches
+            .iter()
+            .map(|m| (m.path.as_str(), m.line, m.text.as_str()))
+            .collect();
+        assert_eq!(
+            matches,
+            [
+                ("src/main.rs", 12, "    let x: u8 = 1;"),
+                ("dir:with:colons/a b.txt", 3, "a:b\0c"),
+                ("line\nbreak.txt", 7, ""),
+            ]
+        );
+        assert!(parse_grep_output("").is_empty());
+    }
+
+    #[test]
+    fn parses_worktree_list() {
+        let worktrees = parse_worktrees(
+            "worktree /repo\nHEAD 1a2b3c\nbranch refs/heads/main\n\n\
+             worktree /repo-topic dir\nHEAD 4d5e6f\ndetached\nlocked in use\n\n\
+             worktree /gone\nHEAD 7a8b9c\nbranch refs/heads/old\nprunable gitdir file points to non-existent location\n",
+        );
+        assert_eq!(worktrees.len(), 3);
+        let main = &worktrees[0];
+        assert_eq!(main.path, PathBuf::from("/repo"));
+        assert_eq!(main.branch.as_deref(), Some("main"));
+        assert_eq!(main.head, "1a2b3c");
+        assert!(!main.locked && !main.prunable && !main.bare);
+        let topic = &worktrees[1];
+        assert_eq!(topic.path, PathBuf::from("/repo-topic dir"));
+        assert_eq!(topic.branch, None);
+        assert!(topic.locked);
+        assert!(worktrees[2].prunable);
+
+        let bare = parse_worktrees("worktree /srv/repo.git\nbare\n");
+        assert!(bare[0].bare && bare[0].head.is_empty());
+    }
+
+    #[test]
+    fn parses_transfer_progress() {
+        let p = parse_transfer_progress("Receiving objects:  42% (42/100), 1.20 MiB | 3.00 MiB/s")
+            .unwrap();
+        assert_eq!(p.phase, "Receiving objects");
+        assert_eq!(p.percent, 42);
+        assert_eq!(p.detail, "(42/100), 1.20 MiB | 3.00 MiB/s");
+        let done = parse_transfer_progress("remote: Counting objects: 100% (7/7), done.").unwrap();
+        assert_eq!(done.phase, "remote: Counting objects");
+        assert_eq!(done.detail, "(7/7)");
+        assert!(parse_transfer_progress("To github.com:o/r.git").is_none());
+    }
+
+    #[test]
+    fn sums_up_transfers() {
+        let lines: Vec<String> = [
+            "Enumerating objects: 5, done.",
+            "Writing objects:  33% (1/3)",
+            "Writing objects: 100% (3/3), 290 bytes | 290.00 KiB/s, done.",
+            "Uploading LFS objects: 100% (2/2), 1.5 MB",
+        ]
+        .map(String::from)
+        .to_vec();
+        assert_eq!(
+            transfer_summary(&lines).as_deref(),
+            Some("sent 3 object(s), 290 bytes at 290.00 KiB/s; LFS uploaded 2 object(s), 1.5 MB")
+        );
+        assert_eq!(transfer_summary(&["Everything up-to-date".into()]), None);
+    }
+}
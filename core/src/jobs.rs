@@ -0,0 +1,250 @@
+//! The worker pool commands run on, with per-job cancellation.
+
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+    mpsc::{self, Sender},
+};
+use std::thread;
+use std::time::Instant;
+
+/// Identifies a job for its lifetime; ids are never reused.
+pub type JobId = u64;
+
+/// Cancellation flag shared between a job and the UI.
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Asks the job to stop; it checks at its own pace.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether `cancel` was called.
+    pub fn is_canceled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// What a job computes; starting an exclusive kind supersedes older jobs of that kind.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JobKind {
+    /// A command from the command line or the CMD panel.
+    Command,
+    /// The steps of a configured flow.
+    Flow,
+    /// A log for the COMMITS panel.
+    Commits,
+    /// `git grep` for the MATCHES panel.
+    Matches,
+    /// Hooks of an event.
+    Event,
+    /// The doctor's checks.
+    Doctor,
+    /// Submodule status.
+    Submodules,
+    /// `git lfs ls-files`.
+    LfsFiles,
+    /// The `[decorations] command` for FILES.
+    Decorations,
+    /// The check for a newer release.
+    Update,
+    /// The commit message lint of the commit preview.
+    Lint,
+}
+
+impl JobKind {
+    fn exclusive(self) -> bool {
+        matches!(
+            self,
+            JobKind::Commits
+                | JobKind::Matches
+                | JobKind::Doctor
+                | JobKind::Submodules
+                | JobKind::LfsFiles
+                | JobKind::Decorations
+                | JobKind::Update
+                | JobKind::Lint
+        )
+    }
+
+    /// Jobs the user is waiting on, canceled together by Ctrl-C.
+    pub fn is_foreground(self) -> bool {
+        matches!(self, JobKind::Command | JobKind::Flow)
+    }
+}
+
+/// A queued or running job as listed by `Jobs::running`.
+pub struct JobInfo {
+    /// The job's id.
+    pub id: JobId,
+    /// What the job computes.
+    pub kind: JobKind,
+    /// What the job runs, for the status line.
+    pub label: String,
+    /// When the job was spawned.
+    pub started: Instant,
+    token: CancelToken,
+}
+
+/// A message from a job; `last` marks the value its closure returned.
+pub struct JobMessage<T> {
+    /// The job that sent the message.
+    pub id: JobId,
+    /// Whether the job is done.
+    pub last: bool,
+    /// The job was canceled before it returned `message`, which may be partial.
+    pub canceled: bool,
+    /// What the job sent.
+    pub message: T,
+}
+
+/// Handed to a running job for cancellation checks and intermediate messages.
+pub struct JobContext<T> {
+    id: JobId,
+    token: CancelToken,
+    results: Sender<JobMessage<T>>,
+}
+
+impl<T> JobContext<T> {
+    /// The job's token, for handing to commands that stop when it is canceled.
+    pub fn cancel_token(&self) -> &CancelToken {
+        &self.token
+    }
+
+    /// Whether the job was canceled.
+    pub fn is_canceled(&self) -> bool {
+        self.token.is_canceled()
+    }
+
+    /// Sends an update before the job finishes; dropped once the job is canceled.
+    pub fn send(&self, message: T) {
+        if !self.is_canceled() {
+            let _ = self.results.send(JobMessage {
+                id: self.id,
+                last: false,
+                canceled: false,
+                message,
+            });
+        }
+    }
+}
+
+type Task = Box<dyn FnOnce() + Send>;
+
+/// A fixed pool of worker threads running jobs that report back as `T` messages.
+pub struct Jobs<T> {
+    queue: Sender<Task>,
+    results: Sender<JobMessage<T>>,
+    next_id: JobId,
+    running: Vec<JobInfo>,
+}
+
+impl<T: Send + 'static> Jobs<T> {
+    /// Starts `workers` threads; every job message is sent to `results`.
+    pub fn new(workers: usize, results: Sender<JobMessage<T>>) -> Self {
+        let (queue, tasks) = mpsc::channel::<Task>();
+        let tasks = Arc::new(Mutex::new(tasks));
+        for _ in 0..workers.max(1) {
+            let tasks = tasks.clone();
+            thread::spawn(move || {
+                loop {
+                    let task = match tasks.lock() {
+                        Ok(rx) => rx.recv(),
+                        Err(_) => return,
+                    };
+                    match task {
+                        Ok(task) => task(),
+                        Err(_) => return,
+                    }
+                }
+            });
+        }
+        Self {
+            queue,
+            results,
+            next_id: 1,
+            running: Vec::new(),
+        }
+    }
+
+    /// Queues `work`; its result is always delivered, marked `canceled` when the job was
+    /// canceled or superseded while it ran.
+    pub fn spawn(
+        &mut self,
+        kind: JobKind,
+        label: impl Into<String>,
+        work: impl FnOnce(&JobContext<T>) -> T + Send + 'static,
+    ) -> JobId {
+        if kind.exclusive() {
+            self.cancel_where(|job| job.kind == kind);
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        let token = CancelToken::default();
+        self.running.push(JobInfo {
+            id,
+            kind,
+            label: label.into(),
+            started: Instant::now(),
+            token: token.clone(),
+        });
+
+        let ctx = JobContext {
+            id,
+            token,
+            results: self.results.clone(),
+        };
+        let _ = self.queue.send(Box::new(move || {
+            let message = work(&ctx);
+            let _ = ctx.results.send(JobMessage {
+                id,
+                last: true,
+                canceled: ctx.is_canceled(),
+                message,
+            });
+        }));
+        id
+    }
+
+    /// Whether `id` is queued or running.
+    pub fn is_running(&self, id: JobId) -> bool {
+        self.running.iter().any(|job| job.id == id)
+    }
+
+    /// Marks `id` done; false when its result is stale and should be dropped.
+    pub fn finish(&mut self, id: JobId) -> bool {
+        let before = self.running.len();
+        self.running.retain(|job| job.id != id);
+        self.running.len() != before
+    }
+
+    /// Cancels `id`; false when it had already finished.
+    pub fn cancel(&mut self, id: JobId) -> bool {
+        self.cancel_where(|job| job.id == id) > 0
+    }
+
+    /// Cancels the jobs the user is waiting on and returns how many there were.
+    pub fn cancel_foreground(&mut self) -> usize {
+        self.cancel_where(|job| job.kind.is_foreground())
+    }
+
+    fn cancel_where(&mut self, pred: impl Fn(&JobInfo) -> bool) -> usize {
+        let before = self.running.len();
+        self.running.retain(|job| {
+            if pred(job) {
+                job.token.cancel();
+                false
+            } else {
+                true
+            }
+        });
+        before - self.running.len()
+    }
+
+    /// Queued and running jobs, oldest first.
+    pub fn running(&self) -> &[JobInfo] {
+        &self.running
+    }
+}
@@ -0,0 +1,24 @@
+//! The git layer of simple-git-tui, usable without the TUI.
+//!
+//! - [`git`] runs git commands and parses their output: status, logs, grep
+//!   matches, LFS follow-up steps and transfer progress.
+//! - [`diff`] models unified diffs for navigation and display.
+//! - [`range_diff`] parses `git range-diff` output the same way.
+//! - [`syntax`] finds keywords, strings, comments and numbers in source lines.
+//! - [`codeowners`] reads CODEOWNERS files to tell who owns a path.
+//! - [`doctor`] diagnoses the repository and git setup, such as an expired signing key.
+//! - [`preview`] reads metadata from the headers of binary assets and LFS pointers.
+//! - [`jobs`] is the worker pool commands run on, with per-job cancellation.
+//! - [`shell`] starts commands through the platform shell.
+
+#![warn(missing_docs)]
+
+pub mod codeowners;
+pub mod diff;
+pub mod doctor;
+pub mod git;
+pub mod jobs;
+pub mod preview;
+pub mod range_diff;
+pub mod shell;
+pub mod syntax;
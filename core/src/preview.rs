@@ -0,0 +1,259 @@
+//! Metadata read from the headers of binary assets and from LFS pointers.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::git::git_command;
+
+/// Extensions of binary assets whose headers are parsed for the LOG pane.
+const ASSET_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "wav", "fbx"];
+
+/// How much of an asset is read; enough for the JPEG frame header after embedded EXIF thumbnails.
+const HEADER_BYTES: u64 = 256 * 1024;
+
+/// Other files are only checked for being an LFS pointer that was never smudged.
+const POINTER_BYTES: u64 = 1024;
+
+/// An LFS pointer file: what git stores in place of the asset.
+pub struct LfsPointer {
+    /// The `sha256:` object id.
+    pub oid: String,
+    /// Size of the asset in bytes.
+    pub size: u64,
+}
+
+/// Whether `path` has the extension of an asset whose header can be read.
+pub fn is_asset(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| ASSET_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// Metadata lines for the asset at `path` (relative to `repo`), or `None` when it is not an
+/// asset or is missing from the work tree.
+pub fn asset_preview(git: &str, repo: &Path, path: &str) -> Option<Vec<String>> {
+    let full = repo.join(path);
+    let size = full.metadata().ok().filter(|m| m.is_file())?.len();
+    let limit = if is_asset(path) {
+        HEADER_BYTES
+    } else {
+        POINTER_BYTES
+    };
+    let mut header = Vec::new();
+    File::open(&full)
+        .ok()?
+        .take(limit)
+        .read_to_end(&mut header)
+        .ok()?;
+    let worktree_pointer = parse_lfs_pointer(&header);
+    if !is_asset(path) && worktree_pointer.is_none() {
+        return None;
+    }
+
+    let mut lines = vec![
+        path.to_string(),
+        format!("size: {} ({} bytes)", format_size(size), size),
+    ];
+    match &worktree_pointer {
+        Some(_) => lines.push("content: LFS pointer only (`:hydrate` downloads it)".into()),
+        None => lines.extend(describe(&header)),
+    }
+    match worktree_pointer.or_else(|| index_lfs_pointer(git, repo, path)) {
+        Some(pointer) => {
+            lines.push(format!("lfs oid: {}", pointer.oid));
+            lines.push(format!("lfs size: {}", format_size(pointer.size)));
+        }
+        None => lines.push("lfs: not tracked".into()),
+    }
+    Some(lines)
+}
+
+/// Type and format lines parsed from the start of a file.
+pub fn describe(header: &[u8]) -> Vec<String> {
+    if let Some(lines) = png(header)
+        .or_else(|| jpeg(header))
+        .or_else(|| gif(header))
+        .or_else(|| wav(header))
+        .or_else(|| fbx(header))
+    {
+        return lines;
+    }
+    vec!["type: unknown".into()]
+}
+
+fn png(h: &[u8]) -> Option<Vec<String>> {
+    if !h.starts_with(b"\x89PNG\r\n\x1a\n") || h.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    let color = match h.get(25)? {
+        0 => "gray",
+        2 => "RGB",
+        3 => "indexed",
+        4 => "gray+alpha",
+        6 => "RGBA",
+        _ => "unknown color",
+    };
+    Some(vec![
+        "type: PNG image".into(),
+        format!("dimensions: {}x{}", be32(h, 16)?, be32(h, 20)?),
+        format!("format: {}-bit {}", h.get(24)?, color),
+    ])
+}
+
+fn jpeg(h: &[u8]) -> Option<Vec<String>> {
+    if !h.starts_with(b"\xff\xd8") {
+        return None;
+    }
+    // Walk the segments up to the start-of-frame marker that holds the size.
+    let mut i = 2;
+    while i + 9 < h.len() {
+        if h[i] != 0xff {
+            return None;
+        }
+        let marker = h[i + 1];
+        let len = u16::from_be_bytes([h[i + 2], h[i + 3]]) as usize;
+        if (0xc0..=0xcf).contains(&marker) && !matches!(marker, 0xc4 | 0xc8 | 0xcc) {
+            let height = u16::from_be_bytes([h[i + 5], h[i + 6]]);
+            let width = u16::from_be_bytes([h[i + 7], h[i + 8]]);
+            return Some(vec![
+                "type: JPEG image".into(),
+                format!("dimensions: {}x{}", width, height),
+                format!("format: {} components", h[i + 9]),
+            ]);
+        }
+        i += 2 + len;
+    }
+    Some(vec!["type: JPEG image".into()])
+}
+
+fn gif(h: &[u8]) -> Option<Vec<String>> {
+    if !h.starts_with(b"GIF87a") && !h.starts_with(b"GIF89a") {
+        return None;
+    }
+    Some(vec![
+        "type: GIF image".into(),
+        format!("dimensions: {}x{}", le16(h, 6)?, le16(h, 8)?),
+    ])
+}
+
+fn wav(h: &[u8]) -> Option<Vec<String>> {
+    if !h.starts_with(b"RIFF") || h.get(8..12)? != b"WAVE" {
+        return None;
+    }
+    let mut lines = vec!["type: WAV audio".into()];
+    let mut byte_rate = 0;
+    let mut i = 12;
+    while i + 8 <= h.len() {
+        let id = &h[i..i + 4];
+        let len = le32(h, i + 4)? as usize;
+        if id == b"fmt " {
+            let format = match le16(h, i + 8)? {
+                1 => "PCM",
+                3 => "float",
+                0xfffe => "extensible",
+                _ => "compressed",
+            };
+            lines.push(format!(
+                "format: {} {}-bit, {} channel(s), {} Hz",
+                format,
+                le16(h, i + 22)?,
+                le16(h, i + 10)?,
+                le32(h, i + 12)?
+            ));
+            byte_rate = le32(h, i + 16)?;
+        } else if id == b"data" {
+            if byte_rate > 0 {
+                lines.push(format!("duration: {:.2}s", len as f64 / byte_rate as f64));
+            }
+            break;
+        }
+        // Chunks are padded to an even length.
+        i += 8 + len + len % 2;
+    }
+    Some(lines)
+}
+
+fn fbx(h: &[u8]) -> Option<Vec<String>> {
+    if h.starts_with(b"Kaydara FBX Binary  \0") {
+        return Some(vec![
+            "type: FBX model (binary)".into(),
+            format!("version: {}", le32(h, 23)?),
+        ]);
+    }
+    let text = String::from_utf8_lossy(&h[..h.len().min(256)]).into_owned();
+    let version = text.lines().next()?.strip_prefix("; FBX ")?;
+    Some(vec![
+        "type: FBX model (ASCII)".into(),
+        format!("version: {}", version.split_whitespace().next()?),
+    ])
+}
+
+/// Parses `version https://git-lfs.github.com/spec/v1` pointer text.
+pub fn parse_lfs_pointer(data: &[u8]) -> Option<LfsPointer> {
+    // Pointers are tiny; anything larger is real content.
+    if data.len() as u64 > POINTER_BYTES
+        || !data.starts_with(b"version https://git-lfs.github.com/spec/")
+    {
+        return None;
+    }
+    let text = std::str::from_utf8(data).ok()?;
+    let field = |key: &str| {
+        text.lines()
+            .find_map(|l| l.strip_prefix(key)?.strip_prefix(' '))
+    };
+    Some(LfsPointer {
+        oid: field("oid")?.to_string(),
+        size: field("size")?.parse().ok()?,
+    })
+}
+
+/// The pointer stored in the index for an LFS-tracked `path`.
+fn index_lfs_pointer(git: &str, repo: &Path, path: &str) -> Option<LfsPointer> {
+    let attr = git_command(git)
+        .args(["check-attr", "filter", "--", path])
+        .current_dir(repo)
+        .output()
+        .ok()?;
+    if !String::from_utf8_lossy(&attr.stdout)
+        .trim_end()
+        .ends_with(": lfs")
+    {
+        return None;
+    }
+    let blob = git_command(git)
+        .args(["cat-file", "blob", &format!(":{}", path)])
+        .current_dir(repo)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    parse_lfs_pointer(&blob.stdout)
+}
+
+/// `bytes` in B, KiB, MiB, GiB or TiB, whichever reads best.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+fn be32(h: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(h.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le32(h: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(h.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le16(h: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(h.get(at..at + 2)?.try_into().ok()?))
+}
@@ -0,0 +1,187 @@
+//! `git range-diff` output parsed into commit pairs and the diffs between them.
+
+use crate::diff::strip_ansi;
+
+/// How a commit of the old range relates to one of the new range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PairStatus {
+    /// `=`: the patch is the same.
+    Same,
+    /// `!`: the patch changed; a diff of the two patches follows.
+    Changed,
+    /// `<`: only in the old range, dropped by the rebase.
+    Dropped,
+    /// `>`: only in the new range.
+    New,
+}
+
+/// What a line of `git range-diff` output is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RangeLineKind {
+    /// A line pairing an old commit with a new one.
+    Pair(PairStatus),
+    /// `@@ Metadata`, `## path ##` and similar headings inside a changed pair.
+    Section,
+    /// Inner lines the new version of the patch has and the old one lacks.
+    Added,
+    /// Inner lines the old version of the patch has and the new one lacks.
+    Removed,
+    /// Inner lines both versions have, and blank lines.
+    Context,
+}
+
+/// A line of the output with what it is.
+#[derive(Clone, Debug)]
+pub struct RangeDiffLine {
+    /// What the line is.
+    pub kind: RangeLineKind,
+    /// The line without color codes.
+    pub text: String,
+}
+
+/// `git range-diff` output, line for line.
+#[derive(Clone, Debug, Default)]
+pub struct RangeDiff {
+    /// Every line of the output.
+    pub lines: Vec<RangeDiffLine>,
+}
+
+impl RangeDiff {
+    /// Parses LOG lines as `git range-diff` output: pair lines such as
+    /// `2:  1a2b3c4 ! 2:  5d6e7f8 Subject`, each followed by the indented diff of the two
+    /// patches. Returns `None` for any other text.
+    pub fn parse(raw: &[String]) -> Option<Self> {
+        let mut lines = Vec::with_capacity(raw.len());
+        for line in raw {
+            let text = strip_ansi(line);
+            let kind = if let Some(status) = pair_status(&text) {
+                RangeLineKind::Pair(status)
+            } else if let Some(inner) = text.strip_prefix("    ") {
+                if lines.is_empty() {
+                    return None;
+                }
+                if inner.starts_with("@@") || inner.starts_with("##") {
+                    RangeLineKind::Section
+                } else if inner.starts_with('+') {
+                    RangeLineKind::Added
+                } else if inner.starts_with('-') {
+                    RangeLineKind::Removed
+                } else {
+                    RangeLineKind::Context
+                }
+            } else if text.trim().is_empty() {
+                RangeLineKind::Context
+            } else {
+                return None;
+            };
+            lines.push(RangeDiffLine { kind, text });
+        }
+        lines
+            .iter()
+            .any(|l| matches!(l.kind, RangeLineKind::Pair(_)))
+            .then_some(RangeDiff { lines })
+    }
+
+    /// Number of lines, the same as the raw output.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Whether there are no lines.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// How many pairs have `status`.
+    pub fn count(&self, status: PairStatus) -> usize {
+        self.lines
+            .iter()
+            .filter(|l| l.kind == RangeLineKind::Pair(status))
+            .count()
+    }
+
+    /// The first pair line after `line`.
+    pub fn next_pair(&self, line: usize) -> Option<usize> {
+        (line + 1..self.lines.len()).find(|&i| matches!(self.lines[i].kind, RangeLineKind::Pair(_)))
+    }
+
+    /// The last pair line before `line`.
+    pub fn prev_pair(&self, line: usize) -> Option<usize> {
+        (0..line.min(self.lines.len()))
+            .rev()
+            .find(|&i| matches!(self.lines[i].kind, RangeLineKind::Pair(_)))
+    }
+}
+
+/// `1:  abc1234 = 1:  def5678 Subject`, where either side may be `-:  -------`.
+fn pair_status(text: &str) -> Option<PairStatus> {
+    let mut fields = text.split_whitespace();
+    let is_number = |f: &str| {
+        f.strip_suffix(':').is_some_and(|n| {
+            !n.is_empty() && (n.bytes().all(|b| b.is_ascii_digit()) || n.bytes().all(|b| b == b'-'))
+        })
+    };
+    let is_hash = |f: &str| !f.is_empty() && f.bytes().all(|b| b.is_ascii_hexdigit() || b == b'-');
+    if !(is_number(fields.next()?) && is_hash(fields.next()?)) {
+        return None;
+    }
+    let status = match fields.next()? {
+        "=" => PairStatus::Same,
+        "!" => PairStatus::Changed,
+        "<" => PairStatus::Dropped,
+        ">" => PairStatus::New,
+        _ => return None,
+    };
+    (is_number(fields.next()?) && is_hash(fields.next()?)).then_some(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_pairs_and_their_diffs() {
+        let diff = RangeDiff::parse(&lines(
+            "1:  1a2b3c4 = 1:  5d6e7f8 Keep this\n\
+             2:  2b3c4d5 ! 2:  6e7f8a9 Change that\n    \
+             @@ src/lib.rs\n    \
+             -    old\n    \
+             +    new\n    \
+              context\n\
+             \n\
+             3:  3c4d5e6 < -:  ------- Drop this\n\
+             -:  ------- > 3:  7f8a9b0 Add this",
+        ))
+        .unwrap();
+        use RangeLineKind::*;
+        let kinds: Vec<RangeLineKind> = diff.lines.iter().map(|l| l.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                Pair(PairStatus::Same),
+                Pair(PairStatus::Changed),
+                Section,
+                Removed,
+                Added,
+                Context,
+                Context,
+                Pair(PairStatus::Dropped),
+                Pair(PairStatus::New),
+            ]
+        );
+        assert_eq!(diff.count(PairStatus::Same), 1);
+        assert_eq!(diff.next_pair(1), Some(7));
+        assert_eq!(diff.prev_pair(7), Some(1));
+    }
+
+    #[test]
+    fn other_text_is_no_range_diff() {
+        assert!(RangeDiff::parse(&lines("    indented first")).is_none());
+        assert!(RangeDiff::parse(&lines("diff --git a/x b/x")).is_none());
+        assert!(RangeDiff::parse(&lines("")).is_none());
+    }
+}
@@ -0,0 +1,92 @@
+//! Starting commands through the platform shell.
+
+use std::process::Command;
+
+/// Keeps a background process from flashing a console window on Windows; a no-op elsewhere.
+/// Not for interactive commands, which need the console.
+pub fn hide_console(cmd: &mut Command) -> &mut Command {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    cmd
+}
+
+/// Builds a command that runs `line` through the platform shell.
+pub fn shell_command(line: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        hide_console(&mut cmd).arg("/C").arg(line);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(line);
+        cmd
+    }
+}
+
+/// Quotes `arg` as a single word for the shell `shell_command` runs it in: single
+/// quotes for `sh`, double quotes for `cmd`, which still expands `%VAR%` inside them.
+pub fn shell_quote(arg: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", arg.replace('"', "\"\""))
+    } else if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:@%+=,".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Opens `url` with the platform's default handler.
+pub fn open_url(url: &str) -> std::io::Result<()> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", "start", ""]).arg(url);
+        cmd
+    } else if cfg!(target_os = "macos") {
+        let mut cmd = Command::new("open");
+        cmd.arg(url);
+        cmd
+    } else {
+        let mut cmd = Command::new("xdg-open");
+        cmd.arg(url);
+        cmd
+    };
+    cmd.stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(windows))]
+    fn quotes_for_sh() {
+        assert_eq!(shell_quote("main"), "main");
+        assert_eq!(shell_quote("feature/x-1.2"), "feature/x-1.2");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("two words"), "'two words'");
+        assert_eq!(shell_quote("$(rm -rf ~)"), "'$(rm -rf ~)'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn quoted_arguments_reach_the_command_intact() {
+        for arg in ["two words", "it's", "$HOME `id` \"q\"", "日本語"] {
+            let out = shell_command(&format!("printf %s {}", shell_quote(arg)))
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8_lossy(&out.stdout), arg);
+        }
+    }
+}
@@ -0,0 +1,439 @@
+//! Language-aware coloring of source lines for diffs. Each line is read on its own, so
+//! a block comment or string spanning lines is only colored on the lines that open it.
+
+use std::ops::Range;
+
+/// What a colored span of a line is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    /// A keyword of the language.
+    Keyword,
+    /// A string or character literal, quotes included.
+    String,
+    /// A line comment or block comment.
+    Comment,
+    /// A numeric literal.
+    Number,
+}
+
+/// What the highlighter knows about a language: enough to find comments, strings,
+/// numbers and keywords.
+#[derive(Debug)]
+pub struct Language {
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// Characters that open and close a string.
+    quotes: &'static [char],
+    /// `'` only opens a character literal such as `'a'` or `'\n'`, as Rust lifetimes
+    /// share the quote.
+    char_literals: bool,
+    keywords: &'static [&'static str],
+}
+
+const RUST: Language = Language {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"'],
+    char_literals: true,
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while",
+    ],
+};
+
+const C_FAMILY: Language = Language {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    char_literals: false,
+    keywords: &[
+        "abstract",
+        "auto",
+        "bool",
+        "break",
+        "case",
+        "catch",
+        "char",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "delete",
+        "do",
+        "double",
+        "else",
+        "enum",
+        "extends",
+        "extern",
+        "false",
+        "final",
+        "float",
+        "for",
+        "fun",
+        "goto",
+        "if",
+        "implements",
+        "import",
+        "include",
+        "int",
+        "interface",
+        "long",
+        "namespace",
+        "new",
+        "null",
+        "nullptr",
+        "override",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "short",
+        "signed",
+        "sizeof",
+        "static",
+        "struct",
+        "switch",
+        "template",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typedef",
+        "union",
+        "unsigned",
+        "using",
+        "val",
+        "var",
+        "virtual",
+        "void",
+        "volatile",
+        "while",
+    ],
+};
+
+const JAVASCRIPT: Language = Language {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\'', '`'],
+    char_literals: false,
+    keywords: &[
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "enum",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "from",
+        "function",
+        "if",
+        "implements",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "let",
+        "new",
+        "null",
+        "of",
+        "private",
+        "public",
+        "readonly",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "type",
+        "typeof",
+        "undefined",
+        "var",
+        "void",
+        "while",
+        "yield",
+    ],
+};
+
+const GO: Language = Language {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\'', '`'],
+    char_literals: false,
+    keywords: &[
+        "break",
+        "case",
+        "chan",
+        "const",
+        "continue",
+        "default",
+        "defer",
+        "else",
+        "false",
+        "fallthrough",
+        "for",
+        "func",
+        "go",
+        "goto",
+        "if",
+        "import",
+        "interface",
+        "map",
+        "nil",
+        "package",
+        "range",
+        "return",
+        "select",
+        "struct",
+        "switch",
+        "true",
+        "type",
+        "var",
+    ],
+};
+
+const PYTHON: Language = Language {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    char_literals: false,
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+        "elif", "else", "except", "False", "finally", "for", "from", "global", "if", "import",
+        "in", "is", "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "self",
+        "True", "try", "while", "with", "yield",
+    ],
+};
+
+const RUBY: Language = Language {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    char_literals: false,
+    keywords: &[
+        "alias", "and", "begin", "break", "case", "class", "def", "do", "else", "elsif", "end",
+        "ensure", "false", "for", "if", "in", "module", "next", "nil", "not", "or", "redo",
+        "rescue", "retry", "return", "self", "super", "then", "true", "unless", "until", "when",
+        "while", "yield",
+    ],
+};
+
+const SHELL: Language = Language {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    char_literals: false,
+    keywords: &[
+        "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+        "in", "local", "return", "then", "until", "while",
+    ],
+};
+
+const CONFIG: Language = Language {
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    char_literals: false,
+    keywords: &["true", "false", "null", "yes", "no", "on", "off"],
+};
+
+const LUA: Language = Language {
+    line_comments: &["--"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    char_literals: false,
+    keywords: &[
+        "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if",
+        "in", "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+    ],
+};
+
+impl Language {
+    /// The language of `path` by its extension or file name; `None` when unknown.
+    pub fn from_path(path: &str) -> Option<&'static Language> {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        if matches!(name, "Dockerfile" | "Makefile" | "makefile" | ".gitignore") {
+            return Some(&SHELL);
+        }
+        let (_, ext) = name.rsplit_once('.')?;
+        Some(match ext.to_ascii_lowercase().as_str() {
+            "rs" => &RUST,
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "cs" | "java" | "kt" | "kts"
+            | "swift" | "scala" | "dart" | "php" | "m" => &C_FAMILY,
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" => &JAVASCRIPT,
+            "go" => &GO,
+            "py" | "pyi" => &PYTHON,
+            "rb" => &RUBY,
+            "sh" | "bash" | "zsh" | "fish" | "ps1" => &SHELL,
+            "toml" | "yaml" | "yml" | "json" | "ini" | "cfg" | "conf" => &CONFIG,
+            "lua" => &LUA,
+            _ => return None,
+        })
+    }
+
+    /// Byte ranges of `line` to color and what they are; the rest is plain text.
+    pub fn tokens(&self, line: &str) -> Vec<(Range<usize>, TokenKind)> {
+        let bytes = line.as_bytes();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < line.len() {
+            let rest = &line[i..];
+            let c = rest.chars().next().unwrap_or_default();
+            if self.line_comments.iter().any(|p| rest.starts_with(p)) {
+                tokens.push((i..line.len(), TokenKind::Comment));
+                break;
+            }
+            if let Some((open, close)) = self.block_comment
+                && rest.starts_with(open)
+            {
+                let end = rest[open.len()..]
+                    .find(close)
+                    .map_or(line.len(), |at| i + open.len() + at + close.len());
+                tokens.push((i..end, TokenKind::Comment));
+                i = end;
+                continue;
+            }
+            if self.quotes.contains(&c) {
+                let end = string_end(rest, c).map_or(line.len(), |len| i + len);
+                tokens.push((i..end, TokenKind::String));
+                i = end;
+                continue;
+            }
+            if c == '\''
+                && self.char_literals
+                && let Some(len) = char_literal_len(rest)
+            {
+                tokens.push((i..i + len, TokenKind::String));
+                i += len;
+                continue;
+            }
+            let word_start = i == 0 || !is_word_byte(bytes[i - 1]);
+            if word_start && (c.is_alphanumeric() || c == '_') {
+                let len = rest
+                    .find(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+                    .unwrap_or(rest.len());
+                let word = &rest[..len];
+                if c.is_ascii_digit() {
+                    // Take the fraction too, as in `1.5`.
+                    let len = rest
+                        .find(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '.'))
+                        .unwrap_or(rest.len());
+                    tokens.push((i..i + len, TokenKind::Number));
+                    i += len;
+                    continue;
+                }
+                if self.keywords.contains(&word) {
+                    tokens.push((i..i + len, TokenKind::Keyword));
+                }
+                i += len;
+                continue;
+            }
+            i += c.len_utf8();
+        }
+        tokens
+    }
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80
+}
+
+/// Length of the string opening `rest` up to its closing quote, skipping escapes.
+fn string_end(rest: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (at, ch) in rest.char_indices().skip(1) {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ if ch == quote => return Some(at + ch.len_utf8()),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Length of a character literal opening `rest`: `'a'`, `'\n'` or `'\u{1F600}'`.
+fn char_literal_len(rest: &str) -> Option<usize> {
+    let mut chars = rest.char_indices().skip(1);
+    let (_, first) = chars.next()?;
+    let close = if first == '\\' {
+        rest.get(3..)?.find('\'').map(|at| at + 3)?
+    } else {
+        let (at, next) = chars.next()?;
+        (next == '\'').then_some(at)?
+    };
+    Some(close + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TokenKind::{Comment, Keyword, Number, String as Str};
+
+    fn spans<'a>(language: &Language, line: &'a str) -> Vec<(&'a str, TokenKind)> {
+        language
+            .tokens(line)
+            .into_iter()
+            .map(|(range, kind)| (&line[range], kind))
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_rust() {
+        let cases: [(&str, &[(&str, TokenKind)]); 9] = [
+            ("fn f<'a>(x: &'a str)", &[("fn", Keyword)]),
+            ("let c = '\\n';", &[("let", Keyword), ("'\\n'", Str)]),
+            ("let c = 'é';", &[("let", Keyword), ("'é'", Str)]),
+            ("'\\u{1F600}'", &[("'\\u{1F600}'", Str)]),
+            (r#"let s = "a\"b";"#, &[("let", Keyword), (r#""a\"b""#, Str)]),
+            (r#"let s = "open"#, &[("let", Keyword), (r#""open"#, Str)]),
+            ("x /* note */ + 1", &[("/* note */", Comment), ("1", Number)]),
+            (
+                "let été = 1.5; // fin",
+                &[("let", Keyword), ("1.5", Number), ("// fin", Comment)],
+            ),
+            ("éfn ifn_ x1fn", &[]),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(spans(&RUST, line), expected, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn single_quotes_are_strings_without_char_literals() {
+        assert_eq!(
+            spans(&C_FAMILY, "char c = 'a'; /* open"),
+            [("char", Keyword), ("'a'", Str), ("/* open", Comment)]
+        );
+    }
+
+    #[test]
+    fn finds_languages_by_path() {
+        let cases = [
+            ("src/main.rs", true),
+            ("Makefile", true),
+            ("web/App.TSX", true),
+            ("notes.txt", false),
+            ("LICENSE", false),
+        ];
+        for (path, known) in cases {
+            assert_eq!(Language::from_path(path).is_some(), known, "{:?}", path);
+        }
+    }
+}
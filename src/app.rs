@@ -1,77 +1,201 @@
-use std::{
-    io::{self, Stdout},
-    sync::{
-        Arc,
-        atomic::AtomicBool,
-        mpsc::{Receiver, Sender},
-    },
-    time::Duration,
-};
+use std::{env, io, path::PathBuf, time::Duration};
 
 use crossterm::{
-    event::{self, Event},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-use ratatui::{Terminal, backend::CrosstermBackend};
+use ratatui::{
+    Terminal,
+    backend::{Backend, CrosstermBackend},
+    layout::Rect,
+};
 
-use crate::{config::Config, theme::Theme};
+use crate::{
+    config::Config,
+    crash,
+    links::{Hyperlink, find_links, write_links},
+    theme::Theme,
+};
 
+mod bench;
+#[cfg(feature = "test-harness")]
+pub mod harness;
 mod view;
 mod view_model;
 
-pub use view_model::{UiMessage, ViewModel};
+use view_model::TabRequest;
+pub use view_model::{Focus, Mode, ViewModel};
+
+/// Where the event loop reads input from: the terminal, or a script under test.
+pub trait EventSource {
+    /// Waits up to `timeout` for the next event.
+    fn next_event(&mut self, timeout: Duration) -> io::Result<Option<Event>>;
+}
+
+pub struct TerminalEvents;
+
+impl EventSource for TerminalEvents {
+    fn next_event(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        if event::poll(timeout)? {
+            event::read().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
 
 pub struct App {
-    view_model: ViewModel,
+    /// The repository first, then any `:review` tabs.
+    tabs: Vec<ViewModel>,
+    active: usize,
+    /// Hyperlinks in the last drawn frame, and those already written to the terminal.
+    links: Vec<Hyperlink>,
+    written_links: Vec<Hyperlink>,
 }
 
 impl App {
-    pub fn new(
-        config: Config,
-        theme: Theme,
-        tx: Sender<UiMessage>,
-        rx: Receiver<UiMessage>,
-        cancel_flag: Arc<AtomicBool>,
-    ) -> Self {
+    pub fn new(config: Config, theme: Theme) -> Self {
+        let cwd = env::current_dir().unwrap_or_else(|_| ".".into());
+        Self::open(config, theme, cwd)
+    }
+
+    /// Opens the repository containing `cwd`.
+    pub fn open(config: Config, theme: Theme, cwd: PathBuf) -> Self {
+        let mut view_model = ViewModel::new(config, theme, cwd);
+        view_model.init();
         Self {
-            view_model: ViewModel::new(config, theme, tx, rx, cancel_flag),
+            tabs: vec![view_model],
+            active: 0,
+            links: Vec::new(),
+            written_links: Vec::new(),
         }
     }
 
+    pub fn view_model(&self) -> &ViewModel {
+        &self.tabs[self.active]
+    }
+
     pub fn run(mut self) -> anyhow::Result<()> {
         enable_raw_mode()?;
         let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen)?;
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
-        let res = self.event_loop(&mut terminal);
+        let res = self.event_loop(&mut terminal, &mut TerminalEvents);
         disable_raw_mode()?;
-        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        execute!(
+            terminal.backend_mut(),
+            DisableMouseCapture,
+            LeaveAlternateScreen
+        )?;
         terminal.show_cursor()?;
         res
     }
 
-    fn event_loop(
+    fn event_loop<B: Backend + io::Write>(
         &mut self,
-        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        terminal: &mut Terminal<B>,
+        events: &mut impl EventSource,
     ) -> anyhow::Result<()> {
-        loop {
-            self.view_model.poll_messages();
-            if self.view_model.take_full_redraw() {
-                terminal.clear()?;
-            }
-            self.view_model.tick();
-            terminal.draw(|f| view::draw(&mut self.view_model, f))?;
-            if event::poll(Duration::from_millis(50))? {
-                if let Event::Key(key) = event::read()? {
-                    let should_quit = self.view_model.handle_key(key)?;
-                    if should_quit {
-                        break;
-                    }
-                }
+        while !self.step(terminal, events)? {
+            if self.links != self.written_links {
+                write_links(terminal.backend_mut(), &self.links)?;
+                self.written_links = self.links.clone();
             }
         }
         Ok(())
     }
+
+    /// Applies finished jobs, redraws and handles at most one event; true once the user quits.
+    pub fn step<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        events: &mut impl EventSource,
+    ) -> anyhow::Result<bool> {
+        // Background tabs keep applying their jobs' results.
+        for tab in &mut self.tabs {
+            tab.poll_messages();
+        }
+        if let Some(report) = crash::take_background_report() {
+            self.tabs[self.active].notify(format!(
+                "ERROR: a background job panicked and will not finish; report: {}",
+                report.display()
+            ));
+        }
+        self.open_requested_tab();
+        let count = self.tabs.len();
+        let view_model = &mut self.tabs[self.active];
+        view_model.set_tab_position(self.active, count);
+        if view_model.take_full_redraw() {
+            terminal.clear()?;
+            self.written_links.clear();
+        }
+        view_model.tick();
+        let mut link_areas = [Rect::default(); 2];
+        let frame = terminal.draw(|f| link_areas = view::draw(view_model, f))?;
+        self.links = match view_model.link_targets() {
+            Some(targets) => link_areas
+                .iter()
+                .flat_map(|area| find_links(frame.buffer, *area, &targets))
+                .collect(),
+            None => Vec::new(),
+        };
+        let quit = match events.next_event(Duration::from_millis(50))? {
+            Some(Event::Key(key)) => self.tabs[self.active].handle_key(key)?,
+            Some(Event::Mouse(mouse)) => {
+                self.tabs[self.active].handle_mouse(mouse);
+                false
+            }
+            _ => false,
+        };
+        if !quit {
+            return Ok(false);
+        }
+        if self.active == 0 {
+            for mut tab in self.tabs.drain(1..) {
+                tab.close_review();
+            }
+            return Ok(true);
+        }
+        self.close_active_tab();
+        Ok(false)
+    }
+
+    /// Quitting a review tab closes it and removes its worktree.
+    fn close_active_tab(&mut self) {
+        let mut tab = self.tabs.remove(self.active);
+        self.active = 0;
+        if let Some(note) = tab.close_review() {
+            self.tabs[0].notify(note);
+        }
+        self.written_links.clear();
+    }
+
+    /// Opens, switches or closes tabs as the active tab asked.
+    fn open_requested_tab(&mut self) {
+        let Some(request) = self.tabs[self.active].take_tab_request() else {
+            return;
+        };
+        let count = self.tabs.len();
+        self.active = match request {
+            TabRequest::Open(review) => {
+                let tab = self.tabs[self.active].open_review(review);
+                self.tabs.push(tab);
+                count
+            }
+            TabRequest::Next => (self.active + 1) % count,
+            TabRequest::Prev => (self.active + count - 1) % count,
+            TabRequest::Switch(path) => {
+                self.tabs[self.active] = self.tabs[self.active].open_worktree(path);
+                self.active
+            }
+            TabRequest::Close if self.active != 0 => {
+                self.close_active_tab();
+                return;
+            }
+            TabRequest::Close => self.active,
+        };
+        self.written_links.clear();
+    }
 }
@@ -0,0 +1,92 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ratatui::{Terminal, backend::TestBackend};
+
+use super::App;
+use super::view;
+use crate::timings::percentile;
+
+/// How long startup jobs get to finish before measuring starts.
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+impl App {
+    /// The hidden `--bench` mode: times the status and diff backends on the current
+    /// repository, and drawing a frame, and returns a table of the results.
+    pub fn bench(mut self, iterations: usize) -> anyhow::Result<String> {
+        let view_model = &mut self.tabs[0];
+        // Startup jobs would compete with the measurements.
+        let deadline = Instant::now() + SETTLE_TIMEOUT;
+        while !view_model.is_idle() && Instant::now() < deadline {
+            view_model.poll_messages();
+            thread::sleep(Duration::from_millis(10));
+        }
+        view_model.poll_messages();
+
+        let mut rows: Vec<(&str, String, Vec<Duration>)> = view_model
+            .bench_backend(iterations)
+            .into_iter()
+            .map(|(what, runs)| ("subprocess", what, runs))
+            .collect();
+        let (width, height) = crossterm::terminal::size().unwrap_or((120, 35));
+        let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+        let mut drawn = Ok(());
+        let runs = time(iterations, || {
+            if let Err(e) = terminal.draw(|f| {
+                view::draw(view_model, f);
+            }) {
+                drawn = Err(e);
+            }
+        });
+        drawn?;
+        rows.push(("-", format!("draw a {}x{} frame", width, height), runs));
+
+        let ms = |d: Duration| format!("{:.2}ms", d.as_secs_f64() * 1000.0);
+        let what_width = rows.iter().map(|(_, w, _)| w.len()).max().unwrap_or(0);
+        let mut out = vec![
+            format!(
+                "{} ({} runs each)",
+                view_model.repo_root().display(),
+                iterations.max(1)
+            ),
+            String::new(),
+            format!(
+                "{:10}  {:what_width$}  {:>9}  {:>9}  {:>9}  {:>9}",
+                "backend",
+                "measurement",
+                "min",
+                "p50",
+                "p90",
+                "max",
+                what_width = what_width
+            ),
+        ];
+        for (backend, what, mut runs) in rows {
+            runs.sort();
+            out.push(format!(
+                "{:10}  {:what_width$}  {:>9}  {:>9}  {:>9}  {:>9}",
+                backend,
+                what,
+                ms(runs[0]),
+                ms(percentile(&runs, 50)),
+                ms(percentile(&runs, 90)),
+                ms(runs[runs.len() - 1]),
+                what_width = what_width
+            ));
+        }
+        out.push(String::new());
+        out.push("libgit2: not built into this binary; git runs as a subprocess only.".to_string());
+        Ok(out.join("\n"))
+    }
+}
+
+/// How long each of `iterations` calls of `run` took.
+pub fn time(iterations: usize, mut run: impl FnMut()) -> Vec<Duration> {
+    (0..iterations.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .collect()
+}
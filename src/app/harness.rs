@@ -0,0 +1,268 @@
+//! Drives the app headlessly for end-to-end tests: scripted keys, an in-memory
+//! terminal, throwaway repositories and a stand-in git executable. Built only with the
+//! `test-harness` feature, which the crate's own tests turn on.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{Terminal, backend::TestBackend};
+
+use super::{App, EventSource, ViewModel};
+use crate::config::{Config, set_app_dir};
+use crate::theme::Theme;
+
+/// How long `settle` waits for background jobs before giving up.
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Events queued by the test; the queue running dry is not an error.
+#[derive(Default)]
+pub struct ScriptedEvents(VecDeque<Event>);
+
+impl EventSource for ScriptedEvents {
+    fn next_event(&mut self, _timeout: Duration) -> io::Result<Option<Event>> {
+        Ok(self.0.pop_front())
+    }
+}
+
+pub struct Harness {
+    app: App,
+    terminal: Terminal<TestBackend>,
+    events: ScriptedEvents,
+}
+
+impl Harness {
+    /// Opens `repo` with `config` on a 120x35 in-memory terminal.
+    /// Persisted state goes to a per-process temporary directory, not the user's config dir.
+    pub fn new(config: Config, repo: &Path) -> Self {
+        set_app_dir(std::env::temp_dir().join(format!("simple-git-tui-state-{}", process::id())));
+        let theme = Theme::from_config(config.theme.as_deref(), &config.colors);
+        let mut harness = Self {
+            app: App::open(config, theme, repo.to_path_buf()),
+            terminal: Terminal::new(TestBackend::new(120, 35)).expect("test backend"),
+            events: ScriptedEvents::default(),
+        };
+        harness.settle();
+        harness
+    }
+
+    pub fn press(&mut self, code: KeyCode) -> &mut Self {
+        self.key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    pub fn key(&mut self, key: KeyEvent) -> &mut Self {
+        self.events.0.push_back(Event::Key(key));
+        self.settle()
+    }
+
+    /// Presses each character of `keys` in turn.
+    pub fn keys(&mut self, keys: &str) -> &mut Self {
+        for c in keys.chars() {
+            self.events.0.push_back(Event::Key(KeyEvent::new(
+                KeyCode::Char(c),
+                KeyModifiers::NONE,
+            )));
+        }
+        self.settle()
+    }
+
+    /// Types `line` on the `:` command line and runs it.
+    pub fn cmdline(&mut self, line: &str) -> &mut Self {
+        self.keys(&format!(":{}", line)).press(KeyCode::Enter)
+    }
+
+    /// Handles queued events, then waits for background jobs and applies their results.
+    pub fn settle(&mut self) -> &mut Self {
+        let deadline = Instant::now() + SETTLE_TIMEOUT;
+        loop {
+            self.app
+                .step(&mut self.terminal, &mut self.events)
+                .expect("app step");
+            if self.events.0.is_empty() && self.app.view_model().is_idle() {
+                break;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "jobs still running after {:?}",
+                SETTLE_TIMEOUT
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+        // One more frame so the screen shows the applied results.
+        self.app
+            .step(&mut self.terminal, &mut self.events)
+            .expect("app step");
+        self
+    }
+
+    pub fn view_model(&self) -> &ViewModel {
+        self.app.view_model()
+    }
+
+    /// The last drawn frame, one line per terminal row.
+    pub fn screen(&self) -> String {
+        let buffer = self.terminal.backend().buffer();
+        buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|row| {
+                let line: String = row.iter().map(|cell| cell.symbol()).collect();
+                line.trim_end().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// A fresh repository under the temp dir, removed on drop.
+pub struct TempRepo {
+    path: PathBuf,
+}
+
+impl TempRepo {
+    pub fn new() -> Self {
+        let path = scratch_dir("repo");
+        let repo = Self { path };
+        repo.git(&["init", "-q", "-b", "main"]);
+        repo.git(&["config", "user.name", "Test"]);
+        repo.git(&["config", "user.email", "test@example.com"]);
+        repo.git(&["config", "commit.gpgsign", "false"]);
+        repo
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write(&self, file: &str, contents: &str) {
+        let path = self.path.join(file);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).expect("create dir");
+        }
+        fs::write(path, contents).expect("write file");
+    }
+
+    pub fn read(&self, file: &str) -> String {
+        fs::read_to_string(self.path.join(file)).unwrap_or_default()
+    }
+
+    /// Runs git in the repository, panicking on failure; returns stdout.
+    pub fn git(&self, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&self.path)
+            .output()
+            .expect("run git");
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    /// Writes `file` and commits it.
+    pub fn commit(&self, file: &str, contents: &str, message: &str) {
+        self.write(file, contents);
+        self.git(&["add", file]);
+        self.git(&["commit", "-q", "-m", message]);
+    }
+}
+
+impl Default for TempRepo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TempRepo {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Stand-in for the git executable, used as `git_path`: records every call and answers
+/// scripted subcommands, passing anything else on to the real git. The app reaches git
+/// only through `git_path`, so this is the git backend tests swap in.
+#[cfg(unix)]
+pub struct FakeGit {
+    dir: PathBuf,
+}
+
+#[cfg(unix)]
+impl FakeGit {
+    pub fn new() -> io::Result<Self> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = scratch_dir("git");
+        fs::create_dir_all(dir.join("responses"))?;
+        let script = dir.join("git");
+        fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\n\
+                 dir='{dir}'\n\
+                 echo \"$*\" >> \"$dir/calls\"\n\
+                 if [ -f \"$dir/responses/$1\" ]; then\n\
+                 \x20 cat \"$dir/responses/$1\"\n\
+                 \x20 exit \"$(cat \"$dir/responses/$1.code\")\"\n\
+                 fi\n\
+                 exec git \"$@\"\n",
+                dir = dir.display()
+            ),
+        )?;
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+        Ok(Self { dir })
+    }
+
+    /// Value for `Config::git_path`.
+    pub fn path(&self) -> String {
+        self.dir.join("git").to_string_lossy().into_owned()
+    }
+
+    /// Makes `git <subcommand> ...` print `stdout` and exit with `code` instead of running.
+    pub fn respond(&self, subcommand: &str, stdout: &str, code: i32) -> io::Result<()> {
+        let responses = self.dir.join("responses");
+        fs::write(responses.join(subcommand), stdout)?;
+        fs::write(
+            responses.join(format!("{}.code", subcommand)),
+            code.to_string(),
+        )
+    }
+
+    /// Argument lists of every call so far, oldest first.
+    pub fn calls(&self) -> Vec<String> {
+        fs::read_to_string(self.dir.join("calls"))
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+#[cfg(unix)]
+impl Drop for FakeGit {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// A unique path under the temp dir; directories of one kind are distinct within a process.
+fn scratch_dir(kind: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "simple-git-tui-{}-{}-{}",
+        kind,
+        process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir).expect("create scratch dir");
+    dir
+}
@@ -1,55 +1,97 @@
+use std::ops::Range;
+
 use ansi_to_tui::IntoText;
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout},
-    style::{Modifier, Style},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph},
 };
 
-use super::view_model::{Focus, Mode, ViewModel};
+use super::view_model::{
+    BODY_LIMIT, CommandForm, CommitEditor, FileFinder, FileRow, Focus, HelpRow, Mode, PaneAreas,
+    SUBJECT_LIMIT, ViewModel,
+};
+use crate::config::Pane;
+use crate::diff::{DiffLine, LineKind, MapCell, ParsedDiff, SplitRow, strip_ansi};
+use crate::git::{CommitRef, RefKind, RepoFile};
+use crate::range_diff::{PairStatus, RangeDiffLine, RangeLineKind};
+use crate::syntax::Language;
+use crate::theme::Theme;
 
-pub(super) fn draw(vm: &mut ViewModel, f: &mut Frame<'_>) {
+/// Draws the whole UI and returns the text areas of LOG and R, where hyperlinks are looked for.
+pub(super) fn draw(vm: &mut ViewModel, f: &mut Frame<'_>) -> [Rect; 2] {
     let size = f.area();
 
-    let lw = vm.layout().cmd_width as u16;
-    let fh = vm.layout().files_height as u16;
-    let rh = vm.layout().result_height as u16;
+    let layout = vm.pane_layout();
+    let position = |pane| layout.panes.iter().position(|p| *p == pane);
 
+    let teach_height = if vm.teaching() { 1 } else { 0 };
     let vertical = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(6), Constraint::Length(1)].as_ref())
+        .constraints(
+            [
+                Constraint::Min(6),
+                Constraint::Length(teach_height),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
         .split(size);
 
-    let top = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Length(lw), Constraint::Min(10)].as_ref())
-        .split(vertical[0]);
-
-    let left = top[0];
-    let right = top[1];
-
-    let left_split = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(fh), Constraint::Min(3)].as_ref())
-        .split(left);
-
-    let cmd_area = left_split[0];
-    let files_area = left_split[1];
-
-    let right_split = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Min(5), Constraint::Length(rh)].as_ref())
-        .split(right);
-
-    let log_area = right_split[0];
-    let result_area = right_split[1];
-    let status_area = vertical[1];
+    // CMD and the list share one column, LOG and R the other; panes the layout hides
+    // get an empty area, which draws nothing.
+    let column = [Pane::Cmd, Pane::Files]
+        .into_iter()
+        .filter_map(position)
+        .min();
+    let (column_area, main_area) = match column {
+        None => (Rect::default(), vertical[0]),
+        Some(first) => {
+            let column_left = first < position(Pane::Log).unwrap_or(usize::MAX);
+            let width = Constraint::Length(layout.cmd_width);
+            let constraints = if column_left {
+                [width, Constraint::Min(10)]
+            } else {
+                [Constraint::Min(10), width]
+            };
+            let top = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(constraints.as_ref())
+                .split(vertical[0]);
+            if column_left {
+                (top[0], top[1])
+            } else {
+                (top[1], top[0])
+            }
+        }
+    };
+    let (cmd_area, files_area) = stack(
+        column_area,
+        (position(Pane::Cmd), layout.files_height),
+        (position(Pane::Files), 3),
+    );
+    let (result_area, mut log_area) = stack(
+        main_area,
+        (position(Pane::Result), layout.result_height),
+        (position(Pane::Log), 5),
+    );
+    let teach_area = vertical[1];
+    let status_area = vertical[2];
 
     vm.update_viewport(
         log_area.height.saturating_sub(2),
         result_area.height.saturating_sub(2),
     );
+    vm.set_pane_areas(PaneAreas {
+        cmd: cmd_area,
+        list: files_area,
+        log: log_area,
+        result: result_area,
+    });
+    let help_area = help_popup(size);
+    vm.set_help_height(help_area.height.saturating_sub(2) as usize);
 
     let theme = vm.theme();
 
@@ -60,16 +102,30 @@ pub(super) fn draw(vm: &mut ViewModel, f: &mut Frame<'_>) {
         .map(|(i, c)| {
             let marker = if i == vm.selected_cmd() { "> " } else { "  " };
             let style = if i == vm.selected_cmd() {
-                Style::default()
-                    .fg(theme.accent)
-                    .add_modifier(Modifier::BOLD)
+                theme.selected(Style::default().fg(theme.accent))
             } else {
                 Style::default()
             };
-            ListItem::new(Line::from(Span::styled(
-                format!("{}{}", marker, c.name),
-                style,
-            )))
+            let mut spans = vec![Span::styled(format!("{}{}", marker, c.name), style)];
+            if vm.needs_missing_lfs(c) {
+                spans.push(Span::styled(
+                    " (no LFS)",
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+            }
+            if vm.needs_offline_network(c) {
+                spans.push(Span::styled(
+                    " (offline)",
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+            }
+            if vm.refused_read_only(c) {
+                spans.push(Span::styled(
+                    " (read-only)",
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+            }
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -79,11 +135,7 @@ pub(super) fn draw(vm: &mut ViewModel, f: &mut Frame<'_>) {
         _ => "CMD",
     };
 
-    let cmd_border_style = if matches!(vm.focus(), Focus::Cmd) {
-        Style::default().fg(theme.accent)
-    } else {
-        Style::default()
-    };
+    let cmd_border_style = theme.border(vm.focus() == Focus::Cmd);
 
     let cmd_list = List::new(cmd_items).block(
         Block::default()
@@ -91,27 +143,832 @@ pub(super) fn draw(vm: &mut ViewModel, f: &mut Frame<'_>) {
             .borders(Borders::ALL)
             .border_style(cmd_border_style),
     );
-    f.render_widget(cmd_list, cmd_area);
+    let mut cmd_state = ListState::default().with_selected(Some(vm.selected_cmd()));
+    f.render_stateful_widget(cmd_list, cmd_area, &mut cmd_state);
+
+    match vm.list_panel() {
+        Focus::Commits => draw_commits(vm, f, files_area),
+        Focus::Matches => draw_matches(vm, f, files_area),
+        Focus::Stashes => draw_stashes(vm, f, files_area),
+        Focus::Branches => draw_branches(vm, f, files_area),
+        Focus::Remotes => draw_remotes(vm, f, files_area),
+        Focus::Worktrees => draw_worktrees(vm, f, files_area),
+        _ => draw_files(vm, f, files_area),
+    }
+
+    let hunk_title = vm.hunk_view().map(|view| {
+        let (side, key) = if view.staged {
+            ("staged", "u:unstage hunk")
+        } else {
+            ("unstaged", "s:stage hunk")
+        };
+        match (vm.focus(), vm.mode()) {
+            (Focus::Log, Mode::Normal) => format!(
+                "DIFF {} {} [FOCUS] (j/k [/]:change {} Tab:{} Esc:close)",
+                view.path,
+                side,
+                key,
+                if view.staged { "unstaged" } else { "staged" }
+            ),
+            _ => format!("DIFF {} {}", view.path, side),
+        }
+    });
+    let log_title = match (vm.focus(), vm.mode()) {
+        _ if let Some(title) = &hunk_title => title,
+        (Focus::Log, Mode::Normal) if vm.log_diff().is_some() => {
+            "LOG [FOCUS] (j/k e:edit [/]:change |:side-by-side v:select)"
+        }
+        (Focus::Log, Mode::Normal) if vm.log_range_diff().is_some() => {
+            "LOG [FOCUS] ([/]:commit v:select)"
+        }
+        (Focus::Log, Mode::Normal) => "LOG [FOCUS] (v:select)",
+        (Focus::Log, Mode::CommandLine) => "LOG [FOCUS :]",
+        _ => "LOG",
+    };
+    let log_title = with_search(log_title, vm.search_status(Focus::Log));
+
+    let log_border_style = theme.border(vm.focus() == Focus::Log);
+
+    let show_minimap = vm.layout().minimap
+        && log_area.width > 12
+        && vm
+            .log_diff()
+            .is_some_and(|d| d.len() > log_area.height.saturating_sub(2) as usize);
+    if show_minimap {
+        let split = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(10), Constraint::Length(1)].as_ref())
+            .split(log_area);
+        log_area = split[0];
+        draw_minimap(vm, f, split[1]);
+    }
+
+    let log_block = Block::default()
+        .title(log_title)
+        .borders(Borders::ALL)
+        .border_style(log_border_style);
+
+    let split = vm.log_diff().filter(|_| vm.split_diff());
+    if let Some(diff) = split {
+        draw_split_diff(vm, diff, log_block, f, log_area);
+    } else {
+        let log_widget = if let Some(diff) = vm.log_diff() {
+            let start = (vm.log_scroll() as usize).min(diff.len());
+            let sticky = diff.sticky_hunk(start);
+            let rows = (log_area.height.saturating_sub(2) as usize)
+                .saturating_sub(sticky.is_some() as usize);
+            let end = (start + rows).min(diff.len());
+            let gutter = diff.max_line_number().max(1).to_string().len();
+            let mut lines: Vec<Line> = diff.lines[start..end]
+                .iter()
+                .enumerate()
+                .map(|(i, l)| {
+                    let line = diff_line(l, diff, gutter, vm.log_highlight(), theme);
+                    if start + i == vm.log_cursor() && matches!(vm.focus(), Focus::Log) {
+                        line.patch_style(Style::default().bg(Color::DarkGray))
+                    } else {
+                        line
+                    }
+                })
+                .collect();
+            if let Some(hunk) = sticky {
+                lines.insert(
+                    0,
+                    Line::from(Span::styled(
+                        diff.hunk_label(hunk),
+                        Style::default()
+                            .fg(theme.accent)
+                            .add_modifier(Modifier::REVERSED),
+                    )),
+                );
+            }
+            Paragraph::new(lines).block(log_block)
+        } else if let Some(diff) = vm.log_range_diff() {
+            let start = (vm.log_scroll() as usize).min(diff.len());
+            let end = (start + log_area.height as usize).min(diff.len());
+            let lines: Vec<Line> = diff.lines[start..end]
+                .iter()
+                .map(|l| range_diff_line(l, theme))
+                .collect();
+            Paragraph::new(lines).block(log_block)
+        } else if let Some(term) = vm.log_highlight() {
+            let start = (vm.log_scroll() as usize).min(vm.log_lines().len());
+            let end = (start + log_area.height as usize).min(vm.log_lines().len());
+            let lines: Vec<Line> = vm.log_lines()[start..end]
+                .iter()
+                .map(|l| highlighted_line(l, term))
+                .collect();
+            Paragraph::new(lines).block(log_block)
+        } else {
+            let log_raw = vm.log_lines().join("\n");
+            let log_text: Text = log_raw
+                .as_str()
+                .into_text()
+                .unwrap_or_else(|_| Text::raw(log_raw));
+            Paragraph::new(log_text)
+                .block(log_block)
+                .scroll((vm.log_scroll(), 0))
+        };
+        f.render_widget(log_widget, log_area);
+    }
+
+    let r_title = match (vm.focus(), vm.mode()) {
+        (Focus::Result, Mode::Normal) => "R [FOCUS] (v:select)",
+        (Focus::Result, Mode::CommandLine) => "R [FOCUS :]",
+        _ => "R",
+    };
+    let r_title = with_search(r_title, vm.search_status(Focus::Result));
+
+    let r_border_style = theme.border(vm.focus() == Focus::Result);
+
+    let r_text: Text = match vm.result_search_term() {
+        Some(term) => vm
+            .result_lines()
+            .iter()
+            .map(|l| highlighted_line(l, term))
+            .collect::<Vec<_>>()
+            .into(),
+        None => {
+            let r_raw = vm.result_lines().join("\n");
+            r_raw
+                .as_str()
+                .into_text()
+                .unwrap_or_else(|_| Text::raw(r_raw))
+        }
+    };
+
+    let r_widget = Paragraph::new(r_text)
+        .block(
+            Block::default()
+                .title(r_title)
+                .borders(Borders::ALL)
+                .border_style(r_border_style),
+        )
+        .scroll((vm.result_scroll(), 0));
+    f.render_widget(r_widget, result_area);
+
+    if let Some(transfer) = vm.transfer()
+        && result_area.height > 3
+    {
+        let gauge_area = Rect {
+            x: result_area.x + 1,
+            y: result_area.bottom() - 2,
+            width: result_area.width.saturating_sub(2),
+            height: 1,
+        };
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(theme.accent))
+            .percent(transfer.percent)
+            .label(format!(
+                "{} {}% {}",
+                transfer.phase, transfer.percent, transfer.detail
+            ));
+        f.render_widget(gauge, gauge_area);
+    }
+
+    let status_line = match (vm.mode(), vm.flow_prompt()) {
+        _ if let Some(input) = vm.search_input() => Line::from(Span::styled(
+            format!("/{}", input),
+            Style::default().add_modifier(Modifier::REVERSED),
+        )),
+        (_, Some((prompt, input))) => Line::from(Span::styled(
+            format!("{}: {}", prompt, input),
+            Style::default().add_modifier(Modifier::REVERSED),
+        )),
+        (Mode::Normal, None) => {
+            let cwd = match vm.review() {
+                Some(review) => review.path.to_string_lossy().into_owned(),
+                None => std::env::current_dir()
+                    .ok()
+                    .and_then(|p| p.to_str().map(|s| s.to_string()))
+                    .unwrap_or_else(|| "?".into()),
+            };
+
+            let file_path = match vm.selected_dir() {
+                Some(dir) => Some(format!("{}/", dir)),
+                None => vm.files().get(vm.selected_file()).map(|f| f.display_path()),
+            };
+
+            let max_len = status_area.width.saturating_sub(40) as usize;
+            let file_display = file_path.map(|p| {
+                if max_len == 0 || p.len() <= max_len {
+                    p
+                } else {
+                    let start = p.len() - max_len;
+                    format!("?{}", &p[start..])
+                }
+            });
+
+            let mut spans: Vec<Span> = vec![
+                Span::styled(
+                    " -- NORMAL -- ",
+                    Style::default().add_modifier(Modifier::REVERSED),
+                ),
+                Span::raw(" "),
+                Span::styled(vm.status_summary(), Style::default().fg(theme.accent)),
+                Span::raw("  "),
+                Span::raw(cwd),
+            ];
+            if vm.offline() {
+                spans.insert(
+                    1,
+                    Span::styled(
+                        " OFFLINE ",
+                        Style::default()
+                            .fg(theme.error)
+                            .add_modifier(Modifier::REVERSED | Modifier::BOLD),
+                    ),
+                );
+            }
+            if vm.audit() {
+                spans.insert(
+                    1,
+                    Span::styled(
+                        " AUDIT ",
+                        Style::default()
+                            .fg(theme.accent)
+                            .add_modifier(Modifier::REVERSED | Modifier::BOLD),
+                    ),
+                );
+            }
+            if vm.read_only() {
+                spans.insert(
+                    1,
+                    Span::styled(
+                        " READ-ONLY ",
+                        Style::default()
+                            .fg(theme.accent)
+                            .add_modifier(Modifier::REVERSED | Modifier::BOLD),
+                    ),
+                );
+            }
+            if let Some(review) = vm.review() {
+                spans.insert(
+                    1,
+                    Span::styled(
+                        format!(" REVIEW {} ", review.target),
+                        Style::default()
+                            .fg(theme.accent)
+                            .add_modifier(Modifier::REVERSED | Modifier::BOLD),
+                    ),
+                );
+            }
+            if let Some(sequence) = vm.pick_sequence() {
+                let color = if sequence.stopped.is_some() {
+                    theme.error
+                } else {
+                    theme.accent
+                };
+                spans.insert(
+                    1,
+                    Span::styled(
+                        format!(
+                            " {} {} of {} applied ",
+                            sequence.command.to_uppercase(),
+                            sequence.applied,
+                            sequence.total
+                        ),
+                        Style::default()
+                            .fg(color)
+                            .add_modifier(Modifier::REVERSED | Modifier::BOLD),
+                    ),
+                );
+            }
+            if let Some(operation) = vm.operation()
+                && vm.pick_sequence().is_none()
+            {
+                spans.insert(
+                    1,
+                    Span::styled(
+                        format!(" {} ", operation.label()),
+                        Style::default()
+                            .fg(theme.error)
+                            .add_modifier(Modifier::REVERSED | Modifier::BOLD),
+                    ),
+                );
+            }
+            let conflicts = vm.conflict_count();
+            if conflicts > 0 {
+                spans.insert(
+                    1,
+                    Span::styled(
+                        format!(" CONFLICTS {} ", conflicts),
+                        Style::default()
+                            .fg(theme.error)
+                            .add_modifier(Modifier::REVERSED | Modifier::BOLD),
+                    ),
+                );
+            }
+            if vm.dry_run() {
+                spans.insert(
+                    1,
+                    Span::styled(
+                        " DRY-RUN ",
+                        Style::default()
+                            .fg(theme.error)
+                            .add_modifier(Modifier::REVERSED | Modifier::BOLD),
+                    ),
+                );
+            }
+
+            if let Some((spinner, cmd)) = vm.running_indicator() {
+                let mut label = format!("{} git {}", spinner, cmd);
+                if let Some((done, total)) = vm.progress() {
+                    label.push_str(&format!(" [{}/{}]", done, total));
+                }
+                if let Some((attempt, retries)) = vm.retry() {
+                    label.push_str(&format!(" (retry {}/{})", attempt, retries));
+                }
+                if vm.queued_commands() > 0 {
+                    label.push_str(&format!(" (+{} queued)", vm.queued_commands()));
+                }
+                let max_len = status_area.width.saturating_sub(8) as usize;
+                if max_len > 3 && label.len() > max_len {
+                    let shortened: String = label.chars().take(max_len - 3).collect();
+                    label = format!("{}...", shortened);
+                }
+                spans.push(Span::raw("  |  "));
+                spans.push(Span::styled(label, Style::default().fg(theme.accent)));
+            }
+
+            let (tab, tabs) = vm.tab_position();
+            if tabs > 1 {
+                spans.push(Span::raw("  |  "));
+                spans.push(Span::raw(format!("tab {}/{}", tab + 1, tabs)));
+            }
+
+            if vm.background_jobs() > 0 {
+                spans.push(Span::raw("  |  "));
+                spans.push(Span::raw(format!("jobs: {}", vm.background_jobs())));
+            }
+
+            if let Some(tag) = vm.update_available() {
+                spans.push(Span::raw("  |  "));
+                spans.push(Span::styled(
+                    format!("{} available (:check-update)", tag),
+                    Style::default().fg(theme.accent),
+                ));
+            }
+
+            if vm.focus() == Focus::Cmd
+                && vm.running_indicator().is_none()
+                && let Some(preview) = vm.command_preview()
+            {
+                spans.push(Span::raw("  |  "));
+                spans.push(Span::styled(
+                    format!("Enter: {}", preview),
+                    Style::default().fg(theme.accent),
+                ));
+            }
+
+            if let Some(scope) = vm.scope() {
+                spans.push(Span::raw("  |  "));
+                spans.push(Span::styled(
+                    format!("scope: {}", scope),
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+
+            if let Some(note) = vm.branch_note() {
+                spans.push(Span::raw("  |  "));
+                spans.push(Span::styled(
+                    format!("note: {}", note),
+                    Style::default().add_modifier(Modifier::ITALIC),
+                ));
+            }
+
+            if let Some(fp) = file_display {
+                spans.push(Span::raw("  |  "));
+                spans.push(Span::raw("file: "));
+                spans.push(Span::styled(fp, Style::default().fg(theme.accent)));
+            }
+
+            Line::from(spans)
+        }
+        (Mode::CommandLine, None) => Line::from(Span::styled(
+            format!(":{}", vm.cmdline()),
+            Style::default().add_modifier(Modifier::REVERSED),
+        )),
+    };
+
+    let status = Paragraph::new(status_line);
+    f.render_widget(status, status_area);
+
+    if vm.teaching() {
+        let teach_line = match vm.teaching_echo() {
+            Some((command, explanation)) => {
+                let mut spans = vec![Span::styled(
+                    format!(" $ {} ", command),
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::REVERSED | Modifier::BOLD),
+                )];
+                if let Some(text) = explanation {
+                    spans.push(Span::raw(format!("  {}", text)));
+                }
+                Line::from(spans)
+            }
+            None => Line::from(Span::styled(
+                " the git command behind each action shows here; :today lists them",
+                Style::default().add_modifier(Modifier::DIM),
+            )),
+        };
+        f.render_widget(Paragraph::new(teach_line), teach_area);
+    }
+
+    if let Some(form) = vm.command_form() {
+        draw_command_form(form, theme, f, size);
+    }
+    if let Some(editor) = vm.commit_editor() {
+        draw_commit_editor(editor, theme, f, size);
+    }
+    if let Some(finder) = vm.finder() {
+        draw_finder(vm, finder, f, size);
+    }
+    if let Some(scroll) = vm.help_scroll() {
+        draw_help(vm.help_rows(), scroll, theme, f, help_area);
+    }
+
+    theme.support.apply(f.buffer_mut());
+    [log_area, result_area].map(|area| area.inner(Margin::new(1, 1)))
+}
+
+/// Splits `area` between a pane of fixed height and one taking the rest, each given as
+/// its position in the layout (`None` when hidden) and a height; the earlier one goes on top.
+fn stack(area: Rect, fixed: (Option<usize>, u16), rest: (Option<usize>, u16)) -> (Rect, Rect) {
+    match (fixed.0, rest.0) {
+        (Some(f), Some(r)) => {
+            let (constraints, fixed_first) = if f < r {
+                ([Constraint::Length(fixed.1), Constraint::Min(rest.1)], true)
+            } else {
+                (
+                    [Constraint::Min(rest.1), Constraint::Length(fixed.1)],
+                    false,
+                )
+            };
+            let split = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(constraints.as_ref())
+                .split(area);
+            if fixed_first {
+                (split[0], split[1])
+            } else {
+                (split[1], split[0])
+            }
+        }
+        (Some(_), None) => (area, Rect::default()),
+        (None, Some(_)) => (Rect::default(), area),
+        (None, None) => (Rect::default(), Rect::default()),
+    }
+}
+
+fn draw_command_form(form: &CommandForm, theme: &Theme, f: &mut Frame<'_>, area: Rect) {
+    let mut lines: Vec<Line> = form
+        .fields()
+        .enumerate()
+        .map(|(i, (label, value))| {
+            let active = i == form.active();
+            let value_style = if active {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{}{:<12}", if active { "> " } else { "  " }, label),
+                    Style::default().fg(theme.accent),
+                ),
+                Span::styled(format!("{} ", value), value_style),
+            ])
+        })
+        .collect();
+    if let Some(error) = form.error() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            error.to_string(),
+            Style::default().fg(theme.error),
+        )));
+    }
+
+    let width = area.width.saturating_sub(4).min(70);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(form.title())
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.accent)),
+        ),
+        popup,
+    );
+}
+
+fn draw_commit_editor(editor: &CommitEditor, theme: &Theme, f: &mut Frame<'_>, area: Rect) {
+    let width = area.width.saturating_sub(4).min(80);
+    let height = area.height.saturating_sub(2).min(20);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    // Borders plus the counter line, and the error line when there is one.
+    let footer = 1 + editor.error().map_or(0, |_| 1);
+    let rows = (height as usize).saturating_sub(2 + footer).max(1);
+    let (row, col) = editor.cursor();
+    let top = (row + 1).saturating_sub(rows);
+
+    let mut lines: Vec<Line> = editor
+        .lines()
+        .iter()
+        .enumerate()
+        .skip(top)
+        .take(rows)
+        .map(|(i, text)| {
+            let style = if i == 0 {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            if i != row {
+                return Line::from(Span::styled(text.clone(), style));
+            }
+            let before: String = text.chars().take(col).collect();
+            let at: String = text.chars().nth(col).map_or(" ".into(), String::from);
+            let after: String = text.chars().skip(col + 1).collect();
+            Line::from(vec![
+                Span::styled(before, style),
+                Span::styled(at, style.add_modifier(Modifier::REVERSED)),
+                Span::styled(after, style),
+            ])
+        })
+        .collect();
+    lines.resize(rows, Line::from(""));
+
+    let counter = |label: String, over: bool| {
+        Span::styled(
+            label,
+            if over {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default().add_modifier(Modifier::DIM)
+            },
+        )
+    };
+    let subject = editor.subject_len();
+    let longest = editor.longest_body_line();
+    lines.push(Line::from(vec![
+        counter(
+            format!("subject {}/{}", subject, SUBJECT_LIMIT),
+            subject > SUBJECT_LIMIT,
+        ),
+        Span::raw("  "),
+        counter(
+            format!(
+                "body {} line(s), longest {}/{}",
+                editor.body_lines(),
+                longest,
+                BODY_LIMIT
+            ),
+            longest > BODY_LIMIT,
+        ),
+    ]));
+    if let Some(error) = editor.error() {
+        lines.push(Line::from(Span::styled(
+            error.to_string(),
+            Style::default().fg(theme.error),
+        )));
+    }
+
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title(editor.title())
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.accent)),
+        ),
+        popup,
+    );
+}
 
+/// Where the key binding overlay goes in `area`.
+fn help_popup(area: Rect) -> Rect {
+    let width = area.width.saturating_sub(4).min(76);
+    let height = area.height.saturating_sub(2);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+fn draw_help(rows: Vec<HelpRow>, scroll: usize, theme: &Theme, f: &mut Frame<'_>, popup: Rect) {
+    let key_width = rows
+        .iter()
+        .filter_map(|row| match row {
+            HelpRow::Binding(keys, _) => Some(keys.chars().count()),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = rows
+        .into_iter()
+        .skip(scroll)
+        .map(|row| match row {
+            HelpRow::Section(title) => Line::from(Span::styled(
+                title,
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            HelpRow::Binding(keys, text) => Line::from(vec![
+                Span::styled(
+                    format!("  {:<width$}  ", keys, width = key_width),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(text),
+            ]),
+            HelpRow::Blank => Line::from(""),
+        })
+        .collect();
+
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title("keys (j/k:scroll Esc:close)")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.accent)),
+        ),
+        popup,
+    );
+}
+
+fn draw_finder(vm: &ViewModel, finder: &FileFinder, f: &mut Frame<'_>, area: Rect) {
+    let theme = vm.theme();
+    let width = area.width.saturating_sub(4).min(90);
+    let height = area.height.saturating_sub(2).min(24);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let field = |label: &str, value: &str, active: bool| {
+        Line::from(vec![
+            Span::styled(
+                format!("{}{:<14}", if active { "> " } else { "  " }, label),
+                Style::default().fg(theme.accent),
+            ),
+            Span::styled(
+                format!("{} ", value),
+                if active {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                },
+            ),
+        ])
+    };
+    let matches = vm.finder_matches();
+    let mut lines = vec![
+        field("find", finder.query(), !finder.editing_ref()),
+        field("diff against", finder.against(), finder.editing_ref()),
+        Line::from(Span::styled(
+            format!(
+                "  {} of {} tracked files",
+                matches.len(),
+                vm.tracked_count()
+            ),
+            Style::default().add_modifier(Modifier::DIM),
+        )),
+    ];
+    let rows = (height as usize).saturating_sub(2 + lines.len());
+    let start = (finder.selected() + 1).saturating_sub(rows);
+    lines.extend(
+        matches
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(rows)
+            .map(|(i, path)| {
+                if i == finder.selected() {
+                    Line::from(Span::styled(
+                        format!("> {}", path),
+                        Style::default()
+                            .fg(theme.accent)
+                            .add_modifier(Modifier::BOLD),
+                    ))
+                } else {
+                    Line::from(format!("  {}", path))
+                }
+            }),
+    );
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .title("FIND FILE (Enter:diff ^E:edit ^B:blame ^L:history Tab:ref Esc)")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.accent)),
+        ),
+        popup,
+    );
+}
+
+fn draw_files(vm: &ViewModel, f: &mut Frame<'_>, files_area: Rect) {
+    let theme = vm.theme();
+    let marks = vm.file_marks();
+
+    // Flat, every file is a row; as a tree, directories get rows of their own and
+    // files are indented under them.
+    let rows: Vec<FileRow> = if vm.files_tree() {
+        vm.file_rows()
+    } else {
+        (0..vm.files().len())
+            .map(|index| FileRow::File { index, depth: 0 })
+            .collect()
+    };
+    let label = |fe: &RepoFile| match fe.display_label() {
+        label if label.is_empty() => fe.path.clone(),
+        label => label,
+    };
+    // Decorations line up in a column after the longest name.
+    let name_width = rows
+        .iter()
+        .filter_map(|row| match row {
+            FileRow::File { index, depth } => {
+                Some(depth * 2 + label(&vm.files()[*index]).chars().count())
+            }
+            FileRow::Dir { .. } => None,
+        })
+        .max()
+        .unwrap_or(0);
     let file_items: Vec<ListItem> = if vm.files().is_empty() {
         vec![ListItem::new(Line::from(Span::raw(
             "<clean or no changes>",
         )))]
     } else {
-        vm.files()
-            .iter()
-            .enumerate()
-            .map(|(i, fe)| {
-                let marker = if i == vm.selected_file() { "> " } else { "  " };
-                let status = fe.status.as_str();
-                let display_name = {
-                    let name = fe.display_label();
-                    if name.is_empty() {
-                        fe.path.clone()
-                    } else {
-                        name
+        rows.iter()
+            .map(|row| {
+                let (i, depth) = match row {
+                    FileRow::File { index, depth } => (*index, *depth),
+                    FileRow::Dir {
+                        path,
+                        depth,
+                        collapsed,
+                    } => {
+                        let selected = vm.selected_dir() == Some(path.as_str());
+                        let name = path.rsplit('/').next().unwrap_or(path);
+                        let mut spans = vec![Span::styled(
+                            format!(
+                                "{}{}{} {}/",
+                                if selected { "> " } else { "  " },
+                                "  ".repeat(*depth),
+                                if *collapsed { '▸' } else { '▾' },
+                                name
+                            ),
+                            if selected {
+                                theme.selected(Style::default())
+                            } else {
+                                Style::default()
+                            },
+                        )];
+                        if *collapsed {
+                            let prefix = format!("{}/", path);
+                            let count = vm
+                                .files()
+                                .iter()
+                                .filter(|fe| fe.path.starts_with(&prefix))
+                                .count();
+                            spans.push(Span::styled(
+                                format!("  {} file(s)", count),
+                                Style::default().add_modifier(Modifier::DIM),
+                            ));
+                        }
+                        return ListItem::new(Line::from(spans));
                     }
                 };
+                let fe = &vm.files()[i];
+                let selected = vm.selected_dir().is_none() && i == vm.selected_file();
+                let marker = format!(
+                    "{}{}",
+                    if selected { '>' } else { ' ' },
+                    if marks[i] { '*' } else { ' ' }
+                );
+                let status = fe.status.as_str();
+                let display_name = label(fe);
 
                 let mut chars = status.chars();
                 let x = chars.next().unwrap_or(' ');
@@ -121,42 +978,78 @@ pub(super) fn draw(vm: &mut ViewModel, f: &mut Frame<'_>) {
                 let has_unstaged = y != ' ';
 
                 let status_label = format!("[{}]", status);
-                let text = format!("{}{} {}", marker, status_label, display_name);
+                let indent = "  ".repeat(depth);
+                let text = format!("{}{}{} {}", marker, indent, status_label, display_name);
 
                 let mut style = Style::default();
 
                 if is_staged {
-                    style = style.fg(theme.accent);
+                    style = style.fg(theme.staged);
+                } else if has_unstaged && !is_untracked {
+                    style = style.fg(theme.unstaged);
                 }
 
                 if is_untracked {
-                    style = style.add_modifier(Modifier::ITALIC);
+                    style = style.fg(theme.untracked).add_modifier(Modifier::ITALIC);
                 }
 
-                if i == vm.selected_file() {
+                if selected {
+                    style = theme.selected(style);
+                } else if marks[i] {
                     style = style.add_modifier(Modifier::BOLD);
                 }
 
-                if is_staged && has_unstaged {
+                if is_staged && has_unstaged && !fe.is_conflicted() {
                     style = style.add_modifier(Modifier::UNDERLINED);
                 }
 
-                ListItem::new(Line::from(Span::styled(text, style)))
+                if fe.is_conflicted() {
+                    style = style.fg(theme.conflicted);
+                }
+
+                let mut spans = vec![Span::styled(text, style)];
+                if fe.is_conflicted() {
+                    spans.push(Span::styled(
+                        "  conflict",
+                        Style::default()
+                            .fg(theme.conflicted)
+                            .add_modifier(Modifier::DIM),
+                    ));
+                }
+                if let Some(note) = vm.file_decoration(i) {
+                    let pad =
+                        name_width.saturating_sub(indent.len() + display_name.chars().count());
+                    spans.push(Span::styled(
+                        format!("{}  {}", " ".repeat(pad), note),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
             })
             .collect()
     };
 
+    let marked = marks.iter().filter(|m| **m).count();
     let files_title = match (vm.focus(), vm.mode()) {
-        (Focus::Files, Mode::Normal) => "FILES [FOCUS] (s:stage/unstage)",
-        (Focus::Files, Mode::CommandLine) => "FILES [FOCUS :]",
-        _ => "FILES",
+        (Focus::Files, Mode::Normal) if vm.visual_range_active() => {
+            format!("FILES [VISUAL] {} (v:mark range Esc:cancel)", marked)
+        }
+        (Focus::Files, Mode::Normal) if marked > 0 => format!(
+            "FILES [FOCUS] {} marked (s:stage/unstage x:discard d:diff Esc:clear)",
+            marked
+        ),
+        (Focus::Files, Mode::Normal) if vm.selected_file_conflicted() => {
+            "FILES [FOCUS] (o:ours t:theirs m:mergetool r:resolved d:3-way diff)".into()
+        }
+        (Focus::Files, Mode::Normal) => {
+            "FILES [FOCUS] (s:stage/unstage Space:mark Enter:hunks Tab:panel)".into()
+        }
+        (Focus::Files, Mode::CommandLine) => "FILES [FOCUS :]".into(),
+        _ if marked > 0 => format!("FILES {} marked", marked),
+        _ => "FILES".into(),
     };
 
-    let files_border_style = if matches!(vm.focus(), Focus::Files) {
-        Style::default().fg(theme.accent)
-    } else {
-        Style::default()
-    };
+    let files_border_style = theme.border(vm.focus() == Focus::Files);
 
     let files_list = List::new(file_items).block(
         Block::default()
@@ -165,123 +1058,643 @@ pub(super) fn draw(vm: &mut ViewModel, f: &mut Frame<'_>) {
             .border_style(files_border_style),
     );
     f.render_widget(files_list, files_area);
+}
 
-    let log_title = match (vm.focus(), vm.mode()) {
-        (Focus::Log, Mode::Normal) => "LOG [FOCUS]",
-        (Focus::Log, Mode::CommandLine) => "LOG [FOCUS :]",
-        _ => "LOG",
-    };
+fn draw_commits(vm: &ViewModel, f: &mut Frame<'_>, area: Rect) {
+    let theme = vm.theme();
+    let list = vm.commit_list();
 
-    let log_border_style = if matches!(vm.focus(), Focus::Log) {
-        Style::default().fg(theme.accent)
+    let items: Vec<ListItem> = if list.commits.is_empty() {
+        vec![ListItem::new(Line::from(Span::raw("<no commits>")))]
     } else {
-        Style::default()
+        list.commits
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let selected = i == vm.selected_commit();
+                let marker = if selected { "> " } else { "  " };
+                let mut style = Style::default();
+                if selected {
+                    style = theme.selected(style);
+                }
+                let mut spans = vec![
+                    Span::raw(marker),
+                    Span::styled(c.short.as_str(), Style::default().fg(theme.accent)),
+                ];
+                for r in &c.refs {
+                    spans.push(Span::raw(" "));
+                    spans.push(ref_badge(r));
+                }
+                spans.push(Span::styled(
+                    format!(" {} {} ", c.date, c.author),
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+                spans.push(Span::styled(c.subject.as_str(), style));
+                ListItem::new(Line::from(spans))
+            })
+            .collect()
     };
 
-    let log_raw = vm.log_lines().join("\n");
-    let log_text: Text = log_raw
-        .as_str()
-        .into_text()
-        .unwrap_or_else(|_| Text::raw(log_raw));
+    let filter = if list.all.is_some() { " [tags]" } else { "" };
+    let title = match (vm.focus(), vm.mode()) {
+        (Focus::Commits, Mode::Normal) => {
+            format!(
+                "COMMITS [FOCUS] {}{} (Enter:diff c:checkout r:revert p:cherry-pick y:copy hash t:tags)",
+                list.title, filter
+            )
+        }
+        (Focus::Commits, Mode::CommandLine) => "COMMITS [FOCUS :]".to_string(),
+        _ => format!("COMMITS {}{}", list.title, filter),
+    };
 
-    let log_widget = Paragraph::new(log_text)
-        .block(
-            Block::default()
-                .title(log_title)
-                .borders(Borders::ALL)
-                .border_style(log_border_style),
-        )
-        .scroll((vm.log_scroll(), 0));
-    f.render_widget(log_widget, log_area);
+    let border_style = theme.border(vm.focus() == Focus::Commits);
 
-    let r_title = match (vm.focus(), vm.mode()) {
-        (Focus::Result, Mode::Normal) => "R [FOCUS]",
-        (Focus::Result, Mode::CommandLine) => "R [FOCUS :]",
-        _ => "R",
+    let commits = List::new(items).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(border_style),
+    );
+    let mut state = ListState::default().with_selected(Some(vm.selected_commit()));
+    f.render_stateful_widget(commits, area, &mut state);
+}
+
+/// A branch, tag or remote head, colored the way `git log --decorate` colors them.
+fn ref_badge(r: &CommitRef) -> Span<'_> {
+    let (label, color) = match r.kind {
+        RefKind::Head if r.name == "HEAD" => (" HEAD ".to_string(), Color::Cyan),
+        RefKind::Head => (format!(" HEAD -> {} ", r.name), Color::Cyan),
+        RefKind::Branch => (format!(" {} ", r.name), Color::Green),
+        RefKind::Remote => (format!(" {} ", r.name), Color::Red),
+        RefKind::Tag => (format!(" tag: {} ", r.name), Color::Yellow),
     };
+    Span::styled(label, Style::default().fg(Color::Black).bg(color))
+}
+
+fn draw_matches(vm: &ViewModel, f: &mut Frame<'_>, area: Rect) {
+    let theme = vm.theme();
+    let list = vm.match_list();
 
-    let r_border_style = if matches!(vm.focus(), Focus::Result) {
-        Style::default().fg(theme.accent)
+    let items: Vec<ListItem> = if list.matches.is_empty() {
+        vec![ListItem::new(Line::from(Span::raw("<no matches>")))]
     } else {
-        Style::default()
+        list.matches
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let selected = i == vm.selected_match();
+                let marker = if selected { "> " } else { "  " };
+                let mut style = Style::default();
+                if selected {
+                    style = theme.selected(style);
+                }
+                ListItem::new(Line::from(vec![
+                    Span::raw(marker),
+                    Span::styled(
+                        format!("{}:{}:", m.path, m.line),
+                        Style::default().fg(theme.accent),
+                    ),
+                    Span::styled(m.text.trim(), style),
+                ]))
+            })
+            .collect()
     };
 
-    let r_raw = vm.result_lines().join("\n");
-    let r_text: Text = r_raw
-        .as_str()
-        .into_text()
-        .unwrap_or_else(|_| Text::raw(r_raw));
+    let title = match (vm.focus(), vm.mode()) {
+        (Focus::Matches, Mode::Normal) => format!("MATCHES [FOCUS] {} (e:edit)", list.title),
+        (Focus::Matches, Mode::CommandLine) => "MATCHES [FOCUS :]".to_string(),
+        _ => format!("MATCHES {}", list.title),
+    };
 
-    let r_widget = Paragraph::new(r_text)
-        .block(
-            Block::default()
-                .title(r_title)
-                .borders(Borders::ALL)
-                .border_style(r_border_style),
-        )
-        .scroll((vm.result_scroll(), 0));
-    f.render_widget(r_widget, result_area);
+    let border_style = theme.border(vm.focus() == Focus::Matches);
+
+    let matches = List::new(items).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(border_style),
+    );
+    let mut state = ListState::default().with_selected(Some(vm.selected_match()));
+    f.render_stateful_widget(matches, area, &mut state);
+}
 
-    let status_line = match vm.mode() {
-        Mode::Normal => {
-            let cwd = std::env::current_dir()
-                .ok()
-                .and_then(|p| p.to_str().map(|s| s.to_string()))
-                .unwrap_or_else(|| "?".into());
+fn draw_stashes(vm: &ViewModel, f: &mut Frame<'_>, area: Rect) {
+    let theme = vm.theme();
 
-            let file_path = vm
-                .files()
-                .get(vm.selected_file())
-                .map(|f| f.path.replace('"', ""));
+    let items: Vec<ListItem> = if vm.stashes().is_empty() {
+        vec![ListItem::new(Line::from(Span::raw("<no stashes>")))]
+    } else {
+        vm.stashes()
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let selected = i == vm.selected_stash();
+                let marker = if selected { "> " } else { "  " };
+                let mut style = Style::default();
+                if selected {
+                    style = theme.selected(style);
+                }
+                ListItem::new(Line::from(vec![
+                    Span::raw(marker),
+                    Span::styled(s.name.as_str(), Style::default().fg(theme.accent)),
+                    Span::styled(
+                        format!(" {} ", s.age),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ),
+                    Span::styled(s.subject.as_str(), style),
+                ]))
+            })
+            .collect()
+    };
 
-            let max_len = status_area.width.saturating_sub(40) as usize;
-            let file_display = file_path.map(|p| {
-                if max_len == 0 || p.len() <= max_len {
-                    p
+    let title = match (vm.focus(), vm.mode()) {
+        (Focus::Stashes, Mode::Normal) => {
+            "STASHES [FOCUS] (Enter:show a:apply p:pop D:drop s:stash S:+untracked w:vs worktree b:branch)"
+        }
+        (Focus::Stashes, Mode::CommandLine) => "STASHES [FOCUS :]",
+        _ => "STASHES",
+    };
+
+    let border_style = theme.border(vm.focus() == Focus::Stashes);
+
+    let stashes = List::new(items).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(border_style),
+    );
+    let mut state = ListState::default().with_selected(Some(vm.selected_stash()));
+    f.render_stateful_widget(stashes, area, &mut state);
+}
+
+fn draw_branches(vm: &ViewModel, f: &mut Frame<'_>, area: Rect) {
+    let theme = vm.theme();
+
+    let items: Vec<ListItem> = if vm.branches().is_empty() {
+        vec![ListItem::new(Line::from(Span::raw("<no branches>")))]
+    } else {
+        vm.branches()
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                let selected = i == vm.selected_branch();
+                let marker = if selected { "> " } else { "  " };
+                let mut name_style = if b.remote {
+                    Style::default().add_modifier(Modifier::DIM)
                 } else {
-                    let start = p.len() - max_len;
-                    format!("?{}", &p[start..])
+                    Style::default().fg(theme.accent)
+                };
+                if b.current {
+                    name_style = name_style.add_modifier(Modifier::BOLD);
                 }
-            });
+                let mut subject_style = Style::default();
+                if selected {
+                    subject_style = theme.selected(subject_style);
+                }
+                let mut track = String::new();
+                if b.gone {
+                    track.push_str(" gone");
+                }
+                if b.ahead > 0 {
+                    track.push_str(&format!(" \u{2191}{}", b.ahead));
+                }
+                if b.behind > 0 {
+                    track.push_str(&format!(" \u{2193}{}", b.behind));
+                }
+                ListItem::new(Line::from(vec![
+                    Span::raw(marker),
+                    Span::raw(if b.current { "* " } else { "  " }),
+                    Span::styled(b.name.as_str(), name_style),
+                    Span::styled(track, Style::default().fg(Color::Yellow)),
+                    Span::raw(" "),
+                    Span::styled(b.subject.as_str(), subject_style),
+                ]))
+            })
+            .collect()
+    };
 
-            let mut spans: Vec<Span> = Vec::new();
-            spans.push(Span::styled(
-                " -- NORMAL -- ",
-                Style::default().add_modifier(Modifier::REVERSED),
-            ));
-            spans.push(Span::raw(" "));
-            spans.push(Span::styled(
-                vm.status_summary(),
-                Style::default().fg(theme.accent),
-            ));
-            spans.push(Span::raw("  "));
-            spans.push(Span::raw(cwd));
+    let title = match (vm.focus(), vm.mode()) {
+        (Focus::Branches, Mode::Normal) => {
+            "BRANCHES [FOCUS] (Enter:checkout n:new r:rename D:delete w:worktree)"
+        }
+        (Focus::Branches, Mode::CommandLine) => "BRANCHES [FOCUS :]",
+        _ => "BRANCHES",
+    };
 
-            if let Some((spinner, cmd)) = vm.running_indicator() {
-                let mut label = format!("{} git {}", spinner, cmd);
-                let max_len = status_area.width.saturating_sub(8) as usize;
-                if max_len > 3 && label.len() > max_len {
-                    let shortened: String = label.chars().take(max_len - 3).collect();
-                    label = format!("{}...", shortened);
+    let border_style = theme.border(vm.focus() == Focus::Branches);
+
+    let branches = List::new(items).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(border_style),
+    );
+    let mut state = ListState::default().with_selected(Some(vm.selected_branch()));
+    f.render_stateful_widget(branches, area, &mut state);
+}
+
+fn draw_remotes(vm: &ViewModel, f: &mut Frame<'_>, area: Rect) {
+    let theme = vm.theme();
+
+    let items: Vec<ListItem> = if vm.remotes().is_empty() {
+        vec![ListItem::new(Line::from(Span::raw("<no remotes> (a:add)")))]
+    } else {
+        vm.remotes()
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let selected = i == vm.selected_remote();
+                let marker = if selected { "> " } else { "  " };
+                let mut url_style = Style::default();
+                if selected {
+                    url_style = theme.selected(url_style);
                 }
-                spans.push(Span::raw("  |  "));
-                spans.push(Span::styled(label, Style::default().fg(theme.accent)));
-            }
+                let mut spans = vec![
+                    Span::raw(marker),
+                    Span::styled(r.name.as_str(), Style::default().fg(theme.accent)),
+                    Span::raw(" "),
+                    Span::styled(r.fetch_url.as_str(), url_style),
+                ];
+                if r.push_url != r.fetch_url {
+                    spans.push(Span::styled(
+                        format!(" push: {}", r.push_url),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect()
+    };
 
-            if let Some(fp) = file_display {
-                spans.push(Span::raw("  |  "));
-                spans.push(Span::raw("file: "));
-                spans.push(Span::styled(fp, Style::default().fg(theme.accent)));
+    let title = match (vm.focus(), vm.mode()) {
+        (Focus::Remotes, Mode::Normal) => {
+            "REMOTES [FOCUS] (Enter:show f:fetch p:prune a:add r:rename u:set url D:remove)"
+        }
+        (Focus::Remotes, Mode::CommandLine) => "REMOTES [FOCUS :]",
+        _ => "REMOTES",
+    };
+
+    let border_style = theme.border(vm.focus() == Focus::Remotes);
+
+    let remotes = List::new(items).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(border_style),
+    );
+    let mut state = ListState::default().with_selected(Some(vm.selected_remote()));
+    f.render_stateful_widget(remotes, area, &mut state);
+}
+
+fn draw_worktrees(vm: &ViewModel, f: &mut Frame<'_>, area: Rect) {
+    let theme = vm.theme();
+
+    let items: Vec<ListItem> = vm
+        .worktrees()
+        .iter()
+        .enumerate()
+        .map(|(i, w)| {
+            let selected = i == vm.selected_worktree();
+            let marker = if selected { "> " } else { "  " };
+            let current = vm.is_current_worktree(w);
+            let head = match &w.branch {
+                Some(branch) => branch.clone(),
+                None if w.bare => "(bare)".to_string(),
+                None => format!("(detached {})", w.head.get(..7).unwrap_or(&w.head)),
+            };
+            let mut head_style = Style::default().fg(theme.accent);
+            if current {
+                head_style = head_style.add_modifier(Modifier::BOLD);
+            }
+            let mut path_style = Style::default();
+            if selected {
+                path_style = theme.selected(path_style);
+            }
+            let mut flags = String::new();
+            if w.locked {
+                flags.push_str(" locked");
             }
+            if w.prunable {
+                flags.push_str(" prunable");
+            }
+            ListItem::new(Line::from(vec![
+                Span::raw(marker),
+                Span::raw(if current { "* " } else { "  " }),
+                Span::styled(head, head_style),
+                Span::styled(flags, Style::default().fg(Color::Yellow)),
+                Span::raw(" "),
+                Span::styled(w.path.to_string_lossy().into_owned(), path_style),
+            ]))
+        })
+        .collect();
 
-            Line::from(spans)
+    let title = match (vm.focus(), vm.mode()) {
+        (Focus::Worktrees, Mode::Normal) => {
+            "WORKTREES [FOCUS] (Enter:switch a:add p:prune D:remove)"
         }
-        Mode::CommandLine => Line::from(Span::styled(
-            format!(":{}", vm.cmdline()),
-            Style::default().add_modifier(Modifier::REVERSED),
-        )),
+        (Focus::Worktrees, Mode::CommandLine) => "WORKTREES [FOCUS :]",
+        _ => "WORKTREES",
     };
 
-    let status = Paragraph::new(status_line);
-    f.render_widget(status, status_area);
+    let border_style = theme.border(vm.focus() == Focus::Worktrees);
+
+    let worktrees = List::new(items).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(border_style),
+    );
+    let mut state = ListState::default().with_selected(Some(vm.selected_worktree()));
+    f.render_stateful_widget(worktrees, area, &mut state);
+}
+
+/// A LOG or R title with the `/` search and its match count appended.
+fn with_search(title: &str, search: Option<String>) -> String {
+    match search {
+        Some(search) => format!("{} {}", title, search),
+        None => title.to_string(),
+    }
+}
+
+fn highlight_ranges(text: &str, term: &str) -> Vec<Range<usize>> {
+    if term.is_empty() {
+        return Vec::new();
+    }
+    text.match_indices(term)
+        .map(|(i, m)| i..i + m.len())
+        .collect()
+}
+
+/// Renders a plain LOG line, dropping its ANSI colors only when it contains `term`.
+fn highlighted_line<'a>(raw: &'a str, term: &str) -> Line<'a> {
+    let text = strip_ansi(raw);
+    let ranges = highlight_ranges(&text, term);
+    if ranges.is_empty() {
+        return raw
+            .into_text()
+            .ok()
+            .and_then(|t| t.lines.into_iter().next())
+            .unwrap_or_else(|| Line::raw(raw));
+    }
+
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    for r in ranges {
+        if r.start > pos {
+            spans.push(Span::raw(text[pos..r.start].to_string()));
+        }
+        spans.push(Span::styled(
+            text[r.clone()].to_string(),
+            Style::default().fg(Color::Black).bg(Color::Yellow),
+        ));
+        pos = r.end;
+    }
+    if pos < text.len() {
+        spans.push(Span::raw(text[pos..].to_string()));
+    }
+    Line::from(spans)
+}
+
+fn diff_line<'a>(
+    line: &'a DiffLine,
+    diff: &ParsedDiff,
+    gutter: usize,
+    highlight: Option<&str>,
+    theme: &Theme,
+) -> Line<'a> {
+    let number = |n: Option<u32>| n.map(|n| n.to_string()).unwrap_or_default();
+    let gutter_span = Span::styled(
+        format!(
+            "{:>w$} {:>w$} \u{2502}",
+            number(line.old_no),
+            number(line.new_no),
+            w = gutter
+        ),
+        Style::default().fg(Color::DarkGray),
+    );
+    let mut spans = vec![gutter_span];
+    spans.extend(diff_text(line, diff, highlight, theme));
+    Line::from(spans)
+}
+
+/// The text of a diff line, colored by its language, its changed words emphasized and
+/// `highlight` marked.
+fn diff_text<'a>(
+    line: &'a DiffLine,
+    diff: &ParsedDiff,
+    highlight: Option<&str>,
+    theme: &Theme,
+) -> Vec<Span<'a>> {
+    let base = match line.kind {
+        LineKind::FileHeader => Style::default().add_modifier(Modifier::BOLD),
+        LineKind::HunkHeader => Style::default().fg(theme.accent),
+        LineKind::Added => Style::default().fg(Color::Green),
+        LineKind::Removed => Style::default().fg(Color::Red),
+        LineKind::Context => Style::default(),
+        LineKind::Meta => Style::default().add_modifier(Modifier::DIM),
+    };
+    let matches = highlight
+        .map(|term| highlight_ranges(&line.text, term))
+        .unwrap_or_default();
+    // Source lines keep their +/-/space prefix out of the highlighter.
+    let tokens: Vec<(Range<usize>, Color)> = match (&theme.syntax, line.kind) {
+        (Some(colors), LineKind::Added | LineKind::Removed | LineKind::Context)
+            if !line.text.is_empty() =>
+        {
+            line.file
+                .and_then(|f| diff.files.get(f))
+                .and_then(|path| Language::from_path(path))
+                .map(|lang| {
+                    lang.tokens(&line.text[1..])
+                        .into_iter()
+                        .map(|(r, kind)| (r.start + 1..r.end + 1, colors.of(kind)))
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+        _ => Vec::new(),
+    };
+    if line.emphasis.is_empty() && matches.is_empty() && tokens.is_empty() {
+        return vec![Span::styled(line.text.as_str(), base)];
+    }
+
+    let mut bounds: Vec<usize> = vec![0, line.text.len()];
+    for r in line
+        .emphasis
+        .iter()
+        .chain(&matches)
+        .chain(tokens.iter().map(|(r, _)| r))
+    {
+        bounds.push(r.start);
+        bounds.push(r.end);
+    }
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    let inside = |ranges: &[Range<usize>], pos: usize| ranges.iter().any(|r| r.contains(&pos));
+    let mut spans = Vec::new();
+    for w in bounds.windows(2) {
+        let (a, b) = (w[0], w[1]);
+        let mut style = base;
+        if let Some((_, color)) = tokens.iter().find(|(r, _)| r.contains(&a)) {
+            style = style.fg(*color);
+        }
+        if inside(&line.emphasis, a) {
+            style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+        }
+        if inside(&matches, a) {
+            style = style.fg(Color::Black).bg(Color::Yellow);
+        }
+        spans.push(Span::styled(&line.text[a..b], style));
+    }
+    spans
+}
+
+/// LOG as two columns, the old side on the left and the new one on the right, from the
+/// row holding line `log_scroll`.
+fn draw_split_diff(
+    vm: &ViewModel,
+    diff: &ParsedDiff,
+    block: Block<'_>,
+    f: &mut Frame<'_>,
+    area: Rect,
+) {
+    let theme = vm.theme();
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ])
+        .split(inner);
+
+    let start = (vm.log_scroll() as usize).min(diff.len());
+    let rows = diff.split_rows();
+    let first = rows
+        .iter()
+        .position(|r| r.last_line() >= start)
+        .unwrap_or(rows.len());
+    let sticky = diff.sticky_hunk(start);
+    let height = (inner.height as usize).saturating_sub(sticky.is_some() as usize);
+    let gutter = diff.max_line_number().max(1).to_string().len();
+    let cursor = matches!(vm.focus(), Focus::Log).then(|| vm.log_cursor());
+
+    let cell = |index: Option<usize>, old: bool| -> Line {
+        let Some(line) = index.map(|i| &diff.lines[i]) else {
+            return Line::from("");
+        };
+        let number = if old { line.old_no } else { line.new_no };
+        let mut spans = vec![Span::styled(
+            format!(
+                "{:>w$} \u{2502}",
+                number.map(|n| n.to_string()).unwrap_or_default(),
+                w = gutter
+            ),
+            Style::default().fg(Color::DarkGray),
+        )];
+        spans.extend(diff_text(line, diff, vm.log_highlight(), theme));
+        let line = Line::from(spans);
+        if index == cursor {
+            line.patch_style(Style::default().bg(Color::DarkGray))
+        } else {
+            line
+        }
+    };
+    let (mut left, mut right): (Vec<Line>, Vec<Line>) = rows[first..]
+        .iter()
+        .take(height)
+        .map(|row| match *row {
+            SplitRow::Full(i) => (cell(Some(i), true), cell(Some(i), false)),
+            SplitRow::Pair(old, new) => (cell(old, true), cell(new, false)),
+        })
+        .unzip();
+    if let Some(hunk) = sticky {
+        let label = Line::from(Span::styled(
+            diff.hunk_label(hunk),
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::REVERSED),
+        ));
+        left.insert(0, label.clone());
+        right.insert(0, label);
+    }
+    let divider: Vec<Line> = (0..inner.height)
+        .map(|_| {
+            Line::from(Span::styled(
+                "\u{2502}",
+                Style::default().fg(Color::DarkGray),
+            ))
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(left), columns[0]);
+    f.render_widget(Paragraph::new(divider), columns[1]);
+    f.render_widget(Paragraph::new(right), columns[2]);
+}
+
+/// Pairs are colored by what the rebase did to the commit; the diff of two patches by its
+/// outer column, so `+` lines are what the new version of the patch adds.
+fn range_diff_line<'a>(line: &'a RangeDiffLine, theme: &Theme) -> Line<'a> {
+    let style = match line.kind {
+        RangeLineKind::Pair(PairStatus::Same) => Style::default().add_modifier(Modifier::DIM),
+        RangeLineKind::Pair(PairStatus::Changed) => Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+        RangeLineKind::Pair(PairStatus::Dropped) => {
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+        }
+        RangeLineKind::Pair(PairStatus::New) => Style::default()
+            .fg(Color::Green)
+            .add_modifier(Modifier::BOLD),
+        RangeLineKind::Section => Style::default().fg(theme.accent),
+        RangeLineKind::Added => Style::default().fg(Color::Green),
+        RangeLineKind::Removed => Style::default().fg(Color::Red),
+        RangeLineKind::Context => Style::default(),
+    };
+    Line::from(Span::styled(line.text.as_str(), style))
+}
+
+fn draw_minimap(vm: &ViewModel, f: &mut Frame<'_>, area: Rect) {
+    let Some(diff) = vm.log_diff() else {
+        return;
+    };
+    let rows = area.height.saturating_sub(2) as usize;
+    if rows == 0 {
+        return;
+    }
+
+    let total = diff.len().max(1);
+    let view_start = vm.log_scroll() as usize * rows / total;
+    let view_end = ((vm.log_scroll() as usize + vm.log_view_height() as usize) * rows)
+        .div_ceil(total)
+        .max(view_start + 1);
+
+    let lines: Vec<Line> = diff
+        .minimap(rows)
+        .into_iter()
+        .enumerate()
+        .map(|(row, cell)| {
+            let (symbol, color) = match cell {
+                MapCell::Empty => ("\u{2502}", Color::DarkGray),
+                MapCell::Added => ("\u{2588}", Color::Green),
+                MapCell::Removed => ("\u{2588}", Color::Red),
+                MapCell::Mixed => ("\u{2588}", Color::Yellow),
+            };
+            let mut style = Style::default().fg(color);
+            if row >= view_start && row < view_end {
+                style = style.bg(Color::Gray);
+            }
+            Line::from(Span::styled(symbol, style))
+        })
+        .collect();
+
+    let inner = Rect {
+        y: area.y + 1,
+        height: rows as u16,
+        ..area
+    };
+    f.render_widget(Paragraph::new(lines), inner);
 }
@@ -1,17 +1,16 @@
 use anyhow::Result;
-use std::env;
-use std::path::PathBuf;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::{
-    Arc,
-    atomic::{AtomicBool, Ordering},
-    mpsc::{Receiver, Sender},
-};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crossterm::{
-    event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    event::{
+        DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+    },
     execute,
     terminal::{
         Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode,
@@ -19,113 +18,831 @@ use crossterm::{
     },
 };
 
-use crate::config::{CommandConfig, Config, LayoutConfig};
+use crate::codeowners::CodeOwners;
+use crate::config::{CommandConfig, CommandScope, CommandSort, Config, LayoutConfig};
+use crate::crash;
+use crate::diff::ParsedDiff;
+use crate::doctor::{Check, Fix};
+use crate::editor::editor_command;
 use crate::git::{
-    CommandResult, LfsMode, RepoFile, RepoStatus, load_repo_status, parse_args_line,
-    parse_lfs_mode, repo_root, run_git_with_lfs,
+    Branch, CommandResult, Commit, ConflictSide, GrepMatch, LfsMode, MergePreview, RefCache,
+    Remote, RepoFile, RepoStatus, SignatureCheck, StageOutput, Stash, SubmoduleState, TODO_MARKERS,
+    TransferProgress, Worktree, branch_base, default_branch, git_command, git_version,
+    grep_pattern, grep_worktree, head_commit, introduced_todos, is_network_command,
+    is_network_error, is_read_only_command, lfs_version, load_commits, load_repo_status, needs_lfs,
+    parse_args_line, quote_arg, remote_url, repo_root, resolve_git_path, run_git_with_lfs,
+    run_step, snapshot_worktree, step_label, three_way_diff_args, transfer_summary,
 };
-use crate::theme::Theme;
+use crate::history;
+use crate::jobs::{JobId, JobKind, JobMessage, Jobs};
+use crate::journal::{self, JournalEntry, is_destructive};
+use crate::keymap::{KeyAction, Keymap};
+use crate::links::{self, LinkTargets};
+use crate::preview::asset_preview;
+use crate::range_diff::RangeDiff;
+use crate::theme::{self, Theme};
+use crate::timings::{self, format_duration};
+use crate::updates::Release;
+
+mod actions;
+mod audit;
+mod bench;
+mod branches;
+mod cmdline_history;
+mod command_form;
+mod command_queue;
+mod commit_editor;
+mod commit_preview;
+mod commits;
+mod confirm;
+mod conflicts;
+mod decorations;
+mod doctor;
+mod events;
+mod file_marks;
+mod file_tree;
+mod finder;
+mod flows;
+mod graph_export;
+mod help;
+mod hunks;
+mod hydrate;
+mod issues;
+mod layouts;
+mod lfs_prune;
+mod merge_preview;
+mod mouse;
+mod new_branch;
+mod notes;
+mod offline;
+mod orphan;
+mod owners;
+mod placeholders;
+mod pull_request;
+mod range_diff;
+mod read_only;
+mod remotes;
+mod review;
+mod scope;
+mod search;
+mod select_text;
+mod sequence;
+mod signoff;
+mod stashes;
+mod submodules;
+mod summary;
+mod teaching;
+mod updates;
+mod verify;
+mod worktrees;
+
+pub use review::{Review, TabRequest};
+
+use actions::Action;
+use cmdline_history::HistoryRecall;
+pub use command_form::CommandForm;
+use command_queue::QueuedCommand;
+pub use commit_editor::{BODY_LIMIT, CommitEditor, SUBJECT_LIMIT};
+use commit_preview::{CommitPreview, has_commit_flag};
+use confirm::{Confirmation, Confirmed};
+use events::Event;
+pub use file_tree::FileRow;
+pub use finder::FileFinder;
+use flows::FlowRun;
+pub use help::HelpRow;
+pub use hunks::HunkView;
+pub use mouse::PaneAreas;
+use new_branch::NewBranch;
+use orphan::Orphan;
+use placeholders::{AskedCommand, CommandInputs};
+use read_only::mutates;
+use search::PaneSearch;
+use summary::SessionStats;
 
 const SPINNER_FRAMES: [char; 4] = ['|', '/', '-', '\\'];
 
+/// Worker threads shared by commands, searches, flows and event hooks.
+const WORKERS: usize = 4;
+
+/// Output arriving this long after a command started is followed in LOG; what quicker
+/// commands print is read from the top.
+const FOLLOW_AFTER: Duration = Duration::from_secs(1);
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Focus {
     Cmd,
     Files,
+    Commits,
+    Matches,
+    Stashes,
+    Branches,
+    Remotes,
+    Worktrees,
     Log,
     Result,
 }
 
+/// Panels that can occupy the list slot below CMD, in `Tab` order.
+const LIST_PANELS: [Focus; 7] = [
+    Focus::Files,
+    Focus::Commits,
+    Focus::Matches,
+    Focus::Stashes,
+    Focus::Branches,
+    Focus::Remotes,
+    Focus::Worktrees,
+];
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
     Normal,
     CommandLine,
 }
 
+/// Everything background jobs report to the UI, delivered through one channel.
 pub enum UiMessage {
+    /// A queued command was picked up by a worker.
+    CommandStarted(String),
+    /// A line the running command printed to stdout, for LOG.
+    CommandOutput(String),
+    /// Result lines a running command produced since its last chunk.
+    OutputChunk(Vec<String>),
+    /// Stages of a running command completed so far.
+    Progress {
+        done: usize,
+        total: usize,
+    },
+    /// The running network command is waiting to be run again.
+    Retrying {
+        attempt: u32,
+        retries: u32,
+    },
+    /// Latest clone/fetch/push/LFS progress line of the running command.
+    Transfer(TransferProgress),
     CommandFinished(CommandResult),
+    /// Repository status reloaded off the UI thread.
+    StatusLoaded(RepoStatus),
+    CommitsLoaded(CommitList),
+    MatchesLoaded(MatchList),
+    FlowStepFinished(CommandResult),
+    EventHookFinished(&'static str, CommandResult),
+    /// Submodule states; `show` lists them in LOG (`:submodules`).
+    SubmodulesLoaded {
+        states: Vec<SubmoduleState>,
+        show: bool,
+    },
+    /// Files still LFS pointers and whether smudging is skipped, listed by `:lfs-pointers`.
+    LfsPointersLoaded {
+        paths: Vec<String>,
+        skip_smudge: bool,
+    },
+    /// `lfs prune --dry-run` finished; the real prune waits for confirmation.
+    PrunePreviewed(CommandResult),
+    /// The worktree for `:review` is checked out, or failed to be.
+    ReviewReady(CommandResult, Review),
+    /// Annotations by path from the `[decorations] command`.
+    DecorationsLoaded(Result<HashMap<String, String>, String>),
+    /// Checks rerun after applying the fix whose output is attached, if any.
+    DoctorFinished(Vec<Check>, Option<CommandResult>),
+    /// `:merge-preview` worked out what merging the branch would do.
+    MergePreviewed(String, Result<MergePreview, String>),
+    /// `:verify` read the signature of every commit in the range.
+    SignaturesVerified(String, Result<Vec<SignatureCheck>, String>),
+    /// Releases newer than this build, for `:check-update` or the startup check.
+    UpdateChecked {
+        quiet: bool,
+        releases: Result<Vec<Release>, String>,
+    },
+    /// The commit preview's `lint` finished, with a warning when it failed.
+    CommitLinted(Option<String>),
+    /// A job failed without producing its result.
+    Error(String),
+}
+
+/// Where `Up`/`Down` move the cursor of a list of `len` rows; `None` for any other key.
+fn list_move(action: Option<KeyAction>, selected: usize, len: usize) -> Option<usize> {
+    match action? {
+        KeyAction::Down => Some((selected + 1).min(len.saturating_sub(1))),
+        KeyAction::Up => Some(selected.saturating_sub(1)),
+        _ => None,
+    }
+}
+
+/// Wait before rerunning a network command for the `attempt`-th time: 2s, 4s, 8s, up to a minute.
+fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_secs((1u64 << attempt.min(6)).min(60))
+}
+
+/// Follow-up commands of a configured command, run on the worker once it exits.
+#[derive(Default)]
+struct Hooks {
+    on_success: Option<String>,
+    on_failure: Option<String>,
+    /// Reruns when a network command cannot reach the remote.
+    retries: u32,
+    /// The CMD entry it was started from, moved up the recent list once it runs.
+    recent: Option<String>,
+}
+
+impl Hooks {
+    fn pick(&self, success: bool) -> Option<&String> {
+        if success {
+            self.on_success.as_ref()
+        } else {
+            self.on_failure.as_ref()
+        }
+    }
+}
+
+/// A command held for confirmation before it runs.
+struct PendingCommand {
+    args_str: String,
+    lfs_mode: LfsMode,
+    interactive: bool,
+    hooks: Hooks,
+}
+
+impl PendingCommand {
+    /// A command without an LFS step or hooks.
+    fn plain(args_str: String) -> Self {
+        PendingCommand {
+            args_str,
+            lfs_mode: LfsMode::None,
+            interactive: false,
+            hooks: Hooks::default(),
+        }
+    }
+}
+
+/// A configured command after its pathspec scope has been resolved.
+enum CommandArgs {
+    Ready(String),
+    /// Prefill the command line so the pathspec can be edited.
+    Ask(String),
+    NoFile,
+    /// A placeholder in the command has no value.
+    Unresolved(String),
+}
+
+#[derive(Default)]
+pub struct CommitList {
+    pub title: String,
+    pub commits: Vec<Commit>,
+    /// Pathspecs passed to `git show` when opening a commit from this list.
+    pub paths: Vec<String>,
+    /// Text highlighted in the opened commit diff.
+    pub highlight: Option<String>,
+    /// The whole list while `t` shows only tagged commits.
+    pub all: Option<Vec<Commit>>,
+    /// For a file's history, the pathspecs to show each commit with instead of `paths`,
+    /// as the file went by other names before a rename.
+    pub followed: HashMap<String, Vec<String>>,
+}
+
+#[derive(Default)]
+pub struct MatchList {
+    pub title: String,
+    pub matches: Vec<GrepMatch>,
+    pub highlight: Option<String>,
 }
 
 pub struct ViewModel {
     config: Config,
     theme: Theme,
+    /// Row in the CMD list as shown, which `cmd_sort = "recent"` reorders.
     selected_cmd: usize,
     selected_file: usize,
+    commit_list: CommitList,
+    selected_commit: usize,
+    match_list: MatchList,
+    selected_match: usize,
+    stashes: Vec<Stash>,
+    selected_stash: usize,
+    branches: Vec<Branch>,
+    selected_branch: usize,
+    remotes: Vec<Remote>,
+    selected_remote: usize,
+    worktrees: Vec<Worktree>,
+    selected_worktree: usize,
+    list_panel: Focus,
     focus: Focus,
     mode: Mode,
     log_lines: Vec<String>,
+    log_diff: Option<ParsedDiff>,
+    /// LOG holds `git range-diff` output.
+    log_range_diff: Option<RangeDiff>,
+    log_cursor: usize,
+    log_highlight: Option<String>,
+    /// The pane `/` was pressed in and what is typed after it.
+    search_input: Option<(Focus, String)>,
+    log_search: Option<PaneSearch>,
+    result_search: Option<PaneSearch>,
+    /// The first `g` of `gg` in LOG or R.
+    pending_g: bool,
+    function_context: bool,
+    /// LOG shows diffs side by side.
+    split_diff: bool,
     result_lines: Vec<String>,
     log_scroll: u16,
     result_scroll: u16,
     log_view_height: u16,
     result_view_height: u16,
     cmdline: String,
+    /// `:` lines run before, oldest first.
+    cmdline_history: Vec<String>,
+    /// Names of the configured commands by last run, for `cmd_sort = "recent"`.
+    recent_commands: Vec<String>,
+    history_recall: Option<HistoryRecall>,
     needs_full_redraw: bool,
     repo_root: PathBuf,
-    tx: Sender<UiMessage>,
-    rx: Receiver<UiMessage>,
-    is_running: bool,
+    jobs: Jobs<UiMessage>,
+    job_results: Receiver<JobMessage<UiMessage>>,
+    /// The git command started from the UI that has not finished yet.
     running_cmd: Option<String>,
+    /// Commands given while another ran, started in order once it finishes.
+    queue: VecDeque<QueuedCommand>,
+    next_queued: usize,
+    /// A diff opened while a network command runs holds LOG; the command's output
+    /// goes to R only.
+    log_detached: bool,
+    /// Whether output chunks of the running command replaced the placeholder yet.
+    output_streamed: bool,
+    /// Whether stdout of the running command replaced the LOG placeholder yet.
+    log_streamed: bool,
+    /// Whether LOG keeps its cursor on the newest output line; `None` until decided.
+    log_follow: Option<bool>,
+    running_since: Instant,
+    progress: Option<(usize, usize)>,
+    /// Retry of the running network command and how many are allowed.
+    retry: Option<(u32, u32)>,
+    transfer: Option<TransferProgress>,
+    /// A journal entry for the running command is on disk.
+    journaled: bool,
+    git_version: Result<String, String>,
+    lfs_version: Option<String>,
+    links_enabled: bool,
+    web_url: Option<String>,
+    /// Fixes offered by the `:doctor` report in LOG, applied with keys 1-9.
+    doctor_fixes: Vec<Fix>,
+    submodules: Vec<SubmoduleState>,
+    /// FILES annotations from the `[decorations] command`, by path.
+    decorations: HashMap<String, String>,
+    /// Paths and statuses the decorations were computed for.
+    decorated_files: Vec<(String, String)>,
+    codeowners: Option<CodeOwners>,
+    keymap: Keymap,
+    /// `[keys]` entries, a `theme` and command keys that could not be used, reported
+    /// once the UI starts.
+    config_errors: Vec<String>,
     spinner_index: usize,
     spinner_last_tick: Instant,
-    cancel_flag: Arc<AtomicBool>,
     status: RepoStatus,
-    pending_discard: Option<usize>,
+    ref_cache: Arc<Mutex<RefCache>>,
+    /// A question in R holding back a command until it is answered: a configured
+    /// `confirm`, discards and deletions, `--audit`, stale locks, `lfs prune` and the
+    /// CODEOWNERS summary before a push.
+    pending_confirmation: Option<Confirmation>,
+    pending_commit: Option<PendingCommand>,
+    commit_preview: Option<CommitPreview>,
+    /// Title and body of an edited pull request description, waiting for `y`.
+    pending_pr: Option<(String, String)>,
+    /// Set in a tab opened by `:review`.
+    review: Option<Review>,
+    tab_request: Option<TabRequest>,
+    tab_position: (usize, usize),
+    /// Name of the layout preset in use.
+    layout_preset: String,
+    /// Last git command shown by teaching mode.
+    teach_echo: Option<String>,
+    /// The running command talks to a remote; checked for network errors when it finishes.
+    network_cmd: Option<(String, LfsMode)>,
+    /// The last network command failed to reach the remote.
+    offline: bool,
+    /// LOG shows one file's hunks for staging; cleared whenever LOG shows anything else.
+    hunk_view: Option<HunkView>,
+    /// Tag of a release newer than this build, once a check found one.
+    update_available: Option<String>,
+    /// Remote default branch, substituted for `{default_branch}` in commands.
+    default_branch: Option<String>,
+    /// Network commands that failed while offline, rerun once a network command succeeds.
+    offline_queue: Vec<(String, LfsMode)>,
+    branch_note: Option<String>,
+    scope: Option<String>,
+    dry_run: bool,
+    command_form: Option<CommandForm>,
+    commit_editor: Option<CommitEditor>,
+    /// Paths marked in FILES with Space or `v`, for stage, discard and diff to act on.
+    marked_files: BTreeSet<String>,
+    /// Path where the `v` range in FILES starts.
+    visual_anchor: Option<String>,
+    /// FILES drawn as a directory tree rather than a flat list.
+    files_tree: bool,
+    /// Directories of the tree showing only their own row.
+    collapsed_dirs: BTreeSet<String>,
+    /// Directory of the tree under the cursor; `None` when it is on `selected_file`.
+    selected_dir: Option<String>,
+    /// Scroll offset of the key binding overlay while it is open.
+    help_scroll: Option<usize>,
+    /// Rows the overlay had room for in the last frame.
+    help_height: usize,
+    pane_areas: PaneAreas,
+    finder: Option<FileFinder>,
+    /// `git ls-files` and the `HEAD` it was read at.
+    tracked: Option<(Option<String>, Vec<String>)>,
+    flow: Option<FlowRun>,
+    /// `:new-branch` collecting the fields of the name template.
+    new_branch: Option<NewBranch>,
+    command_inputs: Option<CommandInputs>,
+    /// A `scope = "ask"` command whose line is being edited on `:`.
+    asked_command: Option<AskedCommand>,
+    /// `:orphan` asking for its name and commit message, or waiting for `y`.
+    orphan: Option<Orphan>,
+    session: SessionStats,
 }
 
 impl ViewModel {
-    pub fn new(
-        config: Config,
-        theme: Theme,
-        tx: Sender<UiMessage>,
-        rx: Receiver<UiMessage>,
-        cancel_flag: Arc<AtomicBool>,
-    ) -> Self {
-        let cwd = current_repo_path();
+    pub fn new(mut config: Config, theme: Theme, cwd: PathBuf) -> Self {
+        config.git_path = resolve_git_path(&config.git_path);
+        let git_version = git_version(&config.git_path);
+        let lfs_version = lfs_version(&config.git_path);
         let repo_root = repo_root(&config.git_path, &cwd);
-        let status = load_repo_status(&config.git_path, &repo_root);
+        let (job_tx, job_results) = mpsc::channel();
+        let mut ref_cache = RefCache::default();
+        let status = load_repo_status(&config.git_path, &repo_root, None, &mut ref_cache);
+        let links_enabled = links::enabled(config.links.mode);
+        let web_url = config.links.web_url.clone().or_else(|| {
+            remote_url(&config.git_path, &repo_root, "origin").and_then(|url| links::web_url(&url))
+        });
+        let default_branch = default_branch(&config.git_path, &repo_root);
+        let (keymap, mut config_errors) = Keymap::new(&config.keys);
+        config_errors.extend(
+            config
+                .keys
+                .actions
+                .keys()
+                .filter(|name| Action::from_name(name).is_none())
+                .map(|name| format!("[keys.actions] {}: no such action (see :actions)", name)),
+        );
+        if let Some(name) = &config.theme
+            && !theme::is_preset(name)
+        {
+            config_errors.push(format!(
+                "theme: unknown preset {:?} (one of {})",
+                name,
+                theme::preset_names().collect::<Vec<_>>().join(", ")
+            ));
+        }
+        config_errors.extend(
+            config
+                .commands
+                .iter()
+                .filter(|cmd| cmd.key.is_some_and(|c| keymap.reserves_command_key(c)))
+                .map(|cmd| {
+                    format!(
+                        "[[commands]] {}: key {:?} is taken by CMD itself",
+                        cmd.name,
+                        cmd.key.unwrap_or_default()
+                    )
+                }),
+        );
+        let split_diff = config.files_diff_split;
+        let files_tree = config.files_tree;
+        let layout_preset = config
+            .layout
+            .preset
+            .clone()
+            .unwrap_or_else(|| "default".into());
         Self {
             config,
             theme,
             selected_cmd: 0,
             selected_file: 0,
+            commit_list: CommitList::default(),
+            selected_commit: 0,
+            match_list: MatchList::default(),
+            selected_match: 0,
+            stashes: Vec::new(),
+            selected_stash: 0,
+            branches: Vec::new(),
+            selected_branch: 0,
+            remotes: Vec::new(),
+            selected_remote: 0,
+            worktrees: Vec::new(),
+            selected_worktree: 0,
+            list_panel: Focus::Files,
             focus: Focus::Cmd,
             mode: Mode::Normal,
             log_lines: vec!["<no output yet>".into()],
+            log_diff: None,
+            log_range_diff: None,
+            log_cursor: 0,
+            log_highlight: None,
+            search_input: None,
+            log_search: None,
+            result_search: None,
+            pending_g: false,
+            function_context: false,
+            split_diff,
             result_lines: vec![],
             log_scroll: 0,
             result_scroll: 0,
             log_view_height: 1,
             result_view_height: 1,
             cmdline: String::new(),
+            cmdline_history: history::load(),
+            recent_commands: history::load_recent_commands(),
+            history_recall: None,
             needs_full_redraw: false,
             repo_root,
-            tx,
-            rx,
-            is_running: false,
+            jobs: Jobs::new(WORKERS, job_tx),
+            job_results,
             running_cmd: None,
+            queue: VecDeque::new(),
+            next_queued: 1,
+            log_detached: false,
+            output_streamed: false,
+            log_streamed: false,
+            log_follow: None,
+            running_since: Instant::now(),
+            progress: None,
+            retry: None,
+            transfer: None,
+            journaled: false,
+            git_version,
+            lfs_version,
+            links_enabled,
+            web_url,
+            doctor_fixes: Vec::new(),
+            submodules: Vec::new(),
+            decorations: HashMap::new(),
+            decorated_files: Vec::new(),
+            codeowners: None,
+            keymap,
+            config_errors,
             spinner_index: 0,
             spinner_last_tick: Instant::now(),
-            cancel_flag,
             status,
-            pending_discard: None,
+            ref_cache: Arc::new(Mutex::new(ref_cache)),
+            pending_confirmation: None,
+            pending_commit: None,
+            commit_preview: None,
+            pending_pr: None,
+            review: None,
+            tab_request: None,
+            tab_position: (0, 1),
+            layout_preset,
+            teach_echo: None,
+            network_cmd: None,
+            offline: false,
+            hunk_view: None,
+            update_available: None,
+            default_branch,
+            offline_queue: Vec::new(),
+            branch_note: None,
+            scope: None,
+            dry_run: false,
+            command_form: None,
+            commit_editor: None,
+            marked_files: BTreeSet::new(),
+            visual_anchor: None,
+            files_tree,
+            collapsed_dirs: BTreeSet::new(),
+            selected_dir: None,
+            help_scroll: None,
+            help_height: 1,
+            pane_areas: PaneAreas::default(),
+            finder: None,
+            tracked: None,
+            flow: None,
+            new_branch: None,
+            command_inputs: None,
+            asked_command: None,
+            orphan: None,
+            session: SessionStats::new(),
+        }
+    }
+
+    pub fn init(&mut self) {
+        if let Err(e) = &self.git_version {
+            self.result_lines = vec![
+                format!("ERROR: {}", e),
+                "Set git_path in config.toml to your git executable.".into(),
+            ];
+            return;
+        }
+        crash::note_repo(&self.repo_root, self.git_version.as_deref().ok());
+        self.load_branch_note();
+        if self.config.submodules.summary {
+            self.load_submodules(false);
+        }
+        self.load_decorations(false);
+        self.reload_codeowners();
+        if !self.config_errors.is_empty() {
+            self.result_lines = self
+                .config_errors
+                .iter()
+                .map(|e| format!("WARN: {}", e))
+                .collect();
         }
+        self.note_operation();
+        if let Some(entry) = journal::take(&self.repo_root) {
+            self.report_interrupted(entry);
+        }
+        if self.config.updates.check_on_start && self.review.is_none() && !self.offline {
+            self.check_update(true);
+        }
+        self.record(&format!("session started in {}", self.repo_root.display()));
+        self.fire_event(Event::RepoOpen);
+    }
+
+    /// Shows a destructive command the previous session never saw finish.
+    fn report_interrupted(&mut self, entry: JournalEntry) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut lines = vec![
+            "The previous session ended while this command was running:".to_string(),
+            format!("  $ git {}", entry.command),
+            format!(
+                "  started {} minute(s) before this session",
+                now.saturating_sub(entry.started) / 60
+            ),
+            String::new(),
+            "To recover:".to_string(),
+        ];
+        lines.extend(
+            entry
+                .recovery_hints()
+                .into_iter()
+                .map(|h| format!("  {}", h)),
+        );
+        self.set_log_lines(lines);
+        self.result_lines = vec!["WARN: an interrupted command was found; see LOG".into()];
+        self.result_scroll = 0;
     }
 
     pub fn poll_messages(&mut self) {
-        while let Ok(msg) = self.rx.try_recv() {
-            match msg {
-                UiMessage::CommandFinished(res) => {
-                    if self.cancel_flag.load(Ordering::Relaxed) {
-                        continue;
+        // Stdout lines go to LOG together, once per poll rather than once per line.
+        let mut streamed = Vec::new();
+        while let Ok(JobMessage {
+            id,
+            last,
+            canceled,
+            message,
+        }) = self.job_results.try_recv()
+        {
+            // Canceled and superseded jobs still report back; drop their messages, but
+            // show what a stopped command printed before it was stopped.
+            let current = if last {
+                self.jobs.finish(id)
+            } else {
+                self.jobs.is_running(id)
+            };
+            match message {
+                UiMessage::CommandOutput(line) if current => streamed.push(line),
+                message if current => {
+                    self.append_command_output(std::mem::take(&mut streamed));
+                    self.handle_message(message);
+                }
+                UiMessage::CommandFinished(res) if canceled => self.show_stopped_command(res),
+                _ => {}
+            }
+        }
+        self.append_command_output(streamed);
+        self.start_next_queued();
+    }
+
+    /// Adds stdout lines of the running command to LOG, replacing the placeholder with the
+    /// first ones. Once the command runs for a while LOG follows the output, until the
+    /// cursor is moved off the last line.
+    fn append_command_output(&mut self, lines: Vec<String>) {
+        if lines.is_empty() || self.log_detached {
+            return;
+        }
+        let (cursor, scroll) = (self.log_cursor, self.log_scroll);
+        self.log_follow = match self.log_follow {
+            None if cursor == 0 => (self.running_since.elapsed() >= FOLLOW_AFTER).then_some(true),
+            None => Some(false),
+            Some(_) => Some(cursor + 1 >= self.log_lines.len()),
+        };
+        let mut log = if self.log_streamed {
+            std::mem::take(&mut self.log_lines)
+        } else {
+            Vec::new()
+        };
+        self.log_streamed = true;
+        log.extend(lines);
+        self.set_log_lines(log);
+        if self.log_follow == Some(true) {
+            self.move_log_cursor(self.log_lines.len() - 1);
+        } else {
+            self.log_cursor = cursor;
+            self.log_scroll = scroll;
+        }
+    }
+
+    fn handle_message(&mut self, message: UiMessage) {
+        match message {
+            UiMessage::CommandStarted(label) => {
+                self.result_lines = vec![format!("$ {}", label), "running...".into()];
+                self.result_scroll = 0;
+            }
+            UiMessage::CommandOutput(line) => self.append_command_output(vec![line]),
+            UiMessage::OutputChunk(result_lines) => {
+                if !self.output_streamed {
+                    self.result_lines.clear();
+                }
+                self.output_streamed = true;
+                self.result_lines.extend(result_lines);
+            }
+            UiMessage::Progress { done, total } => self.progress = Some((done, total)),
+            UiMessage::Retrying { attempt, retries } => self.retry = Some((attempt, retries)),
+            UiMessage::Transfer(transfer) => self.transfer = Some(transfer),
+            UiMessage::CommandFinished(res) => {
+                let committed = res.success && self.running_cmd.as_deref().is_some_and(is_commit);
+                let log_detached = std::mem::take(&mut self.log_detached);
+                self.finish_running();
+                self.record_outcome(res.success);
+                if !log_detached {
+                    self.set_log_lines(res.log_lines);
+                    if self.log_follow == Some(true) {
+                        self.move_log_cursor(self.log_lines.len().saturating_sub(1));
                     }
-                    self.finish_running();
-                    self.log_lines = res.log_lines;
-                    self.result_lines = res.result_lines;
-                    self.log_scroll = 0;
-                    self.result_scroll = 0;
-                    self.refresh_repo_status();
                 }
+                self.result_lines = res.result_lines;
+                self.result_scroll = 0;
+                self.summarize_range_diff();
+                if !res.success {
+                    self.offer_lock_removal();
+                }
+                if committed {
+                    self.note_commit();
+                    self.resume_sequence();
+                }
+                self.default_branch = default_branch(&self.config.git_path, &self.repo_root);
+                self.check_network(res.success);
+                match self.list_panel {
+                    Focus::Stashes => self.load_stashes(),
+                    Focus::Branches => self.load_branches(),
+                    Focus::Remotes => self.load_remotes(),
+                    Focus::Worktrees => self.load_worktrees(),
+                    _ => {}
+                }
+                if !res.success {
+                    self.drop_queue("after the failure");
+                }
+                self.follow_content(!res.success);
+            }
+            UiMessage::StatusLoaded(status) => self.apply_status(status),
+            UiMessage::EventHookFinished(name, res) => self.finish_event_hook(name, res),
+            UiMessage::FlowStepFinished(res) => self.finish_flow_step(res),
+            UiMessage::DecorationsLoaded(loaded) => self.apply_decorations(loaded),
+            UiMessage::SubmodulesLoaded { states, show } => {
+                self.submodules = states;
+                if show {
+                    self.show_submodules();
+                }
+            }
+            UiMessage::LfsPointersLoaded { paths, skip_smudge } => {
+                self.show_lfs_pointers(paths, skip_smudge)
+            }
+            UiMessage::PrunePreviewed(res) => self.confirm_lfs_prune(res),
+            UiMessage::CommitLinted(warning) => self.finish_commit_lint(warning),
+            UiMessage::ReviewReady(res, review) => self.finish_review(res, review),
+            UiMessage::DoctorFinished(checks, fixed) => self.show_doctor_report(checks, fixed),
+            UiMessage::MergePreviewed(branch, Ok(preview)) => {
+                self.show_merge_preview(branch, preview)
+            }
+            UiMessage::MergePreviewed(_, Err(e)) => {
+                self.result_lines = vec![format!("ERROR: merge preview failed: {}", e)];
+                self.result_scroll = 0;
+            }
+            UiMessage::SignaturesVerified(range, Ok(checks)) => self.show_signatures(range, checks),
+            UiMessage::SignaturesVerified(_, Err(e)) => {
+                self.result_lines = vec![format!("ERROR: verify failed: {}", e)];
+                self.result_scroll = 0;
+            }
+            UiMessage::UpdateChecked { quiet, releases } => self.show_update_check(quiet, releases),
+            UiMessage::CommitsLoaded(list) => {
+                self.result_lines =
+                    vec![format!("{}: {} commit(s)", list.title, list.commits.len())];
+                self.result_scroll = 0;
+                self.commit_list = list;
+                self.selected_commit = 0;
+                self.list_panel = Focus::Commits;
+                self.focus = Focus::Commits;
+                self.show_selected_commit();
+            }
+            UiMessage::Error(e) => self.result_lines.push(format!("ERROR: {}", e)),
+            UiMessage::MatchesLoaded(list) => {
+                self.result_lines =
+                    vec![format!("{}: {} match(es)", list.title, list.matches.len())];
+                self.result_scroll = 0;
+                self.match_list = list;
+                self.selected_match = 0;
+                self.list_panel = Focus::Matches;
+                self.focus = Focus::Matches;
             }
         }
     }
@@ -135,6 +852,43 @@ impl ViewModel {
             return Ok(false);
         }
 
+        if self.help_scroll.is_some() {
+            self.handle_help_key(key);
+            return Ok(false);
+        }
+        if self.command_form.is_some() {
+            self.handle_command_form_key(key);
+            return Ok(false);
+        }
+        if self.commit_editor.is_some() {
+            self.handle_commit_editor_key(key);
+            return Ok(false);
+        }
+        if self.finder.is_some() {
+            self.handle_finder_key(key);
+            return Ok(false);
+        }
+        if self.search_input.is_some() {
+            self.handle_search_input_key(key);
+            return Ok(false);
+        }
+        if self.flow_waiting_for_input() {
+            self.handle_flow_prompt_key(key);
+            return Ok(false);
+        }
+        if self.new_branch.is_some() {
+            self.handle_new_branch_key(key);
+            return Ok(false);
+        }
+        if self.command_inputs.is_some() {
+            self.handle_command_inputs_key(key);
+            return Ok(false);
+        }
+        if self.orphan.is_some() {
+            self.handle_orphan_key(key);
+            return Ok(false);
+        }
+
         match self.mode {
             Mode::Normal => self.handle_key_normal(key),
             Mode::CommandLine => self.handle_key_cmdline(key),
@@ -142,57 +896,125 @@ impl ViewModel {
     }
 
     fn handle_key_normal(&mut self, key: KeyEvent) -> anyhow::Result<bool> {
+        if self.pending_confirmation.is_some() {
+            self.handle_confirmation_key(key);
+            return Ok(false);
+        }
+        if self.pending_commit.is_some() {
+            self.handle_commit_preview_key(key);
+            return Ok(false);
+        }
+        if self.pending_pr.is_some() {
+            self.handle_pr_key(key);
+            return Ok(false);
+        }
+        let action = self.key_action(&key);
+        // `gg` is two presses; anything else in between cancels the first.
+        if key.code != KeyCode::Char('g') {
+            self.pending_g = false;
+        }
+
         if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
-            if self.is_running {
-                self.cancel_flag.store(true, Ordering::Relaxed);
+            if self.is_running() {
+                self.jobs.cancel_foreground();
                 self.finish_running();
+                self.record("  -> canceled");
+                self.flow = None;
                 self.log_lines.push("<canceled by user>".into());
                 self.result_lines
-                    .push("canceled by user (git process may still finish in background)".into());
+                    .push("canceled by user: stopping git...".into());
+                self.drop_queue("with it");
                 self.refresh_repo_status();
             }
             return Ok(false);
         }
 
-        if let KeyCode::Char(':') = key.code {
+        if key.code == KeyCode::Char('t') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.open_finder("");
+            return Ok(false);
+        }
+
+        if action == Some(KeyAction::CommandLine) {
             self.mode = Mode::CommandLine;
             self.cmdline.clear();
-            self.pending_discard = None;
             return Ok(false);
         }
 
-        if let KeyCode::Char('q') = key.code {
-            return Ok(true);
+        if action == Some(KeyAction::Quit) {
+            return Ok(self.may_close_review());
+        }
+
+        if let KeyCode::Char('?') = key.code {
+            self.open_help();
+            return Ok(false);
         }
 
         match key.code {
-            KeyCode::Char('h') => {
-                self.pending_discard = None;
-                self.focus = match self.focus {
-                    Focus::Cmd => Focus::Cmd,
-                    Focus::Files => Focus::Cmd,
-                    Focus::Log => Focus::Files,
-                    Focus::Result => Focus::Log,
-                };
+            KeyCode::Char('}') => {
+                self.tab_request = Some(TabRequest::Next);
                 return Ok(false);
             }
-            KeyCode::Char('l') => {
-                self.pending_discard = None;
-                self.focus = match self.focus {
-                    Focus::Cmd => Focus::Files,
-                    Focus::Files => Focus::Log,
-                    Focus::Log => Focus::Result,
-                    Focus::Result => Focus::Result,
-                };
+            KeyCode::Char('{') => {
+                self.tab_request = Some(TabRequest::Prev);
+                return Ok(false);
+            }
+            _ => {}
+        }
+
+        if self.focus == Focus::Files && self.files_tree && self.handle_file_tree_key(key) {
+            return Ok(false);
+        }
+        if matches!(self.focus, Focus::Log | Focus::Result) && self.handle_pane_search_key(key) {
+            return Ok(false);
+        }
+
+        match action {
+            Some(KeyAction::FocusLeft) => {
+                self.move_focus(-1);
+                return Ok(false);
+            }
+            Some(KeyAction::FocusRight) => {
+                self.move_focus(1);
+                return Ok(false);
+            }
+            _ => {}
+        }
+
+        match key.code {
+            KeyCode::Char('L') => {
+                self.cycle_layout();
+                return Ok(false);
+            }
+            KeyCode::Char('|') => {
+                self.run_action(Action::ToggleDiffMode);
+                return Ok(false);
+            }
+            KeyCode::Char('v') if matches!(self.focus, Focus::Log | Focus::Result) => {
+                self.select_text();
+                return Ok(false);
+            }
+            KeyCode::Tab if self.focus == self.list_panel => {
+                self.cycle_list_panel();
                 return Ok(false);
             }
             _ => {}
         }
 
+        if let Some(action) = self.keymap.named_action(&key).and_then(Action::from_name) {
+            self.run_action(action);
+            return Ok(false);
+        }
+
         match self.focus {
             Focus::Cmd => self.handle_cmd_keys(key)?,
             Focus::Files => self.handle_file_keys(key)?,
-            Focus::Log => self.handle_scroll_keys(key, true)?,
+            Focus::Commits => self.handle_commit_keys(key)?,
+            Focus::Matches => self.handle_match_keys(key)?,
+            Focus::Stashes => self.handle_stash_keys(key),
+            Focus::Branches => self.handle_branch_keys(key),
+            Focus::Remotes => self.handle_remote_keys(key),
+            Focus::Worktrees => self.handle_worktree_keys(key),
+            Focus::Log => self.handle_log_keys(key)?,
             Focus::Result => self.handle_scroll_keys(key, false)?,
         }
 
@@ -204,33 +1026,36 @@ impl ViewModel {
             KeyCode::Esc => {
                 self.mode = Mode::Normal;
                 self.cmdline.clear();
-                self.pending_discard = None;
+                self.history_recall = None;
+                self.asked_command = None;
             }
             KeyCode::Enter => {
                 let line = self.cmdline.trim().to_string();
                 self.cmdline.clear();
                 self.mode = Mode::Normal;
+                let asked = self.asked_command.take();
                 if line.is_empty() {
                     return Ok(false);
                 }
-                if line == "q" || line == "quit" {
-                    return Ok(true);
+                if let Some(asked) = asked {
+                    self.record_cmdline(&line);
+                    self.run_asked_command(asked, line);
+                    return Ok(false);
                 }
-                match line.as_str() {
-                    "stage" => self.run_command("add -A".to_string(), LfsMode::None, false),
-                    "unstage" => {
-                        self.run_command("restore --staged .".to_string(), LfsMode::None, false)
-                    }
-                    _ => {
-                        let interactive = self.requires_interactive(&line, None);
-                        self.run_command(line, LfsMode::None, interactive);
-                    }
+                if line == "q" || line == "quit" {
+                    return Ok(self.may_close_review());
                 }
+                self.record_cmdline(&line);
+                self.run_cmdline(line);
             }
+            KeyCode::Up => self.recall_history(true),
+            KeyCode::Down => self.recall_history(false),
             KeyCode::Backspace => {
+                self.history_recall = None;
                 self.cmdline.pop();
             }
             KeyCode::Char(c) => {
+                self.history_recall = None;
                 self.cmdline.push(c);
             }
             _ => {}
@@ -238,22 +1063,97 @@ impl ViewModel {
         Ok(false)
     }
 
-    fn handle_cmd_keys(&mut self, key: KeyEvent) -> anyhow::Result<()> {
-        match key.code {
-            KeyCode::Char('j') => {
-                if self.selected_cmd + 1 < self.config.commands.len() {
-                    self.selected_cmd += 1;
-                }
+    /// Runs a `:` line: a built-in action, an app command, or otherwise a git command.
+    fn run_cmdline(&mut self, line: String) {
+        let (name, rest) = line
+            .split_once(char::is_whitespace)
+            .map_or((line.as_str(), ""), |(n, r)| (n, r.trim()));
+        if let Some(action) = Action::from_name(name).filter(|_| rest.is_empty()) {
+            self.run_action(action);
+            return;
+        }
+        if let Ok(line) = name.parse::<usize>()
+            && rest.is_empty()
+        {
+            self.goto_line(line);
+            return;
+        }
+        match name {
+            "search-history" => self.search_history(rest),
+            "grep" => self.grep(rest),
+            "todo" => self.scan_todos(rest),
+            "note" => self.branch_note_command(rest),
+            "issue" => self.open_issue_command(rest),
+            "scope" => self.set_scope(rest),
+            "dry-run" => self.set_dry_run(rest),
+            "command" => self.command_config_command(rest),
+            "flow" => self.start_flow(rest),
+            "summary" => self.show_summary(rest),
+            "pr" => self.pull_request_command(rest),
+            "review" => self.start_review(rest),
+            "layout" => self.layout_command(rest),
+            "find" => self.open_finder(rest),
+            "commits" => self.load_commit_log(rest),
+            "export-graph" => self.export_graph(rest),
+            "merge-preview" => self.preview_merge(rest),
+            "verify" => self.verify_range(rest),
+            "range-diff" if rest.is_empty() => {
+                self.result_lines = vec![
+                    "usage: :range-diff <old>...<new> | <base> <old> <new> | <old-base>..<old> <new-base>..<new>".into(),
+                ];
+                self.result_scroll = 0;
             }
-            KeyCode::Char('k') => {
-                if self.selected_cmd > 0 {
-                    self.selected_cmd -= 1;
-                }
+            "stash-branch" => self.stash_branch(rest),
+            "cancel" => self.cancel_job(rest),
+            "timings" => self.show_timings(rest),
+            "hydrate" => self.hydrate(rest),
+            "lfs-pointers" => self.load_lfs_pointers(rest),
+            "offline" => self.offline_command(rest),
+            _ => {
+                let line = match self.fill_default_branch(&line) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        self.result_lines = vec![format!("ERROR: {}", e)];
+                        self.result_scroll = 0;
+                        return;
+                    }
+                };
+                self.run_command(line, LfsMode::None, false);
             }
+        }
+    }
+
+    fn handle_cmd_keys(&mut self, key: KeyEvent) -> anyhow::Result<()> {
+        match key.code {
             KeyCode::Enter => {
                 self.run_selected_command();
             }
-            _ => {}
+            KeyCode::Char('J') if self.selected_cmd + 1 < self.config.commands.len() => {
+                self.move_selected_command(self.selected_cmd + 1);
+            }
+            KeyCode::Char('K') if self.selected_cmd > 0 => {
+                self.move_selected_command(self.selected_cmd - 1);
+            }
+            // At either end of the list, where the arms above do not apply.
+            KeyCode::Char('J' | 'K') => {}
+            _ => match self.key_action(&key) {
+                // Also at either end, so an action key never runs a command.
+                Some(action) => {
+                    if let Some(row) =
+                        list_move(Some(action), self.selected_cmd, self.config.commands.len())
+                    {
+                        self.selected_cmd = row;
+                    }
+                }
+                None => {
+                    if let KeyCode::Char(c) = key.code
+                        && let Some(row) = self.commands().iter().position(|cmd| cmd.key == Some(c))
+                    {
+                        self.selected_cmd = row;
+                        self.run_selected_command();
+                    }
+                }
+            },
         }
         Ok(())
     }
@@ -261,43 +1161,494 @@ impl ViewModel {
     fn handle_file_keys(&mut self, key: KeyEvent) -> anyhow::Result<()> {
         let mut selection_changed = false;
         match key.code {
-            KeyCode::Char('j') => {
-                if self.selected_file + 1 < self.status.files.len() {
-                    self.selected_file += 1;
-                    selection_changed = true;
-                }
+            KeyCode::Char('o') => {
+                self.take_conflict_side(ConflictSide::Ours);
             }
-            KeyCode::Char('k') => {
-                if self.selected_file > 0 {
-                    self.selected_file -= 1;
-                    selection_changed = true;
-                }
+            KeyCode::Char('t') => {
+                self.take_conflict_side(ConflictSide::Theirs);
             }
-            KeyCode::Char('s') => {
-                self.pending_discard = None;
-                self.toggle_stage_selected_file();
+            KeyCode::Char('m') => {
+                self.open_mergetool();
             }
-            KeyCode::Char('d') => {
-                self.pending_discard = None;
-                self.show_diff_for_selected_file(false);
+            KeyCode::Char('r') => {
+                self.mark_conflict_resolved();
             }
-            KeyCode::Char('x') => {
-                self.handle_discard_key();
+            KeyCode::Char('v') => {
+                self.toggle_visual_range();
             }
-            _ => {
-                self.pending_discard = None;
+            KeyCode::Char('T') => {
+                self.toggle_files_tree();
             }
+            KeyCode::Esc if self.clear_file_marks() => {
+                self.result_lines = vec!["marks cleared".into()];
+                self.result_scroll = 0;
+            }
+            KeyCode::Char('W') => {
+                self.toggle_function_context();
+            }
+            KeyCode::Char('H') => {
+                self.hydrate("");
+            }
+            KeyCode::Char('f') => {
+                self.show_selected_file_history();
+            }
+            KeyCode::Enter => {
+                self.open_hunk_view();
+            }
+            _ => match self.key_action(&key) {
+                Some(KeyAction::Stage) if self.selected_file_conflicted() => {
+                    self.mark_conflict_resolved();
+                }
+                Some(KeyAction::Stage) => self.toggle_stage_files(),
+                Some(KeyAction::Diff) => self.show_diff_for_target_files(),
+                Some(KeyAction::Discard) => self.handle_discard_key(),
+                Some(KeyAction::Mark) => self.toggle_file_mark(),
+                action => {
+                    if let Some(row) =
+                        list_move(action, self.selected_file, self.status.files.len())
+                    {
+                        selection_changed = row != self.selected_file;
+                        self.selected_file = row;
+                    }
+                }
+            },
         }
 
         if selection_changed {
-            self.pending_discard = None;
             self.show_diff_for_selected_file(true);
         }
 
         Ok(())
     }
 
+    fn handle_log_keys(&mut self, key: KeyEvent) -> anyhow::Result<()> {
+        if let KeyCode::Char(c @ '1'..='9') = key.code
+            && !self.doctor_fixes.is_empty()
+        {
+            self.apply_doctor_fix(c as usize - '1' as usize);
+            return Ok(());
+        }
+        if self.handle_hunk_keys(key) {
+            return Ok(());
+        }
+        if self.log_range_diff.is_some() {
+            return self.handle_range_diff_keys(key);
+        }
+        let Some(diff) = &self.log_diff else {
+            return self.handle_scroll_keys(key, true);
+        };
+        let cursor = self.log_cursor;
+        let action = self.key_action(&key);
+        let target = match key.code {
+            KeyCode::Char(']') => diff.next_change_block(cursor),
+            KeyCode::Char('[') => diff.prev_change_block(cursor),
+            KeyCode::Char('g') => {
+                self.pending_g = !self.pending_g;
+                if self.pending_g {
+                    return Ok(());
+                }
+                Some(0)
+            }
+            KeyCode::Char('G') => Some(diff.len().saturating_sub(1)),
+            KeyCode::Char('e') => {
+                self.open_editor_at_cursor();
+                return Ok(());
+            }
+            KeyCode::Char('W') => {
+                self.toggle_function_context();
+                return Ok(());
+            }
+            _ if matches!(action, Some(KeyAction::Down | KeyAction::Up)) => {
+                list_move(action, cursor, diff.len())
+            }
+            _ => {
+                self.handle_scroll_keys(key, true)?;
+                self.clamp_log_cursor();
+                return Ok(());
+            }
+        };
+        if let Some(line) = target {
+            self.move_log_cursor(line);
+        }
+        Ok(())
+    }
+
+    fn move_log_cursor(&mut self, line: usize) {
+        self.log_cursor = line;
+        // One row is reserved for the sticky hunk header.
+        let view_h = (self.log_view_height as usize).saturating_sub(1).max(1);
+        let scroll = self.log_scroll as usize;
+        if line < scroll {
+            self.scroll_log_to(line);
+        } else if line >= scroll + view_h {
+            self.scroll_log_to(line + 1 - view_h);
+        }
+    }
+
+    fn clamp_log_cursor(&mut self) {
+        let scroll = self.log_scroll as usize;
+        let view_h = self.log_view_height as usize;
+        self.log_cursor = self
+            .log_cursor
+            .clamp(scroll, (scroll + view_h).saturating_sub(1).max(scroll));
+    }
+
+    /// `:<n>` moves LOG or R, whichever has the focus, to its `n`th line.
+    fn goto_line(&mut self, line: usize) {
+        let line = line.saturating_sub(1);
+        match self.focus {
+            Focus::Log if let Some(diff) = &self.log_diff => {
+                self.move_log_cursor(line.min(diff.len().saturating_sub(1)))
+            }
+            Focus::Log => self.scroll_log_to(line),
+            Focus::Result => {
+                let max_scroll = self
+                    .result_lines
+                    .len()
+                    .saturating_sub(self.result_view_height as usize);
+                self.result_scroll = line.min(max_scroll).min(u16::MAX as usize) as u16;
+            }
+            _ => {
+                self.result_lines =
+                    vec!["`:<n>` jumps to a line of LOG or R; focus one first".into()];
+                self.result_scroll = 0;
+            }
+        }
+    }
+
+    fn scroll_log_to(&mut self, line: usize) {
+        let max_scroll = self
+            .log_lines
+            .len()
+            .saturating_sub(self.log_view_height as usize);
+        self.log_scroll = line.min(max_scroll).min(u16::MAX as usize) as u16;
+    }
+
+    fn open_editor_at_cursor(&mut self) {
+        let Some((path, line)) = self
+            .log_diff
+            .as_ref()
+            .and_then(|d| d.location(self.log_cursor))
+            .map(|(p, l)| (self.repo_root.join(p), l))
+        else {
+            self.result_lines
+                .push("WARN: no file under the diff cursor".into());
+            return;
+        };
+        self.open_editor(&path, line);
+    }
+
+    fn open_editor(&mut self, path: &std::path::Path, line: u32) {
+        // Branch notes live outside the repository and stay editable.
+        if path.starts_with(&self.repo_root) && self.refuse_read_only("editing files") {
+            return;
+        }
+        let mut cmd = editor_command(self.config.editor.as_deref(), path, line);
+        let label = format!(
+            "{} {}:{}",
+            cmd.get_program().to_string_lossy(),
+            path.display(),
+            line
+        );
+        match run_suspended(cmd.current_dir(&self.repo_root)) {
+            Ok(code) => self
+                .result_lines
+                .push(format!("editor exited with code {}: {}", code, label)),
+            Err(e) => self
+                .result_lines
+                .push(format!("ERROR: failed to launch editor ({}): {}", label, e)),
+        }
+        self.needs_full_redraw = true;
+        self.refresh_repo_status();
+    }
+
+    fn handle_commit_keys(&mut self, key: KeyEvent) -> anyhow::Result<()> {
+        match key.code {
+            KeyCode::Enter => {
+                self.show_selected_commit();
+                self.focus = Focus::Log;
+            }
+            KeyCode::Char('o') => self.open_selected_commit_issue(),
+            KeyCode::Char('t') => self.toggle_tagged_commits(),
+            KeyCode::Char('c') => self.run_on_selected_commit("switch --detach"),
+            KeyCode::Char('r') => self.run_on_selected_commit("revert --no-edit"),
+            KeyCode::Char('p') => self.run_on_selected_commit("cherry-pick"),
+            KeyCode::Char('y') => self.copy_selected_commit_hash(),
+            _ => {
+                if let Some(row) = list_move(
+                    self.key_action(&key),
+                    self.selected_commit,
+                    self.commit_list.commits.len(),
+                ) && row != self.selected_commit
+                {
+                    self.selected_commit = row;
+                    self.show_selected_commit();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_match_keys(&mut self, key: KeyEvent) -> anyhow::Result<()> {
+        match key.code {
+            KeyCode::Enter => self.preview_selected_match(),
+            KeyCode::Char('e') => {
+                if let Some(m) = self.match_list.matches.get(self.selected_match) {
+                    let path = self.repo_root.join(&m.path);
+                    let line = m.line;
+                    self.open_editor(&path, line);
+                }
+            }
+            _ => {
+                if let Some(row) = list_move(
+                    self.key_action(&key),
+                    self.selected_match,
+                    self.match_list.matches.len(),
+                ) {
+                    self.selected_match = row;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `|`: side-by-side diffs on or off.
+    fn toggle_split_diff(&mut self) {
+        self.split_diff = !self.split_diff;
+        self.result_lines.push(format!(
+            "side-by-side diff: {}",
+            if self.split_diff { "on" } else { "off" }
+        ));
+    }
+
+    fn cycle_list_panel(&mut self) {
+        let pos = LIST_PANELS
+            .iter()
+            .position(|p| *p == self.list_panel)
+            .unwrap_or(0);
+        self.list_panel = LIST_PANELS[(pos + 1) % LIST_PANELS.len()];
+        self.focus = self.list_panel;
+        match self.list_panel {
+            Focus::Commits if self.commit_list.commits.is_empty() => self.load_commit_log(""),
+            Focus::Stashes => self.load_stashes(),
+            Focus::Branches => self.load_branches(),
+            Focus::Remotes => self.load_remotes(),
+            Focus::Worktrees => self.load_worktrees(),
+            _ => {}
+        }
+    }
+
+    fn search_history(&mut self, query: &str) {
+        let mut args = parse_args_line(query);
+        let paths = match args.iter().position(|a| a == "--") {
+            Some(i) => {
+                let paths = args.split_off(i + 1);
+                args.pop();
+                paths
+            }
+            None => self.scope.iter().cloned().collect(),
+        };
+        let mode = match args.first().map(|a| a.as_str()) {
+            Some("-G") | Some("-S") => args.remove(0),
+            _ => "-S".to_string(),
+        };
+        let text = args.join(" ");
+        if text.is_empty() {
+            self.result_lines = vec!["usage: :search-history [-S|-G] <text> [-- <path>...]".into()];
+            self.result_scroll = 0;
+            return;
+        }
+
+        let mut log_args = vec![format!("{}{}", mode, text)];
+        if !paths.is_empty() {
+            log_args.push("--".into());
+            log_args.extend(paths.iter().cloned());
+        }
+        self.result_lines = vec![
+            format!("$ git log {}", log_args.join(" ")),
+            "searching history...".into(),
+        ];
+        self.teach(&format!("git log {}", log_args.join(" ")), true);
+        self.result_scroll = 0;
+
+        let list = CommitList {
+            title: format!("{} \"{}\"", mode, text),
+            commits: Vec::new(),
+            paths,
+            // -G takes a regex; only literal -S searches can be highlighted verbatim.
+            highlight: (mode == "-S").then(|| text.clone()),
+            ..CommitList::default()
+        };
+        let git_path = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        self.jobs.spawn(
+            JobKind::Commits,
+            "search-history",
+            move |_| match load_commits(&git_path, &repo, &log_args) {
+                Ok(commits) => UiMessage::CommitsLoaded(CommitList { commits, ..list }),
+                Err(e) => UiMessage::Error(e),
+            },
+        );
+    }
+
+    /// `:grep` lists the matches in MATCHES. The pattern is highlighted in the previews
+    /// only under `-F`: `git grep` reads it as a regex otherwise.
+    fn grep(&mut self, query: &str) {
+        let mut args = parse_args_line(query);
+        if !args.iter().any(|a| a == "--")
+            && let Some(scope) = &self.scope
+        {
+            args.extend(["--".to_string(), scope.clone()]);
+        }
+        let Some(pattern) = grep_pattern(&args) else {
+            self.result_lines =
+                vec!["usage: :grep [<git grep flags>] <pattern> [-- <path>...]".into()];
+            self.result_scroll = 0;
+            return;
+        };
+
+        self.result_lines = vec![
+            format!("$ git grep -n {}", args.join(" ")),
+            "searching worktree...".into(),
+        ];
+        self.teach(&format!("git grep -n {}", args.join(" ")), true);
+        self.result_scroll = 0;
+
+        let list = MatchList {
+            title: format!("grep {}", args.join(" ")),
+            matches: Vec::new(),
+            highlight: pattern.text.filter(|_| pattern.fixed),
+        };
+        let git_path = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        self.jobs.spawn(JobKind::Matches, "grep", move |_| {
+            match grep_worktree(&git_path, &repo, &args) {
+                Ok(matches) => UiMessage::MatchesLoaded(MatchList { matches, ..list }),
+                Err(e) => UiMessage::Error(e),
+            }
+        });
+    }
+
+    fn scan_todos(&mut self, scope: &str) {
+        let git_path = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+
+        if scope == "all" {
+            let pattern = TODO_MARKERS.join("|");
+            self.result_lines = vec![format!("$ git grep -n -w -E \"{}\"", pattern)];
+            self.result_scroll = 0;
+            self.jobs.spawn(JobKind::Matches, "todo", move |_| {
+                let args = vec!["-w".to_string(), "-E".to_string(), pattern];
+                match grep_worktree(&git_path, &repo, &args) {
+                    Ok(matches) => UiMessage::MatchesLoaded(MatchList {
+                        title: "todo (whole tree)".into(),
+                        matches,
+                        highlight: None,
+                    }),
+                    Err(e) => UiMessage::Error(e),
+                }
+            });
+            return;
+        }
+
+        let base = if scope.is_empty() {
+            branch_base(&git_path, &repo).unwrap_or_else(|| "HEAD".to_string())
+        } else {
+            scope.to_string()
+        };
+        self.result_lines = vec![format!("$ git diff -U0 {}", base)];
+        self.result_scroll = 0;
+        self.jobs.spawn(JobKind::Matches, "todo", move |_| {
+            match introduced_todos(&git_path, &repo, &base) {
+                Ok(matches) => UiMessage::MatchesLoaded(MatchList {
+                    title: format!("todo since {}", base.chars().take(10).collect::<String>()),
+                    matches,
+                    highlight: None,
+                }),
+                Err(e) => UiMessage::Error(e),
+            }
+        });
+    }
+
+    fn preview_selected_match(&mut self) {
+        let Some(m) = self.match_list.matches.get(self.selected_match).cloned() else {
+            return;
+        };
+        let path = self.repo_root.join(&m.path);
+        let target = m.line as usize;
+        let content = match std::fs::read(&path) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Err(e) => {
+                self.result_lines
+                    .push(format!("ERROR: cannot read {}: {}", path.display(), e));
+                return;
+            }
+        };
+
+        let width = content.lines().count().to_string().len();
+        let lines = content
+            .lines()
+            .enumerate()
+            .map(|(i, text)| {
+                let marker = if i + 1 == target { '>' } else { '\u{2502}' };
+                format!("{:>width$}{} {}", i + 1, marker, text)
+            })
+            .collect();
+        self.set_log_lines(lines);
+        self.log_highlight = self.match_list.highlight.clone();
+        let half = self.log_view_height as usize / 2;
+        self.scroll_log_to(target.saturating_sub(1).saturating_sub(half));
+        self.result_lines = vec![format!("{}:{}", m.path, m.line)];
+        self.result_scroll = 0;
+    }
+
+    fn show_selected_commit(&mut self) {
+        let Some(commit) = self.commit_list.commits.get(self.selected_commit) else {
+            return;
+        };
+        let mut args = vec!["show".to_string(), commit.hash.clone()];
+        let paths = self
+            .commit_list
+            .followed
+            .get(&commit.hash)
+            .unwrap_or(&self.commit_list.paths);
+        if !paths.is_empty() {
+            args.push("--".into());
+            args.extend(paths.iter().cloned());
+        }
+        self.teach(&format!("git {}", args.join(" ")), false);
+
+        let output = git_command(&self.config.git_path)
+            .args(&args)
+            .current_dir(&self.repo_root)
+            .output();
+        match output {
+            Ok(o) => {
+                let stdout = String::from_utf8_lossy(&o.stdout);
+                self.set_log_lines(stdout.lines().map(|s| s.to_owned()).collect());
+                self.log_highlight = self.commit_list.highlight.clone();
+                if let Some(first) = self.first_highlight_line() {
+                    self.move_log_cursor(first);
+                }
+            }
+            Err(e) => {
+                self.set_log_lines(vec!["<no commit output>".into()]);
+                self.result_lines
+                    .push(format!("ERROR: failed to run git show: {}", e));
+            }
+        }
+    }
+
+    fn first_highlight_line(&self) -> Option<usize> {
+        let term = self.log_highlight.as_deref()?;
+        let diff = self.log_diff.as_ref()?;
+        diff.lines
+            .iter()
+            .position(|l| l.kind.is_change() && l.text.contains(term))
+    }
+
     fn handle_scroll_keys(&mut self, key: KeyEvent, is_log: bool) -> anyhow::Result<()> {
+        let action = self.key_action(&key);
         let (view_h, lines_len, scroll_ref) = if is_log {
             (
                 self.log_view_height,
@@ -318,6 +1669,16 @@ impl ViewModel {
         let full = view_h.max(1) as i32;
 
         match key.code {
+            KeyCode::Char('g') => {
+                self.pending_g = !self.pending_g;
+                if self.pending_g {
+                    return Ok(());
+                }
+                scroll = 0;
+            }
+            KeyCode::Char('G') => {
+                scroll = max_scroll;
+            }
             KeyCode::PageDown => {
                 scroll += full;
             }
@@ -330,7 +1691,11 @@ impl ViewModel {
             KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 scroll -= half;
             }
-            _ => {}
+            _ => match action {
+                Some(KeyAction::Down) => scroll += 1,
+                Some(KeyAction::Up) => scroll -= 1,
+                _ => {}
+            },
         }
 
         if scroll < 0 {
@@ -345,101 +1710,140 @@ impl ViewModel {
     }
 
     fn run_selected_command(&mut self) {
-        if self.config.commands.is_empty() {
+        let Some(cmd_cfg) = self
+            .selected_command_index()
+            .map(|i| &self.config.commands[i])
+        else {
             return;
-        }
-        let cmd_cfg = &self.config.commands[self.selected_cmd];
-        let cmd_str = cmd_cfg.cmd.clone();
-        let lfs_mode = parse_lfs_mode(cmd_cfg.lfs.as_ref());
-        let interactive = self.requires_interactive(&cmd_str, Some(cmd_cfg));
-        self.run_command(cmd_str, lfs_mode, interactive);
-    }
-
-    fn toggle_stage_selected_file(&mut self) {
-        if self.status.files.is_empty() {
+        };
+        if let Some(line) = cmd_cfg.cmd.strip_prefix(':') {
+            self.run_cmdline(line.trim().to_string());
             return;
         }
-        let entry = &self.status.files[self.selected_file];
-        let status = entry.status.as_str();
-        let mut chars = status.chars();
-        let x = chars.next().unwrap_or(' ');
-        let is_untracked = status == "??";
-        let is_staged = x != ' ' && !is_untracked;
-        let y = chars.next().unwrap_or(' ');
-        let has_unstaged_delete = y == 'D';
-        let operands = Self::quoted_operands(entry);
-
-        if operands.is_empty() {
+        let (cmd_str, ask) = match self.expand_command(cmd_cfg) {
+            CommandArgs::Ready(args) => (args, false),
+            CommandArgs::Ask(args) => (args, true),
+            CommandArgs::NoFile => {
+                self.result_lines = vec![format!("{}: no file selected", cmd_cfg.name)];
+                self.result_scroll = 0;
+                return;
+            }
+            CommandArgs::Unresolved(e) => {
+                self.result_lines = vec![format!("{}: {}", cmd_cfg.name, e)];
+                self.result_scroll = 0;
+                return;
+            }
+        };
+        let pending = PendingCommand {
+            args_str: cmd_str,
+            lfs_mode: cmd_cfg.lfs_mode(),
+            interactive: cmd_cfg.interactive,
+            hooks: Hooks {
+                on_success: cmd_cfg.on_success.clone(),
+                on_failure: cmd_cfg.on_failure.clone(),
+                retries: cmd_cfg.retries,
+                recent: Some(cmd_cfg.name.clone()),
+            },
+        };
+        let name = cmd_cfg.name.clone();
+        let confirm = cmd_cfg.confirm_question();
+        if ask {
+            self.ask_pathspec(name, confirm, pending);
             return;
         }
+        self.run_with_inputs(&name, confirm, pending);
+    }
 
-        let cmd = if is_staged {
-            format!("restore --staged -- {}", operands)
-        } else {
-            if has_unstaged_delete {
-                // Deleted in working tree: use -u so git stages the removal even when file is gone.
-                format!("add -u -- {}", operands)
-            } else {
-                format!("add -- {}", operands)
-            }
+    /// Applies the command's pathspec scope to its configured arguments.
+    fn expand_command(&self, cmd_cfg: &CommandConfig) -> CommandArgs {
+        let cmd = match self.fill_placeholders(&cmd_cfg.cmd) {
+            Ok(cmd) => cmd,
+            Err(e) => return CommandArgs::Unresolved(e),
         };
-        self.run_command(cmd, LfsMode::None, false);
+        let selected = self
+            .status
+            .files
+            .get(self.selected_file)
+            .map(Self::quoted_operands)
+            .filter(|ops| !ops.is_empty());
+        match (cmd_cfg.scope, selected) {
+            (CommandScope::Repo, _) => CommandArgs::Ready(cmd),
+            (CommandScope::Selected, Some(ops)) => {
+                CommandArgs::Ready(format!("{} -- {}", cmd, ops))
+            }
+            (CommandScope::Selected, None) => CommandArgs::NoFile,
+            (CommandScope::Ask, ops) => {
+                CommandArgs::Ask(format!("{} -- {}", cmd, ops.unwrap_or_default()))
+            }
+        }
     }
 
-    fn handle_discard_key(&mut self) {
-        if self.status.files.is_empty() {
-            self.pending_discard = None;
-            return;
+    /// Substitutes `{default_branch}`; an error when it is used but could not be detected.
+    fn fill_default_branch(&self, cmd: &str) -> Result<String, String> {
+        if !cmd.contains("{default_branch}") {
+            return Ok(cmd.to_string());
         }
-
-        if self.pending_discard == Some(self.selected_file) {
-            self.pending_discard = None;
-            self.discard_selected_file();
-            return;
+        match &self.default_branch {
+            Some(branch) => Ok(cmd.replace("{default_branch}", branch)),
+            None => Err("{default_branch} is unknown: origin/HEAD is not set \
+                 (`git remote set-head origin --auto` sets it)"
+                .into()),
         }
+    }
 
-        self.pending_discard = Some(self.selected_file);
-        let entry = &self.status.files[self.selected_file];
-        let label = entry.display_label();
-        self.result_lines = vec![format!(
-            "Discard changes to \"{}\"? (press x again to confirm, any other key cancels)",
-            label
-        )];
-        self.result_scroll = 0;
+    /// The full invocation Enter would run for the highlighted CMD entry.
+    pub fn command_preview(&self) -> Option<String> {
+        let cmd_cfg = &self.config.commands[self.selected_command_index()?];
+        if cmd_cfg.cmd.starts_with(':') {
+            return Some(format!("{} (built-in)", cmd_cfg.cmd));
+        }
+        let args = match self.expand_command(cmd_cfg) {
+            CommandArgs::Ready(args) => args,
+            CommandArgs::Ask(args) => return Some(format!("git {} (edit before running)", args)),
+            CommandArgs::NoFile => return Some("select a file first".into()),
+            CommandArgs::Unresolved(e) => return Some(e),
+        };
+        let args = self.prefill_commit_issue(self.apply_scope(args));
+        let mut preview = format!("git {}", args);
+        if let Some(lfs) = cmd_cfg.lfs_mode().follow_up() {
+            preview.push_str(&format!("; git {}", lfs));
+        }
+        Some(preview)
     }
 
-    fn discard_selected_file(&mut self) {
-        if self.status.files.is_empty() {
+    fn handle_discard_key(&mut self) {
+        if self.refuse_read_only("discarding changes") {
             return;
         }
-        let entry = &self.status.files[self.selected_file];
-        let operands = Self::quoted_operands(entry);
-        if operands.is_empty() {
+        let Some(pending) = self.discard_command() else {
             return;
-        }
-
-        let cmd = if entry.status == "??" {
-            format!("clean -fd -- {}", operands)
-        } else {
-            format!("restore --staged --worktree -- {}", operands)
         };
-        self.run_command(cmd, LfsMode::None, false);
+        let label = match self.target_files().as_slice() {
+            [entry] => format!("\"{}\"", entry.display_label()),
+            targets => format!("{} files", targets.len()),
+        };
+        self.ask_confirmation(
+            format!("Discard changes to {}?", label),
+            Some('x'),
+            Confirmed::Discard(pending),
+        );
     }
 
     fn show_diff_for_selected_file(&mut self, is_auto: bool) {
         if self.status.files.is_empty() {
             return;
         }
-        if self.is_running {
+        if self.diff_blocked() {
             if !is_auto {
                 self.result_lines
                     .push("WARN: cannot show diff while git is running".into());
             }
             return;
         }
+        self.log_detached = self.is_running();
 
         let entry = &self.status.files[self.selected_file];
-        let operands = Self::clean_operands(entry);
+        let operands = entry.operands();
         if operands.is_empty() {
             if !is_auto {
                 self.result_lines
@@ -448,6 +1852,15 @@ impl ViewModel {
             return;
         }
 
+        // Binary assets show their metadata instead of "Binary files differ".
+        let path = operands[operands.len() - 1].clone();
+        if let Some(lines) = asset_preview(&self.config.git_path, &self.repo_root, &path) {
+            self.set_log_lines(lines);
+            self.result_lines = vec![format!("$ preview {}", path)];
+            self.result_scroll = 0;
+            return;
+        }
+
         let (args, cmd_label) = if entry.status == "??" {
             let dev_null: String = if cfg!(windows) {
                 "NUL".into()
@@ -460,29 +1873,42 @@ impl ViewModel {
                 "--".into(),
                 dev_null.clone(),
             ];
+            if self.function_context {
+                args.insert(1, "--function-context".into());
+            }
             args.extend(operands.clone());
-            let pretty_label =
-                format!("git diff --no-index -- {} {}", dev_null, operands.join(" "));
+            let pretty_label = format!("git {}", args.join(" "));
+            (args, pretty_label)
+        } else if entry.is_conflicted() {
+            let args = three_way_diff_args(entry);
+            let pretty_label = format!("git {}", args.join(" "));
             (args, pretty_label)
         } else {
             self.build_diff_command(&operands)
         };
+        self.show_diff_output(args, cmd_label, is_auto);
+    }
+
+    /// Runs the diff `args` into LOG, with `cmd_label` and git's exit code in R.
+    fn show_diff_output(&mut self, args: Vec<String>, cmd_label: String, is_auto: bool) {
+        self.teach(&cmd_label, !is_auto);
 
-        let output = Command::new(&self.config.git_path)
+        let output = git_command(&self.config.git_path)
             .args(&args)
             .current_dir(&self.repo_root)
             .output();
+        let output_failed = output.is_err();
 
         match output {
             Ok(o) => {
                 let stdout = String::from_utf8_lossy(&o.stdout);
                 let stderr = String::from_utf8_lossy(&o.stderr);
 
-                self.log_lines = if stdout.is_empty() {
+                self.set_log_lines(if stdout.is_empty() {
                     vec!["<no diff output>".into()]
                 } else {
                     stdout.lines().map(|s| s.to_owned()).collect()
-                };
+                });
 
                 self.result_lines = vec![format!("$ {}", cmd_label)];
                 self.result_lines
@@ -492,18 +1918,28 @@ impl ViewModel {
                     self.result_lines
                         .extend(stderr.lines().map(|s| s.to_owned()));
                 }
-                self.log_scroll = 0;
                 self.result_scroll = 0;
             }
             Err(e) => {
-                self.log_lines = vec!["<no diff output>".into()];
+                self.set_log_lines(vec!["<no diff output>".into()]);
                 self.result_lines = vec![format!("$ {}", cmd_label)];
                 self.result_lines
                     .push(format!("ERROR: failed to run git diff: {}", e));
-                self.log_scroll = 0;
                 self.result_scroll = 0;
             }
         }
+        if !is_auto {
+            self.follow_content(output_failed);
+        }
+    }
+
+    fn toggle_function_context(&mut self) {
+        self.function_context = !self.function_context;
+        self.show_diff_for_selected_file(false);
+        self.result_lines.push(format!(
+            "function context: {}",
+            if self.function_context { "on" } else { "off" }
+        ));
     }
 
     fn build_diff_command(&self, operands: &[String]) -> (Vec<String>, String) {
@@ -519,6 +1955,9 @@ impl ViewModel {
         if args.is_empty() {
             args = vec!["diff".into(), "HEAD".into(), "--".into()];
         }
+        if self.function_context && args[0] == "diff" {
+            args.insert(1, "--function-context".into());
+        }
 
         let mut final_args = Vec::new();
         let mut inserted_files = false;
@@ -556,115 +1995,511 @@ impl ViewModel {
         (final_args, label)
     }
 
-    fn clean_operands(entry: &RepoFile) -> Vec<String> {
-        entry
-            .operands()
-            .into_iter()
-            .map(|p| p.trim_matches('"').to_string())
-            .collect()
-    }
-
     fn quoted_operands(entry: &RepoFile) -> String {
         entry
             .operands()
             .iter()
-            .map(|p| format!("\"{}\"", p.trim_matches('"')))
+            .map(|p| quote_arg(p))
             .collect::<Vec<_>>()
             .join(" ")
     }
 
     fn run_command(&mut self, args_str: String, lfs_mode: LfsMode, interactive: bool) {
-        self.pending_discard = None;
+        self.run_command_with_hooks(args_str, lfs_mode, interactive, Hooks::default());
+    }
+
+    fn run_command_with_hooks(
+        &mut self,
+        args_str: String,
+        lfs_mode: LfsMode,
+        interactive: bool,
+        hooks: Hooks,
+    ) {
+        let args_str = self.apply_scope(args_str);
+        let Some(args_str) = self.apply_signoff(args_str) else {
+            return;
+        };
+        if self.refuse_mutating_command(&args_str, &lfs_mode, &hooks) {
+            return;
+        }
+        if self.dry_run {
+            self.print_dry_run(args_str, lfs_mode, interactive, &hooks);
+            return;
+        }
+        if self.lfs_version.is_none() && needs_lfs(&args_str, &lfs_mode) {
+            self.result_lines = vec![
+                format!("$ git {}", args_str),
+                "git-lfs is not installed; install it from https://git-lfs.com to run this".into(),
+            ];
+            self.result_scroll = 0;
+            return;
+        }
+        let pending = PendingCommand {
+            args_str,
+            lfs_mode,
+            interactive,
+            hooks,
+        };
+        if self.config.audit && mutates(&pending.args_str, &pending.lfs_mode, &pending.hooks) {
+            self.confirm_command(pending);
+            return;
+        }
+        self.preview_or_execute(pending);
+    }
+
+    fn preview_or_execute(&mut self, pending: PendingCommand) {
+        if self.config.commit_preview.enabled && is_commit(&pending.args_str) {
+            self.show_commit_preview(pending);
+            return;
+        }
+        let Some(pending) = self.show_push_owners(pending) else {
+            return;
+        };
+        self.execute_command(
+            pending.args_str,
+            pending.lfs_mode,
+            pending.interactive,
+            pending.hooks,
+        );
+    }
+
+    fn execute_command(
+        &mut self,
+        args_str: String,
+        lfs_mode: LfsMode,
+        interactive: bool,
+        hooks: Hooks,
+    ) {
+        if !interactive && self.is_commit_needing_editor(&args_str) {
+            self.open_commit_editor(args_str, lfs_mode, hooks);
+            return;
+        }
+        let args_str = self.prefill_commit_issue(args_str);
+        if !interactive && (self.is_running() || self.flow.is_some()) {
+            self.enqueue_command(args_str, lfs_mode, hooks);
+            return;
+        }
+        self.start_command(args_str, lfs_mode, interactive, hooks);
+    }
+
+    /// Runs a command now, noting it first when it is destructive.
+    fn start_command(
+        &mut self,
+        args_str: String,
+        lfs_mode: LfsMode,
+        interactive: bool,
+        mut hooks: Hooks,
+    ) {
+        if let Some(name) = hooks.recent.take() {
+            self.record_recent_command(&name);
+        }
+        if !self.is_running() && is_destructive(&args_str) {
+            self.journal_command(&args_str);
+        }
         if interactive {
-            self.run_command_interactive(args_str);
+            self.run_command_interactive(args_str, hooks);
+        } else {
+            self.run_command_async(args_str, lfs_mode, hooks);
+        }
+    }
+
+    /// Moves a CMD entry to the top of the recent list when commands sort by recency.
+    fn record_recent_command(&mut self, name: &str) {
+        if self.config.layout.cmd_sort != CommandSort::Recent {
+            return;
+        }
+        self.selected_cmd = 0;
+        if let Err(e) = history::record_recent_command(&mut self.recent_commands, name) {
+            self.result_lines
+                .push(format!("WARN: failed to save recent commands: {}", e));
+        }
+    }
+
+    /// Notes a destructive command before it runs so a crash leaves a way back.
+    fn journal_command(&mut self, args_str: &str) {
+        let git = &self.config.git_path;
+        let entry = JournalEntry::new(
+            args_str,
+            head_commit(git, &self.repo_root),
+            snapshot_worktree(git, &self.repo_root),
+        );
+        match journal::write(&self.repo_root, &entry) {
+            Ok(()) => self.journaled = true,
+            Err(e) => self
+                .result_lines
+                .push(format!("WARN: failed to write journal: {}", e)),
+        }
+    }
+
+    fn list_jobs(&mut self) {
+        let jobs = self.jobs.running();
+        self.result_lines = if jobs.is_empty() && self.queue.is_empty() {
+            vec!["no running jobs".into()]
         } else {
-            self.run_command_async(args_str, lfs_mode);
+            let mut lines = vec!["running jobs (:cancel <id>):".to_string()];
+            lines.extend(jobs.iter().map(|job| {
+                format!(
+                    "  #{} {:?} {:.1}s  {}",
+                    job.id,
+                    job.kind,
+                    job.started.elapsed().as_secs_f32(),
+                    job.label
+                )
+            }));
+            lines.extend(self.queue_lines());
+            lines
+        };
+        self.result_scroll = 0;
+    }
+
+    /// `:timings [all]` summarizes recorded command durations, slowest first.
+    fn show_timings(&mut self, arg: &str) {
+        let all = arg == "all";
+        let stats = timings::load_stats((!all).then_some(self.repo_root.as_path()));
+        let mut lines = vec![format!(
+            "{:>5} {:>8} {:>8} {:>8} {:>8} {:>8}  command",
+            "runs", "avg", "p50", "p90", "max", "last"
+        )];
+        lines.extend(stats.iter().map(|s| {
+            let transfer = s
+                .last_transfer
+                .as_ref()
+                .map(|t| format!("  ({})", t))
+                .unwrap_or_default();
+            format!(
+                "{:>5} {:>8} {:>8} {:>8} {:>8} {:>8}  git {}{}",
+                s.runs,
+                format_duration(s.average),
+                format_duration(s.p50),
+                format_duration(s.p90),
+                format_duration(s.max),
+                format_duration(s.last),
+                s.command,
+                transfer
+            )
+        }));
+        self.set_log_lines(lines);
+        self.result_lines = vec![format!(
+            "timings ({}): {} command(s)",
+            if all {
+                "all repositories"
+            } else {
+                "this repository"
+            },
+            stats.len()
+        )];
+        self.result_scroll = 0;
+    }
+
+    /// `:cancel <id>` stops waiting for one job; its result is discarded when it arrives.
+    fn cancel_job(&mut self, id: &str) {
+        if self.cancel_queued(id) {
+            return;
+        }
+        let Some(job) = id
+            .trim_start_matches('#')
+            .parse::<JobId>()
+            .ok()
+            .and_then(|id| self.jobs.running().iter().find(|job| job.id == id))
+        else {
+            self.result_lines = vec![format!("no running job {}", id)];
+            self.result_scroll = 0;
+            return;
+        };
+        let (id, kind) = (job.id, job.kind);
+        self.jobs.cancel(id);
+        if kind.is_foreground() {
+            self.finish_running();
+            self.record("  -> canceled");
+            self.flow = None;
+        }
+        self.result_lines = vec![format!("canceled job #{}", id)];
+        self.result_scroll = 0;
+    }
+
+    /// `:dry-run [on|off]` toggles printing commands instead of running them.
+    fn set_dry_run(&mut self, arg: &str) {
+        self.dry_run = match arg {
+            "" => !self.dry_run,
+            "on" => true,
+            "off" => false,
+            _ => {
+                self.result_lines = vec!["usage: :dry-run [on|off]".into()];
+                self.result_scroll = 0;
+                return;
+            }
+        };
+        self.result_lines = vec![format!(
+            "dry-run {}",
+            if self.dry_run { "on" } else { "off" }
+        )];
+        self.result_scroll = 0;
+    }
+
+    fn print_dry_run(
+        &mut self,
+        args_str: String,
+        lfs_mode: LfsMode,
+        interactive: bool,
+        hooks: &Hooks,
+    ) {
+        let args_str = self.prefill_commit_issue(args_str);
+        let mut lines = vec!["dry-run: would run".to_string()];
+        lines.push(format!(
+            "$ git {}{}",
+            args_str,
+            if interactive { "  (interactive)" } else { "" }
+        ));
+        if let Some(lfs) = lfs_mode.follow_up() {
+            lines.push(format!("$ git {}", lfs));
+        }
+        if let Some(hook) = &hooks.on_success {
+            lines.push(format!("on success: $ {}", step_label(hook)));
         }
+        if let Some(hook) = &hooks.on_failure {
+            lines.push(format!("on failure: $ {}", step_label(hook)));
+        }
+        if hooks.retries > 0 && is_network_command(&args_str, &lfs_mode) {
+            lines.push(format!(
+                "retries: {} when the remote is unreachable",
+                hooks.retries
+            ));
+        }
+        self.result_lines = lines;
+        self.result_scroll = 0;
     }
 
-    fn run_command_async(&mut self, args_str: String, lfs_mode: LfsMode) {
-        if self.is_running {
+    fn run_command_async(&mut self, args_str: String, lfs_mode: LfsMode, hooks: Hooks) {
+        if self.is_running() {
             self.result_lines
                 .push("WARN: already running command".into());
             return;
         }
         self.start_running(&args_str);
-        self.log_lines = vec!["<running...>".into()];
-        self.result_lines = vec![format!("$ git {}", args_str), "running...".into()];
-        self.log_scroll = 0;
+        if let Some(lfs) = lfs_mode.follow_up() {
+            self.record(&format!("  then {}", self.argv(&lfs)));
+        }
+        for (when, hook) in [
+            ("success", &hooks.on_success),
+            ("failure", &hooks.on_failure),
+        ] {
+            if let Some(hook) = hook {
+                self.record(&format!("  on {} {}", when, self.argv(hook)));
+            }
+        }
+        self.network_cmd =
+            is_network_command(&args_str, &lfs_mode).then(|| (args_str.clone(), lfs_mode.clone()));
+        self.set_log_lines(vec!["<running...>".into()]);
+        self.result_lines = vec![format!("$ git {}", args_str), "queued...".into()];
         self.result_scroll = 0;
 
-        let tx = self.tx.clone();
         let git_path = self.config.git_path.clone();
-        let cancel_flag = self.cancel_flag.clone();
         let repo_path = self.repo_root.clone();
+        let label = format!("git {}", args_str);
+        let load_status = self.status_loader();
+
+        self.jobs
+            .spawn(JobKind::Command, label.clone(), move |ctx| {
+                ctx.send(UiMessage::CommandStarted(label));
+                let started = Instant::now();
+                let network = is_network_command(&args_str, &lfs_mode);
+                let mut attempts = Vec::new();
+                let mut res = loop {
+                    let res = run_git_with_lfs(
+                        git_path.clone(),
+                        args_str.clone(),
+                        lfs_mode.clone(),
+                        ctx.cancel_token(),
+                        repo_path.clone(),
+                        &mut |stage: StageOutput| {
+                            ctx.send(UiMessage::OutputChunk(stage.result_lines));
+                            ctx.send(UiMessage::Progress {
+                                done: stage.done,
+                                total: stage.total,
+                            });
+                        },
+                        &mut |transfer| ctx.send(UiMessage::Transfer(transfer)),
+                        &mut |line| ctx.send(UiMessage::CommandOutput(line.to_string())),
+                    );
+                    let attempt = attempts.len() as u32 + 1;
+                    if res.success
+                        || !network
+                        || attempt > hooks.retries
+                        || ctx.is_canceled()
+                        || !is_network_error(&res.result_lines)
+                    {
+                        break res;
+                    }
+                    let delay = retry_delay(attempt);
+                    let note = format!(
+                        "attempt {}/{} could not reach the remote; retried after {}s",
+                        attempt,
+                        hooks.retries + 1,
+                        delay.as_secs()
+                    );
+                    ctx.send(UiMessage::OutputChunk(vec![note.clone()]));
+                    ctx.send(UiMessage::Retrying {
+                        attempt,
+                        retries: hooks.retries,
+                    });
+                    attempts.push(note);
+                    let until = Instant::now() + delay;
+                    while Instant::now() < until && !ctx.is_canceled() {
+                        thread::sleep(Duration::from_millis(100));
+                    }
+                };
+                for (i, note) in attempts.into_iter().enumerate() {
+                    res.result_lines.insert(1 + i, note);
+                }
+                let elapsed = started.elapsed();
+                let transfer = transfer_summary(&res.result_lines);
+                if let Some(transfer) = &transfer {
+                    res.result_lines.insert(
+                        1,
+                        format!("transfer: {} in {}", transfer, format_duration(elapsed)),
+                    );
+                }
+                if !ctx.is_canceled()
+                    && let Err(e) = timings::record(
+                        &repo_path,
+                        &args_str,
+                        elapsed,
+                        res.success,
+                        transfer.as_deref(),
+                    )
+                {
+                    res.result_lines
+                        .push(format!("WARN: failed to record timing: {}", e));
+                }
+                if !ctx.is_canceled()
+                    && let Some(hook) = hooks.pick(res.success)
+                {
+                    run_step(&git_path, hook, &repo_path, ctx.cancel_token(), &mut res);
+                }
+                ctx.send(UiMessage::StatusLoaded(load_status()));
+                UiMessage::CommandFinished(res)
+            });
+    }
 
-        thread::spawn(move || {
-            let res =
-                run_git_with_lfs(git_path, args_str, lfs_mode, cancel_flag.clone(), repo_path);
-            if !cancel_flag.load(Ordering::Relaxed) {
-                let _ = tx.send(UiMessage::CommandFinished(res));
-            }
-        });
+    /// Runs a hook on the worker after an interactive command, keeping the current output.
+    fn run_hook_async(&mut self, hook: String) {
+        self.start_running(&hook);
+        let mut res = CommandResult {
+            log_lines: self.log_lines.clone(),
+            result_lines: self.result_lines.clone(),
+            success: true,
+        };
+        let git_path = self.config.git_path.clone();
+        let repo_path = self.repo_root.clone();
+        let load_status = self.status_loader();
+
+        self.jobs
+            .spawn(JobKind::Command, step_label(&hook), move |ctx| {
+                run_step(&git_path, &hook, &repo_path, ctx.cancel_token(), &mut res);
+                ctx.send(UiMessage::StatusLoaded(load_status()));
+                UiMessage::CommandFinished(res)
+            });
     }
 
-    fn run_command_interactive(&mut self, args_str: String) {
-        if self.is_running {
+    fn run_command_interactive(&mut self, args_str: String, hooks: Hooks) {
+        if self.is_running() {
             self.result_lines
                 .push("WARN: already running command".into());
             return;
         }
 
         self.start_running(&args_str);
-        self.log_lines = vec!["<interactive command: terminal will switch>".into()];
+        self.set_log_lines(vec!["<interactive command: terminal will switch>".into()]);
         self.result_lines = vec![format!("$ git {}", args_str)];
 
-        let git_path = self.config.git_path.clone();
-        let repo = self.repo_root.clone();
         let args = parse_args_line(&args_str);
-
-        let exit_code = (|| -> Result<i32> {
-            disable_raw_mode().ok();
-            {
-                let mut stdout = std::io::stdout();
-                execute!(stdout, LeaveAlternateScreen)?;
-            }
-
-            let status = std::process::Command::new(&git_path)
+        // Interactive git needs the console, so no `git_command` here.
+        let exit_code = run_suspended(
+            Command::new(&self.config.git_path)
                 .args(&args)
-                .current_dir(&repo)
-                .status()?;
+                .current_dir(&self.repo_root),
+        );
 
-            {
-                let mut stdout = std::io::stdout();
-                execute!(stdout, EnterAlternateScreen, Clear(ClearType::All))?;
+        let success = match exit_code {
+            Ok(code) => {
+                self.result_lines.push(format!("git exit code: {}", code));
+                code == 0
             }
-            enable_raw_mode().ok();
-
-            Ok(status.code().unwrap_or(-1))
-        })();
-
-        match exit_code {
-            Ok(code) => self.result_lines.push(format!("git exit code: {}", code)),
-            Err(e) => self
-                .result_lines
-                .push(format!("ERROR: failed interactive git: {e}")),
-        }
+            Err(e) => {
+                self.result_lines
+                    .push(format!("ERROR: failed interactive git: {e}"));
+                false
+            }
+        };
 
         self.finish_running();
+        self.record_outcome(success);
         self.needs_full_redraw = true;
         self.refresh_repo_status();
+        let committed = success && is_commit(&args_str);
+        if committed {
+            self.note_commit();
+        }
+        if let Some(hook) = hooks.pick(success) {
+            self.run_hook_async(hook.clone());
+        }
+        if committed {
+            self.resume_sequence();
+        }
+    }
+
+    fn set_log_lines(&mut self, lines: Vec<String>) {
+        self.log_diff = ParsedDiff::parse(&lines);
+        self.log_range_diff = RangeDiff::parse(&lines);
+        self.log_lines = lines;
+        self.doctor_fixes.clear();
+        self.hunk_view = None;
+        self.log_scroll = 0;
+        self.log_cursor = 0;
+        self.log_highlight = None;
+        self.log_search = None;
     }
 
     fn refresh_repo_status(&mut self) {
-        self.status = load_repo_status(&self.config.git_path, &self.repo_root);
+        let status = self.status_loader()();
+        self.apply_status(status);
+    }
+
+    /// Status loading for worker threads; shares the ref cache with the UI thread.
+    fn status_loader(&self) -> impl Fn() -> RepoStatus + Send + 'static {
+        let git_path = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        let scope = self.scope.clone();
+        let cache = self.ref_cache.clone();
+        move || {
+            let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
+            load_repo_status(&git_path, &repo, scope.as_deref(), &mut cache)
+        }
+    }
+
+    fn apply_status(&mut self, status: RepoStatus) {
+        let previous = std::mem::replace(&mut self.status, status);
+        self.session.note_status(&self.status.files);
+        if previous.operation.is_none() {
+            self.note_operation();
+        }
+        if self.status.branch != previous.branch {
+            self.load_branch_note();
+            self.fire_event(Event::BranchChange);
+        }
         if self.selected_file >= self.status.files.len() && !self.status.files.is_empty() {
             self.selected_file = self.status.files.len() - 1;
         }
         if self.status.files.is_empty() {
             self.selected_file = 0;
         }
-        self.pending_discard = None;
+        self.retain_file_marks();
+        self.retain_file_tree();
+        if self.config.submodules.summary {
+            self.load_submodules(false);
+        }
+        self.load_decorations(false);
+        self.reload_codeowners();
     }
 
     pub fn update_viewport(&mut self, log_height: u16, result_height: u16) {
@@ -672,6 +2507,10 @@ impl ViewModel {
         self.result_view_height = result_height.max(1);
     }
 
+    pub fn split_diff(&self) -> bool {
+        self.split_diff
+    }
+
     pub fn theme(&self) -> &Theme {
         &self.theme
     }
@@ -680,8 +2519,31 @@ impl ViewModel {
         &self.config.layout
     }
 
-    pub fn commands(&self) -> &[CommandConfig] {
-        &self.config.commands
+    /// Indexes into `config.commands` in the order the CMD list shows them.
+    fn command_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.config.commands.len()).collect();
+        if self.config.layout.cmd_sort == CommandSort::Recent {
+            order.sort_by_key(|&i| {
+                self.recent_commands
+                    .iter()
+                    .position(|name| *name == self.config.commands[i].name)
+                    .unwrap_or(usize::MAX)
+            });
+        }
+        order
+    }
+
+    /// Index into `config.commands` of the highlighted CMD row.
+    fn selected_command_index(&self) -> Option<usize> {
+        self.command_order().get(self.selected_cmd).copied()
+    }
+
+    /// The configured commands as the CMD list shows them.
+    pub fn commands(&self) -> Vec<&CommandConfig> {
+        self.command_order()
+            .into_iter()
+            .map(|i| &self.config.commands[i])
+            .collect()
     }
 
     pub fn focus(&self) -> Focus {
@@ -692,8 +2554,21 @@ impl ViewModel {
         self.mode
     }
 
+    /// Stages done out of the total for the running command, once the first one ends.
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        self.progress
+    }
+
+    pub fn retry(&self) -> Option<(u32, u32)> {
+        self.retry
+    }
+
+    pub fn transfer(&self) -> Option<&TransferProgress> {
+        self.transfer.as_ref()
+    }
+
     pub fn running_indicator(&self) -> Option<(char, &str)> {
-        if !self.is_running {
+        if !self.is_running() {
             return None;
         }
         let spinner = SPINNER_FRAMES[self.spinner_index];
@@ -701,6 +2576,11 @@ impl ViewModel {
         Some((spinner, cmd))
     }
 
+    /// The `[keys]` action `key` does where the focus is.
+    fn key_action(&self, key: &KeyEvent) -> Option<KeyAction> {
+        self.keymap.action(key, self.focus == Focus::Files)
+    }
+
     pub fn selected_cmd(&self) -> usize {
         self.selected_cmd
     }
@@ -721,6 +2601,50 @@ impl ViewModel {
         &self.result_lines
     }
 
+    pub fn log_diff(&self) -> Option<&ParsedDiff> {
+        self.log_diff.as_ref()
+    }
+
+    pub fn log_range_diff(&self) -> Option<&RangeDiff> {
+        self.log_range_diff.as_ref()
+    }
+
+    /// The `/` search of LOG, or else the term the commit or match shown was found by.
+    pub fn log_highlight(&self) -> Option<&str> {
+        self.log_search
+            .as_ref()
+            .map(|s| s.term.as_str())
+            .or(self.log_highlight.as_deref())
+    }
+
+    pub fn list_panel(&self) -> Focus {
+        self.list_panel
+    }
+
+    pub fn match_list(&self) -> &MatchList {
+        &self.match_list
+    }
+
+    pub fn selected_match(&self) -> usize {
+        self.selected_match
+    }
+
+    pub fn commit_list(&self) -> &CommitList {
+        &self.commit_list
+    }
+
+    pub fn selected_commit(&self) -> usize {
+        self.selected_commit
+    }
+
+    pub fn log_cursor(&self) -> usize {
+        self.log_cursor
+    }
+
+    pub fn log_view_height(&self) -> u16 {
+        self.log_view_height
+    }
+
     pub fn log_scroll(&self) -> u16 {
         self.log_scroll
     }
@@ -729,8 +2653,115 @@ impl ViewModel {
         self.result_scroll
     }
 
+    pub fn command_form(&self) -> Option<&CommandForm> {
+        self.command_form.as_ref()
+    }
+
+    /// The command cannot run because it needs git-lfs, which is missing.
+    pub fn needs_missing_lfs(&self, cmd: &CommandConfig) -> bool {
+        self.lfs_version.is_none() && needs_lfs(&cmd.cmd, &cmd.lfs_mode())
+    }
+
+    /// The command talks to a remote while the last network command could not reach one.
+    pub fn needs_offline_network(&self, cmd: &CommandConfig) -> bool {
+        self.offline && is_network_command(&cmd.cmd, &cmd.lfs_mode())
+    }
+
+    pub fn offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Configured commands that `--read-only` refuses, marked in the CMD pane.
+    pub fn refused_read_only(&self, cmd: &CommandConfig) -> bool {
+        self.config.read_only
+            && (!is_read_only_command(&cmd.cmd, &cmd.lfs_mode())
+                || [&cmd.on_success, &cmd.on_failure]
+                    .into_iter()
+                    .flatten()
+                    .any(|hook| !self.allows_step(hook)))
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.config.read_only
+    }
+
+    pub fn audit(&self) -> bool {
+        self.config.audit
+    }
+
+    pub fn teaching(&self) -> bool {
+        self.config.teaching
+    }
+
+    pub(super) fn version_lines(&self) -> Vec<String> {
+        vec![
+            format!("simple-git-tui {}", env!("CARGO_PKG_VERSION")),
+            match &self.git_version {
+                Ok(v) => format!("{} ({})", v, self.config.git_path),
+                Err(e) => format!("git: {}", e),
+            },
+            match &self.lfs_version {
+                Some(v) => v.clone(),
+                None => "git-lfs: not installed (LFS commands are disabled)".into(),
+            },
+        ]
+    }
+
+    /// No job is queued or running, so every result has been applied.
+    /// What LOG and R text links to; `None` when hyperlinks are off.
+    pub(super) fn link_targets(&self) -> Option<LinkTargets<'_>> {
+        if !self.links_enabled {
+            return None;
+        }
+        let mut files: Vec<&str> = self.status.files.iter().map(|f| f.path.as_str()).collect();
+        if let Some(diff) = &self.log_diff {
+            files.extend(diff.files.iter().map(String::as_str));
+        }
+        Some(LinkTargets {
+            web: self.web_url.as_deref(),
+            repo_root: &self.repo_root,
+            files,
+            issue_url: self.config.issues.url.as_deref(),
+        })
+    }
+
+    pub fn repo_root(&self) -> &Path {
+        &self.repo_root
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.jobs.running().is_empty()
+    }
+
+    /// Number of jobs still running in the background, besides the foreground command.
+    pub fn background_jobs(&self) -> usize {
+        self.jobs
+            .running()
+            .iter()
+            .filter(|job| !job.kind.is_foreground())
+            .count()
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn scope(&self) -> Option<&str> {
+        self.scope.as_deref()
+    }
+
+    pub fn branch_note(&self) -> Option<&str> {
+        self.branch_note.as_deref()
+    }
+
     pub fn status_summary(&self) -> String {
-        self.status.summary()
+        match self
+            .submodule_summary()
+            .filter(|_| self.config.submodules.summary)
+        {
+            Some(submodules) => format!("{} {}", self.status.summary(), submodules),
+            None => self.status.summary(),
+        }
     }
 
     pub fn cmdline(&self) -> &str {
@@ -743,13 +2774,6 @@ impl ViewModel {
         flag
     }
 
-    fn requires_interactive(&self, args_str: &str, cfg: Option<&CommandConfig>) -> bool {
-        if cfg.map(|c| c.interactive).unwrap_or(false) {
-            return true;
-        }
-        self.is_commit_needing_editor(args_str)
-    }
-
     fn is_commit_needing_editor(&self, args_str: &str) -> bool {
         let parts = parse_args_line(args_str);
         if parts.is_empty() {
@@ -760,15 +2784,25 @@ impl ViewModel {
             return false;
         }
 
-        let has_message_flag = parts
-            .iter()
-            .any(|p| p == "-m" || p == "--message" || p.starts_with("--message="));
+        let has_message_flag = parts.iter().any(|p| {
+            ["--message=", "--file=", "--reuse-message=", "--fixup="]
+                .iter()
+                .any(|long| p.starts_with(long))
+        }) || [
+            (Some('m'), "--message"),
+            (Some('F'), "--file"),
+            (Some('C'), "--reuse-message"),
+            (None, "--fixup"),
+            (None, "--no-edit"),
+        ]
+        .iter()
+        .any(|(short, long)| has_commit_flag(args_str, *short, long));
 
         !has_message_flag
     }
 
     pub fn tick(&mut self) {
-        if !self.is_running {
+        if !self.is_running() {
             return;
         }
 
@@ -779,20 +2813,99 @@ impl ViewModel {
     }
 
     fn start_running(&mut self, args_str: &str) {
-        self.is_running = true;
+        self.record(&format!("$ {}", self.argv(args_str)));
+        self.begin_running(args_str);
+    }
+
+    /// `start_running` for a command the transcript already recorded.
+    fn begin_running(&mut self, args_str: &str) {
+        self.teach(&step_label(args_str), true);
         self.running_cmd = Some(args_str.to_string());
+        self.log_detached = false;
+        self.session.note_command(args_str);
+        crash::note_command(args_str);
+        self.network_cmd = None;
+        self.output_streamed = false;
+        self.log_streamed = false;
+        self.log_follow = None;
+        self.running_since = Instant::now();
+        self.progress = None;
+        self.retry = None;
+        self.transfer = None;
         self.spinner_index = 0;
         self.spinner_last_tick = Instant::now();
-        self.cancel_flag.store(false, Ordering::Relaxed);
+    }
+
+    /// The partial output of a command canceled with Ctrl-C, once git has exited;
+    /// dropped when another command already took over the panes.
+    fn show_stopped_command(&mut self, res: CommandResult) {
+        if self.is_running() {
+            return;
+        }
+        let mut log_lines = res.log_lines;
+        log_lines.push("<canceled by user>".into());
+        self.set_log_lines(log_lines);
+        self.result_lines = res.result_lines;
+        self.result_scroll = 0;
+        self.refresh_repo_status();
+        self.follow_content(true);
+    }
+
+    /// A git command started from the UI has not finished yet.
+    fn is_running(&self) -> bool {
+        self.running_cmd.is_some()
+    }
+
+    /// Diffs only read, so they may open while a network command waits on the remote;
+    /// any other command could be changing the files under them.
+    fn diff_blocked(&self) -> bool {
+        self.is_running() && self.network_cmd.is_none()
     }
 
     fn finish_running(&mut self) {
-        self.is_running = false;
         self.running_cmd = None;
+        if std::mem::take(&mut self.journaled) {
+            journal::clear(&self.repo_root);
+        }
+        self.progress = None;
+        self.retry = None;
+        self.transfer = None;
         self.spinner_index = 0;
     }
 }
 
-fn current_repo_path() -> std::path::PathBuf {
-    env::current_dir().unwrap_or_else(|_| ".".into())
+/// Runs `cmd` with the terminal handed back to it, restoring the TUI afterwards.
+fn run_suspended(cmd: &mut Command) -> Result<i32> {
+    let status = suspended(|| cmd.status())?;
+    Ok(status?.code().unwrap_or(-1))
+}
+
+/// Runs `f` on the normal screen with the terminal in cooked mode, then brings the TUI back.
+fn suspended<T>(f: impl FnOnce() -> T) -> Result<T> {
+    disable_raw_mode().ok();
+    {
+        let mut stdout = std::io::stdout();
+        execute!(stdout, DisableMouseCapture, LeaveAlternateScreen)?;
+    }
+
+    let out = f();
+
+    {
+        let mut stdout = std::io::stdout();
+        execute!(
+            stdout,
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            Clear(ClearType::All)
+        )?;
+    }
+    enable_raw_mode().ok();
+
+    Ok(out)
+}
+
+fn is_commit(args_str: &str) -> bool {
+    parse_args_line(args_str)
+        .first()
+        .is_some_and(|a| a == "commit")
 }
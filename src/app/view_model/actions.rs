@@ -0,0 +1,133 @@
+use super::ViewModel;
+use crate::git::LfsMode;
+
+/// Built-in actions, runnable as `:<name>`, from commands with `cmd = ":<name>"` and
+/// from keys in `[keys.actions]`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) enum Action {
+    StageAll,
+    UnstageAll,
+    Refresh,
+    ToggleFunctionContext,
+    ToggleDryRun,
+    ToggleDiffMode,
+    NextPanel,
+    OpenBranchPicker,
+    ShowStashes,
+    ShowRemotes,
+    ShowWorktrees,
+    ShowSubmodules,
+    NewBranch,
+    Orphan,
+    Today,
+    Decorate,
+    Owners,
+    Continue,
+    Abort,
+    Skip,
+    CheckUpdate,
+    ListJobs,
+    ListActions,
+    ShowVersion,
+    Doctor,
+    LfsPrune,
+    ExportGraph,
+}
+
+/// Name lookup table; later entries with the same action are aliases.
+const ACTIONS: [(&str, Action); 30] = [
+    ("stage-all", Action::StageAll),
+    ("unstage-all", Action::UnstageAll),
+    ("refresh", Action::Refresh),
+    ("toggle-function-context", Action::ToggleFunctionContext),
+    ("toggle-dry-run", Action::ToggleDryRun),
+    ("toggle-diff-mode", Action::ToggleDiffMode),
+    ("next-panel", Action::NextPanel),
+    ("branches", Action::OpenBranchPicker),
+    ("stashes", Action::ShowStashes),
+    ("remotes", Action::ShowRemotes),
+    ("worktrees", Action::ShowWorktrees),
+    ("submodules", Action::ShowSubmodules),
+    ("new-branch", Action::NewBranch),
+    ("orphan", Action::Orphan),
+    ("today", Action::Today),
+    ("decorate", Action::Decorate),
+    ("owners", Action::Owners),
+    ("continue", Action::Continue),
+    ("abort", Action::Abort),
+    ("skip", Action::Skip),
+    ("check-update", Action::CheckUpdate),
+    ("jobs", Action::ListJobs),
+    ("actions", Action::ListActions),
+    ("version", Action::ShowVersion),
+    ("doctor", Action::Doctor),
+    ("lfs-prune", Action::LfsPrune),
+    ("export-graph", Action::ExportGraph),
+    ("stage", Action::StageAll),
+    ("unstage", Action::UnstageAll),
+    ("open-branch-picker", Action::OpenBranchPicker),
+];
+
+impl Action {
+    pub(super) fn from_name(name: &str) -> Option<Action> {
+        ACTIONS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, action)| *action)
+    }
+}
+
+impl ViewModel {
+    pub(super) fn run_action(&mut self, action: Action) {
+        match action {
+            Action::StageAll => self.run_command("add -A".to_string(), LfsMode::None, false),
+            Action::UnstageAll => {
+                self.run_command("restore --staged .".to_string(), LfsMode::None, false)
+            }
+            Action::Refresh => {
+                self.refresh_repo_status();
+                self.result_lines = vec!["status refreshed".into()];
+                self.result_scroll = 0;
+            }
+            Action::ToggleFunctionContext => self.toggle_function_context(),
+            Action::ToggleDryRun => self.set_dry_run(""),
+            Action::ToggleDiffMode => self.toggle_split_diff(),
+            Action::NextPanel => self.cycle_list_panel(),
+            Action::OpenBranchPicker => self.show_branches(),
+            Action::ShowStashes => self.show_stashes(),
+            Action::ShowRemotes => self.show_remotes(),
+            Action::ShowWorktrees => self.show_worktrees(),
+            Action::ShowSubmodules => self.load_submodules(true),
+            Action::NewBranch => self.start_new_branch(),
+            Action::Orphan => self.start_orphan(),
+            Action::Today => self.show_today(),
+            Action::Decorate => self.load_decorations(true),
+            Action::Owners => self.show_owners(),
+            Action::Continue => self.continue_sequence(),
+            Action::Abort => self.abort_operation(),
+            Action::Skip => self.skip_operation(),
+            Action::CheckUpdate => self.check_update(false),
+            Action::ListJobs => self.list_jobs(),
+            Action::ShowVersion => {
+                self.result_lines = self.version_lines();
+                self.result_scroll = 0;
+            }
+            Action::Doctor => self.run_doctor(),
+            Action::LfsPrune => self.preview_lfs_prune(),
+            Action::ExportGraph => self.export_graph(""),
+            Action::ListActions => {
+                let mut lines = vec!["actions (run as :<name> or cmd = \":<name>\"):".to_string()];
+                let mut seen = Vec::new();
+                for (name, action) in ACTIONS {
+                    if seen.contains(&action) {
+                        continue;
+                    }
+                    seen.push(action);
+                    lines.push(format!("  {}", name));
+                }
+                self.result_lines = lines;
+                self.result_scroll = 0;
+            }
+        }
+    }
+}
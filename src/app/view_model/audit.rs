@@ -0,0 +1,129 @@
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::SystemTime;
+
+use super::summary::{unix_secs, utc_datetime};
+use super::{Confirmed, HunkView, PendingCommand, Review, ViewModel};
+use crate::config::app_dir;
+use crate::doctor::Fix;
+use crate::git::{git_command, parse_args_line};
+use crate::notes::append_line;
+use crate::shell::shell_command;
+
+/// A mutation `--audit` holds back until it is confirmed with `y`.
+pub(super) enum Audited {
+    Command(PendingCommand),
+    /// The current step of the running flow.
+    FlowStep,
+    /// A fix from the `:doctor` report, kept since the report is replaced meanwhile.
+    DoctorFix(usize, Fix),
+    /// The `:review` worktree and the git steps that check it out.
+    Review(Review, Vec<String>),
+    /// A hunk to apply to the index: the view it came from, its index and its patch.
+    Hunk(HunkView, usize, String),
+}
+
+impl ViewModel {
+    /// Lists the exact argv of every process `pending` may start and waits for `y`.
+    pub(super) fn confirm_command(&mut self, mut pending: PendingCommand) {
+        pending.args_str = self.prefill_commit_issue(pending.args_str);
+        let mut lines = vec![
+            "== audit: confirm ==".to_string(),
+            format!("run: {}", self.argv(&pending.args_str)),
+        ];
+        if pending.interactive {
+            lines.push("  (interactive: the terminal is handed to git)".into());
+        }
+        if let Some(lfs) = pending.lfs_mode.follow_up() {
+            lines.push(format!("then: {}", self.argv(&lfs)));
+        }
+        if let Some(hook) = &pending.hooks.on_success {
+            lines.push(format!("on success: {}", self.argv(hook)));
+        }
+        if let Some(hook) = &pending.hooks.on_failure {
+            lines.push(format!("on failure: {}", self.argv(hook)));
+        }
+        self.ask_audit(lines, Audited::Command(pending));
+    }
+
+    /// `lines[1]` names what would run; it is recorded if the user declines.
+    pub(super) fn ask_audit(&mut self, lines: Vec<String>, audited: Audited) {
+        self.set_log_lines(lines);
+        self.result_lines = vec!["audit: run this? (y runs it, any other key cancels)".into()];
+        self.result_scroll = 0;
+        self.await_confirmation(None, Confirmed::Audited(audited));
+    }
+
+    pub(super) fn run_audited(&mut self, audited: Audited) {
+        match audited {
+            Audited::Command(pending) => self.preview_or_execute(pending),
+            Audited::FlowStep => self.run_confirmed_flow_step(),
+            Audited::DoctorFix(index, fix) => self.spawn_doctor_fix(index, fix),
+            Audited::Review(review, steps) => self.create_review(review, steps),
+            Audited::Hunk(view, hunk, patch) => self.apply_hunk(view, hunk, patch),
+        }
+    }
+
+    /// Records what was declined; a declined flow step ends the flow.
+    pub(super) fn decline_audited(&mut self, audited: Audited) -> String {
+        let declined = self.log_lines.get(1).cloned().unwrap_or_default();
+        self.record(&format!("declined {}", declined));
+        if matches!(audited, Audited::FlowStep) {
+            self.flow = None;
+            "flow aborted".into()
+        } else {
+            "canceled".into()
+        }
+    }
+
+    /// The exact argv of a git step, or of the shell running a `!` step.
+    pub(super) fn argv(&self, step: &str) -> String {
+        let cmd = match step.strip_prefix('!') {
+            Some(line) => shell_command(line.trim()),
+            None => {
+                let mut cmd = git_command(&self.config.git_path);
+                cmd.args(parse_args_line(step));
+                cmd
+            }
+        };
+        command_argv(&cmd)
+    }
+
+    /// Appends a timestamped `entry` to this session's transcript under `--audit`.
+    pub(super) fn record(&mut self, entry: &str) {
+        if !self.config.audit {
+            return;
+        }
+        let Some(path) = self.transcript_path() else {
+            return;
+        };
+        let secs = unix_secs(SystemTime::now());
+        let line = format!("{}:{:02} UTC  {}", utc_datetime(secs), secs % 60, entry);
+        if let Err(e) = append_line(&path, &line) {
+            self.result_lines
+                .push(format!("WARN: cannot write {}: {}", path.display(), e));
+        }
+    }
+
+    pub(super) fn record_outcome(&mut self, success: bool) {
+        self.record(if success { "  -> ok" } else { "  -> failed" });
+    }
+
+    pub(super) fn transcript_path(&self) -> Option<PathBuf> {
+        let started = unix_secs(self.session.started_at());
+        Some(
+            app_dir()?
+                .join("transcripts")
+                .join(format!("session-{}.log", started)),
+        )
+    }
+}
+
+/// `cmd` as the list of arguments the OS receives.
+pub(super) fn command_argv(cmd: &Command) -> String {
+    let argv: Vec<String> = std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect();
+    format!("{:?}", argv)
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use super::ViewModel;
+use crate::app::bench::time;
+use crate::diff::ParsedDiff;
+use crate::git::{RefCache, git_command, load_repo_status};
+
+impl ViewModel {
+    /// For `--bench`: times a status refresh, with the ref cache cold and warm, and the
+    /// diff FILES shows, for the whole worktree, `iterations` times each. The diff is left
+    /// in LOG so drawing can be timed on it.
+    pub fn bench_backend(&mut self, iterations: usize) -> Vec<(String, Vec<Duration>)> {
+        let git = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        let scope = self.scope.clone();
+        let mut rows = Vec::new();
+
+        let runs = time(iterations, || {
+            load_repo_status(&git, &repo, scope.as_deref(), &mut RefCache::default());
+        });
+        rows.push(("status refresh, refs cold".to_string(), runs));
+
+        let load_status = self.status_loader();
+        load_status();
+        let runs = time(iterations, || {
+            load_status();
+        });
+        rows.push(("status refresh, refs cached".to_string(), runs));
+
+        let (args, label) = self.build_diff_command(&[]);
+        let mut lines = Vec::new();
+        let runs = time(iterations, || {
+            lines = git_command(&git)
+                .args(&args)
+                .current_dir(&repo)
+                .output()
+                .map(|o| {
+                    String::from_utf8_lossy(&o.stdout)
+                        .lines()
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+            ParsedDiff::parse(&lines);
+        });
+        rows.push((format!("{} ({} lines), parsed", label, lines.len()), runs));
+        self.set_log_lines(lines);
+        rows
+    }
+}
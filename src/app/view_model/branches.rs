@@ -0,0 +1,130 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::{Confirmed, Focus, Mode, PendingCommand, ViewModel, list_move};
+use crate::git::{Branch, LfsMode, load_branches, quote_arg};
+
+impl ViewModel {
+    /// Rereads local and remote-tracking branches for the BRANCHES panel.
+    pub(super) fn load_branches(&mut self) {
+        self.branches = load_branches(&self.config.git_path, &self.repo_root);
+        self.selected_branch = self
+            .selected_branch
+            .min(self.branches.len().saturating_sub(1));
+    }
+
+    /// `:branches` shows the BRANCHES panel with the cursor on the current branch.
+    pub(super) fn show_branches(&mut self) {
+        self.load_branches();
+        self.selected_branch = self.branches.iter().position(|b| b.current).unwrap_or(0);
+        self.list_panel = Focus::Branches;
+        self.focus = Focus::Branches;
+        let local = self.branches.iter().filter(|b| !b.remote).count();
+        self.result_lines = vec![format!(
+            "{} local branch(es), {} remote-tracking",
+            local,
+            self.branches.len() - local
+        )];
+        self.result_scroll = 0;
+    }
+
+    pub(super) fn handle_branch_keys(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.checkout_selected_branch(),
+            KeyCode::Char('n') => self.start_new_branch(),
+            KeyCode::Char('r') => {
+                if let Some(branch) = self.selected_local_branch("rename") {
+                    self.mode = Mode::CommandLine;
+                    self.cmdline = format!("branch -m {} ", quote_arg(&branch));
+                }
+            }
+            KeyCode::Char('D') => self.handle_branch_delete_key(),
+            KeyCode::Char('w') => self.add_worktree_for_selected_branch(),
+            _ => {
+                if let Some(row) = list_move(
+                    self.key_action(&key),
+                    self.selected_branch,
+                    self.branches.len(),
+                ) {
+                    self.selected_branch = row;
+                }
+            }
+        }
+    }
+
+    /// Enter: switches to a local branch, or creates a local branch tracking a
+    /// remote-tracking one.
+    fn checkout_selected_branch(&mut self) {
+        let Some(branch) = self.branches.get(self.selected_branch) else {
+            return;
+        };
+        if branch.current {
+            self.result_lines = vec![format!("already on {}", branch.name)];
+            self.result_scroll = 0;
+            return;
+        }
+        let cmd = if branch.remote {
+            format!("switch --track {}", quote_arg(&branch.name))
+        } else {
+            format!("switch {}", quote_arg(&branch.name))
+        };
+        self.run_command(cmd, LfsMode::None, false);
+    }
+
+    /// `D` asks first; pressing it again (or `y`) runs `git branch -d`, which
+    /// still refuses to drop unmerged work.
+    fn handle_branch_delete_key(&mut self) {
+        if self.refuse_read_only("deleting branches") {
+            return;
+        }
+        let Some(name) = self.selected_local_branch("delete") else {
+            return;
+        };
+        if self.branches[self.selected_branch].current {
+            self.result_lines = vec![format!(
+                "{} is checked out; switch to another branch first",
+                name
+            )];
+            self.result_scroll = 0;
+            return;
+        }
+        self.ask_confirmation(
+            format!("Delete branch \"{}\"?", name),
+            Some('D'),
+            Confirmed::Command(PendingCommand::plain(format!(
+                "branch -d {}",
+                quote_arg(&name)
+            ))),
+        );
+    }
+
+    /// The selected branch when it is a local one; remote-tracking branches only get a
+    /// hint, as changing them means changing the remote.
+    fn selected_local_branch(&mut self, what: &str) -> Option<String> {
+        let branch = self.branches.get(self.selected_branch)?;
+        if branch.remote {
+            let hint = match branch.name.split_once('/') {
+                Some((remote, name)) if what == "delete" => format!(
+                    "`:push {} --delete {}` deletes it on the remote",
+                    quote_arg(remote),
+                    quote_arg(name)
+                ),
+                _ => "Enter creates a local branch tracking it".to_string(),
+            };
+            self.result_lines = vec![format!(
+                "{} is remote-tracking, so it cannot be {}d here; {}",
+                branch.name, what, hint
+            )];
+            self.result_scroll = 0;
+            return None;
+        }
+        Some(branch.name.clone())
+    }
+
+    pub fn branches(&self) -> &[Branch] {
+        &self.branches
+    }
+
+    pub fn selected_branch(&self) -> usize {
+        self.selected_branch
+    }
+}
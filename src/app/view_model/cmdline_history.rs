@@ -0,0 +1,56 @@
+use super::ViewModel;
+use crate::history;
+
+/// Where Up/Down stand in the command-line history.
+pub(super) struct HistoryRecall {
+    /// Index into the history of the line shown.
+    index: usize,
+    /// What was typed before recalling; only lines starting with it are recalled, and
+    /// Down past the newest brings it back.
+    draft: String,
+}
+
+impl ViewModel {
+    /// Up (`older`) or Down in `:` mode: the previous or next history line starting
+    /// with what was typed.
+    pub(super) fn recall_history(&mut self, older: bool) {
+        let draft = self
+            .history_recall
+            .as_ref()
+            .map_or(self.cmdline.as_str(), |r| r.draft.as_str())
+            .to_string();
+        let matches = |l: &&String| l.starts_with(&draft);
+        let found = match (&self.history_recall, older) {
+            (None, true) => self.cmdline_history.iter().rposition(|l| matches(&l)),
+            (None, false) => None,
+            (Some(r), true) => self.cmdline_history[..r.index]
+                .iter()
+                .rposition(|l| matches(&l)),
+            (Some(r), false) => self.cmdline_history[r.index + 1..]
+                .iter()
+                .position(|l| matches(&l))
+                .map(|i| r.index + 1 + i),
+        };
+        match found {
+            Some(index) => {
+                self.cmdline = self.cmdline_history[index].clone();
+                self.history_recall = Some(HistoryRecall { index, draft });
+            }
+            // Down past the newest match restores the draft; Up past the oldest stays.
+            None if !older && self.history_recall.is_some() => {
+                self.cmdline = draft;
+                self.history_recall = None;
+            }
+            None => {}
+        }
+    }
+
+    /// Saves a line run from `:` to the history file.
+    pub(super) fn record_cmdline(&mut self, line: &str) {
+        self.history_recall = None;
+        if let Err(e) = history::record(&mut self.cmdline_history, line) {
+            self.result_lines
+                .push(format!("WARN: failed to save command history: {}", e));
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::ViewModel;
+use crate::config::{
+    CommandConfig, CommandScope, CommandSort, move_command, remove_command, save_command,
+};
+use crate::keymap::Keymap;
+
+const FIELDS: [&str; 5] = ["name", "cmd", "lfs", "interactive", "key"];
+
+/// Editable copy of a `[[commands]]` entry shown by `:command add|edit`.
+pub struct CommandForm {
+    /// Command being edited; `None` when adding a new one.
+    index: Option<usize>,
+    values: [String; 5],
+    active: usize,
+    error: Option<String>,
+}
+
+impl CommandForm {
+    fn new(index: Option<usize>, cmd: Option<&CommandConfig>) -> Self {
+        let values = match cmd {
+            Some(c) => [
+                c.name.clone(),
+                c.cmd.clone(),
+                c.lfs.clone().unwrap_or_default(),
+                if c.interactive { "true" } else { "false" }.to_string(),
+                c.key.map(String::from).unwrap_or_default(),
+            ],
+            None => Default::default(),
+        };
+        Self {
+            index,
+            values,
+            active: 0,
+            error: None,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self.index {
+            Some(_) => "EDIT COMMAND (Tab:next Enter:save Esc:cancel)",
+            None => "ADD COMMAND (Tab:next Enter:save Esc:cancel)",
+        }
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        FIELDS
+            .iter()
+            .zip(&self.values)
+            .map(|(label, value)| (*label, value.as_str()))
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Builds the command, keeping fields the form does not edit from `base`.
+    fn to_command(
+        &self,
+        base: Option<&CommandConfig>,
+        keymap: &Keymap,
+    ) -> Result<CommandConfig, String> {
+        let [name, cmd, lfs, interactive, key] = self.values.each_ref().map(|v| v.trim());
+        if name.is_empty() || cmd.is_empty() {
+            return Err("name and cmd are required".into());
+        }
+        let lfs = (!lfs.is_empty()).then(|| lfs.to_string());
+        let interactive = match interactive {
+            "" | "false" | "no" | "n" => false,
+            "true" | "yes" | "y" => true,
+            _ => return Err("interactive must be true or false".into()),
+        };
+        let mut chars = key.chars();
+        let key = match (chars.next(), chars.next()) {
+            (None, _) => None,
+            (Some(c), None) if keymap.reserves_command_key(c) => {
+                return Err(format!("key {} is taken by CMD itself", c));
+            }
+            (Some(c), None) => Some(c),
+            _ => return Err("key must be a single character".into()),
+        };
+        Ok(CommandConfig {
+            name: name.to_string(),
+            cmd: cmd.to_string(),
+            lfs,
+            lfs_remote: base.and_then(|c| c.lfs_remote.clone()),
+            lfs_ref: base.and_then(|c| c.lfs_ref.clone()),
+            lfs_include: base.and_then(|c| c.lfs_include.clone()),
+            lfs_exclude: base.and_then(|c| c.lfs_exclude.clone()),
+            interactive,
+            scope: base.map_or(CommandScope::default(), |c| c.scope),
+            key,
+            on_success: base.and_then(|c| c.on_success.clone()),
+            on_failure: base.and_then(|c| c.on_failure.clone()),
+            retries: base.map_or(0, |c| c.retries),
+            confirm: base.and_then(|c| c.confirm.clone()),
+        })
+    }
+}
+
+impl ViewModel {
+    /// `:command add|edit|remove` manages the CMD list and persists it to config.toml.
+    pub(super) fn command_config_command(&mut self, action: &str) {
+        let selected = self.selected_command_index();
+        match (action, selected) {
+            ("add", _) => self.command_form = Some(CommandForm::new(None, None)),
+            ("edit", Some(i)) => {
+                self.command_form = Some(CommandForm::new(Some(i), self.config.commands.get(i)))
+            }
+            ("remove", Some(i)) => match remove_command(i) {
+                Ok(()) => {
+                    let removed = self.config.commands.remove(i);
+                    self.selected_cmd = self
+                        .selected_cmd
+                        .min(self.config.commands.len().saturating_sub(1));
+                    self.result_lines = vec![format!("removed command \"{}\"", removed.name)];
+                }
+                Err(e) => self.result_lines = vec![format!("ERROR: {:#}", e)],
+            },
+            ("edit" | "remove", None) => self.result_lines = vec!["no command selected".into()],
+            _ => self.result_lines = vec!["usage: :command add|edit|remove".into()],
+        }
+        self.result_scroll = 0;
+    }
+
+    /// Moves the highlighted command to `to` and persists the new order.
+    pub(super) fn move_selected_command(&mut self, to: usize) {
+        if self.config.layout.cmd_sort == CommandSort::Recent {
+            self.result_lines =
+                vec!["J/K reorder config.toml; the list is sorted by cmd_sort = \"recent\"".into()];
+            self.result_scroll = 0;
+            return;
+        }
+        if let Err(e) = move_command(self.selected_cmd, to) {
+            self.result_lines = vec![format!("ERROR: {:#}", e)];
+            self.result_scroll = 0;
+            return;
+        }
+        let cmd = self.config.commands.remove(self.selected_cmd);
+        self.config.commands.insert(to, cmd);
+        self.selected_cmd = to;
+    }
+
+    pub(super) fn handle_command_form_key(&mut self, key: KeyEvent) {
+        let Some(form) = self.command_form.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.command_form = None,
+            KeyCode::Tab | KeyCode::Down => form.active = (form.active + 1) % FIELDS.len(),
+            KeyCode::BackTab | KeyCode::Up => {
+                form.active = (form.active + FIELDS.len() - 1) % FIELDS.len()
+            }
+            KeyCode::Backspace => {
+                form.values[form.active].pop();
+            }
+            KeyCode::Char(c) => form.values[form.active].push(c),
+            KeyCode::Enter => self.save_command_form(),
+            _ => {}
+        }
+    }
+
+    fn save_command_form(&mut self) {
+        let Some(form) = self.command_form.as_mut() else {
+            return;
+        };
+        let base = form.index.and_then(|i| self.config.commands.get(i));
+        let cmd = match form.to_command(base, &self.keymap) {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                form.error = Some(e);
+                return;
+            }
+        };
+        if let Err(e) = save_command(form.index, &cmd) {
+            form.error = Some(format!("{:#}", e));
+            return;
+        }
+
+        self.result_lines = vec![format!("saved command \"{}\" to config.toml", cmd.name)];
+        self.result_scroll = 0;
+        let index = match form.index {
+            Some(i) => {
+                self.config.commands[i] = cmd;
+                i
+            }
+            None => {
+                self.config.commands.push(cmd);
+                self.config.commands.len() - 1
+            }
+        };
+        self.selected_cmd = self
+            .command_order()
+            .iter()
+            .position(|&i| i == index)
+            .unwrap_or(0);
+        self.command_form = None;
+    }
+}
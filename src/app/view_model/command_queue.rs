@@ -0,0 +1,99 @@
+use super::{Hooks, ViewModel};
+use crate::git::{LfsMode, step_label};
+
+/// A command given while another one ran, waiting for its turn.
+pub(super) struct QueuedCommand {
+    /// `q<id>` in `:jobs` and `:cancel`.
+    id: usize,
+    args_str: String,
+    lfs_mode: LfsMode,
+    hooks: Hooks,
+}
+
+impl ViewModel {
+    /// Puts a command given while another runs at the end of the queue.
+    pub(super) fn enqueue_command(&mut self, args_str: String, lfs_mode: LfsMode, hooks: Hooks) {
+        let id = self.next_queued;
+        self.next_queued += 1;
+        self.record(&format!("queued q{}: {}", id, self.argv(&args_str)));
+        self.result_lines.push(format!(
+            "queued q{}: {} ({} ahead of it; :cancel q{} drops it)",
+            id,
+            step_label(&args_str),
+            self.queue.len() + 1,
+            id
+        ));
+        self.queue.push_back(QueuedCommand {
+            id,
+            args_str,
+            lfs_mode,
+            hooks,
+        });
+    }
+
+    /// Starts the oldest queued command once nothing else runs.
+    pub(super) fn start_next_queued(&mut self) {
+        if self.is_running() || self.flow.is_some() {
+            return;
+        }
+        let Some(next) = self.queue.pop_front() else {
+            return;
+        };
+        self.start_command(next.args_str, next.lfs_mode, false, next.hooks);
+    }
+
+    /// Empties the queue, saying in R what was dropped and `why`.
+    pub(super) fn drop_queue(&mut self, why: &str) {
+        if self.queue.is_empty() {
+            return;
+        }
+        let dropped: Vec<String> = self
+            .queue
+            .drain(..)
+            .map(|cmd| format!("  q{} {}", cmd.id, step_label(&cmd.args_str)))
+            .collect();
+        self.record(&format!("  -> {} queued command(s) dropped", dropped.len()));
+        self.result_lines.push(format!(
+            "dropped {} queued command(s) {}:",
+            dropped.len(),
+            why
+        ));
+        self.result_lines.extend(dropped);
+    }
+
+    /// `:cancel q<id>` takes one command off the queue; false when `id` is not one.
+    pub(super) fn cancel_queued(&mut self, id: &str) -> bool {
+        let Some(id) = id.strip_prefix('q').and_then(|n| n.parse::<usize>().ok()) else {
+            return false;
+        };
+        let Some(index) = self.queue.iter().position(|cmd| cmd.id == id) else {
+            self.result_lines = vec![format!("no queued command q{}", id)];
+            self.result_scroll = 0;
+            return true;
+        };
+        if let Some(cmd) = self.queue.remove(index) {
+            self.record(&format!("  -> q{} dropped", id));
+            self.result_lines = vec![format!("dropped q{}: {}", id, step_label(&cmd.args_str))];
+            self.result_scroll = 0;
+        }
+        true
+    }
+
+    /// Lines of `:jobs` for the queue.
+    pub(super) fn queue_lines(&self) -> Vec<String> {
+        if self.queue.is_empty() {
+            return Vec::new();
+        }
+        let mut lines = vec!["queued commands, run in this order (:cancel q<n>):".to_string()];
+        lines.extend(
+            self.queue
+                .iter()
+                .map(|cmd| format!("  q{} {}", cmd.id, step_label(&cmd.args_str))),
+        );
+        lines
+    }
+
+    pub fn queued_commands(&self) -> usize {
+        self.queue.len()
+    }
+}
@@ -0,0 +1,236 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use super::commit_preview::has_commit_flag;
+use super::{Hooks, ViewModel};
+use crate::git::{LfsMode, head_message, quote_arg, write_commit_message};
+
+/// Subject length past which the counter turns to a warning, as `git log --oneline`
+/// and most forges cut it there.
+pub const SUBJECT_LIMIT: usize = 50;
+/// Body line length past which the counter turns to a warning.
+pub const BODY_LIMIT: usize = 72;
+
+/// Commit message being written in the popup that replaces `$EDITOR` for `commit`.
+pub struct CommitEditor {
+    /// The subject is the first line, the body the rest.
+    lines: Vec<String>,
+    row: usize,
+    /// Cursor position in characters.
+    col: usize,
+    args_str: String,
+    lfs_mode: LfsMode,
+    hooks: Hooks,
+    error: Option<String>,
+}
+
+impl CommitEditor {
+    fn new(message: &str, args_str: String, lfs_mode: LfsMode, hooks: Hooks) -> Self {
+        let mut lines: Vec<String> = message.trim_end().lines().map(String::from).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        Self {
+            col: lines[0].chars().count(),
+            lines,
+            row: 0,
+            args_str,
+            lfs_mode,
+            hooks,
+            error: None,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        format!(
+            "git {} (Ctrl-S:commit Esc:cancel)",
+            self.args_str.trim_end()
+        )
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Row and character column of the cursor.
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    pub fn subject_len(&self) -> usize {
+        self.lines[0].chars().count()
+    }
+
+    /// Longest body line in characters; 0 without a body.
+    pub fn longest_body_line(&self) -> usize {
+        self.lines[1..]
+            .iter()
+            .map(|l| l.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn body_lines(&self) -> usize {
+        self.lines[1..]
+            .iter()
+            .skip_while(|l| l.trim().is_empty())
+            .count()
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// The message as git should read it, with a blank line between subject and body.
+    fn message(&self) -> String {
+        let subject = self.lines[0].trim();
+        let body: Vec<&str> = self.lines[1..]
+            .iter()
+            .map(|l| l.trim_end())
+            .skip_while(|l| l.is_empty())
+            .collect();
+        let body = body.join("\n");
+        let body = body.trim_end();
+        if body.is_empty() {
+            format!("{}\n", subject)
+        } else {
+            format!("{}\n\n{}\n", subject, body)
+        }
+    }
+
+    fn byte_col(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(self.col)
+            .map_or(line.len(), |(i, _)| i)
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn insert(&mut self, c: char) {
+        let at = self.byte_col();
+        self.lines[self.row].insert(at, c);
+        self.col += 1;
+    }
+
+    fn newline(&mut self) {
+        let at = self.byte_col();
+        let rest = self.lines[self.row].split_off(at);
+        self.row += 1;
+        self.lines.insert(self.row, rest);
+        self.col = 0;
+    }
+
+    fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let at = self.byte_col();
+            self.lines[self.row].remove(at);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.col < self.line_len(self.row) {
+            let at = self.byte_col();
+            self.lines[self.row].remove(at);
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&next);
+        }
+    }
+
+    fn move_to_row(&mut self, row: usize) {
+        self.row = row;
+        self.col = self.col.min(self.line_len(row));
+    }
+}
+
+impl ViewModel {
+    /// Opens the commit message popup for a `commit` that would otherwise start
+    /// `$EDITOR`; `--amend` starts from the message of HEAD.
+    pub(super) fn open_commit_editor(&mut self, args_str: String, lfs_mode: LfsMode, hooks: Hooks) {
+        if self.is_running() {
+            self.result_lines
+                .push("WARN: already running command".into());
+            return;
+        }
+        let message = if has_commit_flag(&args_str, None, "--amend") {
+            head_message(&self.config.git_path, &self.repo_root).unwrap_or_default()
+        } else {
+            self.commit_issue_prefix().unwrap_or_default()
+        };
+        self.commit_editor = Some(CommitEditor::new(&message, args_str, lfs_mode, hooks));
+    }
+
+    pub(super) fn handle_commit_editor_key(&mut self, key: KeyEvent) {
+        let Some(editor) = self.commit_editor.as_mut() else {
+            return;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => {
+                self.commit_editor = None;
+                self.result_lines = vec!["commit canceled".into()];
+                self.result_scroll = 0;
+            }
+            KeyCode::Char('s') if ctrl => self.submit_commit_editor(),
+            KeyCode::Char(c) if !ctrl => editor.insert(c),
+            KeyCode::Enter => editor.newline(),
+            KeyCode::Backspace => editor.backspace(),
+            KeyCode::Delete => editor.delete(),
+            KeyCode::Left if editor.col > 0 => editor.col -= 1,
+            KeyCode::Left if editor.row > 0 => {
+                editor.row -= 1;
+                editor.col = editor.line_len(editor.row);
+            }
+            KeyCode::Right if editor.col < editor.line_len(editor.row) => editor.col += 1,
+            KeyCode::Right if editor.row + 1 < editor.lines.len() => {
+                editor.row += 1;
+                editor.col = 0;
+            }
+            KeyCode::Up if editor.row > 0 => editor.move_to_row(editor.row - 1),
+            KeyCode::Down if editor.row + 1 < editor.lines.len() => {
+                editor.move_to_row(editor.row + 1)
+            }
+            KeyCode::Home => editor.col = 0,
+            KeyCode::End => editor.col = editor.line_len(editor.row),
+            _ => {}
+        }
+    }
+
+    /// Ctrl-S: writes the message into the git dir and runs the commit with `-F`.
+    fn submit_commit_editor(&mut self) {
+        let Some(editor) = self.commit_editor.as_mut() else {
+            return;
+        };
+        if editor.lines[0].trim().is_empty() {
+            editor.error = Some("the subject line is empty".into());
+            return;
+        }
+        let path =
+            match write_commit_message(&self.config.git_path, &self.repo_root, &editor.message()) {
+                Ok(path) => path,
+                Err(e) => {
+                    editor.error = Some(format!("could not write the message: {}", e));
+                    return;
+                }
+            };
+        let editor = self.commit_editor.take().expect("checked above");
+        self.execute_command(
+            format!("{} -F {}", editor.args_str.trim_end(), quote_arg(&path)),
+            editor.lfs_mode,
+            false,
+            editor.hooks,
+        );
+    }
+
+    pub fn commit_editor(&self) -> Option<&CommitEditor> {
+        self.commit_editor.as_ref()
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::BTreeSet;
+use std::fs;
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::{PendingCommand, UiMessage, ViewModel};
+use crate::diff::LineKind;
+use crate::git::{commit_numstat, commit_patch, parse_args_line, signoff_trailer};
+use crate::jobs::JobKind;
+use crate::shell::shell_command;
+
+const SECRET_KEYWORDS: [&str; 6] = [
+    "password",
+    "passwd",
+    "secret",
+    "api_key",
+    "apikey",
+    "access_token",
+];
+
+/// The commit preview in LOG, kept to add the `lint` outcome once it is in.
+pub(super) struct CommitPreview {
+    lines: Vec<String>,
+    files: usize,
+    warnings: Vec<String>,
+    /// The `lint` command while it still runs.
+    lint: Option<String>,
+}
+
+impl ViewModel {
+    pub(super) fn show_commit_preview(&mut self, pending: PendingCommand) {
+        if self.is_running() {
+            self.result_lines
+                .push("WARN: already running command".into());
+            return;
+        }
+
+        let git = self.config.git_path.clone();
+        let all = commits_all_tracked(&pending.args_str);
+        let entries = match commit_numstat(&git, &self.repo_root, all) {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.result_lines
+                    .push(format!("ERROR: commit preview failed: {}", e));
+                return;
+            }
+        };
+
+        let added: u32 = entries.iter().filter_map(|e| e.added).sum();
+        let deleted: u32 = entries.iter().filter_map(|e| e.deleted).sum();
+        let dirs: BTreeSet<&str> = entries
+            .iter()
+            .map(|e| match e.path.split_once('/') {
+                Some((dir, _)) => dir,
+                None => "(root)",
+            })
+            .collect();
+
+        let mut lines = vec![
+            "== commit preview ==".to_string(),
+            format!("$ git {}", pending.args_str),
+        ];
+        if has_commit_flag(&pending.args_str, Some('s'), "--signoff")
+            && let Some(trailer) = signoff_trailer(&git, &self.repo_root)
+        {
+            lines.push(format!("sign-off: {}", trailer));
+        }
+        lines.extend([
+            String::new(),
+            format!("files: {}   +{} -{}", entries.len(), added, deleted),
+            format!(
+                "directories: {}",
+                dirs.into_iter().collect::<Vec<_>>().join(", ")
+            ),
+            String::new(),
+        ]);
+        for e in &entries {
+            let stat = match (e.added, e.deleted) {
+                (Some(a), Some(d)) => format!("+{} -{}", a, d),
+                _ => "binary".to_string(),
+            };
+            lines.push(format!("  {:>12}  {}", stat, e.path));
+        }
+
+        let mut warnings = Vec::new();
+        if entries.is_empty() {
+            warnings.push("nothing to commit".to_string());
+        }
+        let limit = self.config.commit_preview.large_file_kb * 1024;
+        for e in &entries {
+            if let Ok(meta) = fs::metadata(self.repo_root.join(&e.path))
+                && meta.len() > limit
+            {
+                warnings.push(format!(
+                    "large file: {} ({} KiB)",
+                    e.path,
+                    meta.len() / 1024
+                ));
+            }
+        }
+        if let Some(diff) = commit_patch(&git, &self.repo_root, all) {
+            for line in diff.lines.iter().filter(|l| l.kind == LineKind::Added) {
+                if looks_like_secret(&line.text[1..]) {
+                    let path = line
+                        .file
+                        .and_then(|i| diff.files.get(i))
+                        .map(|p| p.as_str())
+                        .unwrap_or("?");
+                    warnings.push(format!(
+                        "possible secret: {}:{}",
+                        path,
+                        line.new_no.unwrap_or(0)
+                    ));
+                }
+            }
+        }
+        // The lint may take a while, so the preview shows without it and waits for `y`
+        // meanwhile.
+        let lint = self.config.commit_preview.lint.clone();
+        if let Some(lint) = lint.clone() {
+            let repo = self.repo_root.clone();
+            self.jobs.spawn(JobKind::Lint, lint.clone(), move |_| {
+                let warning = match shell_command(&lint).current_dir(&repo).output() {
+                    Ok(o) if o.status.success() => None,
+                    Ok(o) => Some(format!(
+                        "lint failed (exit {}): {}",
+                        o.status.code().unwrap_or(-1),
+                        lint
+                    )),
+                    Err(e) => Some(format!("lint could not run: {}: {}", lint, e)),
+                };
+                UiMessage::CommitLinted(warning)
+            });
+        }
+
+        self.commit_preview = Some(CommitPreview {
+            lines,
+            files: entries.len(),
+            warnings,
+            lint,
+        });
+        self.draw_commit_preview();
+        self.pending_commit = Some(pending);
+    }
+
+    /// Adds the outcome of `lint` to the preview still waiting for an answer.
+    pub(super) fn finish_commit_lint(&mut self, warning: Option<String>) {
+        let Some(preview) = self.commit_preview.as_mut() else {
+            return;
+        };
+        preview.lint = None;
+        preview.warnings.extend(warning);
+        self.draw_commit_preview();
+    }
+
+    fn draw_commit_preview(&mut self) {
+        let Some(preview) = &self.commit_preview else {
+            return;
+        };
+        let mut lines = preview.lines.clone();
+        lines.push(String::new());
+        if preview.warnings.is_empty() && preview.lint.is_none() {
+            lines.push("no warnings".into());
+        } else {
+            lines.push("warnings:".into());
+            lines.extend(preview.warnings.iter().map(|w| format!("  ! {}", w)));
+        }
+        if let Some(lint) = &preview.lint {
+            lines.push(format!("  … lint running: {}", lint));
+        }
+        let warnings = match (preview.warnings.len(), &preview.lint) {
+            (0, None) => String::new(),
+            (0, Some(_)) => " (lint still running)".to_string(),
+            (n, None) => format!(" with {} warning(s)", n),
+            (n, Some(_)) => format!(" with {} warning(s) so far (lint still running)", n),
+        };
+        self.result_lines = vec![format!(
+            "Commit {} file(s){}? (y/Enter to commit, any other key cancels)",
+            preview.files, warnings
+        )];
+        self.set_log_lines(lines);
+        self.result_scroll = 0;
+    }
+
+    pub(super) fn handle_commit_preview_key(&mut self, key: KeyEvent) {
+        self.commit_preview = None;
+        let Some(pending) = self.pending_commit.take() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => self.execute_command(
+                pending.args_str,
+                pending.lfs_mode,
+                pending.interactive,
+                pending.hooks,
+            ),
+            _ => self.result_lines = vec!["commit canceled".into()],
+        }
+    }
+}
+
+/// True for `commit -a`, `--all` or bundled short flags such as `-am`.
+fn commits_all_tracked(args_str: &str) -> bool {
+    has_commit_flag(args_str, Some('a'), "--all")
+}
+
+/// Whether a commit command line passes `long` or `short`, alone or in a bundle such as `-sm`.
+pub(super) fn has_commit_flag(args_str: &str, short: Option<char>, long: &str) -> bool {
+    for arg in parse_args_line(args_str).iter().skip(1) {
+        if arg == long {
+            return true;
+        }
+        if arg == "--" {
+            break;
+        }
+        let Some(flags) = arg.strip_prefix('-').filter(|f| !f.starts_with('-')) else {
+            continue;
+        };
+        for c in flags.chars() {
+            match c {
+                c if Some(c) == short => return true,
+                // The rest of the bundle is this flag's value.
+                'm' | 'F' | 'c' | 'C' | 't' => break,
+                _ => {}
+            }
+        }
+    }
+    false
+}
+
+fn looks_like_secret(text: &str) -> bool {
+    if text.contains("-----BEGIN") && text.contains("PRIVATE KEY") {
+        return true;
+    }
+    if let Some(pos) = text.find("AKIA") {
+        let id: String = text[pos..].chars().take(20).collect();
+        if id.len() == 20
+            && id
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        {
+            return true;
+        }
+    }
+    let lower = text.to_lowercase();
+    SECRET_KEYWORDS.iter().any(|k| {
+        lower.find(k).is_some_and(|pos| {
+            let rest = lower[pos + k.len()..].trim_start_matches(['"', '\'', ' ']);
+            (rest.starts_with('=') || rest.starts_with(':'))
+                && rest[1..].trim().trim_matches(['"', '\'', ',', ';']).len() >= 8
+        })
+    })
+}
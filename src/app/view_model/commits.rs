@@ -0,0 +1,163 @@
+use super::{CommitList, Focus, UiMessage, ViewModel};
+use crate::clipboard;
+use crate::git::{LfsMode, RefKind, followed_paths, load_commits, parse_args_line, quote_arg};
+use crate::jobs::JobKind;
+
+/// Commits `:commits` lists unless its arguments say otherwise.
+const LIMIT: &str = "-n300";
+
+impl ViewModel {
+    /// `:commits [<git log args>]` lists commits in the COMMITS panel with their branches,
+    /// tags and remote heads; `t` there narrows it to tagged commits.
+    pub(super) fn load_commit_log(&mut self, query: &str) {
+        let mut args = parse_args_line(query);
+        if !args.iter().any(|a| a == "--")
+            && let Some(scope) = &self.scope
+        {
+            args.extend(["--".to_string(), scope.clone()]);
+        }
+        let paths = match args.iter().position(|a| a == "--") {
+            Some(i) => args[i + 1..].to_vec(),
+            None => Vec::new(),
+        };
+        let shown = format!("git log {}", args.join(" "));
+        self.result_lines = vec![format!("$ {}", shown.trim_end()), "loading log...".into()];
+        self.result_scroll = 0;
+        self.teach(shown.trim_end(), true);
+
+        let list = CommitList {
+            title: format!("log {}", args.join(" ")).trim_end().to_string(),
+            commits: Vec::new(),
+            paths,
+            ..CommitList::default()
+        };
+        let log_args: Vec<String> = std::iter::once(LIMIT.to_string()).chain(args).collect();
+        let git_path = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        self.jobs.spawn(JobKind::Commits, "log", move |_| {
+            match load_commits(&git_path, &repo, &log_args) {
+                Ok(commits) => UiMessage::CommitsLoaded(CommitList { commits, ..list }),
+                Err(e) => UiMessage::Error(e),
+            }
+        });
+        self.focus = Focus::Commits;
+    }
+
+    /// The commits that touched `path`, following renames, in the COMMITS panel; each
+    /// opens with the patch of the file under the name it had then.
+    pub(super) fn load_file_history(&mut self, path: String) {
+        let log_args = vec!["--follow".to_string(), "--".to_string(), path.clone()];
+        self.result_lines = vec![
+            format!("$ git log --follow -- {}", quote_arg(&path)),
+            "loading history...".into(),
+        ];
+        self.result_scroll = 0;
+        self.teach(&format!("git log --follow -- {}", quote_arg(&path)), true);
+        let list = CommitList {
+            title: format!("history {}", path),
+            paths: vec![path.clone()],
+            ..CommitList::default()
+        };
+        let git_path = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        self.jobs.spawn(JobKind::Commits, "file-history", move |_| {
+            let commits = match load_commits(&git_path, &repo, &log_args) {
+                Ok(commits) => commits,
+                Err(e) => return UiMessage::Error(e),
+            };
+            let names = followed_paths(&git_path, &repo, &path);
+            let followed = names
+                .iter()
+                .enumerate()
+                .map(|(i, (hash, name))| {
+                    let mut paths = vec![name.clone()];
+                    // The older name too, so the commit renaming the file shows as a rename.
+                    if let Some((_, older)) = names.get(i + 1)
+                        && older != name
+                    {
+                        paths.push(older.clone());
+                    }
+                    (hash.clone(), paths)
+                })
+                .collect();
+            UiMessage::CommitsLoaded(CommitList {
+                commits,
+                followed,
+                ..list
+            })
+        });
+        self.focus = Focus::Commits;
+    }
+
+    /// `f` in FILES: the history of the selected file, or of the selected directory
+    /// of the tree.
+    pub(super) fn show_selected_file_history(&mut self) {
+        if let Some(dir) = self.selected_dir.clone() {
+            self.load_commit_log(&format!("-- {}", quote_arg(&dir)));
+            return;
+        }
+        let Some(file) = self.status.files.get(self.selected_file) else {
+            return;
+        };
+        if file.status == "??" {
+            self.result_lines = vec![format!("{} is untracked; it has no history yet", file.path)];
+            self.result_scroll = 0;
+            return;
+        }
+        self.load_file_history(file.path.clone());
+    }
+
+    /// `t` in COMMITS shows only commits a tag points at, or the whole list again,
+    /// keeping the selected commit when it is still listed.
+    pub(super) fn toggle_tagged_commits(&mut self) {
+        let selected = self
+            .commit_list
+            .commits
+            .get(self.selected_commit)
+            .map(|c| c.hash.clone());
+        let list = &mut self.commit_list;
+        match list.all.take() {
+            Some(all) => list.commits = all,
+            None => {
+                let tagged = list
+                    .commits
+                    .iter()
+                    .filter(|c| c.refs.iter().any(|r| r.kind == RefKind::Tag))
+                    .cloned()
+                    .collect();
+                list.all = Some(std::mem::replace(&mut list.commits, tagged));
+            }
+        }
+        self.selected_commit = selected
+            .and_then(|hash| list.commits.iter().position(|c| c.hash == hash))
+            .unwrap_or(0);
+        self.result_lines = vec![match list.all {
+            Some(_) => format!("{} tagged commit(s) (t shows all)", list.commits.len()),
+            None => format!("{} commit(s)", list.commits.len()),
+        }];
+        self.result_scroll = 0;
+        self.show_selected_commit();
+    }
+
+    /// `c`, `r` and `p` in COMMITS: `git <verb> <selected commit>`, e.g. checking it out
+    /// detached, reverting it or cherry-picking it onto the current branch.
+    pub(super) fn run_on_selected_commit(&mut self, verb: &str) {
+        let Some(commit) = self.commit_list.commits.get(self.selected_commit) else {
+            return;
+        };
+        let cmd = format!("{} {}", verb, commit.hash);
+        self.run_command(cmd, LfsMode::None, false);
+    }
+
+    /// `y` in COMMITS copies the selected commit's full hash.
+    pub(super) fn copy_selected_commit_hash(&mut self) {
+        let Some(commit) = self.commit_list.commits.get(self.selected_commit) else {
+            return;
+        };
+        self.result_lines = vec![match clipboard::copy(&commit.hash) {
+            Ok(()) => format!("copied {} to the clipboard", commit.hash),
+            Err(e) => format!("ERROR: cannot copy to the clipboard: {}", e),
+        }];
+        self.result_scroll = 0;
+    }
+}
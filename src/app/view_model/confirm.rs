@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::audit::Audited;
+use super::{Focus, PendingCommand, ViewModel};
+use crate::git::step_label;
+
+/// Something held back until the question about it in R is answered.
+pub(super) struct Confirmation {
+    /// The key that asked, which confirms when pressed again: `x` in FILES, `D` in
+    /// the list panels.
+    again: Option<char>,
+    confirmed: Confirmed,
+}
+
+/// What a confirmed question goes on to do.
+pub(super) enum Confirmed {
+    Command(PendingCommand),
+    /// Discards changes to the FILES entries, then drops their marks.
+    Discard(PendingCommand),
+    /// A push held back while the CODEOWNERS review summary is shown.
+    Push(PendingCommand),
+    /// A stale lock file a failed command ran into.
+    RemoveLock(PathBuf),
+    /// `git lfs prune`, once its dry run is shown.
+    LfsPrune,
+    /// A mutation `--audit` shows the argv of first.
+    Audited(Audited),
+    /// Closing a review tab whose worktree has work in it.
+    CloseReview,
+}
+
+impl Confirmed {
+    fn command(&self) -> Option<&PendingCommand> {
+        match self {
+            Confirmed::Command(pending) | Confirmed::Discard(pending) => Some(pending),
+            _ => None,
+        }
+    }
+
+    /// Audit, lock removal and closing a review with work in it take only `y`; Enter
+    /// is too easy to press by habit.
+    fn enter_confirms(&self) -> bool {
+        !matches!(
+            self,
+            Confirmed::Audited(_) | Confirmed::RemoveLock(_) | Confirmed::CloseReview
+        )
+    }
+}
+
+impl ViewModel {
+    /// Asks `question` in R with the commands that would run below it; `y`, Enter or
+    /// `again` goes on, any other key cancels.
+    pub(super) fn ask_confirmation(
+        &mut self,
+        question: String,
+        again: Option<char>,
+        confirmed: Confirmed,
+    ) {
+        let keys = match again {
+            Some(c) => format!("y/Enter or {} again", c),
+            None => "y/Enter".to_string(),
+        };
+        let mut lines = vec![format!(
+            "{} ({} confirms, any other key cancels)",
+            question, keys
+        )];
+        if let Some(pending) = confirmed.command() {
+            lines.push(format!("  $ {}", step_label(&pending.args_str)));
+            if let Some(hook) = &pending.hooks.on_success {
+                lines.push(format!("  then $ {}", step_label(hook)));
+            }
+        }
+        self.result_lines = lines;
+        self.result_scroll = 0;
+        self.await_confirmation(again, confirmed);
+    }
+
+    /// Waits for the answer to a question the caller has already shown. A question
+    /// still open is dropped, as R no longer shows it.
+    pub(super) fn await_confirmation(&mut self, again: Option<char>, confirmed: Confirmed) {
+        if let Some(earlier) = self
+            .pending_confirmation
+            .replace(Confirmation { again, confirmed })
+        {
+            self.decline(earlier.confirmed);
+        }
+    }
+
+    pub(super) fn handle_confirmation_key(&mut self, key: KeyEvent) {
+        let Some(confirmation) = self.pending_confirmation.take() else {
+            return;
+        };
+        let again = confirmation
+            .again
+            .is_some_and(|c| self.keymap.means(&key, c, self.focus == Focus::Files));
+        let confirms = match key.code {
+            KeyCode::Char('y') => true,
+            KeyCode::Enter => confirmation.confirmed.enter_confirms(),
+            _ => again,
+        };
+        if !confirms {
+            self.result_lines = vec![self.decline(confirmation.confirmed)];
+            self.result_scroll = 0;
+            return;
+        }
+        match confirmation.confirmed {
+            Confirmed::Command(pending) => self.run_command_with_hooks(
+                pending.args_str,
+                pending.lfs_mode,
+                pending.interactive,
+                pending.hooks,
+            ),
+            Confirmed::Discard(pending) => {
+                self.run_command_with_hooks(
+                    pending.args_str,
+                    pending.lfs_mode,
+                    pending.interactive,
+                    pending.hooks,
+                );
+                self.clear_file_marks();
+            }
+            Confirmed::Push(pending) => self.execute_command(
+                pending.args_str,
+                pending.lfs_mode,
+                pending.interactive,
+                pending.hooks,
+            ),
+            Confirmed::RemoveLock(lock) => self.remove_lock(lock),
+            Confirmed::LfsPrune => self.run_lfs_prune(),
+            Confirmed::Audited(audited) => self.run_audited(audited),
+            Confirmed::CloseReview => self.close_review_anyway(),
+        }
+    }
+
+    /// Leaves `confirmed` undone and says so.
+    fn decline(&mut self, confirmed: Confirmed) -> String {
+        match confirmed {
+            Confirmed::Push(_) => "push canceled".into(),
+            Confirmed::RemoveLock(lock) => format!("kept {}", lock.display()),
+            Confirmed::LfsPrune => "lfs prune canceled".into(),
+            Confirmed::Audited(audited) => self.decline_audited(audited),
+            Confirmed::CloseReview => "review tab kept open".into(),
+            Confirmed::Command(_) | Confirmed::Discard(_) => "canceled".into(),
+        }
+    }
+}
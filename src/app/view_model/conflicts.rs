@@ -0,0 +1,61 @@
+use super::ViewModel;
+use crate::git::{
+    ConflictSide, LfsMode, RepoFile, mark_resolved_command, quote_arg, take_side_command,
+};
+
+impl ViewModel {
+    /// `o` / `t` in FILES: keeps our or their version of the selected conflicted file;
+    /// `r` then marks it resolved.
+    pub(super) fn take_conflict_side(&mut self, side: ConflictSide) {
+        if let Some(file) = self.selected_conflict() {
+            self.run_command(take_side_command(&file, side), LfsMode::None, false);
+        }
+    }
+
+    /// `m` in FILES: `git mergetool` on the selected conflicted file, in the terminal.
+    pub(super) fn open_mergetool(&mut self) {
+        if let Some(file) = self.selected_conflict() {
+            self.run_command(
+                format!("mergetool -- {}", quote_arg(&file.path)),
+                LfsMode::None,
+                true,
+            );
+        }
+    }
+
+    /// `r` (or `s`) in FILES: stages the selected conflicted file as it is in the
+    /// worktree.
+    pub(super) fn mark_conflict_resolved(&mut self) {
+        if let Some(file) = self.selected_conflict() {
+            let cmd = mark_resolved_command(&file, &self.repo_root);
+            self.run_command(cmd, LfsMode::None, false);
+        }
+    }
+
+    fn selected_conflict(&mut self) -> Option<RepoFile> {
+        let file = self.status.files.get(self.selected_file)?;
+        if !file.is_conflicted() {
+            self.result_lines = vec![format!("{} has no conflict", file.path)];
+            self.result_scroll = 0;
+            return None;
+        }
+        Some(file.clone())
+    }
+
+    pub fn conflict_count(&self) -> usize {
+        self.status
+            .files
+            .iter()
+            .filter(|f| f.is_conflicted())
+            .count()
+    }
+
+    pub fn selected_file_conflicted(&self) -> bool {
+        self.selected_dir.is_none()
+            && self
+                .status
+                .files
+                .get(self.selected_file)
+                .is_some_and(RepoFile::is_conflicted)
+    }
+}
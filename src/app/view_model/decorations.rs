@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use super::{UiMessage, ViewModel};
+use crate::decorations::run_decorator;
+use crate::jobs::JobKind;
+
+impl ViewModel {
+    /// Reruns the `[decorations] command` when the FILES list changed, or always with `force`.
+    pub(super) fn load_decorations(&mut self, force: bool) {
+        let Some(command) = self.config.decorations.command.clone() else {
+            if force {
+                self.result_lines = vec!["no [decorations] command configured".into()];
+                self.result_scroll = 0;
+            }
+            return;
+        };
+        let files: Vec<(String, String)> = self
+            .status
+            .files
+            .iter()
+            .map(|f| (f.path.clone(), f.status.clone()))
+            .collect();
+        if !force && files == self.decorated_files {
+            return;
+        }
+        self.decorated_files = files;
+        if self.status.files.is_empty() {
+            self.decorations.clear();
+            return;
+        }
+        let paths: Vec<String> = self.status.files.iter().map(|f| f.path.clone()).collect();
+        let repo = self.repo_root.clone();
+        self.jobs
+            .spawn(JobKind::Decorations, "decorations", move |_| {
+                UiMessage::DecorationsLoaded(run_decorator(&command, &repo, &paths))
+            });
+    }
+
+    pub(super) fn apply_decorations(&mut self, loaded: Result<HashMap<String, String>, String>) {
+        match loaded {
+            Ok(decorations) => self.decorations = decorations,
+            Err(e) => {
+                self.decorations.clear();
+                self.result_lines
+                    .push(format!("WARN: decorator failed: {}", e));
+            }
+        }
+    }
+
+    /// CODEOWNERS owners and the decorator's annotation for FILES entry `index`.
+    pub fn file_decoration(&self, index: usize) -> Option<String> {
+        let path = self.status.files.get(index)?.path.clone();
+        let parts: Vec<String> = self
+            .file_owners(index)
+            .into_iter()
+            .chain(self.decorations.get(&path).cloned())
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("  "))
+    }
+}
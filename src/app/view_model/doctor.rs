@@ -0,0 +1,159 @@
+use std::fs;
+use std::path::PathBuf;
+
+use super::audit::Audited;
+use super::{Confirmed, Focus, UiMessage, ViewModel};
+use crate::doctor::{
+    Check, Fix, Severity, apply_fix, git_process_running, lock_conflict, run_checks,
+};
+use crate::git::CommandResult;
+use crate::jobs::JobKind;
+
+impl ViewModel {
+    /// `:doctor` checks the repository and git setup in the background.
+    pub(super) fn run_doctor(&mut self) {
+        let git = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        self.jobs.spawn(JobKind::Doctor, "doctor", move |_| {
+            UiMessage::DoctorFinished(run_checks(&git, &repo), None)
+        });
+        self.result_lines = vec!["doctor: running checks...".into()];
+        self.result_scroll = 0;
+    }
+
+    /// Applies fix `index` of the report in LOG, then runs the checks again.
+    pub(super) fn apply_doctor_fix(&mut self, index: usize) {
+        let Some(fix) = self.doctor_fixes.get(index).cloned() else {
+            self.result_lines = vec![format!("doctor: no fix [{}]", index + 1)];
+            self.result_scroll = 0;
+            return;
+        };
+        if self.refuse_read_only("doctor fixes") {
+            return;
+        }
+        if self.dry_run {
+            self.result_lines = vec![format!("dry-run: would {}", fix.label())];
+            self.result_scroll = 0;
+            return;
+        }
+        if self.config.audit {
+            let action = match &fix {
+                Fix::Step(step) => format!("run: {}", self.argv(step)),
+                Fix::RemoveFiles(_) => fix.label(),
+            };
+            let lines = vec![format!("== audit: doctor fix [{}] ==", index + 1), action];
+            self.ask_audit(lines, Audited::DoctorFix(index, fix));
+            return;
+        }
+        self.spawn_doctor_fix(index, fix);
+    }
+
+    pub(super) fn spawn_doctor_fix(&mut self, index: usize, fix: Fix) {
+        self.record(&format!("doctor fix: {}", fix.label()));
+        let git = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        self.jobs.spawn(JobKind::Doctor, "doctor fix", move |ctx| {
+            let mut res = CommandResult {
+                log_lines: Vec::new(),
+                result_lines: Vec::new(),
+                success: false,
+            };
+            res.success = apply_fix(&git, &repo, &fix, ctx.cancel_token(), &mut res);
+            UiMessage::DoctorFinished(run_checks(&git, &repo), Some(res))
+        });
+        self.result_lines = vec![format!("doctor: applying fix [{}]...", index + 1)];
+        self.result_scroll = 0;
+    }
+
+    pub(super) fn show_doctor_report(&mut self, checks: Vec<Check>, fixed: Option<CommandResult>) {
+        if let Some(res) = &fixed {
+            self.record_outcome(res.success);
+        }
+        let mut lines = vec!["== doctor ==".to_string(), String::new()];
+        let mut fixes = Vec::new();
+        for check in &checks {
+            let tag = match check.severity {
+                Severity::Ok => " ok ",
+                Severity::Warning => "warn",
+                Severity::Problem => "FAIL",
+            };
+            lines.push(format!("[{}] {}: {}", tag, check.name, check.detail));
+            match (&check.fix, &check.hint) {
+                (Some(fix), _) if fixes.len() < 9 => {
+                    fixes.push(fix.clone());
+                    lines.push(format!("       [{}] {}", fixes.len(), fix.label()));
+                }
+                (_, Some(hint)) => lines.push(format!("       hint: {}", hint)),
+                _ => {}
+            }
+        }
+        if !fixes.is_empty() {
+            lines.push(String::new());
+            lines.push("press 1-9 in LOG to apply a fix".into());
+        }
+        let count = |severity| checks.iter().filter(|c| c.severity == severity).count();
+        self.set_log_lines(lines);
+        self.doctor_fixes = fixes;
+        if !self.doctor_fixes.is_empty() {
+            self.focus = Focus::Log;
+        }
+        let mut result = fixed.map(|res| res.result_lines).unwrap_or_default();
+        if !result.is_empty() {
+            self.refresh_repo_status();
+            result.push(String::new());
+        }
+        result.push(format!(
+            "doctor: {} problem(s), {} warning(s), {} ok",
+            count(Severity::Problem),
+            count(Severity::Warning),
+            count(Severity::Ok)
+        ));
+        self.result_lines = result;
+        self.result_scroll = 0;
+    }
+}
+
+impl ViewModel {
+    /// After a failed command, offers to remove the lock file it tripped over when no other
+    /// git process holds it, e.g. an `index.lock` left by a crashed IDE integration.
+    pub(super) fn offer_lock_removal(&mut self) {
+        if self.config.read_only {
+            return;
+        }
+        let Some(lock) = lock_conflict(&self.repo_root, &self.result_lines) else {
+            return;
+        };
+        self.result_lines.push(String::new());
+        if git_process_running() {
+            self.result_lines.push(format!(
+                "{} is held by another running git process; retry once it finishes",
+                lock.display()
+            ));
+            return;
+        }
+        self.result_lines.push(format!(
+            "{} looks stale: no git process is running",
+            lock.display()
+        ));
+        self.result_lines
+            .push("remove it? (y to remove, any other key keeps it)".into());
+        // Keep the question in view below the command's output.
+        self.result_scroll = self
+            .result_lines
+            .len()
+            .saturating_sub(self.result_view_height as usize)
+            .min(u16::MAX as usize) as u16;
+        self.await_confirmation(None, Confirmed::RemoveLock(lock));
+    }
+
+    pub(super) fn remove_lock(&mut self, lock: PathBuf) {
+        self.result_lines = match fs::remove_file(&lock) {
+            Ok(()) => {
+                self.record(&format!("removed {}", lock.display()));
+                vec![format!("removed {}; run the command again", lock.display())]
+            }
+            Err(e) => vec![format!("ERROR: cannot remove {}: {}", lock.display(), e)],
+        };
+        self.result_scroll = 0;
+    }
+}
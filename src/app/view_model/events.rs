@@ -0,0 +1,106 @@
+use super::read_only::is_read_only_step;
+use super::{UiMessage, ViewModel};
+use crate::git::{CommandResult, quote_arg, run_step, step_label};
+use crate::jobs::JobKind;
+use crate::shell::shell_quote;
+
+/// Points in the UI that can trigger an `[events]` hook.
+#[derive(Clone, Copy)]
+pub(super) enum Event {
+    RepoOpen,
+    BranchChange,
+    Commit,
+}
+
+impl Event {
+    fn name(self) -> &'static str {
+        match self {
+            Event::RepoOpen => "on_repo_open",
+            Event::BranchChange => "on_branch_change",
+            Event::Commit => "on_commit",
+        }
+    }
+}
+
+impl ViewModel {
+    /// Runs the hook configured for `event` in the background; only failures are reported.
+    pub(super) fn fire_event(&mut self, event: Event) {
+        let events = &self.config.events;
+        let hook = match event {
+            Event::RepoOpen => &events.on_repo_open,
+            Event::BranchChange => &events.on_branch_change,
+            Event::Commit => &events.on_commit,
+        };
+        let Some(hook) = hook else {
+            return;
+        };
+        // Branch names come from remotes too, so they go in as one quoted word.
+        let quote: fn(&str) -> String = if hook.starts_with('!') {
+            shell_quote
+        } else {
+            quote_arg
+        };
+        let mut hook = hook
+            .replace("{branch}", &quote(&self.status.branch))
+            .replace("{repo}", &quote(&self.repo_root.to_string_lossy()));
+        if let Some(branch) = &self.default_branch {
+            hook = hook.replace("{default_branch}", &quote(branch));
+        }
+        let hook = match self.fill_default_branch(&hook) {
+            Ok(hook) => hook,
+            Err(e) => {
+                self.result_lines
+                    .push(format!("{} hook skipped: {}", event.name(), e));
+                return;
+            }
+        };
+        if !self.allows_step(&hook) {
+            self.result_lines
+                .push(format!("{} hook skipped: read-only", event.name()));
+            return;
+        }
+        if self.dry_run {
+            self.result_lines.push(format!(
+                "dry-run: {} would run: $ {}",
+                event.name(),
+                step_label(&hook)
+            ));
+            return;
+        }
+        // Nobody is asked to confirm a hook, so audit mode only runs those that read.
+        if self.config.audit && !is_read_only_step(&hook) {
+            self.result_lines
+                .push(format!("{} hook skipped: audit mode", event.name()));
+            self.record(&format!(
+                "skipped {} ({} hook)",
+                self.argv(&hook),
+                event.name()
+            ));
+            return;
+        }
+
+        self.record(&format!("$ {} ({} hook)", self.argv(&hook), event.name()));
+        let git_path = self.config.git_path.clone();
+        let repo_path = self.repo_root.clone();
+        self.jobs.spawn(JobKind::Event, event.name(), move |ctx| {
+            let mut res = CommandResult {
+                log_lines: Vec::new(),
+                result_lines: Vec::new(),
+                success: false,
+            };
+            res.success = run_step(&git_path, &hook, &repo_path, ctx.cancel_token(), &mut res);
+            UiMessage::EventHookFinished(event.name(), res)
+        });
+    }
+
+    pub(super) fn finish_event_hook(&mut self, name: &str, res: CommandResult) {
+        self.record_outcome(res.success);
+        if res.success {
+            return;
+        }
+        self.result_lines
+            .push(format!("WARN: {} hook failed", name));
+        self.result_lines
+            .extend(res.result_lines.into_iter().skip(2));
+    }
+}
@@ -0,0 +1,234 @@
+use super::{Hooks, PendingCommand, ViewModel};
+use crate::git::{LfsMode, RepoFile};
+
+impl ViewModel {
+    /// Space in FILES marks the selected file, or unmarks it; on a directory of the
+    /// tree, all its files.
+    pub(super) fn toggle_file_mark(&mut self) {
+        if let Some(dir_files) = self.files_in_selected_dir() {
+            let paths: Vec<String> = dir_files
+                .into_iter()
+                .map(|i| self.status.files[i].path.clone())
+                .collect();
+            if paths.iter().all(|p| self.marked_files.contains(p)) {
+                for path in &paths {
+                    self.marked_files.remove(path);
+                }
+            } else {
+                self.marked_files.extend(paths);
+            }
+            return;
+        }
+        let Some(entry) = self.status.files.get(self.selected_file) else {
+            return;
+        };
+        if !self.marked_files.remove(&entry.path) {
+            self.marked_files.insert(entry.path.clone());
+        }
+    }
+
+    /// `v` in FILES starts a range at the selected file that j/k stretch; `v` again
+    /// marks the files in it.
+    pub(super) fn toggle_visual_range(&mut self) {
+        if self.selected_dir.is_some() {
+            return;
+        }
+        match self.visual_range() {
+            Some(range) => {
+                let paths: Vec<String> = range
+                    .into_iter()
+                    .map(|i| self.status.files[i].path.clone())
+                    .collect();
+                self.marked_files.extend(paths);
+                self.visual_anchor = None;
+            }
+            None => {
+                self.visual_anchor = self
+                    .status
+                    .files
+                    .get(self.selected_file)
+                    .map(|f| f.path.clone());
+            }
+        }
+    }
+
+    /// Esc in FILES drops the marks and the range; false when there were none.
+    pub(super) fn clear_file_marks(&mut self) -> bool {
+        let had_marks = !self.marked_files.is_empty() || self.visual_anchor.is_some();
+        self.marked_files.clear();
+        self.visual_anchor = None;
+        had_marks
+    }
+
+    /// Files from the start of the `v` range to the selected file, in the order FILES
+    /// shows them.
+    fn visual_range(&self) -> Option<Vec<usize>> {
+        let anchor = self.visual_anchor.as_ref()?;
+        let order: Vec<usize> = if self.files_tree {
+            self.tree_file_order()
+        } else {
+            (0..self.status.files.len()).collect()
+        };
+        let start = order
+            .iter()
+            .position(|&i| &self.status.files[i].path == anchor)?;
+        let end = order.iter().position(|&i| i == self.selected_file)?;
+        Some(order[start.min(end)..=start.max(end)].to_vec())
+    }
+
+    pub fn visual_range_active(&self) -> bool {
+        self.visual_range().is_some()
+    }
+
+    /// For each status entry, whether it is marked or inside the `v` range.
+    pub fn file_marks(&self) -> Vec<bool> {
+        let mut marks: Vec<bool> = self
+            .status
+            .files
+            .iter()
+            .map(|f| self.marked_files.contains(&f.path))
+            .collect();
+        for i in self.visual_range().unwrap_or_default() {
+            marks[i] = true;
+        }
+        marks
+    }
+
+    /// Files that stage, discard and `d` act on: the marked ones and the `v` range,
+    /// or the selected file or tree directory when nothing is marked.
+    pub(super) fn target_files(&self) -> Vec<&RepoFile> {
+        let marked: Vec<&RepoFile> = self
+            .status
+            .files
+            .iter()
+            .zip(self.file_marks())
+            .filter_map(|(f, marked)| marked.then_some(f))
+            .collect();
+        if !marked.is_empty() {
+            return marked;
+        }
+        if let Some(dir_files) = self.files_in_selected_dir() {
+            return dir_files
+                .into_iter()
+                .map(|i| &self.status.files[i])
+                .collect();
+        }
+        self.status
+            .files
+            .get(self.selected_file)
+            .into_iter()
+            .collect()
+    }
+
+    /// Drops the marks of files the refreshed status no longer lists.
+    pub(super) fn retain_file_marks(&mut self) {
+        let files = &self.status.files;
+        self.marked_files
+            .retain(|path| files.iter().any(|f| &f.path == path));
+        if let Some(anchor) = &self.visual_anchor
+            && !files.iter().any(|f| &f.path == anchor)
+        {
+            self.visual_anchor = None;
+        }
+    }
+
+    /// `s` in FILES: unstages the target files when all of them are staged, and stages
+    /// them otherwise, in one command.
+    pub(super) fn toggle_stage_files(&mut self) {
+        let targets = self.target_files();
+        let operands = Self::quoted_file_operands(&targets);
+        if operands.is_empty() {
+            return;
+        }
+        let all_staged = targets.iter().all(|f| {
+            let x = f.status.chars().next().unwrap_or(' ');
+            x != ' ' && f.status != "??"
+        });
+        let deletes = targets
+            .iter()
+            .filter(|f| f.status.chars().nth(1) == Some('D'))
+            .count();
+
+        let cmd = if all_staged {
+            format!("restore --staged -- {}", operands)
+        } else if deletes == targets.len() {
+            // Deleted in working tree: use -u so git stages the removal even when file is gone.
+            format!("add -u -- {}", operands)
+        } else if deletes > 0 {
+            format!("add -A -- {}", operands)
+        } else {
+            format!("add -- {}", operands)
+        };
+        self.run_command(cmd, LfsMode::None, false);
+    }
+
+    /// What `x` runs once confirmed: restores the tracked target files and removes the
+    /// untracked ones, the latter as a follow-up when there are both.
+    pub(super) fn discard_command(&self) -> Option<PendingCommand> {
+        let (untracked, tracked): (Vec<&RepoFile>, Vec<&RepoFile>) = self
+            .target_files()
+            .into_iter()
+            .partition(|f| f.status == "??");
+        let restore = Self::quoted_file_operands(&tracked);
+        let clean = Self::quoted_file_operands(&untracked);
+        let restore =
+            (!restore.is_empty()).then(|| format!("restore --staged --worktree -- {}", restore));
+        let clean = (!clean.is_empty()).then(|| format!("clean -fd -- {}", clean));
+        match (restore, clean) {
+            (Some(restore), Some(clean)) => Some(PendingCommand {
+                args_str: restore,
+                lfs_mode: LfsMode::None,
+                interactive: false,
+                hooks: Hooks {
+                    on_success: Some(clean),
+                    ..Hooks::default()
+                },
+            }),
+            (Some(cmd), None) | (None, Some(cmd)) => Some(PendingCommand::plain(cmd)),
+            (None, None) => None,
+        }
+    }
+
+    /// `d` in FILES: the diff of the selected file, or of every marked tracked file.
+    pub(super) fn show_diff_for_target_files(&mut self) {
+        let targets = self.target_files();
+        if targets.len() < 2 && self.selected_dir.is_none() {
+            self.show_diff_for_selected_file(false);
+            return;
+        }
+        if self.diff_blocked() {
+            self.result_lines
+                .push("WARN: cannot show diff while git is running".into());
+            return;
+        }
+        let untracked = targets.iter().filter(|f| f.status == "??").count();
+        let operands: Vec<String> = targets
+            .iter()
+            .filter(|f| f.status != "??")
+            .flat_map(|f| f.operands())
+            .collect();
+        if operands.is_empty() {
+            self.result_lines
+                .push("WARN: untracked files have no diff to show together".into());
+            return;
+        }
+        let (args, label) = self.build_diff_command(&operands);
+        self.log_detached = self.is_running();
+        self.show_diff_output(args, label, false);
+        if untracked > 0 {
+            self.result_lines.push(format!(
+                "{} untracked file(s) left out; d on one shows it",
+                untracked
+            ));
+        }
+    }
+
+    fn quoted_file_operands(files: &[&RepoFile]) -> String {
+        files
+            .iter()
+            .map(|f| Self::quoted_operands(f))
+            .filter(|ops| !ops.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
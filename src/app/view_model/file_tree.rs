@@ -0,0 +1,186 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::{ViewModel, list_move};
+use crate::keymap::KeyAction;
+
+/// A row of FILES drawn as a directory tree.
+pub enum FileRow {
+    Dir {
+        /// Path from the repository root, without a trailing `/`.
+        path: String,
+        depth: usize,
+        collapsed: bool,
+    },
+    File {
+        /// Index into the status entries.
+        index: usize,
+        depth: usize,
+    },
+}
+
+impl FileRow {
+    fn dir_path(&self) -> Option<&str> {
+        match self {
+            FileRow::Dir { path, .. } => Some(path),
+            FileRow::File { .. } => None,
+        }
+    }
+}
+
+impl ViewModel {
+    pub fn files_tree(&self) -> bool {
+        self.files_tree
+    }
+
+    pub fn selected_dir(&self) -> Option<&str> {
+        self.selected_dir.as_deref()
+    }
+
+    /// `T` in FILES switches between the flat list and the directory tree.
+    pub(super) fn toggle_files_tree(&mut self) {
+        self.files_tree = !self.files_tree;
+        self.selected_dir = None;
+        self.visual_anchor = None;
+        self.result_lines.push(format!(
+            "FILES as a tree: {}",
+            if self.files_tree { "on" } else { "off" }
+        ));
+    }
+
+    /// The rows of the tree: directories in path order, each followed by what it holds
+    /// unless collapsed.
+    pub fn file_rows(&self) -> Vec<FileRow> {
+        let files = &self.status.files;
+        let mut order: Vec<usize> = (0..files.len()).collect();
+        order.sort_by(|&a, &b| files[a].path.split('/').cmp(files[b].path.split('/')));
+
+        let hidden = |dirs: &[&str]| {
+            (1..=dirs.len()).any(|n| self.collapsed_dirs.contains(&dirs[..n].join("/")))
+        };
+        let mut rows = Vec::new();
+        let mut open: Vec<&str> = Vec::new();
+        for index in order {
+            let parts: Vec<&str> = files[index].path.split('/').collect();
+            let dirs = &parts[..parts.len() - 1];
+            let common = open.iter().zip(dirs).take_while(|(a, b)| a == b).count();
+            open.truncate(common);
+            for depth in common..dirs.len() {
+                open.push(dirs[depth]);
+                if !hidden(&dirs[..depth]) {
+                    let path = dirs[..=depth].join("/");
+                    rows.push(FileRow::Dir {
+                        collapsed: self.collapsed_dirs.contains(&path),
+                        path,
+                        depth,
+                    });
+                }
+            }
+            if !hidden(dirs) {
+                rows.push(FileRow::File {
+                    index,
+                    depth: dirs.len(),
+                });
+            }
+        }
+        rows
+    }
+
+    /// Index into the rows of the tree under the cursor.
+    pub fn file_tree_cursor(&self, rows: &[FileRow]) -> usize {
+        rows.iter()
+            .position(|row| match (row, &self.selected_dir) {
+                (FileRow::Dir { path, .. }, Some(dir)) => path == dir,
+                (FileRow::File { index, .. }, None) => *index == self.selected_file,
+                _ => false,
+            })
+            .unwrap_or(0)
+    }
+
+    /// Files of the tree in the order it shows them, collapsed ones left out.
+    pub(super) fn tree_file_order(&self) -> Vec<usize> {
+        self.file_rows()
+            .into_iter()
+            .filter_map(|row| match row {
+                FileRow::File { index, .. } => Some(index),
+                FileRow::Dir { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Status entries under the directory the tree cursor is on.
+    pub(super) fn files_in_selected_dir(&self) -> Option<Vec<usize>> {
+        let prefix = format!("{}/", self.selected_dir.as_ref()?);
+        Some(
+            (0..self.status.files.len())
+                .filter(|&i| self.status.files[i].path.starts_with(&prefix))
+                .collect(),
+        )
+    }
+
+    /// Moves the tree cursor to `row`, showing the diff when it lands on a file.
+    pub(super) fn select_file_tree_row(&mut self, row: usize) {
+        let rows = self.file_rows();
+        match rows.get(row) {
+            Some(FileRow::Dir { path, .. }) => self.selected_dir = Some(path.clone()),
+            Some(FileRow::File { index, .. }) => {
+                let changed = self.selected_dir.is_some() || *index != self.selected_file;
+                self.selected_dir = None;
+                self.selected_file = *index;
+                if changed {
+                    self.show_diff_for_selected_file(true);
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Keys FILES takes differently as a tree: j/k walk its rows, and on a directory
+    /// l expands it, h collapses it and Enter does either. False when `key` is left to
+    /// the usual FILES and global handling, so h/l still move the focus from files and
+    /// from directories already collapsed or expanded.
+    pub(super) fn handle_file_tree_key(&mut self, key: KeyEvent) -> bool {
+        let rows = self.file_rows();
+        let cursor = self.file_tree_cursor(&rows);
+        let dir = rows
+            .get(cursor)
+            .and_then(FileRow::dir_path)
+            .map(String::from);
+        let collapsed = dir
+            .as_ref()
+            .is_some_and(|d| self.collapsed_dirs.contains(d));
+        let action = self.key_action(&key);
+        let expand = key.code == KeyCode::Enter || action == Some(KeyAction::FocusRight);
+        let collapse = key.code == KeyCode::Enter || action == Some(KeyAction::FocusLeft);
+        match key.code {
+            _ if expand && collapsed => {
+                self.collapsed_dirs
+                    .remove(dir.as_deref().unwrap_or_default());
+            }
+            _ if collapse && dir.is_some() => {
+                self.collapsed_dirs.extend(dir);
+            }
+            // Keys for a single file do nothing on a directory; stage, discard, diff and
+            // mark take all its files.
+            KeyCode::Char('o' | 't' | 'm' | 'r' | 'W' | 'H' | 'v') if dir.is_some() => {}
+            _ => match list_move(action, cursor, rows.len()) {
+                Some(row) => self.select_file_tree_row(row),
+                None => return false,
+            },
+        }
+        true
+    }
+
+    /// Keeps the tree cursor and collapsed directories to those the refreshed status
+    /// still has.
+    pub(super) fn retain_file_tree(&mut self) {
+        let files = &self.status.files;
+        let exists = |dir: &String| {
+            let prefix = format!("{}/", dir);
+            files.iter().any(|f| f.path.starts_with(&prefix))
+        };
+        self.collapsed_dirs.retain(exists);
+        if self.selected_dir.as_ref().is_some_and(|d| !exists(d)) {
+            self.selected_dir = None;
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use super::ViewModel;
+use crate::git::{LfsMode, head_commit, quote_arg, tracked_files};
+
+/// Most matches listed at once; typing narrows the rest down.
+const SHOWN: usize = 200;
+
+/// The Ctrl-T finder over every tracked file.
+pub struct FileFinder {
+    query: String,
+    /// Ref a selected file is diffed against.
+    against: String,
+    editing_ref: bool,
+    /// Indices into the tracked files, best match first.
+    matches: Vec<usize>,
+    selected: usize,
+}
+
+impl FileFinder {
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn against(&self) -> &str {
+        &self.against
+    }
+
+    pub fn editing_ref(&self) -> bool {
+        self.editing_ref
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+}
+
+impl ViewModel {
+    /// Ctrl-T (or `:find [text]`) opens the finder; the file list is read again only
+    /// when `HEAD` moved since the last time.
+    pub(super) fn open_finder(&mut self, query: &str) {
+        let head = head_commit(&self.config.git_path, &self.repo_root);
+        if self.tracked.as_ref().is_none_or(|(h, _)| *h != head) {
+            let files = tracked_files(&self.config.git_path, &self.repo_root);
+            self.tracked = Some((head, files));
+        }
+        self.teach("git ls-files", false);
+        self.finder = Some(FileFinder {
+            query: query.to_string(),
+            against: "HEAD".into(),
+            editing_ref: false,
+            matches: Vec::new(),
+            selected: 0,
+        });
+        self.refilter();
+    }
+
+    pub(super) fn handle_finder_key(&mut self, key: KeyEvent) {
+        let Some(finder) = self.finder.as_mut() else {
+            return;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.finder = None,
+            KeyCode::Down => finder.selected += 1,
+            KeyCode::Char('n') if ctrl => finder.selected += 1,
+            KeyCode::Up => finder.selected = finder.selected.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => finder.selected = finder.selected.saturating_sub(1),
+            KeyCode::Tab => finder.editing_ref = !finder.editing_ref,
+            KeyCode::Enter => self.finder_action(FinderAction::Diff),
+            KeyCode::Char('e') if ctrl => self.finder_action(FinderAction::Edit),
+            KeyCode::Char('b') if ctrl => self.finder_action(FinderAction::Blame),
+            KeyCode::Char('l') if ctrl => self.finder_action(FinderAction::History),
+            KeyCode::Backspace if finder.editing_ref => {
+                finder.against.pop();
+            }
+            KeyCode::Backspace => {
+                finder.query.pop();
+                self.refilter();
+            }
+            KeyCode::Char(c) if !ctrl && finder.editing_ref => finder.against.push(c),
+            KeyCode::Char(c) if !ctrl => {
+                finder.query.push(c);
+                self.refilter();
+            }
+            _ => {}
+        }
+        if let Some(finder) = self.finder.as_mut() {
+            finder.selected = finder.selected.min(finder.matches.len().saturating_sub(1));
+        }
+    }
+
+    fn refilter(&mut self) {
+        let (Some(finder), Some((_, files))) = (self.finder.as_mut(), &self.tracked) else {
+            return;
+        };
+        let query = finder.query.to_lowercase();
+        let mut scored: Vec<(i64, usize)> = files
+            .iter()
+            .enumerate()
+            .filter_map(|(i, path)| fuzzy_score(&query, path).map(|s| (s, i)))
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        finder.matches = scored.into_iter().take(SHOWN).map(|(_, i)| i).collect();
+        finder.selected = 0;
+    }
+
+    fn finder_action(&mut self, action: FinderAction) {
+        let Some(finder) = self.finder.take() else {
+            return;
+        };
+        let Some(path) = self.finder_path(&finder) else {
+            self.result_lines = vec!["no file matches".into()];
+            self.result_scroll = 0;
+            return;
+        };
+        let against = match finder.against.trim() {
+            "" => "HEAD".to_string(),
+            r => r.to_string(),
+        };
+        match action {
+            FinderAction::Diff => self.run_command(
+                format!("diff {} -- {}", quote_arg(&against), quote_arg(&path)),
+                LfsMode::None,
+                false,
+            ),
+            FinderAction::Blame => self.run_command(
+                format!("blame -- {}", quote_arg(&path)),
+                LfsMode::None,
+                false,
+            ),
+            FinderAction::Edit => {
+                let full = self.repo_root.join(&path);
+                self.open_editor(&full, 1);
+            }
+            FinderAction::History => self.load_file_history(path),
+        }
+    }
+
+    fn finder_path(&self, finder: &FileFinder) -> Option<String> {
+        let (_, files) = self.tracked.as_ref()?;
+        let index = *finder.matches.get(finder.selected)?;
+        files.get(index).cloned()
+    }
+
+    pub fn finder(&self) -> Option<&FileFinder> {
+        self.finder.as_ref()
+    }
+
+    /// Paths the finder lists, best match first.
+    pub fn finder_matches(&self) -> Vec<&str> {
+        match (&self.finder, &self.tracked) {
+            (Some(finder), Some((_, files))) => finder
+                .matches
+                .iter()
+                .filter_map(|i| files.get(*i).map(String::as_str))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn tracked_count(&self) -> usize {
+        self.tracked.as_ref().map_or(0, |(_, files)| files.len())
+    }
+}
+
+enum FinderAction {
+    Diff,
+    Edit,
+    Blame,
+    History,
+}
+
+/// Scores `path` against the lowercase `query` when its characters appear in order:
+/// runs of adjacent characters, word starts and hits in the file name score higher,
+/// and shorter paths win ties.
+fn fuzzy_score(query: &str, path: &str) -> Option<i64> {
+    let name_start = path.rfind('/').map_or(0, |i| path[..=i].chars().count());
+    let mut score = -(path.len() as i64) / 8;
+    let mut chars = path.chars().enumerate();
+    let mut previous: Option<(usize, char)> = None;
+    let mut last_match: Option<usize> = None;
+    for q in query.chars() {
+        loop {
+            let (i, c) = chars.next()?;
+            let before = previous.map(|(_, p)| p);
+            previous = Some((i, c));
+            if c.to_lowercase().next() != Some(q) {
+                continue;
+            }
+            score += 1;
+            if last_match.is_some_and(|m| m + 1 == i) {
+                score += 5;
+            }
+            if before.is_none_or(|p| matches!(p, '/' | '_' | '-' | '.' | ' ')) {
+                score += 8;
+            }
+            if i >= name_start {
+                score += 2;
+            }
+            last_match = Some(i);
+            break;
+        }
+    }
+    Some(score)
+}
@@ -0,0 +1,307 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::audit::Audited;
+use super::read_only::is_read_only_step;
+use super::{UiMessage, ViewModel};
+use crate::config::StepCondition;
+use crate::git::{CommandResult, quote_arg, run_step, step_label};
+use crate::jobs::JobKind;
+use crate::shell::shell_quote;
+
+/// Progress through a `[[flows]]` entry.
+pub(super) struct FlowRun {
+    flow: usize,
+    step: usize,
+    vars: Vec<(String, String)>,
+    last_success: bool,
+    /// Answer being typed while the current step is a prompt.
+    input: Option<String>,
+    /// The current step was confirmed under `--audit`.
+    confirmed: bool,
+    transcript: Vec<String>,
+}
+
+impl ViewModel {
+    /// `:flow <name>` starts a configured flow; `:flow` lists them.
+    pub(super) fn start_flow(&mut self, name: &str) {
+        if name.is_empty() {
+            let mut lines = vec!["flows:".to_string()];
+            lines.extend(self.config.flows.iter().map(|f| format!("  {}", f.name)));
+            self.result_lines = lines;
+            self.result_scroll = 0;
+            return;
+        }
+        let Some(flow) = self.config.flows.iter().position(|f| f.name == name) else {
+            self.result_lines = vec![format!("no flow named \"{}\"", name)];
+            self.result_scroll = 0;
+            return;
+        };
+        if self.is_running() || self.flow.is_some() {
+            self.result_lines
+                .push("WARN: already running command".into());
+            return;
+        }
+        self.flow = Some(FlowRun {
+            flow,
+            step: 0,
+            vars: self
+                .default_branch
+                .iter()
+                .map(|b| ("default_branch".to_string(), b.clone()))
+                .collect(),
+            last_success: true,
+            input: None,
+            confirmed: false,
+            transcript: vec![format!("== flow: {} ==", name)],
+        });
+        self.advance_flow();
+    }
+
+    /// Runs steps until one needs input or a background command, or the flow ends.
+    fn advance_flow(&mut self) {
+        let Some(run) = self.flow.as_mut() else {
+            return;
+        };
+        let flow = &self.config.flows[run.flow];
+        let total = flow.steps.len();
+
+        while let Some(step) = flow.steps.get(run.step) {
+            let number = run.step + 1;
+            let applies = match step.when {
+                StepCondition::Success => run.last_success,
+                StepCondition::Failure => !run.last_success,
+                StepCondition::Always => true,
+            };
+            if !applies {
+                run.transcript
+                    .push(format!("[{}/{}] skipped", number, total));
+                run.step += 1;
+                continue;
+            }
+
+            if let Some(prompt) = &step.prompt {
+                run.input = Some(step.default.clone().unwrap_or_default());
+                self.result_lines = vec![format!(
+                    "[{}/{}] {} (Enter to continue, Esc aborts the flow)",
+                    number, total, prompt
+                )];
+                self.result_scroll = 0;
+                let transcript = run.transcript.clone();
+                self.set_log_lines(transcript);
+                return;
+            }
+
+            let Some(cmd) = &step.run else {
+                run.step += 1;
+                continue;
+            };
+            let cmd = fill_vars(cmd, &run.vars);
+            if self.config.read_only && !is_read_only_step(&cmd) {
+                run.transcript.push(format!(
+                    "[{}/{}] refused (read-only): $ {}",
+                    number,
+                    total,
+                    step_label(&cmd)
+                ));
+                run.last_success = false;
+                run.step += 1;
+                continue;
+            }
+            if self.dry_run {
+                run.transcript.push(format!(
+                    "[{}/{}] would run: $ {}",
+                    number,
+                    total,
+                    step_label(&cmd)
+                ));
+                run.last_success = true;
+                run.step += 1;
+                continue;
+            }
+            if self.config.audit && !run.confirmed && !is_read_only_step(&cmd) {
+                let lines = vec![
+                    format!("== audit: flow step {}/{} ==", number, total),
+                    format!("run: {}", self.argv(&cmd)),
+                ];
+                self.ask_audit(lines, Audited::FlowStep);
+                return;
+            }
+            run.confirmed = false;
+
+            run.transcript
+                .push(format!("[{}/{}] $ {}", number, total, step_label(&cmd)));
+            let transcript = run.transcript.clone();
+            self.set_log_lines(transcript);
+            self.result_lines = vec![format!("[{}/{}] running...", number, total)];
+            self.result_scroll = 0;
+            self.start_running(&cmd);
+
+            let git_path = self.config.git_path.clone();
+            let repo_path = self.repo_root.clone();
+            self.jobs
+                .spawn(JobKind::Flow, step_label(&cmd), move |ctx| {
+                    let mut res = CommandResult {
+                        log_lines: Vec::new(),
+                        result_lines: Vec::new(),
+                        success: false,
+                    };
+                    res.success =
+                        run_step(&git_path, &cmd, &repo_path, ctx.cancel_token(), &mut res);
+                    UiMessage::FlowStepFinished(res)
+                });
+            return;
+        }
+
+        let outcome = if run.last_success {
+            "flow finished"
+        } else {
+            "flow finished with a failed step"
+        };
+        run.transcript.push(outcome.to_string());
+        let transcript = std::mem::take(&mut run.transcript);
+        self.flow = None;
+        self.set_log_lines(transcript);
+        self.result_lines = vec![outcome.to_string()];
+        self.result_scroll = 0;
+        self.refresh_repo_status();
+    }
+
+    pub(super) fn finish_flow_step(&mut self, res: CommandResult) {
+        self.finish_running();
+        self.record_outcome(res.success);
+        let Some(run) = self.flow.as_mut() else {
+            return;
+        };
+        run.transcript.extend(res.log_lines.into_iter().skip(2));
+        run.transcript.extend(
+            res.result_lines
+                .into_iter()
+                .skip(2)
+                .map(|l| format!("  {}", l)),
+        );
+        run.last_success = res.success;
+        run.step += 1;
+        self.refresh_repo_status();
+        self.advance_flow();
+    }
+
+    pub(super) fn run_confirmed_flow_step(&mut self) {
+        if let Some(run) = self.flow.as_mut() {
+            run.confirmed = true;
+        }
+        self.advance_flow();
+    }
+
+    pub(super) fn flow_waiting_for_input(&self) -> bool {
+        self.flow.as_ref().is_some_and(|run| run.input.is_some())
+    }
+
+    pub(super) fn handle_flow_prompt_key(&mut self, key: KeyEvent) {
+        let Some(run) = self.flow.as_mut() else {
+            return;
+        };
+        let Some(input) = run.input.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.flow = None;
+                self.result_lines = vec!["flow aborted".into()];
+                self.result_scroll = 0;
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Enter => {
+                let answer = run.input.take().unwrap_or_default();
+                let step = &self.config.flows[run.flow].steps[run.step];
+                let var = step
+                    .var
+                    .clone()
+                    .or_else(|| step.prompt.clone())
+                    .unwrap_or_default();
+                run.transcript.push(format!(
+                    "[{}/{}] {} = {}",
+                    run.step + 1,
+                    self.config.flows[run.flow].steps.len(),
+                    var,
+                    answer
+                ));
+                run.vars.push((var, answer));
+                run.step += 1;
+                self.advance_flow();
+            }
+            _ => {}
+        }
+    }
+
+    /// Question and typed answer of the flow step (or `:new-branch` field, `:orphan`
+    /// question or `{input:prompt}` of a command) waiting for input.
+    pub fn flow_prompt(&self) -> Option<(&str, &str)> {
+        if let Some(prompt) = self
+            .new_branch_prompt()
+            .or_else(|| self.orphan_prompt())
+            .or_else(|| self.command_inputs_prompt())
+        {
+            return Some(prompt);
+        }
+        let run = self.flow.as_ref()?;
+        let input = run.input.as_deref()?;
+        let prompt = self.config.flows[run.flow].steps[run.step]
+            .prompt
+            .as_deref()?;
+        Some((prompt, input))
+    }
+}
+
+/// Replaces `{name}` placeholders with collected answers, each quoted as one word
+/// for the shell in a `!` step and for git otherwise.
+fn fill_vars(cmd: &str, vars: &[(String, String)]) -> String {
+    let quote: fn(&str) -> String = if cmd.starts_with('!') {
+        shell_quote
+    } else {
+        quote_arg
+    };
+    vars.iter().fold(cmd.to_string(), |cmd, (name, value)| {
+        cmd.replace(&format!("{{{}}}", name), &quote(value))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::parse_args_line;
+
+    #[test]
+    fn fills_answers_as_single_words() {
+        let vars = [
+            ("message".to_string(), "fix the build".to_string()),
+            ("version".to_string(), "1.2$(touch pwned)".to_string()),
+        ];
+        let git = fill_vars("commit -m {message} --author {version}", &vars);
+        assert_eq!(
+            parse_args_line(&git),
+            [
+                "commit",
+                "-m",
+                "fix the build",
+                "--author",
+                "1.2$(touch pwned)"
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fills_shell_steps_without_expansion() {
+        let vars = [
+            ("message".to_string(), "fix the build".to_string()),
+            ("version".to_string(), "1.2; $(touch pwned)".to_string()),
+        ];
+        assert_eq!(
+            fill_vars("!echo {version} {message}", &vars),
+            "!echo '1.2; $(touch pwned)' 'fix the build'"
+        );
+    }
+}
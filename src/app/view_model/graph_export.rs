@@ -0,0 +1,77 @@
+use std::fs;
+use std::time::SystemTime;
+
+use super::ViewModel;
+use super::summary::unix_secs;
+use crate::clipboard;
+use crate::diff::strip_ansi;
+use crate::graph_export::{dot, export_path};
+
+impl ViewModel {
+    /// `:export-graph [text|dot] [clip|<path>]` saves what is being looked at: `text` the
+    /// LOG pane (the Graph command's output, say) and `dot` a graphviz file of the commits
+    /// COMMITS lists, which `:commits <range>` picks. Without a path it goes to the config
+    /// directory; `clip` copies it instead.
+    pub(super) fn export_graph(&mut self, arg: &str) {
+        let (format, target) = match arg.split_once(char::is_whitespace) {
+            Some((f @ ("text" | "dot"), rest)) => (f, rest.trim()),
+            None if matches!(arg, "text" | "dot") => (arg, ""),
+            _ => ("text", arg),
+        };
+        let text = if format == "dot" {
+            if self.commit_list.commits.is_empty() {
+                self.result_lines =
+                    vec!["no commits listed; :commits <range> lists some to export".into()];
+                self.result_scroll = 0;
+                return;
+            }
+            dot(&self.commit_list.commits)
+        } else {
+            let mut lines: Vec<String> = self.log_lines.iter().map(|l| strip_ansi(l)).collect();
+            lines.push(String::new());
+            lines.join("\n")
+        };
+        let what = if format == "dot" {
+            format!("{} commit(s) as DOT", self.commit_list.commits.len())
+        } else {
+            format!("{} line(s) of LOG", self.log_lines.len())
+        };
+        self.result_scroll = 0;
+
+        if target == "clip" {
+            self.result_lines = vec![match clipboard::copy(&text) {
+                Ok(()) => format!("copied {} to the clipboard", what),
+                Err(e) => format!("ERROR: cannot copy to the clipboard: {}", e),
+            }];
+            return;
+        }
+        let extension = if format == "dot" { "dot" } else { "txt" };
+        let path = if target.is_empty() {
+            let secs = unix_secs(SystemTime::now());
+            match export_path(&self.repo_root, secs, extension) {
+                Some(path) => path,
+                None => {
+                    self.result_lines =
+                        vec!["ERROR: no config directory; give a path to export to".into()];
+                    return;
+                }
+            }
+        } else {
+            let path = self.repo_root.join(target);
+            if path.starts_with(&self.repo_root)
+                && self.refuse_read_only("writing into the repository")
+            {
+                return;
+            }
+            path
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&path, text));
+        self.result_lines = vec![match written {
+            Ok(()) => format!("exported {} to {}", what, path.display()),
+            Err(e) => format!("ERROR: cannot write {}: {}", path.display(), e),
+        }];
+    }
+}
@@ -0,0 +1,284 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use super::ViewModel;
+use crate::keymap::KeyAction;
+
+/// A key in the help overlay: a rebindable action, shown with whatever `[keys]` bound
+/// it to, or a key that cannot be changed.
+#[derive(Clone, Copy)]
+enum HelpKey {
+    Action(KeyAction),
+    Fixed(&'static str),
+}
+
+use HelpKey::{Action, Fixed};
+
+const GLOBAL: &[(HelpKey, &str)] = &[
+    (Action(KeyAction::FocusLeft), "focus the pane to the left"),
+    (Action(KeyAction::FocusRight), "focus the pane to the right"),
+    (
+        Fixed("Tab"),
+        "on the list panel: switch between FILES, COMMITS, STASHES…",
+    ),
+    (Fixed("L"), "next layout"),
+    (Fixed("{ }"), "previous / next tab"),
+    (Action(KeyAction::CommandLine), "command line"),
+    (Fixed("Ctrl-T"), "find a file"),
+    (Fixed("|"), "side-by-side diffs on / off"),
+    (
+        Fixed("Ctrl-C"),
+        "cancel the running git command and drop the queued ones",
+    ),
+    (Fixed("?"), "this help"),
+    (Action(KeyAction::Quit), "quit"),
+    (
+        Fixed("mouse"),
+        "click focuses and selects, the wheel scrolls",
+    ),
+];
+
+const CMD: &[(HelpKey, &str)] = &[
+    (Action(KeyAction::Down), "next command"),
+    (Action(KeyAction::Up), "previous command"),
+    (Fixed("Enter"), "run the command"),
+    (Fixed("J K"), "move the command down / up"),
+];
+
+const FILES: &[(HelpKey, &str)] = &[
+    (Action(KeyAction::Down), "next file"),
+    (Action(KeyAction::Up), "previous file"),
+    (
+        Action(KeyAction::Stage),
+        "stage / unstage; marks a conflict resolved",
+    ),
+    (Action(KeyAction::Diff), "diff in LOG"),
+    (
+        Action(KeyAction::Discard),
+        "discard the changes (press twice)",
+    ),
+    (
+        Action(KeyAction::Mark),
+        "mark / unmark the file for s, x and d",
+    ),
+    (Fixed("v"), "mark a range: v at both ends"),
+    (Fixed("Esc"), "clear the marks"),
+    (Fixed("T"), "directory tree / flat list"),
+    (Fixed("h l Enter"), "tree: collapse / expand the directory"),
+    (Fixed("Enter"), "stage hunk by hunk in LOG"),
+    (Fixed("f"), "history of the file or directory in COMMITS"),
+    (Fixed("W"), "whole functions as diff context on / off"),
+    (Fixed("H"), "download the LFS content of the file"),
+    (Fixed("o t"), "conflict: take ours / theirs"),
+    (Fixed("m"), "conflict: open the mergetool"),
+    (Fixed("r"), "conflict: mark resolved"),
+];
+
+const COMMITS: &[(HelpKey, &str)] = &[
+    (Action(KeyAction::Down), "next commit"),
+    (Action(KeyAction::Up), "previous commit"),
+    (Fixed("Enter"), "show the commit in LOG"),
+    (Fixed("o"), "open the issue the message refers to"),
+    (Fixed("t"), "only tagged commits on / off"),
+    (Fixed("c"), "check out the commit (detached)"),
+    (Fixed("r"), "revert the commit"),
+    (Fixed("p"), "cherry-pick the commit"),
+    (Fixed("y"), "copy the hash"),
+];
+
+const MATCHES: &[(HelpKey, &str)] = &[
+    (Action(KeyAction::Down), "next match"),
+    (Action(KeyAction::Up), "previous match"),
+    (Fixed("Enter"), "show the match in LOG"),
+    (Fixed("e"), "open the match in the editor"),
+];
+
+const STASHES: &[(HelpKey, &str)] = &[
+    (Action(KeyAction::Down), "next stash"),
+    (Action(KeyAction::Up), "previous stash"),
+    (Fixed("Enter"), "show the stash"),
+    (Fixed("w"), "diff the stash against the worktree"),
+    (Fixed("a"), "apply the stash"),
+    (Fixed("p"), "pop the stash"),
+    (Fixed("b"), "branch from the stash"),
+    (Fixed("D"), "drop the stash (press twice)"),
+    (Fixed("s"), "stash the changes"),
+    (Fixed("S"), "stash the changes and untracked files"),
+];
+
+const BRANCHES: &[(HelpKey, &str)] = &[
+    (Action(KeyAction::Down), "next branch"),
+    (Action(KeyAction::Up), "previous branch"),
+    (Fixed("Enter"), "switch to the branch"),
+    (Fixed("n"), "new branch"),
+    (Fixed("r"), "rename the branch"),
+    (Fixed("D"), "delete the branch (press twice)"),
+    (Fixed("w"), "check the branch out in a new worktree"),
+];
+
+const REMOTES: &[(HelpKey, &str)] = &[
+    (Action(KeyAction::Down), "next remote"),
+    (Action(KeyAction::Up), "previous remote"),
+    (Fixed("Enter"), "show the remote and its branches"),
+    (Fixed("f"), "fetch from the remote"),
+    (Fixed("p"), "prune branches the remote no longer has"),
+    (Fixed("a"), "add a remote"),
+    (Fixed("r"), "rename the remote"),
+    (Fixed("u"), "change the URL of the remote"),
+    (Fixed("D"), "remove the remote (press twice)"),
+];
+
+const WORKTREES: &[(HelpKey, &str)] = &[
+    (Action(KeyAction::Down), "next worktree"),
+    (Action(KeyAction::Up), "previous worktree"),
+    (Fixed("Enter"), "switch to the worktree"),
+    (Fixed("a"), "add a worktree"),
+    (Fixed("p"), "prune worktrees whose directory is gone"),
+    (Fixed("D"), "remove the worktree (press twice)"),
+];
+
+const LOG: &[(HelpKey, &str)] = &[
+    (Action(KeyAction::Down), "diff: cursor down"),
+    (Action(KeyAction::Up), "diff: cursor up"),
+    (Fixed("] ["), "next / previous change"),
+    (Fixed("e"), "open the line under the cursor in the editor"),
+    (Fixed("W"), "whole functions as diff context on / off"),
+    (Fixed("s u"), "hunks: stage / unstage the hunk"),
+    (Fixed("Tab"), "hunks: staged / unstaged changes"),
+    (Fixed("Esc"), "hunks: back to the diff"),
+    (Fixed("1-9"), "doctor: apply the fix"),
+];
+
+const SCROLL: &[(HelpKey, &str)] = &[
+    (Fixed("PageDown PageUp"), "scroll a page"),
+    (Fixed("Ctrl-D Ctrl-U"), "scroll half a page"),
+    (Action(KeyAction::Down), "scroll down a line"),
+    (Action(KeyAction::Up), "scroll up a line"),
+    (Fixed("gg G"), "top / bottom"),
+    (Fixed(":<n>"), "go to line n"),
+    (Fixed("v"), "print the text to select it with the mouse"),
+    (Fixed("/"), "search the pane"),
+    (Fixed("n N"), "next / previous match"),
+    (Fixed("Esc"), "drop the search"),
+];
+
+const COMMAND_LINE: &[(HelpKey, &str)] = &[
+    (Fixed("Enter"), "run"),
+    (Fixed("Esc"), "cancel"),
+    (
+        Fixed("Up Down"),
+        "earlier / later lines starting with what was typed",
+    ),
+    (
+        Fixed(":cancel q<n>"),
+        "drop a command queued behind the running one",
+    ),
+];
+
+const SECTIONS: &[(&str, &[(HelpKey, &str)])] = &[
+    ("Everywhere", GLOBAL),
+    ("CMD", CMD),
+    ("FILES", FILES),
+    ("COMMITS", COMMITS),
+    ("MATCHES", MATCHES),
+    ("STASHES", STASHES),
+    ("BRANCHES", BRANCHES),
+    ("REMOTES", REMOTES),
+    ("WORKTREES", WORKTREES),
+    ("LOG", LOG),
+    ("LOG and R", SCROLL),
+    (": command line", COMMAND_LINE),
+];
+
+/// A row of the help overlay.
+pub enum HelpRow {
+    Section(&'static str),
+    Binding(String, String),
+    Blank,
+}
+
+impl ViewModel {
+    /// `?`: opens the list of key bindings.
+    pub(super) fn open_help(&mut self) {
+        self.help_scroll = Some(0);
+    }
+
+    pub fn help_scroll(&self) -> Option<usize> {
+        self.help_scroll
+    }
+
+    /// Rows of the help overlay, with the keys `[keys]` bound and the configured
+    /// command shortcuts.
+    pub fn help_rows(&self) -> Vec<HelpRow> {
+        let mut rows = Vec::new();
+        for (title, bindings) in SECTIONS {
+            if !rows.is_empty() {
+                rows.push(HelpRow::Blank);
+            }
+            rows.push(HelpRow::Section(title));
+            for (key, text) in *bindings {
+                let keys = match key {
+                    Action(action) => match self.keymap.keys(*action) {
+                        Some(keys) => keys,
+                        None => continue,
+                    },
+                    Fixed(keys) => keys.to_string(),
+                };
+                rows.push(HelpRow::Binding(keys, text.to_string()));
+            }
+            if *title == "Everywhere" {
+                rows.extend(
+                    self.keymap
+                        .named_keys()
+                        .into_iter()
+                        .map(|(keys, name)| HelpRow::Binding(keys, format!(":{}", name))),
+                );
+            }
+            if *title == "CMD" {
+                rows.extend(self.config.commands.iter().filter_map(|cmd| {
+                    Some(HelpRow::Binding(
+                        cmd.key?.to_string(),
+                        format!("run {}", cmd.name),
+                    ))
+                }));
+            }
+        }
+        rows
+    }
+
+    /// Rows of the overlay the last frame had room for, to scroll by pages and stop at
+    /// the end.
+    pub fn set_help_height(&mut self, height: usize) {
+        self.help_height = height.max(1);
+        if let Some(scroll) = self.help_scroll {
+            self.help_scroll = Some(scroll.min(self.help_max_scroll()));
+        }
+    }
+
+    fn help_max_scroll(&self) -> usize {
+        self.help_rows().len().saturating_sub(self.help_height)
+    }
+
+    pub(super) fn handle_help_key(&mut self, key: KeyEvent) {
+        let Some(scroll) = self.help_scroll else {
+            return;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let page = self.help_height;
+        let scroll = match key.code {
+            KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') => {
+                self.help_scroll = None;
+                return;
+            }
+            KeyCode::Char('j') | KeyCode::Down => scroll + 1,
+            KeyCode::Char('k') | KeyCode::Up => scroll.saturating_sub(1),
+            KeyCode::Char('d') if ctrl => scroll + page / 2,
+            KeyCode::Char('u') if ctrl => scroll.saturating_sub(page / 2),
+            KeyCode::PageDown | KeyCode::Char(' ') => scroll + page,
+            KeyCode::PageUp => scroll.saturating_sub(page),
+            KeyCode::Home => 0,
+            KeyCode::End => usize::MAX,
+            _ => return,
+        };
+        self.help_scroll = Some(scroll.min(self.help_max_scroll()));
+    }
+}
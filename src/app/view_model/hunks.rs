@@ -0,0 +1,194 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::audit::Audited;
+use super::{Focus, ViewModel};
+use crate::config::Pane;
+use crate::git::{apply_to_index, file_patch, quote_arg};
+
+/// The file LOG shows hunk by hunk so single hunks can be staged or unstaged.
+#[derive(Clone)]
+pub struct HunkView {
+    pub path: String,
+    /// Showing what is staged (`git diff --cached`) rather than what is not.
+    pub staged: bool,
+}
+
+impl ViewModel {
+    /// Enter on FILES: LOG shows the selected file's unstaged changes, or its staged ones
+    /// when nothing is left unstaged, for staging hunk by hunk.
+    pub(super) fn open_hunk_view(&mut self) {
+        let Some(entry) = self.status.files.get(self.selected_file) else {
+            return;
+        };
+        if entry.is_conflicted() {
+            self.show_diff_for_selected_file(false);
+            self.result_lines.push(
+                "conflicted: o keeps ours, t theirs, m opens the mergetool, r marks it resolved"
+                    .into(),
+            );
+            return;
+        }
+        if entry.status == "??" {
+            self.result_lines = vec![format!(
+                "{} is untracked: s stages all of it, or `:add -N {}` first to pick hunks",
+                entry.path,
+                quote_arg(&entry.path)
+            )];
+            self.result_scroll = 0;
+            return;
+        }
+        if !self.pane_layout().panes.contains(&Pane::Log) {
+            self.result_lines = vec!["this layout hides LOG; L switches layouts".into()];
+            self.result_scroll = 0;
+            return;
+        }
+        let path = entry.path.clone();
+        let staged = entry.status.ends_with(' ');
+        self.load_hunk_view(HunkView { path, staged }, 0);
+        self.focus = Focus::Log;
+    }
+
+    /// Shows `view` in LOG with the cursor on hunk `hunk`, or the last one when there are
+    /// fewer now.
+    fn load_hunk_view(&mut self, view: HunkView, hunk: usize) {
+        let lines = match file_patch(
+            &self.config.git_path,
+            &self.repo_root,
+            &view.path,
+            view.staged,
+        ) {
+            Ok(lines) => lines,
+            Err(e) => {
+                self.result_lines = vec![format!("ERROR: cannot diff {}: {}", view.path, e)];
+                self.result_scroll = 0;
+                return;
+            }
+        };
+        let side = if view.staged { "staged" } else { "unstaged" };
+        self.result_lines = vec![if lines.is_empty() {
+            format!(
+                "{} has no {} changes (Tab shows the other side)",
+                view.path, side
+            )
+        } else if view.staged {
+            "u unstages the hunk under the cursor; Tab shows unstaged changes, Esc closes".into()
+        } else {
+            "s stages the hunk under the cursor; Tab shows staged changes, Esc closes".into()
+        }];
+        self.result_scroll = 0;
+        self.set_log_lines(lines);
+        self.hunk_view = Some(view);
+        let target = self.log_diff.as_ref().and_then(|diff| {
+            let last = diff.hunks.len().checked_sub(1)?;
+            Some(diff.hunks[hunk.min(last)].line)
+        });
+        if let Some(line) = target {
+            self.move_log_cursor(line);
+        }
+    }
+
+    /// Keys of the hunk view in LOG; false when `key` is not one of them.
+    pub(super) fn handle_hunk_keys(&mut self, key: KeyEvent) -> bool {
+        let Some(view) = self.hunk_view.clone() else {
+            return false;
+        };
+        match key.code {
+            KeyCode::Char('s') if !view.staged => self.apply_hunk_at_cursor(view),
+            KeyCode::Char('u') if view.staged => self.apply_hunk_at_cursor(view),
+            KeyCode::Tab => self.load_hunk_view(
+                HunkView {
+                    staged: !view.staged,
+                    ..view
+                },
+                0,
+            ),
+            KeyCode::Esc => {
+                self.hunk_view = None;
+                self.show_diff_for_selected_file(false);
+                if self.list_panel == Focus::Files {
+                    self.focus = Focus::Files;
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn apply_hunk_at_cursor(&mut self, view: HunkView) {
+        if self.refuse_read_only("staging hunks") {
+            return;
+        }
+        if self.is_running() {
+            self.result_lines
+                .push("WARN: cannot stage hunks while git is running".into());
+            return;
+        }
+        let Some(diff) = &self.log_diff else {
+            return;
+        };
+        let Some((hunk, patch)) = diff
+            .lines
+            .get(self.log_cursor)
+            .and_then(|l| l.hunk)
+            .and_then(|h| Some((h, diff.hunk_patch(h)?)))
+        else {
+            self.result_lines = vec!["move the cursor into a hunk first ([/] jump)".into()];
+            self.result_scroll = 0;
+            return;
+        };
+        let shown = apply_label(view.staged);
+        if self.dry_run {
+            self.result_lines = vec![format!("dry run: would run git {} with this patch:", shown)];
+            self.result_lines.extend(patch.lines().map(str::to_string));
+            self.result_scroll = 0;
+            return;
+        }
+        if self.config.audit {
+            let mut lines = vec![
+                "== audit: confirm ==".to_string(),
+                format!("run: {} (patch on stdin)", self.argv(shown)),
+                String::new(),
+            ];
+            lines.extend(patch.lines().map(str::to_string));
+            self.ask_audit(lines, Audited::Hunk(view, hunk, patch));
+            return;
+        }
+        self.apply_hunk(view, hunk, patch);
+    }
+
+    pub(super) fn apply_hunk(&mut self, view: HunkView, hunk: usize, patch: String) {
+        let shown = apply_label(view.staged);
+        self.record(&format!(
+            "$ {} (hunk {} of {})",
+            self.argv(shown),
+            hunk + 1,
+            view.path
+        ));
+        self.teach(&format!("git {}", shown), true);
+        let applied = apply_to_index(&self.config.git_path, &self.repo_root, &patch, view.staged);
+        self.refresh_repo_status();
+        let done = if view.staged { "unstaged" } else { "staged" };
+        let path = view.path.clone();
+        self.load_hunk_view(view, hunk);
+        match applied {
+            Ok(()) => self
+                .result_lines
+                .insert(0, format!("{} hunk {} of {}", done, hunk + 1, path)),
+            Err(e) => {
+                self.result_lines = vec![format!("ERROR: git {} failed: {}", shown, e)];
+            }
+        }
+    }
+
+    pub fn hunk_view(&self) -> Option<&HunkView> {
+        self.hunk_view.as_ref()
+    }
+}
+
+fn apply_label(staged: bool) -> &'static str {
+    if staged {
+        "apply --cached --reverse -"
+    } else {
+        "apply --cached -"
+    }
+}
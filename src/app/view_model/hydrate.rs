@@ -0,0 +1,89 @@
+use super::{UiMessage, ViewModel};
+use crate::git::{LfsMode, lfs_pointer_files, lfs_skip_smudge, quote_arg};
+use crate::jobs::JobKind;
+
+impl ViewModel {
+    /// `:hydrate <path>` downloads LFS content for a file or everything under a directory;
+    /// without a path (or `H` in FILES) it takes the selected file.
+    pub(super) fn hydrate(&mut self, path: &str) {
+        let path = path.trim_matches('"').trim_end_matches(['/', '\\']);
+        let path = if path.is_empty() {
+            match self.status.files.get(self.selected_file) {
+                Some(entry) => entry.path.clone(),
+                None => {
+                    self.result_lines = vec!["usage: :hydrate <file or directory>".into()];
+                    self.result_scroll = 0;
+                    return;
+                }
+            }
+        } else {
+            path.replace('\\', "/")
+        };
+        let full = self.repo_root.join(&path);
+        if !full.exists() {
+            self.result_lines = vec![format!("ERROR: path does not exist: {}", path)];
+            self.result_scroll = 0;
+            return;
+        }
+        // `--include` takes gitignore-style patterns; `dir/**` matches the whole subtree.
+        let pattern = if full.is_dir() {
+            format!("{}/**", path)
+        } else {
+            path
+        };
+        self.run_command(
+            format!("lfs pull --include={}", quote_arg(&pattern)),
+            LfsMode::None,
+            false,
+        );
+    }
+
+    /// `:lfs-pointers [dir]` lists LFS files whose content was never downloaded.
+    pub(super) fn load_lfs_pointers(&mut self, dir: &str) {
+        if self.lfs_version.is_none() {
+            self.result_lines = vec!["ERROR: git-lfs is not installed".into()];
+            self.result_scroll = 0;
+            return;
+        }
+        let dir = dir
+            .trim_matches('"')
+            .trim_end_matches(['/', '\\'])
+            .to_string();
+        let git = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        self.jobs
+            .spawn(JobKind::LfsFiles, "lfs-pointers", move |_| {
+                let prefix = format!("{}/", dir);
+                UiMessage::LfsPointersLoaded {
+                    paths: lfs_pointer_files(&git, &repo)
+                        .into_iter()
+                        .filter(|p| dir.is_empty() || p.starts_with(&prefix))
+                        .collect(),
+                    skip_smudge: lfs_skip_smudge(&git, &repo),
+                }
+            });
+        self.result_lines = vec!["lfs-pointers: loading...".into()];
+        self.result_scroll = 0;
+    }
+
+    pub(super) fn show_lfs_pointers(&mut self, paths: Vec<String>, skip_smudge: bool) {
+        let mut lines = vec![if skip_smudge {
+            "LFS smudging is skipped: checkouts leave pointer files".to_string()
+        } else {
+            "LFS smudging is on: checkouts download LFS content".to_string()
+        }];
+        if paths.is_empty() {
+            lines.push("all LFS files are downloaded".into());
+        } else {
+            lines.push(format!(
+                "{} file(s) not downloaded; :hydrate <file or directory> fetches them",
+                paths.len()
+            ));
+            lines.push(String::new());
+            lines.extend(paths.iter().map(|p| format!("  {}", p)));
+        }
+        self.set_log_lines(lines);
+        self.result_lines = vec![format!("lfs-pointers: {} file(s)", paths.len())];
+        self.result_scroll = 0;
+    }
+}
@@ -0,0 +1,83 @@
+use super::{ViewModel, is_commit};
+use crate::issues::{branch_issue_id, find_issue_ids, issue_url};
+use crate::shell::open_url;
+
+impl ViewModel {
+    /// `:issue [id]` opens the given issue, or the one referenced by the branch name.
+    pub(super) fn open_issue_command(&mut self, id: &str) {
+        let id = if id.is_empty() {
+            match branch_issue_id(&self.status.branch) {
+                Some(id) => id,
+                None => {
+                    self.result_lines = vec![format!(
+                        "no issue ID found in branch name \"{}\"",
+                        self.status.branch
+                    )];
+                    self.result_scroll = 0;
+                    return;
+                }
+            }
+        } else {
+            id.to_string()
+        };
+        self.open_issue(&id);
+    }
+
+    pub(super) fn open_selected_commit_issue(&mut self) {
+        let ids = self
+            .commit_list
+            .commits
+            .get(self.selected_commit)
+            .map(|c| find_issue_ids(&c.subject))
+            .unwrap_or_default();
+        match ids.first() {
+            Some(id) => {
+                let id = id.clone();
+                self.open_issue(&id);
+            }
+            None => {
+                self.result_lines = vec!["no issue reference in the selected commit".into()];
+                self.result_scroll = 0;
+            }
+        }
+    }
+
+    fn open_issue(&mut self, id: &str) {
+        let Some(template) = self.config.issues.url.as_deref() else {
+            self.result_lines = vec![format!(
+                "issue {}: set [issues] url in config.toml to open it",
+                id
+            )];
+            self.result_scroll = 0;
+            return;
+        };
+        let url = issue_url(template, id);
+        self.result_lines = match open_url(&url) {
+            Ok(()) => vec![format!("opened {}", url)],
+            Err(e) => vec![format!("ERROR: cannot open {}: {}", url, e)],
+        };
+        self.result_scroll = 0;
+    }
+
+    /// Editor-based commits get the branch's issue ID as the message prefix when enabled.
+    pub(super) fn prefill_commit_issue(&self, args_str: String) -> String {
+        if !self.config.issues.insert_in_commit
+            || !is_commit(&args_str)
+            || !self.is_commit_needing_editor(&args_str)
+        {
+            return args_str;
+        }
+        match self.commit_issue_prefix() {
+            Some(prefix) => format!("{} -e -m \"{}\"", args_str, prefix),
+            None => args_str,
+        }
+    }
+
+    /// `ABC-123: ` when `[issues] insert_in_commit` is on and the branch names an issue.
+    pub(super) fn commit_issue_prefix(&self) -> Option<String> {
+        if !self.config.issues.insert_in_commit {
+            return None;
+        }
+        branch_issue_id(&self.status.branch).map(|id| format!("{}: ", id))
+    }
+}
@@ -0,0 +1,191 @@
+use super::{Focus, ViewModel};
+use crate::config::Pane;
+
+/// A built-in preset: panes in order and the sizes it overrides.
+struct Preset {
+    name: &'static str,
+    panes: &'static [Pane],
+    cmd_width: Option<u16>,
+    result_height: Option<u16>,
+}
+
+const PRESETS: [Preset; 4] = [
+    Preset {
+        name: "default",
+        panes: &[Pane::Cmd, Pane::Files, Pane::Log, Pane::Result],
+        cmd_width: None,
+        result_height: None,
+    },
+    Preset {
+        name: "wide-diff",
+        panes: &[Pane::Log, Pane::Result],
+        cmd_width: None,
+        result_height: None,
+    },
+    Preset {
+        name: "review",
+        panes: &[Pane::Files, Pane::Log, Pane::Result],
+        cmd_width: Some(40),
+        result_height: Some(8),
+    },
+    Preset {
+        name: "minimal",
+        panes: &[Pane::Files, Pane::Log],
+        cmd_width: Some(28),
+        result_height: None,
+    },
+];
+
+/// The arrangement view.rs draws.
+pub struct PaneLayout {
+    pub panes: Vec<Pane>,
+    pub cmd_width: u16,
+    pub files_height: u16,
+    pub result_height: u16,
+}
+
+impl ViewModel {
+    /// Built-in preset names followed by those from `[layouts.<name>]`.
+    fn layout_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = PRESETS.iter().map(|p| p.name.to_string()).collect();
+        names.extend(
+            self.config
+                .layouts
+                .keys()
+                .filter(|n| !names.contains(n))
+                .cloned()
+                .collect::<Vec<_>>(),
+        );
+        names
+    }
+
+    /// `L` switches to the next layout preset.
+    pub(super) fn cycle_layout(&mut self) {
+        let names = self.layout_names();
+        let next = names
+            .iter()
+            .position(|n| *n == self.layout_preset)
+            .map_or(0, |i| (i + 1) % names.len());
+        self.set_layout(names[next].clone());
+    }
+
+    /// `:layout` lists the presets; `:layout <name>` switches to one.
+    pub(super) fn layout_command(&mut self, name: &str) {
+        let names = self.layout_names();
+        if name.is_empty() {
+            let mut lines = vec!["layouts (L cycles, :layout <name> picks one):".to_string()];
+            lines.extend(names.iter().map(|n| {
+                let marker = if *n == self.layout_preset { "*" } else { " " };
+                format!("{} {}", marker, n)
+            }));
+            self.result_lines = lines;
+            self.result_scroll = 0;
+            return;
+        }
+        if !names.iter().any(|n| n == name) {
+            self.result_lines = vec![format!("unknown layout: {} ({})", name, names.join(", "))];
+            self.result_scroll = 0;
+            return;
+        }
+        self.set_layout(name.to_string());
+    }
+
+    fn set_layout(&mut self, name: String) {
+        self.layout_preset = name;
+        let panes = self.pane_layout().panes;
+        // Focus on a pane the new layout hides moves to LOG, which always shows.
+        if !panes.contains(&self.focus_pane()) {
+            self.focus = Focus::Log;
+        }
+        self.needs_full_redraw = true;
+        self.result_lines = vec![format!("layout: {}", self.layout_preset)];
+        self.result_scroll = 0;
+    }
+
+    /// The current preset with `[layout]` filling in the sizes it leaves out.
+    pub fn pane_layout(&self) -> PaneLayout {
+        let base = &self.config.layout;
+        let (mut panes, cmd_width, files_height, result_height) =
+            match self.config.layouts.get(&self.layout_preset) {
+                Some(preset) => (
+                    preset.panes.clone(),
+                    preset.cmd_width,
+                    preset.files_height,
+                    preset.result_height,
+                ),
+                None => {
+                    let preset = PRESETS
+                        .iter()
+                        .find(|p| p.name == self.layout_preset)
+                        .unwrap_or(&PRESETS[0]);
+                    (
+                        preset.panes.to_vec(),
+                        preset.cmd_width,
+                        None,
+                        preset.result_height,
+                    )
+                }
+            };
+        if !panes.contains(&Pane::Log) {
+            panes.push(Pane::Log);
+        }
+        PaneLayout {
+            panes,
+            cmd_width: cmd_width.unwrap_or(base.cmd_width),
+            files_height: files_height.unwrap_or(base.files_height),
+            result_height: result_height.unwrap_or(base.result_height),
+        }
+    }
+
+    /// `h`/`l`: the next pane shown in the layout's order, `step` being -1 or 1.
+    pub(super) fn move_focus(&mut self, step: isize) {
+        let panes = self.pane_layout().panes;
+        let Some(current) = panes.iter().position(|p| *p == self.focus_pane()) else {
+            self.focus = Focus::Log;
+            return;
+        };
+        let next = current.saturating_add_signed(step).min(panes.len() - 1);
+        self.focus = self.pane_focus(panes[next]);
+    }
+
+    /// Applies the `[focus]` rule for what just finished, unless the layout hides its pane.
+    pub(super) fn follow_content(&mut self, failed: bool) {
+        let rules = &self.config.focus;
+        let rule = if failed {
+            rules.after_failure
+        } else if self.log_diff.is_some() {
+            rules.after_diff.or(rules.after_success)
+        } else {
+            rules.after_success
+        };
+        if let Some(pane) = rule
+            && self.pane_layout().panes.contains(&pane)
+        {
+            self.focus = self.pane_focus(pane);
+        }
+    }
+
+    fn pane_focus(&self, pane: Pane) -> Focus {
+        match pane {
+            Pane::Cmd => Focus::Cmd,
+            Pane::Files => self.list_panel,
+            Pane::Log => Focus::Log,
+            Pane::Result => Focus::Result,
+        }
+    }
+
+    fn focus_pane(&self) -> Pane {
+        match self.focus {
+            Focus::Cmd => Pane::Cmd,
+            Focus::Log => Pane::Log,
+            Focus::Result => Pane::Result,
+            Focus::Files
+            | Focus::Commits
+            | Focus::Matches
+            | Focus::Stashes
+            | Focus::Branches
+            | Focus::Remotes
+            | Focus::Worktrees => Pane::Files,
+        }
+    }
+}
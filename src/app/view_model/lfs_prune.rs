@@ -0,0 +1,94 @@
+use super::{Confirmed, UiMessage, ViewModel};
+use crate::git::{CommandResult, lfs_storage_size, run_step};
+use crate::jobs::JobKind;
+use crate::preview::format_size;
+
+const PREVIEW: &str = "lfs prune --dry-run --verbose";
+const PRUNE: &str = "lfs prune --verbose";
+
+impl ViewModel {
+    /// `:lfs-prune` lists what `git lfs prune` would delete before anything is removed.
+    pub(super) fn preview_lfs_prune(&mut self) {
+        if self.refuse_read_only("`git lfs prune`") {
+            return;
+        }
+        if self.is_running() {
+            self.result_lines
+                .push("WARN: already running command".into());
+            return;
+        }
+        if self.lfs_version.is_none() {
+            self.result_lines = vec!["ERROR: git-lfs is not installed".into()];
+            self.result_scroll = 0;
+            return;
+        }
+        self.start_running(PREVIEW);
+        self.set_log_lines(vec!["<running...>".into()]);
+        self.result_lines = vec![format!("$ git {}", PREVIEW)];
+        self.result_scroll = 0;
+        let git = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        self.jobs
+            .spawn(JobKind::Command, format!("git {}", PREVIEW), move |ctx| {
+                let mut res = CommandResult {
+                    log_lines: Vec::new(),
+                    result_lines: Vec::new(),
+                    success: false,
+                };
+                res.success = run_step(&git, PREVIEW, &repo, ctx.cancel_token(), &mut res);
+                UiMessage::PrunePreviewed(res)
+            });
+    }
+
+    pub(super) fn confirm_lfs_prune(&mut self, res: CommandResult) {
+        self.finish_running();
+        let mut log = res.log_lines;
+        if log.is_empty() {
+            log.push("<no output from git lfs prune>".into());
+        }
+        self.set_log_lines(log);
+        self.result_lines = res.result_lines;
+        if res.success {
+            self.result_lines.push(String::new());
+            self.result_lines
+                .push("prune these objects? (y/Enter to prune, any other key cancels)".into());
+            self.await_confirmation(None, Confirmed::LfsPrune);
+        }
+        self.result_scroll = self
+            .result_lines
+            .len()
+            .saturating_sub(self.result_view_height as usize)
+            .min(u16::MAX as usize) as u16;
+    }
+
+    pub(super) fn run_lfs_prune(&mut self) {
+        self.start_running(PRUNE);
+        self.set_log_lines(vec!["<running...>".into()]);
+        self.result_lines = vec![format!("$ git {}", PRUNE)];
+        self.result_scroll = 0;
+        let git = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        self.jobs
+            .spawn(JobKind::Command, format!("git {}", PRUNE), move |ctx| {
+                let before = lfs_storage_size(&git, &repo);
+                let mut res = CommandResult {
+                    log_lines: Vec::new(),
+                    result_lines: Vec::new(),
+                    success: false,
+                };
+                res.success = run_step(&git, PRUNE, &repo, ctx.cancel_token(), &mut res);
+                let after = lfs_storage_size(&git, &repo);
+                // First, so it is visible without scrolling the R pane.
+                res.result_lines.insert(
+                    0,
+                    format!(
+                        "reclaimed {} (LFS storage {} -> {})",
+                        format_size(before.saturating_sub(after)),
+                        format_size(before),
+                        format_size(after)
+                    ),
+                );
+                UiMessage::CommandFinished(res)
+            });
+    }
+}
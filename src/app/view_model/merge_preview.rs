@@ -0,0 +1,67 @@
+use super::{UiMessage, ViewModel};
+use crate::git::{MergePreview, merge_preview, quote_arg};
+use crate::jobs::JobKind;
+
+impl ViewModel {
+    /// `:merge-preview [<branch>]` shows whether `branch` (the default branch when left
+    /// out) merges into `HEAD` cleanly and what it would change, conflicts included,
+    /// leaving the worktree and index alone.
+    pub(super) fn preview_merge(&mut self, branch: &str) {
+        let branch = match (branch, &self.default_branch) {
+            ("", Some(default)) => default.clone(),
+            ("", None) => {
+                self.result_lines = vec!["usage: :merge-preview <branch>".into()];
+                self.result_scroll = 0;
+                return;
+            }
+            (branch, _) => branch.to_string(),
+        };
+        let shown = format!("git merge-tree --write-tree HEAD {}", quote_arg(&branch));
+        self.teach(&shown, true);
+        self.result_lines = vec![format!("$ {}", shown), "previewing merge...".into()];
+        self.result_scroll = 0;
+        let git = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        self.jobs.spawn(
+            JobKind::Commits,
+            format!("merge-preview {}", branch),
+            move |_| {
+                let preview = merge_preview(&git, &repo, &branch);
+                UiMessage::MergePreviewed(branch, preview)
+            },
+        );
+    }
+
+    pub(super) fn show_merge_preview(&mut self, branch: String, preview: MergePreview) {
+        let into = &self.status.branch;
+        let mut lines = vec![format!("== merge preview: {} into {} ==", branch, into)];
+        if preview.conflicts.is_empty() {
+            lines.push("merges cleanly".into());
+        } else {
+            lines.push(format!("CONFLICTS in {} file(s):", preview.conflicts.len()));
+            lines.extend(preview.conflicts.iter().map(|p| format!("  {}", p)));
+        }
+        lines.extend(preview.messages.iter().map(|m| format!("  {}", m)));
+        lines.push(String::new());
+        if preview.diff.is_empty() {
+            lines.push(format!("{} has nothing {} lacks", branch, into));
+        }
+        lines.extend(preview.diff);
+        self.result_lines = vec![if preview.conflicts.is_empty() {
+            format!(
+                "{} merges cleanly into {} (nothing was changed)",
+                branch, into
+            )
+        } else {
+            format!(
+                "{} conflicts with {} in {} file(s) (nothing was changed)",
+                branch,
+                into,
+                preview.conflicts.len()
+            )
+        }];
+        self.result_scroll = 0;
+        self.set_log_lines(lines);
+        self.follow_content(false);
+    }
+}
@@ -0,0 +1,161 @@
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Margin, Position, Rect};
+
+use super::{Focus, Mode, ViewModel};
+
+/// Lines one wheel notch scrolls LOG and R by.
+const WHEEL_LINES: i32 = 3;
+
+/// Where the panes were drawn in the last frame, to find the one under the mouse.
+#[derive(Clone, Copy, Default)]
+pub struct PaneAreas {
+    pub cmd: Rect,
+    /// FILES, or whichever panel shares its place.
+    pub list: Rect,
+    pub log: Rect,
+    pub result: Rect,
+}
+
+impl ViewModel {
+    pub fn set_pane_areas(&mut self, areas: PaneAreas) {
+        self.pane_areas = areas;
+    }
+
+    /// A left click focuses the pane under the mouse and selects the clicked row of CMD
+    /// or the list panel; the wheel scrolls LOG and R and moves the selection of lists.
+    pub fn handle_mouse(&mut self, event: MouseEvent) {
+        if self.mouse_blocked() {
+            return;
+        }
+        let at = Position::new(event.column, event.row);
+        let Some((pane, area)) = self.pane_at(at) else {
+            return;
+        };
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.focus = pane;
+                let inner = area.inner(Margin::new(1, 1));
+                if inner.contains(at) {
+                    let row = self.list_offset(pane, inner.height) + (at.y - inner.y) as usize;
+                    self.select_row(pane, row);
+                }
+            }
+            MouseEventKind::ScrollDown => self.scroll_pane(pane, 1),
+            MouseEventKind::ScrollUp => self.scroll_pane(pane, -1),
+            _ => {}
+        }
+    }
+
+    /// Popups and prompts keep the keyboard to themselves.
+    fn mouse_blocked(&self) -> bool {
+        self.mode != Mode::Normal
+            || self.help_scroll.is_some()
+            || self.command_form.is_some()
+            || self.commit_editor.is_some()
+            || self.finder.is_some()
+            || self.new_branch.is_some()
+            || self.orphan.is_some()
+            || self.search_input.is_some()
+            || self.flow_waiting_for_input()
+            || self.pending_confirmation.is_some()
+            || self.pending_commit.is_some()
+            || self.pending_pr.is_some()
+    }
+
+    fn pane_at(&self, at: Position) -> Option<(Focus, Rect)> {
+        let areas = self.pane_areas;
+        [
+            (Focus::Cmd, areas.cmd),
+            (self.list_panel, areas.list),
+            (Focus::Log, areas.log),
+            (Focus::Result, areas.result),
+        ]
+        .into_iter()
+        .find(|(_, area)| area.contains(at))
+    }
+
+    /// First row shown by a list of `height` rows; lists scroll only as far as keeps
+    /// the selection in view, and FILES does not scroll.
+    fn list_offset(&self, pane: Focus, height: u16) -> usize {
+        match pane {
+            Focus::Files | Focus::Log | Focus::Result => 0,
+            _ => self.list_selection(pane).map_or(0, |(selected, _)| {
+                selected.saturating_sub(height.saturating_sub(1) as usize)
+            }),
+        }
+    }
+
+    /// Selected row and row count of CMD or a list panel.
+    fn list_selection(&self, pane: Focus) -> Option<(usize, usize)> {
+        Some(match pane {
+            Focus::Cmd => (self.selected_cmd, self.config.commands.len()),
+            Focus::Files if self.files_tree => {
+                let rows = self.file_rows();
+                (self.file_tree_cursor(&rows), rows.len())
+            }
+            Focus::Files => (self.selected_file, self.status.files.len()),
+            Focus::Commits => (self.selected_commit, self.commit_list.commits.len()),
+            Focus::Matches => (self.selected_match, self.match_list.matches.len()),
+            Focus::Stashes => (self.selected_stash, self.stashes.len()),
+            Focus::Branches => (self.selected_branch, self.branches.len()),
+            Focus::Remotes => (self.selected_remote, self.remotes.len()),
+            Focus::Worktrees => (self.selected_worktree, self.worktrees.len()),
+            Focus::Log | Focus::Result => return None,
+        })
+    }
+
+    /// Selects `row` as moving there with j/k would.
+    fn select_row(&mut self, pane: Focus, row: usize) {
+        let Some((selected, len)) = self.list_selection(pane) else {
+            return;
+        };
+        if row >= len || row == selected {
+            return;
+        }
+        match pane {
+            Focus::Cmd => self.selected_cmd = row,
+            Focus::Files if self.files_tree => self.select_file_tree_row(row),
+            Focus::Files => {
+                self.selected_file = row;
+                self.show_diff_for_selected_file(true);
+            }
+            Focus::Commits => {
+                self.selected_commit = row;
+                self.show_selected_commit();
+            }
+            Focus::Matches => self.selected_match = row,
+            Focus::Stashes => self.selected_stash = row,
+            Focus::Branches => self.selected_branch = row,
+            Focus::Remotes => self.selected_remote = row,
+            Focus::Worktrees => self.selected_worktree = row,
+            Focus::Log | Focus::Result => {}
+        }
+    }
+
+    fn scroll_pane(&mut self, pane: Focus, notches: i32) {
+        let (view_h, len, scroll) = match pane {
+            Focus::Log => (self.log_view_height, self.log_lines.len(), self.log_scroll),
+            Focus::Result => (
+                self.result_view_height,
+                self.result_lines.len(),
+                self.result_scroll,
+            ),
+            _ => {
+                if let Some((selected, _)) = self.list_selection(pane) {
+                    self.select_row(pane, selected.saturating_add_signed(notches as isize));
+                }
+                return;
+            }
+        };
+        let max_scroll = len.saturating_sub(view_h as usize) as i32;
+        let scroll = (scroll as i32 + notches * WHEEL_LINES).clamp(0, max_scroll.max(0)) as u16;
+        if pane == Focus::Log {
+            self.log_scroll = scroll;
+            if self.log_diff.is_some() {
+                self.clamp_log_cursor();
+            }
+        } else {
+            self.result_scroll = scroll;
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::ViewModel;
+use crate::git::{LfsMode, quote_arg, valid_branch_name};
+
+/// Fields of `[branches] template` answered so far by `:new-branch`.
+pub(super) struct NewBranch {
+    fields: Vec<String>,
+    answers: Vec<String>,
+    input: String,
+}
+
+impl ViewModel {
+    /// `:new-branch` asks for each `{field}` of the name template, then creates the branch.
+    pub(super) fn start_new_branch(&mut self) {
+        if self.refuse_read_only("creating branches") {
+            return;
+        }
+        if self.is_running() || self.flow.is_some() {
+            self.result_lines
+                .push("WARN: already running command".into());
+            return;
+        }
+        let fields = template_fields(&self.config.branches.template);
+        if fields.is_empty() {
+            self.result_lines = vec![format!(
+                "[branches] template has no {{field}}: \"{}\"",
+                self.config.branches.template
+            )];
+            self.result_scroll = 0;
+            return;
+        }
+        self.result_lines = vec![format!(
+            "new branch: {} (Enter to continue, Esc cancels)",
+            self.config.branches.template
+        )];
+        self.result_scroll = 0;
+        self.new_branch = Some(NewBranch {
+            fields,
+            answers: Vec::new(),
+            input: String::new(),
+        });
+    }
+
+    pub(super) fn handle_new_branch_key(&mut self, key: KeyEvent) {
+        let Some(form) = self.new_branch.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.new_branch = None;
+                self.result_lines = vec!["new branch canceled".into()];
+                self.result_scroll = 0;
+            }
+            KeyCode::Backspace => {
+                form.input.pop();
+            }
+            KeyCode::Char(c) => form.input.push(c),
+            KeyCode::Enter => {
+                let answer = std::mem::take(&mut form.input);
+                form.answers.push(answer.trim().to_string());
+                if form.answers.len() == form.fields.len() {
+                    let form = self.new_branch.take().expect("checked above");
+                    self.create_branch_from_template(form);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn create_branch_from_template(&mut self, form: NewBranch) {
+        let name = form.fields.iter().zip(&form.answers).fold(
+            self.config.branches.template.clone(),
+            |name, (field, answer)| {
+                let value = if field == "slug" {
+                    slugify(answer)
+                } else {
+                    answer.clone()
+                };
+                name.replace(&format!("{{{}}}", field), &value)
+            },
+        );
+        let name = tidy_separators(&name);
+        if !valid_branch_name(&self.config.git_path, &self.repo_root, &name) {
+            self.result_lines = vec![format!(
+                "ERROR: \"{}\" is not a valid branch name (git check-ref-format)",
+                name
+            )];
+            self.result_scroll = 0;
+            return;
+        }
+        self.run_command(
+            format!("switch -c {}", quote_arg(&name)),
+            LfsMode::None,
+            false,
+        );
+    }
+
+    pub(super) fn new_branch_prompt(&self) -> Option<(&str, &str)> {
+        let form = self.new_branch.as_ref()?;
+        let field = form.fields.get(form.answers.len())?;
+        Some((field.as_str(), form.input.as_str()))
+    }
+}
+
+/// `{name}` placeholders of `template` in order of first appearance.
+fn template_fields(template: &str) -> Vec<String> {
+    let mut fields: Vec<String> = Vec::new();
+    let mut rest = template;
+    while let Some((_, after)) = rest.split_once('{') {
+        let Some((field, tail)) = after.split_once('}') else {
+            break;
+        };
+        if !field.is_empty() && !fields.iter().any(|f| f == field) {
+            fields.push(field.to_string());
+        }
+        rest = tail;
+    }
+    fields
+}
+
+/// `Fix login crash!` becomes `fix-login-crash`.
+fn slugify(text: &str) -> String {
+    let slug: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    slug.split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Drops separators left dangling by empty fields: `feat/-login` becomes `feat/login`.
+fn tidy_separators(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        let dangling = matches!(c, '-' | '_' | '/')
+            && out
+                .chars()
+                .last()
+                .is_none_or(|prev| matches!(prev, '-' | '_' | '/'));
+        if !dangling {
+            out.push(c);
+        }
+    }
+    out.trim_end_matches(['-', '_', '/']).to_string()
+}
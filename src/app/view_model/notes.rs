@@ -0,0 +1,37 @@
+use super::ViewModel;
+use crate::notes::{append_line, first_line, note_path};
+
+impl ViewModel {
+    pub(super) fn load_branch_note(&mut self) {
+        self.branch_note =
+            note_path(&self.repo_root, &self.status.branch).and_then(|p| first_line(&p));
+    }
+
+    /// `:note` opens the current branch's scratchpad in the editor; `:note <text>` appends to it.
+    pub(super) fn branch_note_command(&mut self, text: &str) {
+        let Some(path) = note_path(&self.repo_root, &self.status.branch) else {
+            self.result_lines
+                .push("ERROR: no config directory for branch notes".into());
+            return;
+        };
+
+        if text.is_empty() {
+            if let Some(dir) = path.parent()
+                && let Err(e) = std::fs::create_dir_all(dir)
+            {
+                self.result_lines
+                    .push(format!("ERROR: cannot create {}: {}", dir.display(), e));
+                return;
+            }
+            self.open_editor(&path, 1);
+        } else if let Err(e) = append_line(&path, text) {
+            self.result_lines
+                .push(format!("ERROR: cannot write {}: {}", path.display(), e));
+            return;
+        } else {
+            self.result_lines = vec![format!("note added for {}", self.status.branch)];
+            self.result_scroll = 0;
+        }
+        self.load_branch_note();
+    }
+}
@@ -0,0 +1,62 @@
+use super::ViewModel;
+use crate::git::{is_network_error, step_label};
+
+impl ViewModel {
+    /// After a network command: a failure to reach the remote enters offline mode,
+    /// a success leaves it and reruns the next queued command.
+    pub(super) fn check_network(&mut self, success: bool) {
+        let Some((args_str, lfs_mode)) = self.network_cmd.take() else {
+            return;
+        };
+        if success {
+            self.offline = false;
+            if !self.offline_queue.is_empty() {
+                let (args_str, lfs_mode) = self.offline_queue.remove(0);
+                self.run_command(args_str, lfs_mode, false);
+            }
+            return;
+        }
+        if !is_network_error(&self.result_lines) {
+            return;
+        }
+        self.offline = true;
+        let note = if !self.config.network.retry_offline {
+            "offline: remote unreachable; network commands are marked until one succeeds"
+        } else if self.offline_queue.iter().any(|(a, _)| *a == args_str) {
+            "offline: remote unreachable; already queued for retry"
+        } else {
+            self.offline_queue.push((args_str, lfs_mode));
+            "offline: remote unreachable; queued to retry after the next network command succeeds"
+        };
+        self.result_lines.insert(0, note.into());
+        self.result_scroll = 0;
+    }
+
+    /// `:offline` shows the offline state and retry queue; `:offline clear` drops both.
+    pub(super) fn offline_command(&mut self, arg: &str) {
+        if arg == "clear" {
+            self.offline = false;
+            self.offline_queue.clear();
+            self.result_lines = vec!["offline state and retry queue cleared".into()];
+            self.result_scroll = 0;
+            return;
+        }
+        let mut lines = vec![if self.offline {
+            "offline: the last network command could not reach the remote".to_string()
+        } else {
+            "online".to_string()
+        }];
+        if self.offline_queue.is_empty() {
+            lines.push("no commands queued for retry".into());
+        } else {
+            lines.push("queued for retry (:offline clear drops them):".into());
+            lines.extend(
+                self.offline_queue
+                    .iter()
+                    .map(|(a, _)| format!("  {}", step_label(a))),
+            );
+        }
+        self.result_lines = lines;
+        self.result_scroll = 0;
+    }
+}
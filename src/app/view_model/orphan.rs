@@ -0,0 +1,190 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::{UiMessage, ViewModel};
+use crate::git::{
+    CommandResult, local_branch_exists, quote_arg, run_step, tracked_files, valid_branch_name,
+};
+use crate::jobs::JobKind;
+
+/// `:orphan` collecting a branch name and an initial commit message, then waiting for `y`.
+pub(super) struct Orphan {
+    name: Option<String>,
+    message: Option<String>,
+    input: String,
+    /// Git steps shown with the warnings, run once confirmed.
+    steps: Vec<String>,
+}
+
+impl ViewModel {
+    /// `:orphan` creates a branch with no history (for docs or `gh-pages`): it asks for a
+    /// name and an optional initial commit, warns what happens to the worktree, and runs
+    /// `checkout --orphan`, clears the index, then commits if a message was given.
+    pub(super) fn start_orphan(&mut self) {
+        if self.refuse_read_only("creating branches") {
+            return;
+        }
+        if self.is_running() || self.flow.is_some() {
+            self.result_lines
+                .push("WARN: already running command".into());
+            return;
+        }
+        self.result_lines = vec!["orphan branch: a branch with no history (Esc cancels)".into()];
+        self.result_scroll = 0;
+        self.orphan = Some(Orphan {
+            name: None,
+            message: None,
+            input: String::new(),
+            steps: Vec::new(),
+        });
+    }
+
+    pub(super) fn handle_orphan_key(&mut self, key: KeyEvent) {
+        let Some(form) = self.orphan.as_mut() else {
+            return;
+        };
+        if form.message.is_some() {
+            let form = self.orphan.take().expect("checked above");
+            if key.code == KeyCode::Char('y') {
+                self.create_orphan(form.steps);
+            } else {
+                self.record(&format!("declined {}", self.argv(&form.steps[0])));
+                self.result_lines = vec!["orphan branch canceled".into()];
+                self.result_scroll = 0;
+            }
+            return;
+        }
+        match key.code {
+            KeyCode::Esc => {
+                self.orphan = None;
+                self.result_lines = vec!["orphan branch canceled".into()];
+                self.result_scroll = 0;
+            }
+            KeyCode::Backspace => {
+                form.input.pop();
+            }
+            KeyCode::Char(c) => form.input.push(c),
+            KeyCode::Enter => {
+                let answer = std::mem::take(&mut form.input).trim().to_string();
+                if form.name.is_none() {
+                    self.orphan_name(answer);
+                } else {
+                    self.warn_orphan(answer);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn orphan_name(&mut self, name: String) {
+        let git = &self.config.git_path;
+        let error = if name.is_empty() || !valid_branch_name(git, &self.repo_root, &name) {
+            format!("not a valid branch name: {:?}", name)
+        } else if local_branch_exists(git, &self.repo_root, &name) {
+            format!("branch {} already exists", name)
+        } else {
+            if let Some(form) = self.orphan.as_mut() {
+                form.name = Some(name);
+            }
+            return;
+        };
+        self.result_lines = vec![format!("ERROR: {} (type another, Esc cancels)", error)];
+        self.result_scroll = 0;
+    }
+
+    /// Spells out what the orphan branch does to this worktree and lists the steps.
+    fn warn_orphan(&mut self, message: String) {
+        let Some(form) = self.orphan.as_mut() else {
+            return;
+        };
+        let name = form.name.clone().unwrap_or_default();
+        let mut steps = vec![
+            format!("checkout --orphan {}", quote_arg(&name)),
+            "rm -r -q --cached --ignore-unmatch .".to_string(),
+        ];
+        if !message.is_empty() {
+            steps.push(format!("commit --allow-empty -m {}", quote_arg(&message)));
+        }
+        form.steps.clone_from(&steps);
+        form.message = Some(message);
+
+        let branch = self.status.branch.clone();
+        let tracked = tracked_files(&self.config.git_path, &self.repo_root).len();
+        let mut lines =
+            vec![
+            format!("== orphan branch: {} ==", name),
+            String::new(),
+            format!("!! {} starts with NO history: it shares no commit with {}.", name, branch),
+            format!(
+                "!! The index is cleared: all {} tracked file(s) become untracked.",
+                tracked
+            ),
+            "!! The files stay on disk; remove what the new branch should not hold before `add`."
+                .into(),
+        ];
+        let changed = self.status.staged + self.status.unstaged;
+        if changed > 0 {
+            lines.push(format!(
+                "!! {} uncommitted change(s) are no longer tracked; staged-only content is lost.",
+                changed
+            ));
+        }
+        lines.push(format!(
+            "!! Switching back to {} fails while untracked files would be overwritten.",
+            branch
+        ));
+        lines.push(String::new());
+        lines.extend(steps.iter().map(|s| format!("run: {}", self.argv(s))));
+        self.set_log_lines(lines);
+        self.result_lines = vec![format!(
+            "Create orphan branch {}? (y creates it, any other key cancels)",
+            name
+        )];
+        self.result_scroll = 0;
+    }
+
+    fn create_orphan(&mut self, steps: Vec<String>) {
+        if self.dry_run {
+            let mut lines = vec!["dry-run: would run".to_string()];
+            lines.extend(steps.iter().map(|s| format!("  git {}", s)));
+            self.result_lines = lines;
+            self.result_scroll = 0;
+            return;
+        }
+        for step in &steps[..steps.len() - 1] {
+            self.record(&format!("$ {}", self.argv(step)));
+        }
+        self.start_running(&steps[steps.len() - 1]);
+        self.set_log_lines(vec!["<running...>".into()]);
+        self.result_lines = vec!["creating orphan branch...".into()];
+        self.result_scroll = 0;
+        let git = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        let load_status = self.status_loader();
+        self.jobs
+            .spawn(JobKind::Command, "orphan branch", move |ctx| {
+                let mut res = CommandResult {
+                    log_lines: Vec::new(),
+                    result_lines: Vec::new(),
+                    success: true,
+                };
+                for step in &steps {
+                    if !run_step(&git, step, &repo, ctx.cancel_token(), &mut res) {
+                        res.success = false;
+                        break;
+                    }
+                }
+                ctx.send(UiMessage::StatusLoaded(load_status()));
+                UiMessage::CommandFinished(res)
+            });
+    }
+
+    pub(super) fn orphan_prompt(&self) -> Option<(&str, &str)> {
+        let form = self.orphan.as_ref()?;
+        let question = match (&form.name, &form.message) {
+            (None, _) => "orphan branch name",
+            (Some(_), None) => "initial commit message (empty: no commit)",
+            (Some(_), Some(_)) => return None,
+        };
+        Some((question, form.input.as_str()))
+    }
+}
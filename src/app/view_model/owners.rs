@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use super::{Confirmed, PendingCommand, ViewModel};
+use crate::codeowners::CodeOwners;
+use crate::git::{branch_changed_files, parse_args_line};
+
+const UNOWNED: &str = "(no owner)";
+
+impl ViewModel {
+    pub(super) fn reload_codeowners(&mut self) {
+        self.codeowners = CodeOwners::load(&self.repo_root);
+    }
+
+    /// `:owners` lists who owns the files this branch changes.
+    pub(super) fn show_owners(&mut self) {
+        let Some(owners) = &self.codeowners else {
+            self.result_lines = vec!["no CODEOWNERS file".into()];
+            self.result_scroll = 0;
+            return;
+        };
+        let path = owners.path.clone();
+        match self.branch_owners() {
+            Some(groups) => {
+                let reviewers = reviewers(&groups);
+                let mut lines = vec![format!("== owners ({}) ==", path.display())];
+                lines.extend(group_lines(&groups));
+                self.set_log_lines(lines);
+                self.result_lines = vec![if reviewers.is_empty() {
+                    "no owner reviews needed".into()
+                } else {
+                    format!("reviews needed from: {}", reviewers.join(", "))
+                }];
+            }
+            None => {
+                self.result_lines = vec!["cannot tell where this branch forked".into()];
+            }
+        }
+        self.result_scroll = 0;
+    }
+
+    /// Before a push, lists the owners whose review the branch needs and waits for `y`.
+    /// Returns the command back when there is nothing to show.
+    pub(super) fn show_push_owners(&mut self, pending: PendingCommand) -> Option<PendingCommand> {
+        let is_push = parse_args_line(&pending.args_str)
+            .first()
+            .is_some_and(|a| a == "push");
+        if !is_push || !self.config.codeowners.push_summary || self.codeowners.is_none() {
+            return Some(pending);
+        }
+        let Some(groups) = self.branch_owners().filter(|g| !g.is_empty()) else {
+            return Some(pending);
+        };
+        let reviewers = reviewers(&groups);
+        let mut lines = vec![
+            "== pre-push: code owners ==".to_string(),
+            format!("$ git {}", pending.args_str),
+        ];
+        lines.extend(group_lines(&groups));
+        self.set_log_lines(lines);
+        self.result_lines = vec![format!(
+            "Push? {} (y/Enter to push, any other key cancels)",
+            if reviewers.is_empty() {
+                "no owner reviews needed".to_string()
+            } else {
+                format!("reviews needed from {}", reviewers.join(", "))
+            }
+        )];
+        self.result_scroll = 0;
+        self.await_confirmation(None, Confirmed::Push(pending));
+        None
+    }
+
+    /// Owners of FILES entry `index`, as listed in CODEOWNERS.
+    pub(super) fn file_owners(&self, index: usize) -> Option<String> {
+        let owners = self.codeowners.as_ref()?;
+        let path = self.status.files.get(index)?.path.clone();
+        let owners = owners.owners(&path);
+        (!owners.is_empty()).then(|| owners.join(" "))
+    }
+
+    /// Files changed on this branch by owner; unowned files are grouped under `(no owner)`.
+    fn branch_owners(&self) -> Option<BTreeMap<String, Vec<String>>> {
+        let owners = self.codeowners.as_ref()?;
+        let files = branch_changed_files(
+            &self.config.git_path,
+            &self.repo_root,
+            self.default_branch.as_deref(),
+        )?;
+        let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for file in files {
+            match owners.owners(&file) {
+                [] => groups.entry(UNOWNED.into()).or_default().push(file),
+                list => {
+                    for owner in list {
+                        groups.entry(owner.clone()).or_default().push(file.clone());
+                    }
+                }
+            }
+        }
+        Some(groups)
+    }
+}
+
+fn reviewers(groups: &BTreeMap<String, Vec<String>>) -> Vec<&str> {
+    groups
+        .keys()
+        .map(String::as_str)
+        .filter(|o| *o != UNOWNED)
+        .collect()
+}
+
+fn group_lines(groups: &BTreeMap<String, Vec<String>>) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (owner, files) in groups {
+        lines.push(String::new());
+        lines.push(format!("{}  {} file(s)", owner, files.len()));
+        lines.extend(files.iter().map(|f| format!("  {}", f)));
+    }
+    lines
+}
@@ -0,0 +1,185 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::{Confirmed, Mode, PendingCommand, ViewModel};
+use crate::git::quote_arg;
+
+/// A configured command waiting for its `{input:prompt}` answers.
+pub(super) struct CommandInputs {
+    name: String,
+    /// Question of `confirm`, asked once the answers are in.
+    confirm: Option<String>,
+    pending: PendingCommand,
+    /// `{input:...}` placeholders in order of first appearance.
+    placeholders: Vec<String>,
+    answers: Vec<String>,
+    input: String,
+}
+
+/// A configured command with `scope = "ask"`, whose line waits on `:` for a pathspec.
+pub(super) struct AskedCommand {
+    name: String,
+    confirm: Option<String>,
+    pending: PendingCommand,
+}
+
+impl ViewModel {
+    /// Fills `{default_branch}`, `{branch}`, `{file}` and `{files}` in a configured
+    /// command; an error names the first one without a value.
+    pub(super) fn fill_placeholders(&self, cmd: &str) -> Result<String, String> {
+        let mut cmd = self.fill_default_branch(cmd)?;
+        if cmd.contains("{branch}") {
+            let branch = &self.status.branch;
+            if branch.is_empty() || branch == "HEAD" {
+                return Err("{branch} is unknown: HEAD is not on a branch".into());
+            }
+            cmd = cmd.replace("{branch}", &quote_arg(branch));
+        }
+        if cmd.contains("{file}") {
+            let Some(entry) = self.status.files.get(self.selected_file) else {
+                return Err("{file} is unknown: no file selected".into());
+            };
+            cmd = cmd.replace("{file}", &quote_arg(&entry.path));
+        }
+        if cmd.contains("{files}") {
+            let files: Vec<String> = self
+                .target_files()
+                .iter()
+                .map(|f| quote_arg(&f.path))
+                .collect();
+            if files.is_empty() {
+                return Err("{files} is unknown: no file selected or marked".into());
+            }
+            cmd = cmd.replace("{files}", &files.join(" "));
+        }
+        Ok(cmd)
+    }
+
+    /// Runs a configured command, first asking for each `{input:prompt}` it has and
+    /// then its `confirm` question.
+    pub(super) fn run_with_inputs(
+        &mut self,
+        name: &str,
+        confirm: Option<String>,
+        pending: PendingCommand,
+    ) {
+        let placeholders = input_placeholders(&pending.args_str);
+        if placeholders.is_empty() {
+            self.confirm_or_run(confirm, pending);
+            return;
+        }
+        self.result_lines = vec![format!("{}: (Enter to continue, Esc cancels)", name)];
+        self.result_scroll = 0;
+        self.command_inputs = Some(CommandInputs {
+            name: name.to_string(),
+            confirm,
+            pending,
+            placeholders,
+            answers: Vec::new(),
+            input: String::new(),
+        });
+    }
+
+    /// Puts the command's line on `:` to edit; Enter runs it as the configured command,
+    /// with its inputs, `confirm` question and hooks.
+    pub(super) fn ask_pathspec(
+        &mut self,
+        name: String,
+        confirm: Option<String>,
+        pending: PendingCommand,
+    ) {
+        self.cmdline = pending.args_str.clone();
+        self.mode = Mode::CommandLine;
+        self.asked_command = Some(AskedCommand {
+            name,
+            confirm,
+            pending,
+        });
+    }
+
+    pub(super) fn run_asked_command(&mut self, mut asked: AskedCommand, line: String) {
+        asked.pending.args_str = line;
+        self.run_with_inputs(&asked.name, asked.confirm, asked.pending);
+    }
+
+    fn confirm_or_run(&mut self, confirm: Option<String>, pending: PendingCommand) {
+        match confirm {
+            Some(question) => self.ask_confirmation(question, None, Confirmed::Command(pending)),
+            None => self.run_command_with_hooks(
+                pending.args_str,
+                pending.lfs_mode,
+                pending.interactive,
+                pending.hooks,
+            ),
+        }
+    }
+
+    pub(super) fn handle_command_inputs_key(&mut self, key: KeyEvent) {
+        let Some(form) = self.command_inputs.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.result_lines = vec![format!("{} canceled", form.name)];
+                self.result_scroll = 0;
+                self.command_inputs = None;
+            }
+            KeyCode::Backspace => {
+                form.input.pop();
+            }
+            KeyCode::Char(c) => form.input.push(c),
+            KeyCode::Enter => {
+                let answer = std::mem::take(&mut form.input);
+                form.answers.push(answer);
+                if form.answers.len() == form.placeholders.len() {
+                    let mut form = self.command_inputs.take().expect("checked above");
+                    form.pending.args_str = form.placeholders.iter().zip(&form.answers).fold(
+                        form.pending.args_str,
+                        |cmd, (placeholder, answer)| {
+                            cmd.replace(placeholder.as_str(), &quote_arg(answer))
+                        },
+                    );
+                    self.confirm_or_run(form.confirm, form.pending);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Question and typed answer of the `{input:prompt}` being asked.
+    pub(super) fn command_inputs_prompt(&self) -> Option<(&str, &str)> {
+        let form = self.command_inputs.as_ref()?;
+        let placeholder = form.placeholders.get(form.answers.len())?;
+        Some((input_prompt(placeholder), form.input.as_str()))
+    }
+}
+
+/// `{input:prompt}` placeholders of `cmd` in order of first appearance; `{input}`
+/// asks with a plain `input`.
+fn input_placeholders(cmd: &str) -> Vec<String> {
+    let mut placeholders: Vec<String> = Vec::new();
+    let mut rest = cmd;
+    while let Some(start) = rest.find("{input") {
+        let after = &rest[start..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        let placeholder = &after[..=end];
+        if (placeholder == "{input}" || placeholder.starts_with("{input:"))
+            && !placeholders.iter().any(|p| p == placeholder)
+        {
+            placeholders.push(placeholder.to_string());
+        }
+        rest = &after[end + 1..];
+    }
+    placeholders
+}
+
+/// The question of an `{input:prompt}` placeholder.
+fn input_prompt(placeholder: &str) -> &str {
+    placeholder
+        .trim_start_matches("{input")
+        .trim_end_matches('}')
+        .strip_prefix(':')
+        .filter(|prompt| !prompt.is_empty())
+        .unwrap_or("input")
+}
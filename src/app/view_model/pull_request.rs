@@ -0,0 +1,124 @@
+use std::fs;
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::audit::command_argv;
+use super::{UiMessage, ViewModel};
+use crate::git::branch_commit_messages;
+use crate::jobs::JobKind;
+use crate::pull_request::{draft, draft_path, gh_pr_create, load_template, parse_draft, run_gh};
+
+const LABEL: &str = "!gh pr create";
+
+impl ViewModel {
+    /// `:pr` writes a pull request description from the repository's template and this
+    /// branch's commits, opens it in the editor, then offers `gh pr create`. An earlier
+    /// draft is reopened as is; `:pr new` starts over.
+    pub(super) fn pull_request_command(&mut self, arg: &str) {
+        if self.refuse_read_only("creating a pull request") {
+            return;
+        }
+        if self.is_running() {
+            self.result_lines
+                .push("WARN: already running command".into());
+            return;
+        }
+        let branch = self.status.branch.clone();
+        let Some(path) = draft_path(&self.repo_root, &branch) else {
+            self.result_lines = vec!["ERROR: no config directory for pull request drafts".into()];
+            self.result_scroll = 0;
+            return;
+        };
+        if arg == "new" || !path.exists() {
+            let messages = branch_commit_messages(
+                &self.config.git_path,
+                &self.repo_root,
+                self.default_branch.as_deref(),
+            )
+            .unwrap_or_default();
+            if messages.is_empty() {
+                self.result_lines = vec![format!(
+                    "no commits on {} to open a pull request for",
+                    branch
+                )];
+                self.result_scroll = 0;
+                return;
+            }
+            let template = load_template(&self.repo_root).map(|(_, text)| text);
+            let text = draft(&branch, &messages, template.as_deref());
+            if let Err(e) = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, text))
+            {
+                self.result_lines = vec![format!("ERROR: cannot write {}: {}", path.display(), e)];
+                self.result_scroll = 0;
+                return;
+            }
+        }
+        self.open_editor(&path, 1);
+        let Some((title, body)) = fs::read_to_string(&path)
+            .ok()
+            .as_deref()
+            .and_then(parse_draft)
+        else {
+            self.result_lines =
+                vec!["pull request canceled: the first line (title) is empty".into()];
+            self.result_scroll = 0;
+            return;
+        };
+        let argv = command_argv(&gh_pr_create(&title));
+        let mut lines = vec![
+            "== pull request ==".to_string(),
+            title.clone(),
+            String::new(),
+        ];
+        lines.extend(body.lines().map(str::to_string));
+        self.set_log_lines(lines);
+        self.result_scroll = 0;
+        if self.dry_run {
+            self.result_lines = vec![format!("dry-run: would run {}", argv)];
+            return;
+        }
+        self.result_lines = vec![
+            format!("$ {}", argv),
+            format!("draft: {}", path.display()),
+            "Create this pull request? (y/Enter to create, e to edit again, any other key cancels)"
+                .into(),
+        ];
+        self.pending_pr = Some((title, body));
+    }
+
+    pub(super) fn handle_pr_key(&mut self, key: KeyEvent) {
+        let Some((title, body)) = self.pending_pr.take() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => self.create_pull_request(title, body),
+            KeyCode::Char('e') => self.pull_request_command(""),
+            _ => {
+                self.result_lines = vec!["pull request canceled; :pr reopens the draft".into()];
+                self.result_scroll = 0;
+            }
+        }
+    }
+
+    fn create_pull_request(&mut self, title: String, body: String) {
+        self.record(&format!("$ {}", command_argv(&gh_pr_create(&title))));
+        self.begin_running(LABEL);
+        self.set_log_lines(vec!["<running...>".into()]);
+        self.result_lines = vec![format!("$ gh pr create --title {:?}", title)];
+        self.result_scroll = 0;
+        let repo = self.repo_root.clone();
+        let path = draft_path(&self.repo_root, &self.status.branch);
+        self.jobs.spawn(JobKind::Command, "gh pr create", move |_| {
+            let res = run_gh(gh_pr_create(&title), &repo, &body);
+            if res.success
+                && let Some(path) = path
+            {
+                let _ = fs::remove_file(path);
+            }
+            UiMessage::CommandFinished(res)
+        });
+    }
+}
@@ -0,0 +1,38 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::ViewModel;
+use crate::range_diff::PairStatus;
+
+impl ViewModel {
+    /// Puts a count of each kind of pair above the output of `:range-diff`.
+    pub(super) fn summarize_range_diff(&mut self) {
+        let Some(diff) = &self.log_range_diff else {
+            return;
+        };
+        let summary = format!(
+            "range-diff: {} same, {} changed, {} dropped, {} new ([/] jump between commits)",
+            diff.count(PairStatus::Same),
+            diff.count(PairStatus::Changed),
+            diff.count(PairStatus::Dropped),
+            diff.count(PairStatus::New)
+        );
+        self.result_lines.insert(0, summary);
+    }
+
+    /// `]`/`[` scroll LOG to the next or previous commit pair; other keys scroll as usual.
+    pub(super) fn handle_range_diff_keys(&mut self, key: KeyEvent) -> anyhow::Result<()> {
+        let Some(diff) = &self.log_range_diff else {
+            return Ok(());
+        };
+        let top = self.log_scroll as usize;
+        let target = match key.code {
+            KeyCode::Char(']') => diff.next_pair(top),
+            KeyCode::Char('[') => diff.prev_pair(top),
+            _ => return self.handle_scroll_keys(key, true),
+        };
+        if let Some(line) = target {
+            self.scroll_log_to(line);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use super::{Hooks, ViewModel};
+use crate::git::{LfsMode, is_read_only_command, step_label};
+
+impl ViewModel {
+    /// Under `--read-only` (or `read_only = true`), reports `what` as disabled and returns true.
+    pub(super) fn refuse_read_only(&mut self, what: &str) -> bool {
+        if !self.config.read_only {
+            return false;
+        }
+        self.result_lines = vec![format!("read-only: {} is disabled", what)];
+        self.result_scroll = 0;
+        true
+    }
+
+    /// Whether a hook or flow step may run.
+    pub(super) fn allows_step(&self, step: &str) -> bool {
+        !self.config.read_only || is_read_only_step(step)
+    }
+
+    /// Refuses a command that changes the repository, or whose hooks might.
+    pub(super) fn refuse_mutating_command(
+        &mut self,
+        args_str: &str,
+        lfs_mode: &LfsMode,
+        hooks: &Hooks,
+    ) -> bool {
+        if !self.config.read_only {
+            return false;
+        }
+        if !is_read_only_command(args_str, lfs_mode) {
+            return self.refuse_read_only(&format!("`git {}`", args_str));
+        }
+        match [&hooks.on_success, &hooks.on_failure]
+            .into_iter()
+            .flatten()
+            .find(|hook| !self.allows_step(hook))
+        {
+            Some(hook) => {
+                let what = format!("`{}` (hook of `git {}`)", step_label(hook), args_str);
+                self.refuse_read_only(&what)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Whether `git <args_str>`, its LFS step or its hooks could change anything.
+pub(super) fn mutates(args_str: &str, lfs_mode: &LfsMode, hooks: &Hooks) -> bool {
+    !is_read_only_command(args_str, lfs_mode)
+        || [&hooks.on_success, &hooks.on_failure]
+            .into_iter()
+            .flatten()
+            .any(|hook| !is_read_only_step(hook))
+}
+
+/// A git step that only reads; shell steps could change anything.
+pub(super) fn is_read_only_step(step: &str) -> bool {
+    !step.starts_with('!') && is_read_only_command(step, &LfsMode::None)
+}
@@ -0,0 +1,102 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::{Confirmed, Focus, Mode, PendingCommand, ViewModel, list_move};
+use crate::git::{LfsMode, Remote, quote_arg, remote_list};
+
+impl ViewModel {
+    /// Rereads `git remote -v` for the REMOTES panel.
+    pub(super) fn load_remotes(&mut self) {
+        self.remotes = remote_list(&self.config.git_path, &self.repo_root);
+        self.selected_remote = self
+            .selected_remote
+            .min(self.remotes.len().saturating_sub(1));
+    }
+
+    /// `:remotes` shows the REMOTES panel.
+    pub(super) fn show_remotes(&mut self) {
+        self.load_remotes();
+        self.list_panel = Focus::Remotes;
+        self.focus = Focus::Remotes;
+        self.result_lines = vec![format!("{} remote(s)", self.remotes.len())];
+        self.result_scroll = 0;
+    }
+
+    pub(super) fn handle_remote_keys(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.run_on_selected_remote("remote show"),
+            KeyCode::Char('f') => self.run_on_selected_remote("fetch"),
+            KeyCode::Char('p') => self.run_on_selected_remote("remote prune"),
+            KeyCode::Char('a') => {
+                self.mode = Mode::CommandLine;
+                self.cmdline = "remote add ".into();
+            }
+            KeyCode::Char('r') => self.prefill_for_selected_remote("remote rename"),
+            KeyCode::Char('u') => self.prefill_for_selected_remote("remote set-url"),
+            KeyCode::Char('D') => self.handle_remote_remove_key(),
+            _ => {
+                if let Some(row) = list_move(
+                    self.key_action(&key),
+                    self.selected_remote,
+                    self.remotes.len(),
+                ) {
+                    self.selected_remote = row;
+                }
+            }
+        }
+    }
+
+    /// `git <verb> <selected remote>`.
+    fn run_on_selected_remote(&mut self, verb: &str) {
+        if let Some(name) = self.selected_remote_name() {
+            self.run_command(
+                format!("{} {}", verb, quote_arg(&name)),
+                LfsMode::None,
+                false,
+            );
+        }
+    }
+
+    /// Opens the command line with `<verb> <selected remote> ` for the rest to be typed.
+    fn prefill_for_selected_remote(&mut self, verb: &str) {
+        if let Some(name) = self.selected_remote_name() {
+            self.mode = Mode::CommandLine;
+            self.cmdline = format!("{} {} ", verb, quote_arg(&name));
+        }
+    }
+
+    /// `D` asks first, as removing a remote also drops its remote-tracking branches;
+    /// pressing it again (or `y`) removes it.
+    fn handle_remote_remove_key(&mut self) {
+        if self.refuse_read_only("removing remotes") {
+            return;
+        }
+        let Some(name) = self.selected_remote_name() else {
+            return;
+        };
+        self.ask_confirmation(
+            format!(
+                "Remove remote \"{}\" and its remote-tracking branches?",
+                name
+            ),
+            Some('D'),
+            Confirmed::Command(PendingCommand::plain(format!(
+                "remote remove {}",
+                quote_arg(&name)
+            ))),
+        );
+    }
+
+    fn selected_remote_name(&self) -> Option<String> {
+        self.remotes
+            .get(self.selected_remote)
+            .map(|r| r.name.clone())
+    }
+
+    pub fn remotes(&self) -> &[Remote] {
+        &self.remotes
+    }
+
+    pub fn selected_remote(&self) -> usize {
+        self.selected_remote
+    }
+}
@@ -0,0 +1,241 @@
+use std::env;
+use std::path::PathBuf;
+
+use super::audit::Audited;
+use super::confirm::Confirmed;
+use super::{UiMessage, ViewModel};
+use crate::git::{CommandResult, git_command, head_commit, quote_arg, run_step};
+use crate::jobs::JobKind;
+use crate::notes::sanitize;
+
+/// A temporary worktree for reviewing a branch or pull request, removed with its tab.
+pub struct Review {
+    /// The branch, or `#<number>` for a pull request.
+    pub target: String,
+    pub path: PathBuf,
+    /// The repository the worktree belongs to.
+    main_root: PathBuf,
+    /// `HEAD` when the tab opened; commits made since sit on a detached HEAD.
+    start: Option<String>,
+    /// Closing was confirmed, so uncommitted changes go with the worktree.
+    discard: bool,
+}
+
+/// What a tab asks of the app: opening a review tab, moving between tabs, reopening
+/// this one on another worktree, or closing this review tab.
+pub enum TabRequest {
+    Open(Review),
+    Next,
+    Prev,
+    Switch(PathBuf),
+    Close,
+}
+
+impl ViewModel {
+    /// `:review <branch>` or `:review #<number>` checks the branch (or the pull request's
+    /// head from `origin`) out into a temporary worktree and opens a tab on it.
+    pub(super) fn start_review(&mut self, target: &str) {
+        if target.is_empty() {
+            self.result_lines = vec!["usage: :review <branch> | :review #<pr number>".into()];
+            self.result_scroll = 0;
+            return;
+        }
+        if self.refuse_read_only("review worktrees") {
+            return;
+        }
+        if self.is_running() {
+            self.result_lines
+                .push("WARN: already running command".into());
+            return;
+        }
+        let path = env::temp_dir().join(format!(
+            "simple-git-tui-review-{}-{}",
+            sanitize(target.trim_start_matches('#')),
+            std::process::id()
+        ));
+        let worktree = format!(
+            "worktree add --detach {}",
+            quote_arg(&path.to_string_lossy())
+        );
+        let steps = match target.strip_prefix('#') {
+            Some(number) if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) => {
+                vec![
+                    format!("fetch origin pull/{}/head", number),
+                    format!("{} FETCH_HEAD", worktree),
+                ]
+            }
+            Some(_) => {
+                self.result_lines = vec![format!("not a pull request number: {}", target)];
+                self.result_scroll = 0;
+                return;
+            }
+            None => vec![format!("{} {}", worktree, quote_arg(target))],
+        };
+        let review = Review {
+            target: target.to_string(),
+            path,
+            main_root: self.repo_root.clone(),
+            start: None,
+            discard: false,
+        };
+        if self.dry_run {
+            let mut lines = vec!["dry-run: would run".to_string()];
+            lines.extend(steps.iter().map(|s| format!("  git {}", s)));
+            self.result_lines = lines;
+            self.result_scroll = 0;
+            return;
+        }
+        if self.config.audit {
+            let mut lines = vec![
+                "== audit: confirm ==".to_string(),
+                format!("run: {}", self.argv(&steps[0])),
+            ];
+            lines.extend(steps[1..].iter().map(|s| format!("then: {}", self.argv(s))));
+            self.ask_audit(lines, Audited::Review(review, steps));
+            return;
+        }
+        self.create_review(review, steps);
+    }
+
+    pub(super) fn create_review(&mut self, review: Review, steps: Vec<String>) {
+        for step in &steps[..steps.len() - 1] {
+            self.record(&format!("$ {}", self.argv(step)));
+        }
+        self.start_running(&steps[steps.len() - 1]);
+        self.set_log_lines(vec!["<running...>".into()]);
+        self.result_lines = vec![format!("creating a review worktree for {}", review.target)];
+        self.result_scroll = 0;
+        let git = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        self.jobs.spawn(
+            JobKind::Command,
+            format!("review {}", review.target),
+            move |ctx| {
+                let mut res = CommandResult {
+                    log_lines: Vec::new(),
+                    result_lines: Vec::new(),
+                    success: true,
+                };
+                for step in &steps {
+                    if !run_step(&git, step, &repo, ctx.cancel_token(), &mut res) {
+                        res.success = false;
+                        break;
+                    }
+                }
+                UiMessage::ReviewReady(res, review)
+            },
+        );
+    }
+
+    pub(super) fn finish_review(&mut self, res: CommandResult, review: Review) {
+        self.finish_running();
+        self.record_outcome(res.success);
+        self.set_log_lines(res.log_lines);
+        self.result_lines = res.result_lines;
+        self.result_scroll = 0;
+        if res.success {
+            self.result_lines.insert(
+                0,
+                format!(
+                    "review tab opened for {} ({{ and }} switch tabs, q closes it)",
+                    review.target
+                ),
+            );
+            self.tab_request = Some(TabRequest::Open(review));
+        }
+    }
+
+    /// A tab on the worktree of `review`, set up like this one.
+    pub fn open_review(&self, mut review: Review) -> ViewModel {
+        let mut tab = ViewModel::new(self.config.clone(), self.theme.clone(), review.path.clone());
+        review.start = head_commit(&self.config.git_path, &review.path);
+        tab.review = Some(review);
+        tab.init();
+        tab
+    }
+
+    /// `q` in a review tab: whether to close it now. Uncommitted changes or commits
+    /// made in the worktree are asked about first, as removing it loses them.
+    pub(super) fn may_close_review(&mut self) -> bool {
+        let Some(review) = &self.review else {
+            return true;
+        };
+        let git = &self.config.git_path;
+        let dirty = git_command(git)
+            .args(["status", "--porcelain"])
+            .current_dir(&review.path)
+            .output()
+            .is_ok_and(|o| !o.stdout.is_empty());
+        let moved = head_commit(git, &review.path) != review.start;
+        let lost = match (dirty, moved) {
+            (false, false) => return true,
+            (true, false) => "its uncommitted changes",
+            (false, true) => "commits made on its detached HEAD",
+            (true, true) => "its uncommitted changes and commits made on its detached HEAD",
+        };
+        let question = format!(
+            "Close the review of {}? Removing the worktree loses {}",
+            review.target, lost
+        );
+        self.ask_confirmation(question, None, Confirmed::CloseReview);
+        false
+    }
+
+    /// The close was confirmed: the worktree goes even with changes in it.
+    pub(super) fn close_review_anyway(&mut self) {
+        if let Some(review) = &mut self.review {
+            review.discard = true;
+            self.tab_request = Some(TabRequest::Close);
+        }
+    }
+
+    /// Removes this tab's review worktree; returns what happened, for the tab left behind.
+    /// Unless closing was confirmed, git refuses to remove one with changes in it.
+    pub fn close_review(&mut self) -> Option<String> {
+        let review = self.review.take()?;
+        self.jobs.cancel_foreground();
+        let mut remove = git_command(&self.config.git_path);
+        remove.args(["worktree", "remove"]);
+        if review.discard {
+            remove.arg("--force");
+        }
+        let removed = remove
+            .arg(&review.path)
+            .current_dir(&review.main_root)
+            .output();
+        Some(match removed {
+            Ok(output) if output.status.success() => {
+                format!("review of {} closed; worktree removed", review.target)
+            }
+            Ok(output) => format!(
+                "WARN: cannot remove {}: {}",
+                review.path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => format!("WARN: cannot remove {}: {}", review.path.display(), e),
+        })
+    }
+
+    /// Shows `line` in R; how the app reports a closed review tab.
+    pub fn notify(&mut self, line: String) {
+        self.result_lines = vec![line];
+        self.result_scroll = 0;
+    }
+
+    pub fn take_tab_request(&mut self) -> Option<TabRequest> {
+        self.tab_request.take()
+    }
+
+    /// Where this tab sits among `count` open tabs, for the status bar.
+    pub fn set_tab_position(&mut self, index: usize, count: usize) {
+        self.tab_position = (index, count);
+    }
+
+    pub fn tab_position(&self) -> (usize, usize) {
+        self.tab_position
+    }
+
+    pub fn review(&self) -> Option<&Review> {
+        self.review.as_ref()
+    }
+}
@@ -0,0 +1,41 @@
+use super::ViewModel;
+use crate::git::{parse_args_line, quote_arg};
+
+/// Subcommands whose output is narrowed to the active scope.
+const SCOPED_SUBCOMMANDS: [&str; 4] = ["log", "status", "diff", "show"];
+
+impl ViewModel {
+    /// `:scope <path>` restricts status, diffs and logs to a subtree; `:scope` clears it.
+    pub(super) fn set_scope(&mut self, path: &str) {
+        let path = path.trim_matches('"').trim_end_matches(['/', '\\']);
+        if path.is_empty() || path == "." {
+            self.scope = None;
+            self.result_lines = vec!["scope cleared".into()];
+        } else if !self.repo_root.join(path).exists() {
+            self.result_lines = vec![format!("ERROR: scope path does not exist: {}", path)];
+            self.result_scroll = 0;
+            return;
+        } else {
+            self.scope = Some(path.replace('\\', "/"));
+            self.result_lines = vec![format!("scope: {}", path)];
+        }
+        self.result_scroll = 0;
+        self.selected_file = 0;
+        self.refresh_repo_status();
+    }
+
+    /// Appends the scope pathspec to log/status/diff/show invocations that have none.
+    pub(super) fn apply_scope(&self, args_str: String) -> String {
+        let Some(scope) = &self.scope else {
+            return args_str;
+        };
+        let args = parse_args_line(&args_str);
+        let scoped = args
+            .first()
+            .is_some_and(|a| SCOPED_SUBCOMMANDS.contains(&a.as_str()));
+        if !scoped || args.iter().any(|a| a == "--") {
+            return args_str;
+        }
+        format!("{} -- {}", args_str, quote_arg(scope))
+    }
+}
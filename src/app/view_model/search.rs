@@ -0,0 +1,190 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::{Focus, ViewModel};
+use crate::diff::strip_ansi;
+
+/// A `/` search of LOG or R.
+pub(super) struct PaneSearch {
+    pub(super) term: String,
+    /// Line of the match `n`/`N` last moved to.
+    line: Option<usize>,
+}
+
+impl ViewModel {
+    /// `/` in LOG or R: starts typing a search of the pane.
+    fn start_pane_search(&mut self) {
+        self.search_input = Some((self.focus, String::new()));
+    }
+
+    /// What is typed after `/`, shown on the status line.
+    pub fn search_input(&self) -> Option<&str> {
+        self.search_input.as_ref().map(|(_, input)| input.as_str())
+    }
+
+    pub(super) fn handle_search_input_key(&mut self, key: KeyEvent) {
+        let Some((pane, input)) = &mut self.search_input else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.search_input = None,
+            KeyCode::Enter => {
+                let (pane, term) = (*pane, std::mem::take(input));
+                self.search_input = None;
+                self.search_pane(pane, term);
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    /// Keys of LOG and R for searching: `/`, `n`/`N` to the next and previous match
+    /// and Esc to drop the search. False when `key` is left to the pane.
+    pub(super) fn handle_pane_search_key(&mut self, key: KeyEvent) -> bool {
+        if self.focus == Focus::Log && self.hunk_view.is_some() {
+            return false;
+        }
+        let active = self.pane_search(self.focus).is_some();
+        match key.code {
+            KeyCode::Char('/') => self.start_pane_search(),
+            KeyCode::Char('n') if active => self.next_search_match(self.focus, true),
+            KeyCode::Char('N') if active => self.next_search_match(self.focus, false),
+            KeyCode::Esc if active => *self.pane_search_mut(self.focus) = None,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Searches `pane` for `term` from where it is scrolled to; an empty term drops
+    /// the search.
+    fn search_pane(&mut self, pane: Focus, term: String) {
+        if term.is_empty() {
+            *self.pane_search_mut(pane) = None;
+            return;
+        }
+        *self.pane_search_mut(pane) = Some(PaneSearch { term, line: None });
+        self.next_search_match(pane, true);
+    }
+
+    /// Moves to the first match after the current one, or before it when not
+    /// `forward`, wrapping around at the ends.
+    fn next_search_match(&mut self, pane: Focus, forward: bool) {
+        let matches = self.search_matches(pane);
+        let Some(search) = self.pane_search(pane) else {
+            return;
+        };
+        // Without a match moved to yet, the line searching starts from counts too.
+        let (after, before) = match search.line {
+            Some(line) => (line + 1, line),
+            None => {
+                let top = self.search_position(pane);
+                (top, top)
+            }
+        };
+        let target = if forward {
+            matches.iter().find(|&&l| l >= after).or(matches.first())
+        } else {
+            matches
+                .iter()
+                .rev()
+                .find(|&&l| l < before)
+                .or(matches.last())
+        };
+        let Some(&line) = target else {
+            if let Some(search) = self.pane_search_mut(pane) {
+                search.line = None;
+            }
+            return;
+        };
+        if let Some(search) = self.pane_search_mut(pane) {
+            search.line = Some(line);
+        }
+        self.show_search_match(pane, line);
+    }
+
+    /// The line searching starts from: the diff cursor, or the top of the pane.
+    fn search_position(&self, pane: Focus) -> usize {
+        match pane {
+            Focus::Log if self.log_diff.is_some() => self.log_cursor,
+            Focus::Log => self.log_scroll as usize,
+            _ => self.result_scroll as usize,
+        }
+    }
+
+    /// Brings `line` into view, in the middle of the pane where it can be.
+    fn show_search_match(&mut self, pane: Focus, line: usize) {
+        match pane {
+            Focus::Log if self.log_diff.is_some() => self.move_log_cursor(line),
+            Focus::Log => {
+                let half = self.log_view_height as usize / 2;
+                self.scroll_log_to(line.saturating_sub(half));
+            }
+            _ => {
+                let view_h = self.result_view_height as usize;
+                let max_scroll = self.result_lines.len().saturating_sub(view_h);
+                let top = line.saturating_sub(view_h / 2).min(max_scroll);
+                self.result_scroll = top.min(u16::MAX as usize) as u16;
+            }
+        }
+    }
+
+    /// Lines of `pane` holding the search term, as indexes the pane scrolls by.
+    fn search_matches(&self, pane: Focus) -> Vec<usize> {
+        let Some(search) = self.pane_search(pane) else {
+            return Vec::new();
+        };
+        let term = search.term.as_str();
+        match pane {
+            Focus::Log if let Some(diff) = &self.log_diff => diff
+                .lines
+                .iter()
+                .enumerate()
+                .filter_map(|(i, l)| l.text.contains(term).then_some(i))
+                .collect(),
+            Focus::Log => Self::matching_lines(&self.log_lines, term),
+            _ => Self::matching_lines(&self.result_lines, term),
+        }
+    }
+
+    fn matching_lines(lines: &[String], term: &str) -> Vec<usize> {
+        lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, l)| strip_ansi(l).contains(term).then_some(i))
+            .collect()
+    }
+
+    fn pane_search(&self, pane: Focus) -> Option<&PaneSearch> {
+        match pane {
+            Focus::Log => self.log_search.as_ref(),
+            Focus::Result => self.result_search.as_ref(),
+            _ => None,
+        }
+    }
+
+    fn pane_search_mut(&mut self, pane: Focus) -> &mut Option<PaneSearch> {
+        match pane {
+            Focus::Log => &mut self.log_search,
+            _ => &mut self.result_search,
+        }
+    }
+
+    /// The term R is searched for, to mark it.
+    pub fn result_search_term(&self) -> Option<&str> {
+        self.result_search.as_ref().map(|s| s.term.as_str())
+    }
+
+    /// `/term 3/17` for the title of LOG or R: the match moved to and how many
+    /// lines match.
+    pub fn search_status(&self, pane: Focus) -> Option<String> {
+        let search = self.pane_search(pane)?;
+        let matches = self.search_matches(pane);
+        let current = search
+            .line
+            .and_then(|line| matches.iter().position(|&l| l == line))
+            .map_or(0, |i| i + 1);
+        Some(format!("/{} {}/{}", search.term, current, matches.len()))
+    }
+}
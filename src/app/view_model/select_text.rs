@@ -0,0 +1,37 @@
+use std::io::{self, BufRead, Write};
+
+use super::{Focus, ViewModel, suspended};
+use crate::diff::strip_ansi;
+
+impl ViewModel {
+    /// `v` on LOG or R prints the pane's text, without borders or colors, on the normal
+    /// screen where the terminal's own mouse selection can copy from it, and comes back
+    /// on Enter.
+    pub(super) fn select_text(&mut self) {
+        let (pane, lines) = if self.focus == Focus::Log {
+            ("LOG", &self.log_lines)
+        } else {
+            ("R", &self.result_lines)
+        };
+        let text: Vec<String> = lines.iter().map(|l| strip_ansi(l)).collect();
+        let shown = suspended(|| -> io::Result<()> {
+            let mut stdout = io::stdout().lock();
+            for line in &text {
+                writeln!(stdout, "{}", line)?;
+            }
+            write!(
+                stdout,
+                "\n-- {}: select with the mouse to copy; Enter returns --",
+                pane
+            )?;
+            stdout.flush()?;
+            io::stdin().lock().read_line(&mut String::new())?;
+            Ok(())
+        });
+        if let Err(e) = shown.and_then(|shown| Ok(shown?)) {
+            self.result_lines
+                .push(format!("ERROR: cannot show {} for selection: {}", pane, e));
+        }
+        self.needs_full_redraw = true;
+    }
+}
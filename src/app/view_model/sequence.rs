@@ -0,0 +1,133 @@
+use super::ViewModel;
+use crate::git::{LfsMode, Operation, PickSequence};
+
+impl ViewModel {
+    /// `:continue` carries on with a stopped merge, rebase, cherry-pick, revert or
+    /// `git am` once every conflict is resolved and staged.
+    pub(super) fn continue_sequence(&mut self) {
+        let Some(command) = self.operation_command("continue") else {
+            return;
+        };
+        let unmerged: Vec<String> = self
+            .status
+            .files
+            .iter()
+            .filter(|f| f.is_conflicted())
+            .map(|f| format!("  {}", f.path))
+            .collect();
+        if !unmerged.is_empty() {
+            self.result_lines = vec![format!(
+                "resolve and stage these before continuing the {}:",
+                command
+            )];
+            self.result_lines.extend(unmerged);
+            self.result_scroll = 0;
+            return;
+        }
+        let args = match command {
+            // A merge is concluded by committing it, with the message git prepared.
+            "merge" => "commit --no-edit".to_string(),
+            // Keeps the message of a commit the rebase stopped on instead of asking for one.
+            "rebase" => "-c core.editor=true rebase --continue".to_string(),
+            "am" => "am --continue".to_string(),
+            _ => format!("{} --no-edit --continue", command),
+        };
+        self.run_command(args, LfsMode::None, false);
+    }
+
+    /// `:abort` gives up the stopped operation and puts back what was checked out
+    /// before it started.
+    pub(super) fn abort_operation(&mut self) {
+        if self.refuse_read_only("aborting") {
+            return;
+        }
+        if let Some(command) = self.operation_command("abort") {
+            self.run_command(format!("{} --abort", command), LfsMode::None, false);
+        }
+    }
+
+    /// `:skip` leaves out the commit or patch the operation stopped on and goes on
+    /// with the rest.
+    pub(super) fn skip_operation(&mut self) {
+        let Some(command) = self.operation_command("skip") else {
+            return;
+        };
+        if command == "merge" {
+            self.result_lines = vec!["a merge has nothing to skip; :continue or :abort it".into()];
+            self.result_scroll = 0;
+            return;
+        }
+        self.run_command(format!("{} --skip", command), LfsMode::None, false);
+    }
+
+    /// The git command of the merge, rebase, cherry-pick, revert or `git am` in
+    /// progress; reports that there is none to `action` otherwise.
+    fn operation_command(&mut self, action: &str) -> Option<&'static str> {
+        let command = match (self.status.operation, &self.status.sequence) {
+            (Some(operation), _) => Some(operation.command()),
+            (None, Some(sequence)) => Some(sequence.command),
+            (None, None) => None,
+        };
+        if command.is_none() {
+            self.result_lines = vec![format!(
+                "nothing to {}: no merge, rebase, cherry-pick or revert in progress",
+                action
+            )];
+            self.result_scroll = 0;
+        }
+        command
+    }
+
+    /// Says how to go on when a merge, rebase, cherry-pick or revert has just stopped.
+    pub(super) fn note_operation(&mut self) {
+        let Some(operation) = self.status.operation else {
+            return;
+        };
+        let skip = if operation == Operation::Merge {
+            ""
+        } else {
+            ", :skip leaves this step out"
+        };
+        self.result_lines.push(format!(
+            "{}: resolve and stage the conflicts, then :continue; :abort gives up{}",
+            operation.label(),
+            skip
+        ));
+    }
+
+    /// After a commit: when it was the resolution of the commit a cherry-pick or revert
+    /// stopped on, applies the rest of the range the way `--continue` would.
+    pub(super) fn resume_sequence(&mut self) {
+        let Some(sequence) = &self.status.sequence else {
+            return;
+        };
+        if sequence.stopped.is_some() {
+            return;
+        }
+        let (command, remaining) = (sequence.command, sequence.remaining());
+        if self.is_running() {
+            self.result_lines.push(format!(
+                "{} has {} commit(s) left; :continue applies them",
+                command, remaining
+            ));
+            return;
+        }
+        self.run_command(
+            format!("{} --no-edit --continue", command),
+            LfsMode::None,
+            false,
+        );
+        self.result_lines.insert(
+            0,
+            format!("resuming the {}: {} commit(s) left", command, remaining),
+        );
+    }
+
+    pub fn pick_sequence(&self) -> Option<&PickSequence> {
+        self.status.sequence.as_ref()
+    }
+
+    pub fn operation(&self) -> Option<Operation> {
+        self.status.operation
+    }
+}
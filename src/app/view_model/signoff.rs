@@ -0,0 +1,35 @@
+use super::commit_preview::has_commit_flag;
+use super::{ViewModel, is_commit};
+use crate::git::{signoff_required, signoff_trailer};
+
+impl ViewModel {
+    /// Adds `--signoff` to commits in repositories that require DCO sign-off.
+    /// `None` when the commit is blocked because it would not be signed off.
+    pub(super) fn apply_signoff(&mut self, args_str: String) -> Option<String> {
+        let git = &self.config.git_path;
+        if !is_commit(&args_str) || !signoff_required(git, &self.repo_root) {
+            return Some(args_str);
+        }
+        let blocked = if has_commit_flag(&args_str, None, "--no-signoff") {
+            Some("this repository requires DCO sign-off; drop --no-signoff")
+        } else if signoff_trailer(git, &self.repo_root).is_none() {
+            Some("sign-off needs a committer identity; set user.name and user.email")
+        } else {
+            None
+        };
+        if let Some(reason) = blocked {
+            self.result_lines = vec![
+                format!("$ git {}", args_str),
+                format!("commit blocked: {}", reason),
+            ];
+            self.result_scroll = 0;
+            return None;
+        }
+        if has_commit_flag(&args_str, Some('s'), "--signoff") {
+            return Some(args_str);
+        }
+        // Right after `commit`, so it cannot end up behind a `--` pathspec separator.
+        let rest = args_str.trim_start().strip_prefix("commit").unwrap_or("");
+        Some(format!("commit --signoff{}", rest))
+    }
+}
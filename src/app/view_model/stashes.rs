@@ -0,0 +1,145 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::{Confirmed, Focus, Mode, PendingCommand, ViewModel, list_move};
+use crate::git::{LfsMode, Stash, quote_arg, stash_list, stash_paths, valid_branch_name};
+
+impl ViewModel {
+    /// Rereads `git stash list` for the STASHES panel.
+    pub(super) fn load_stashes(&mut self) {
+        self.stashes = stash_list(&self.config.git_path, &self.repo_root);
+        self.selected_stash = self
+            .selected_stash
+            .min(self.stashes.len().saturating_sub(1));
+    }
+
+    /// `:stashes` shows the STASHES panel.
+    pub(super) fn show_stashes(&mut self) {
+        self.load_stashes();
+        self.list_panel = Focus::Stashes;
+        self.focus = Focus::Stashes;
+        self.result_lines = vec![format!("{} stash(es)", self.stashes.len())];
+        self.result_scroll = 0;
+    }
+
+    pub(super) fn handle_stash_keys(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {
+                if let Some(name) = self.selected_stash_name() {
+                    self.run_command(
+                        format!("stash show -p {}", quote_arg(&name)),
+                        LfsMode::None,
+                        false,
+                    );
+                }
+            }
+            KeyCode::Char('w') => self.diff_stash_worktree(),
+            KeyCode::Char('b') if !self.stashes.is_empty() => {
+                self.mode = Mode::CommandLine;
+                self.cmdline = "stash-branch ".into();
+            }
+            KeyCode::Char('a') => self.run_on_selected_stash("apply"),
+            KeyCode::Char('p') => self.run_on_selected_stash("pop"),
+            KeyCode::Char('D') => self.handle_stash_drop_key(),
+            KeyCode::Char('s') => self.run_command("stash push".into(), LfsMode::None, false),
+            KeyCode::Char('S') => self.run_command(
+                "stash push --include-untracked".into(),
+                LfsMode::None,
+                false,
+            ),
+            _ => {
+                if let Some(row) = list_move(
+                    self.key_action(&key),
+                    self.selected_stash,
+                    self.stashes.len(),
+                ) {
+                    self.selected_stash = row;
+                }
+            }
+        }
+    }
+
+    /// `git stash <verb> <selected stash>`.
+    fn run_on_selected_stash(&mut self, verb: &str) {
+        if let Some(name) = self.selected_stash_name() {
+            self.run_command(
+                format!("stash {} {}", verb, quote_arg(&name)),
+                LfsMode::None,
+                false,
+            );
+        }
+    }
+
+    /// `D` asks first, as a dropped stash is only recoverable by its hash; pressing it
+    /// again (or `y`) drops it.
+    fn handle_stash_drop_key(&mut self) {
+        if self.refuse_read_only("dropping stashes") {
+            return;
+        }
+        let Some(name) = self.selected_stash_name() else {
+            return;
+        };
+        let subject = &self.stashes[self.selected_stash].subject;
+        self.ask_confirmation(
+            format!("Drop {} ({})?", name, subject),
+            Some('D'),
+            Confirmed::Command(PendingCommand::plain(format!(
+                "stash drop {}",
+                quote_arg(&name)
+            ))),
+        );
+    }
+
+    /// How the files the stash touches differ from the worktree: `-` lines are the
+    /// worktree now, `+` lines what the stash holds.
+    fn diff_stash_worktree(&mut self) {
+        let Some(name) = self.selected_stash_name() else {
+            return;
+        };
+        let paths = stash_paths(&self.config.git_path, &self.repo_root, &name);
+        if paths.is_empty() {
+            self.result_lines = vec![format!("{} changes no tracked files", name)];
+            self.result_scroll = 0;
+            return;
+        }
+        let operands: Vec<String> = paths.iter().map(|p| quote_arg(p)).collect();
+        self.run_command(
+            format!("diff -R {} -- {}", quote_arg(&name), operands.join(" ")),
+            LfsMode::None,
+            false,
+        );
+    }
+
+    /// `:stash-branch <name>` creates `name` at the commit the selected stash was made on
+    /// and applies the stash there, dropping it if that succeeds.
+    pub(super) fn stash_branch(&mut self, name: &str) {
+        let Some(stash) = self.selected_stash_name() else {
+            self.result_lines = vec!["no stash selected; :stashes lists them".into()];
+            self.result_scroll = 0;
+            return;
+        };
+        if name.is_empty() || !valid_branch_name(&self.config.git_path, &self.repo_root, name) {
+            self.result_lines = vec![format!("not a valid branch name: {:?}", name)];
+            self.result_scroll = 0;
+            return;
+        }
+        self.run_command(
+            format!("stash branch {} {}", quote_arg(name), quote_arg(&stash)),
+            LfsMode::None,
+            false,
+        );
+    }
+
+    fn selected_stash_name(&self) -> Option<String> {
+        self.stashes
+            .get(self.selected_stash)
+            .map(|s| s.name.clone())
+    }
+
+    pub fn stashes(&self) -> &[Stash] {
+        &self.stashes
+    }
+
+    pub fn selected_stash(&self) -> usize {
+        self.selected_stash
+    }
+}
@@ -0,0 +1,102 @@
+use super::{UiMessage, ViewModel};
+use crate::git::{SubmoduleState, submodule_states};
+use crate::jobs::JobKind;
+
+impl ViewModel {
+    /// Refreshes submodule states in the background; `show` lists them in LOG when done.
+    pub(super) fn load_submodules(&mut self, show: bool) {
+        if !self.repo_root.join(".gitmodules").exists() {
+            self.submodules.clear();
+            if show {
+                self.result_lines = vec!["no submodules".into()];
+                self.result_scroll = 0;
+            }
+            return;
+        }
+        let git = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        self.jobs
+            .spawn(JobKind::Submodules, "submodules", move |_| {
+                UiMessage::SubmodulesLoaded {
+                    states: submodule_states(&git, &repo),
+                    show,
+                }
+            });
+        if show {
+            self.result_lines = vec!["submodules: loading...".into()];
+            self.result_scroll = 0;
+        }
+    }
+
+    pub(super) fn show_submodules(&mut self) {
+        let width = self
+            .submodules
+            .iter()
+            .map(|s| s.path.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        let mut lines = vec![format!("{:<width$}  {:<7}  state", "path", "head")];
+        for s in &self.submodules {
+            let mut state = Vec::new();
+            if !s.initialized {
+                state.push("not initialized".to_string());
+            }
+            if s.moved {
+                state.push("moved from recorded commit".to_string());
+            }
+            if s.is_dirty() {
+                state.push(format!("{} change(s)", s.changes));
+            }
+            if s.ahead > 0 {
+                state.push(format!("{} ahead", s.ahead));
+            }
+            if s.behind > 0 {
+                state.push(format!("{} behind", s.behind));
+            }
+            if state.is_empty() {
+                state.push("clean".to_string());
+            }
+            lines.push(format!(
+                "{:<width$}  {:<7}  {}",
+                s.path,
+                s.head,
+                state.join(", ")
+            ));
+        }
+        self.set_log_lines(lines);
+        self.result_lines = vec![format!(
+            "submodules: {} ({})",
+            self.submodules.len(),
+            self.submodule_summary()
+                .map(|s| s.trim_start_matches("submodules: ").to_string())
+                .unwrap_or_else(|| "none".into())
+        )];
+        self.result_scroll = 0;
+    }
+
+    /// Status bar text such as `submodules: 2 dirty, 1 ahead`.
+    pub(super) fn submodule_summary(&self) -> Option<String> {
+        if self.submodules.is_empty() {
+            return None;
+        }
+        let count =
+            |f: fn(&SubmoduleState) -> bool| self.submodules.iter().filter(|s| f(s)).count();
+        let parts: Vec<String> = [
+            (count(|s| s.is_dirty()), "dirty"),
+            (count(|s| s.ahead > 0), "ahead"),
+            (count(|s| s.behind > 0), "behind"),
+            (count(|s| s.moved), "moved"),
+            (count(|s| !s.initialized), "uninitialized"),
+        ]
+        .into_iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, label)| format!("{} {}", n, label))
+        .collect();
+        Some(if parts.is_empty() {
+            format!("submodules: {} clean", self.submodules.len())
+        } else {
+            format!("submodules: {}", parts.join(", "))
+        })
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use super::ViewModel;
+use super::events::Event;
+use crate::config::app_dir;
+use crate::git::{RepoFile, head_summary};
+
+/// Commit counts worth a note in the R pane.
+const MILESTONES: [usize; 6] = [5, 10, 25, 50, 100, 250];
+
+/// What happened since the TUI started, shown by `:summary`.
+pub(super) struct SessionStats {
+    started: Instant,
+    started_at: SystemTime,
+    /// `<short hash> <subject>` of each commit made.
+    commits: Vec<String>,
+    /// Paths that had staged changes at some point.
+    staged: BTreeSet<String>,
+    commands: Vec<String>,
+}
+
+impl SessionStats {
+    pub(super) fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            started_at: SystemTime::now(),
+            commits: Vec::new(),
+            staged: BTreeSet::new(),
+            commands: Vec::new(),
+        }
+    }
+
+    pub(super) fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+
+    pub(super) fn note_command(&mut self, args_str: &str) {
+        self.commands.push(args_str.to_string());
+    }
+
+    pub(super) fn note_status(&mut self, files: &[RepoFile]) {
+        self.staged.extend(
+            files
+                .iter()
+                .filter(|f| !matches!(f.status.chars().next(), Some(' ' | '?') | None))
+                .map(|f| f.path.clone()),
+        );
+    }
+}
+
+impl ViewModel {
+    /// Counts a commit made from the TUI, then runs the `on_commit` hook.
+    pub(super) fn note_commit(&mut self) {
+        let summary = head_summary(&self.config.git_path, &self.repo_root)
+            .unwrap_or_else(|| "(unknown commit)".into());
+        self.session.commits.push(summary);
+        let count = self.session.commits.len();
+        if MILESTONES.contains(&count) {
+            self.result_lines
+                .push(format!("milestone: {} commits this session", count));
+        }
+        self.fire_event(Event::Commit);
+    }
+
+    /// `:summary` lists this session's work; `:summary export [path]` also writes it as Markdown.
+    pub(super) fn show_summary(&mut self, arg: &str) {
+        let lines = self.summary_lines();
+        let export = match arg.split_once(char::is_whitespace) {
+            Some(("export", path)) => Some(Some(path.trim())),
+            None if arg == "export" => Some(None),
+            _ => None,
+        };
+        self.set_log_lines(lines.clone());
+        self.result_lines = vec![format!(
+            "session: {} commit(s), {} file(s) staged, {} command(s)",
+            self.session.commits.len(),
+            self.session.staged.len(),
+            self.session.commands.len()
+        )];
+        self.result_scroll = 0;
+        let Some(path) = export else {
+            return;
+        };
+        let path = match path.filter(|p| !p.is_empty()) {
+            Some(p) => self.repo_root.join(p),
+            None => match app_dir() {
+                Some(dir) => dir
+                    .join("summaries")
+                    .join(format!("session-{}.md", unix_secs(self.session.started_at))),
+                None => {
+                    self.result_lines
+                        .push("ERROR: no config directory; give a path to export to".into());
+                    return;
+                }
+            },
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&path, markdown(&lines)));
+        self.result_lines.push(match written {
+            Ok(()) => format!("exported to {}", path.display()),
+            Err(e) => format!("ERROR: cannot write {}: {}", path.display(), e),
+        });
+    }
+
+    fn summary_lines(&self) -> Vec<String> {
+        let s = &self.session;
+        let mut lines = vec![
+            format!("== session summary: {} ==", self.repo_root.display()),
+            format!(
+                "started {} UTC, {} ago",
+                utc_datetime(unix_secs(s.started_at)),
+                format_elapsed(s.started.elapsed().as_secs())
+            ),
+            String::new(),
+            format!("commits made: {}", s.commits.len()),
+        ];
+        if self.config.audit
+            && let Some(path) = self.transcript_path()
+        {
+            lines.insert(2, format!("transcript: {}", path.display()));
+        }
+        lines.extend(s.commits.iter().map(|c| format!("  {}", c)));
+        lines.push(String::new());
+        lines.push(format!("files staged: {}", s.staged.len()));
+        lines.extend(s.staged.iter().map(|p| format!("  {}", p)));
+        lines.push(String::new());
+        lines.push(format!("commands run: {}", s.commands.len()));
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for cmd in &s.commands {
+            match counts.iter_mut().find(|(c, _)| *c == cmd) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((cmd, 1)),
+            }
+        }
+        counts.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+        lines.extend(
+            counts
+                .iter()
+                .map(|(cmd, n)| format!("  {:>3}x  git {}", n, cmd)),
+        );
+        lines
+    }
+}
+
+/// The summary as a Markdown document: the title as a heading, sections as lists.
+fn markdown(lines: &[String]) -> String {
+    let mut out = String::new();
+    for line in lines {
+        let text = if let Some(title) = line.strip_prefix("== ").and_then(|l| l.strip_suffix(" =="))
+        {
+            format!("# {}", title)
+        } else if let Some(item) = line.strip_prefix("  ") {
+            format!("- `{}`", item.trim())
+        } else if line.is_empty() {
+            String::new()
+        } else {
+            format!("**{}**", line)
+        };
+        out.push_str(&text);
+        out.push('\n');
+    }
+    out
+}
+
+/// `1h 05m` or `12m`.
+fn format_elapsed(secs: u64) -> String {
+    match (secs / 3600, secs % 3600 / 60) {
+        (0, m) => format!("{}m", m),
+        (h, m) => format!("{}h {:02}m", h, m),
+    }
+}
+
+pub(super) fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// `2024-03-01 09:30` for seconds since the epoch.
+pub(super) fn utc_datetime(secs: u64) -> String {
+    // Civil-from-days conversion for the proleptic Gregorian calendar.
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs % 86_400 / 3600,
+        secs % 3600 / 60
+    )
+}
@@ -0,0 +1,141 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use super::ViewModel;
+use super::summary::{unix_secs, utc_datetime};
+use crate::config::app_dir;
+use crate::notes::append_line;
+
+/// One-line explanations shown next to the echoed command.
+const EXPLANATIONS: [(&str, &str); 22] = [
+    ("add", "stages changes for the next commit"),
+    (
+        "restore",
+        "--staged unstages files; otherwise throws away edits",
+    ),
+    ("commit", "records the staged changes as a new commit"),
+    ("push", "uploads your commits to the remote"),
+    (
+        "pull",
+        "fetches from the remote and merges into this branch",
+    ),
+    ("fetch", "downloads commits without changing your branch"),
+    ("diff", "shows changes that are not committed yet"),
+    ("show", "shows one commit: its message and changes"),
+    ("log", "lists commits, newest first"),
+    ("status", "lists staged, unstaged and untracked files"),
+    ("clean", "deletes untracked files"),
+    ("switch", "changes to another branch; -c creates it first"),
+    ("checkout", "switches branches or restores files"),
+    ("branch", "lists, creates or deletes branches"),
+    ("stash", "shelves your changes to get a clean work tree"),
+    ("merge", "joins another branch into this one"),
+    ("rebase", "replays your commits on top of another base"),
+    (
+        "reset",
+        "moves the branch tip; may unstage or discard changes",
+    ),
+    ("grep", "searches the tracked files"),
+    ("blame", "shows who last changed each line"),
+    ("tag", "names a commit, usually a release"),
+    ("lfs", "Git LFS: keeps large files outside the repository"),
+];
+
+impl ViewModel {
+    /// With `teaching = true`, shows `command` above the status bar; `log` also adds it
+    /// to today's list (`:today`).
+    pub(super) fn teach(&mut self, command: &str, log: bool) {
+        if !self.config.teaching {
+            return;
+        }
+        self.teach_echo = Some(command.to_string());
+        if !log {
+            return;
+        }
+        let Some(path) = today_path() else {
+            return;
+        };
+        let secs = unix_secs(SystemTime::now());
+        let time = &utc_datetime(secs)[11..];
+        if let Err(e) = append_line(&path, &format!("{}\t{}", time, command)) {
+            self.result_lines
+                .push(format!("WARN: cannot write {}: {}", path.display(), e));
+        }
+    }
+
+    /// `:today` lists the commands behind what you did today (UTC), with a tally.
+    pub(super) fn show_today(&mut self) {
+        let entries: Vec<(String, String)> = today_path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|l| l.split_once('\t'))
+            .map(|(time, cmd)| (time.to_string(), cmd.to_string()))
+            .collect();
+        if entries.is_empty() {
+            self.result_lines = vec![if self.config.teaching {
+                "no commands recorded today".into()
+            } else {
+                "no commands recorded today; set teaching = true to keep the list".into()
+            }];
+            self.result_scroll = 0;
+            return;
+        }
+        let mut lines = vec![
+            format!("== commands you ran today ({} UTC) ==", today()),
+            String::new(),
+        ];
+        lines.extend(
+            entries
+                .iter()
+                .map(|(time, cmd)| format!("{}  {}", time, cmd)),
+        );
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for (_, cmd) in &entries {
+            let sub = subcommand(cmd).unwrap_or("shell");
+            match counts.iter_mut().find(|(s, _)| *s == sub) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((sub, 1)),
+            }
+        }
+        counts.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+        lines.push(String::new());
+        lines.push(format!(
+            "most used: {}",
+            counts
+                .iter()
+                .map(|(sub, n)| format!("{} {}x", sub, n))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        self.set_log_lines(lines);
+        self.result_lines = vec![format!("{} command(s) today", entries.len())];
+        self.result_scroll = 0;
+    }
+
+    /// The last echoed command and what its subcommand does.
+    pub fn teaching_echo(&self) -> Option<(&str, Option<&str>)> {
+        let command = self.teach_echo.as_deref()?;
+        let explanation = subcommand(command).and_then(|sub| {
+            EXPLANATIONS
+                .iter()
+                .find(|(name, _)| *name == sub)
+                .map(|(_, text)| *text)
+        });
+        Some((command, explanation))
+    }
+}
+
+/// `add` for `git add -- a.txt`; `None` for shell commands.
+fn subcommand(command: &str) -> Option<&str> {
+    command.strip_prefix("git ")?.split_whitespace().next()
+}
+
+fn today() -> String {
+    utc_datetime(unix_secs(SystemTime::now()))[..10].to_string()
+}
+
+fn today_path() -> Option<PathBuf> {
+    Some(app_dir()?.join("teaching").join(format!("{}.txt", today())))
+}
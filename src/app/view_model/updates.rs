@@ -0,0 +1,74 @@
+use super::{UiMessage, ViewModel};
+use crate::jobs::JobKind;
+use crate::updates::{Release, fetch_releases, newer_releases};
+
+impl ViewModel {
+    /// `:check-update` asks GitHub whether a newer release exists and shows the changelogs
+    /// of those that do in LOG. With `[updates] check_on_start` the same check runs quietly
+    /// at startup and only the status bar says so. Nothing is downloaded either way. GitHub
+    /// is asked through `[updates] curl_path`.
+    pub(super) fn check_update(&mut self, quiet: bool) {
+        if !quiet {
+            self.result_lines = vec!["checking for a newer release...".into()];
+            self.result_scroll = 0;
+        }
+        let curl = self.config.updates.curl_path.clone();
+        self.jobs.spawn(JobKind::Update, "check-update", move |_| {
+            UiMessage::UpdateChecked {
+                quiet,
+                releases: fetch_releases(&curl).map(newer_releases),
+            }
+        });
+    }
+
+    pub(super) fn show_update_check(
+        &mut self,
+        quiet: bool,
+        releases: Result<Vec<Release>, String>,
+    ) {
+        let current = env!("CARGO_PKG_VERSION");
+        let releases = match releases {
+            Ok(releases) => releases,
+            Err(_) if quiet => return,
+            Err(e) => {
+                self.result_lines = vec![format!("ERROR: cannot check for updates: {}", e)];
+                self.result_scroll = 0;
+                return;
+            }
+        };
+        self.update_available = releases.first().map(|r| r.tag.clone());
+        if quiet {
+            return;
+        }
+        let Some(latest) = releases.first() else {
+            self.result_lines = vec![format!("simple-git-tui {} is up to date", current)];
+            self.result_scroll = 0;
+            return;
+        };
+        self.result_lines = vec![format!(
+            "simple-git-tui {} is out (this is {}); changelog in LOG, download from {}",
+            latest.tag, current, latest.url
+        )];
+        self.result_scroll = 0;
+        let mut lines = Vec::new();
+        for release in &releases {
+            let title = if release.name.is_empty() || release.name == release.tag {
+                release.tag.clone()
+            } else {
+                format!("{}: {}", release.tag, release.name)
+            };
+            lines.push(format!("== {} ==", title));
+            lines.push(release.url.clone());
+            lines.push(String::new());
+            lines.extend(release.body.lines().map(|l| l.trim_end().to_string()));
+            lines.push(String::new());
+        }
+        self.set_log_lines(lines);
+        self.follow_content(false);
+    }
+
+    /// The tag of the newest release when it is newer than this build.
+    pub fn update_available(&self) -> Option<&str> {
+        self.update_available.as_deref()
+    }
+}
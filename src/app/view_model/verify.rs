@@ -0,0 +1,82 @@
+use super::{UiMessage, ViewModel};
+use crate::git::{SignatureCheck, parse_args_line, verify_signatures};
+use crate::jobs::JobKind;
+
+impl ViewModel {
+    /// `:verify <range>` checks the signature of every commit in the range and lists the
+    /// ones that are unsigned or do not verify, the check to make before tagging a release
+    /// from a branch others push to.
+    pub(super) fn verify_range(&mut self, range: &str) {
+        if range.is_empty() {
+            self.result_lines = vec!["usage: :verify <range> (e.g. v1.2.0..HEAD)".into()];
+            self.result_scroll = 0;
+            return;
+        }
+        let args = parse_args_line(range);
+        let shown = format!("git log --format=%G? {}", range);
+        self.teach(&shown, true);
+        self.result_lines = vec![format!("$ {}", shown), "verifying signatures...".into()];
+        self.result_scroll = 0;
+        let git = self.config.git_path.clone();
+        let repo = self.repo_root.clone();
+        let range = range.to_string();
+        self.jobs
+            .spawn(JobKind::Commits, format!("verify {}", range), move |_| {
+                let checks = verify_signatures(&git, &repo, &args);
+                UiMessage::SignaturesVerified(range, checks)
+            });
+    }
+
+    pub(super) fn show_signatures(&mut self, range: String, checks: Vec<SignatureCheck>) {
+        let count = |f: fn(char) -> bool| checks.iter().filter(|c| f(c.status)).count();
+        let good = count(|s| s == 'G');
+        let untrusted = count(|s| s == 'U');
+        let unsigned = count(|s| s == 'N');
+        let invalid = checks.len() - good - untrusted - unsigned;
+        let mut lines = vec![
+            format!("== verify {}: {} commit(s) ==", range, checks.len()),
+            format!(
+                "{} good, {} untrusted key, {} unsigned, {} invalid",
+                good, untrusted, unsigned, invalid
+            ),
+        ];
+        let problems: Vec<&SignatureCheck> = checks.iter().filter(|c| c.status != 'G').collect();
+        if !problems.is_empty() {
+            lines.push(String::new());
+        }
+        for c in &problems {
+            let signer = if c.signer.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", c.signer)
+            };
+            lines.push(format!(
+                "{} {} ({}) {}{}: {}",
+                c.status,
+                c.short,
+                c.author,
+                c.describe(),
+                signer,
+                c.subject
+            ));
+        }
+        self.result_lines = vec![if checks.is_empty() {
+            format!("verify: no commits in {}", range)
+        } else if problems.is_empty() {
+            format!(
+                "verify: all {} commit(s) have good signatures",
+                checks.len()
+            )
+        } else {
+            format!(
+                "verify: {} of {} commit(s) in {} are not signed with a trusted key",
+                problems.len(),
+                checks.len(),
+                range
+            )
+        }];
+        self.result_scroll = 0;
+        self.set_log_lines(lines);
+        self.follow_content(false);
+    }
+}
@@ -0,0 +1,183 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::{Confirmed, Focus, Mode, PendingCommand, TabRequest, ViewModel, list_move};
+use crate::git::{LfsMode, Worktree, quote_arg, worktree_list};
+use crate::notes::sanitize;
+
+impl ViewModel {
+    /// Rereads `git worktree list` for the WORKTREES panel.
+    pub(super) fn load_worktrees(&mut self) {
+        self.worktrees = worktree_list(&self.config.git_path, &self.repo_root);
+        self.selected_worktree = self
+            .selected_worktree
+            .min(self.worktrees.len().saturating_sub(1));
+    }
+
+    /// `:worktrees` shows the WORKTREES panel with the cursor on this one.
+    pub(super) fn show_worktrees(&mut self) {
+        self.load_worktrees();
+        self.selected_worktree = self
+            .worktrees
+            .iter()
+            .position(|w| same_path(&w.path, &self.repo_root))
+            .unwrap_or(0);
+        self.list_panel = Focus::Worktrees;
+        self.focus = Focus::Worktrees;
+        self.result_lines = vec![format!("{} worktree(s)", self.worktrees.len())];
+        self.result_scroll = 0;
+    }
+
+    pub(super) fn handle_worktree_keys(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.switch_to_selected_worktree(),
+            KeyCode::Char('a') => {
+                self.mode = Mode::CommandLine;
+                self.cmdline = "worktree add ".into();
+            }
+            KeyCode::Char('p') => {
+                self.run_command("worktree prune".into(), LfsMode::None, false);
+            }
+            KeyCode::Char('D') => self.handle_worktree_remove_key(),
+            _ => {
+                if let Some(row) = list_move(
+                    self.key_action(&key),
+                    self.selected_worktree,
+                    self.worktrees.len(),
+                ) {
+                    self.selected_worktree = row;
+                }
+            }
+        }
+    }
+
+    /// Enter: reopens the UI on the selected worktree in place of this one.
+    fn switch_to_selected_worktree(&mut self) {
+        let Some(worktree) = self.worktrees.get(self.selected_worktree) else {
+            return;
+        };
+        let refusal = if same_path(&worktree.path, &self.repo_root) {
+            Some("already in this worktree".to_string())
+        } else if worktree.bare || !worktree.path.is_dir() {
+            Some(format!("{} has no files to open", worktree.path.display()))
+        } else if self.review.is_some() {
+            Some("a review tab stays on its worktree; q closes it".to_string())
+        } else if self.is_running() || self.flow.is_some() || self.queued_commands() > 0 {
+            Some("WARN: already running command".to_string())
+        } else {
+            None
+        };
+        if let Some(refusal) = refusal {
+            self.result_lines = vec![refusal];
+            self.result_scroll = 0;
+            return;
+        }
+        self.tab_request = Some(TabRequest::Switch(worktree.path.clone()));
+    }
+
+    /// `D` asks first; pressing it again (or `y`) runs `git worktree remove`, which
+    /// still refuses while the worktree has changes.
+    fn handle_worktree_remove_key(&mut self) {
+        if self.refuse_read_only("removing worktrees") {
+            return;
+        }
+        let Some(worktree) = self.worktrees.get(self.selected_worktree) else {
+            return;
+        };
+        let path = worktree.path.clone();
+        if self.selected_worktree == 0 || same_path(&path, &self.repo_root) {
+            self.result_lines = vec![format!(
+                "{} is {}; only other linked worktrees can be removed",
+                path.display(),
+                if self.selected_worktree == 0 {
+                    "the main worktree"
+                } else {
+                    "open here"
+                }
+            )];
+            self.result_scroll = 0;
+            return;
+        }
+        self.ask_confirmation(
+            format!("Remove worktree {}?", path.display()),
+            Some('D'),
+            Confirmed::Command(PendingCommand::plain(format!(
+                "worktree remove {}",
+                quote_arg(&path.to_string_lossy())
+            ))),
+        );
+    }
+
+    /// `w` in BRANCHES: a worktree for the selected branch next to this one, named
+    /// after both. A remote-tracking branch gets a local branch tracking it, as Enter
+    /// would.
+    pub(super) fn add_worktree_for_selected_branch(&mut self) {
+        let Some(branch) = self.branches.get(self.selected_branch) else {
+            return;
+        };
+        if branch.current {
+            self.result_lines = vec![format!(
+                "{} is checked out here; a worktree needs another branch",
+                branch.name
+            )];
+            self.result_scroll = 0;
+            return;
+        }
+        let dir = self
+            .repo_root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let path = self
+            .repo_root
+            .with_file_name(format!("{}-{}", dir, sanitize(&branch.name)));
+        let path = quote_arg(&path.to_string_lossy());
+        let cmd = match branch.name.split_once('/') {
+            Some((_, local)) if branch.remote => format!(
+                "worktree add --track -b {} {} {}",
+                quote_arg(local),
+                path,
+                quote_arg(&branch.name)
+            ),
+            _ => format!("worktree add {} {}", path, quote_arg(&branch.name)),
+        };
+        self.run_command(cmd, LfsMode::None, false);
+    }
+
+    /// A tab like this one, on the worktree at `path`; the process follows it there.
+    pub fn open_worktree(&self, path: PathBuf) -> ViewModel {
+        let moved = env::set_current_dir(&path);
+        let mut tab = ViewModel::new(self.config.clone(), self.theme.clone(), path.clone());
+        tab.init();
+        tab.show_worktrees();
+        tab.result_lines = vec![format!("switched to the worktree at {}", path.display())];
+        if let Err(e) = moved {
+            tab.result_lines
+                .push(format!("WARN: cannot change directory there: {}", e));
+        }
+        tab
+    }
+
+    pub fn worktrees(&self) -> &[Worktree] {
+        &self.worktrees
+    }
+
+    pub fn selected_worktree(&self) -> usize {
+        self.selected_worktree
+    }
+
+    /// Whether `worktree` is the one this tab is open on, to mark it.
+    pub fn is_current_worktree(&self, worktree: &Worktree) -> bool {
+        same_path(&worktree.path, &self.repo_root)
+    }
+}
+
+/// Compares after resolving symlinks, as git lists worktrees by their real path.
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
@@ -0,0 +1,347 @@
+//! Command-line arguments, described once so parsing, `--help`, the man page and the shell
+//! completion scripts cannot drift apart.
+
+pub const NAME: &str = "simple-git-tui";
+
+pub struct Flag {
+    pub long: &'static str,
+    pub short: Option<char>,
+    pub help: &'static str,
+}
+
+pub const FLAGS: &[Flag] = &[
+    Flag {
+        long: "read-only",
+        short: None,
+        help: "Only browse: commands that change the repository, its files or remotes are refused",
+    },
+    Flag {
+        long: "audit",
+        short: None,
+        help: "Show the argv of every command that could change something and wait for y",
+    },
+    Flag {
+        long: "help",
+        short: Some('h'),
+        help: "Print this help",
+    },
+];
+
+pub struct Subcommand {
+    pub name: &'static str,
+    /// Placeholder for the argument, when it takes one.
+    pub arg: Option<&'static str>,
+    pub help: &'static str,
+}
+
+pub const SUBCOMMANDS: &[Subcommand] = &[
+    Subcommand {
+        name: "completions",
+        arg: Some("shell"),
+        help: "Print the completion script for bash, zsh or fish",
+    },
+    Subcommand {
+        name: "man",
+        arg: None,
+        help: "Print the man page (roff)",
+    },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub const ALL: [Shell; 3] = [Shell::Bash, Shell::Zsh, Shell::Fish];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.name() == name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Options {
+    pub read_only: bool,
+    pub audit: bool,
+}
+
+/// Runs each `--bench` measurement takes when not given `--bench=<n>`.
+const BENCH_RUNS: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Invocation {
+    Run(Options),
+    /// Hidden from `--help`: times the status and diff backends and drawing, `n` runs each.
+    Bench(usize),
+    Help,
+    Man,
+    Completions(Shell),
+}
+
+/// Parses the arguments after the program name.
+pub fn parse(args: &[String]) -> Result<Invocation, String> {
+    match args.first().map(String::as_str) {
+        Some("completions") => {
+            let shells: Vec<&str> = Shell::ALL.iter().map(|s| s.name()).collect();
+            return match &args[1..] {
+                [shell] => Shell::from_name(shell)
+                    .map(Invocation::Completions)
+                    .ok_or_else(|| {
+                        format!("unknown shell: {} (one of {})", shell, shells.join(", "))
+                    }),
+                _ => Err(format!(
+                    "usage: {} completions <{}>",
+                    NAME,
+                    shells.join("|")
+                )),
+            };
+        }
+        Some("man") if args.len() == 1 => return Ok(Invocation::Man),
+        _ => {}
+    }
+    let mut options = Options::default();
+    for arg in args {
+        if arg == "--bench" {
+            return Ok(Invocation::Bench(BENCH_RUNS));
+        }
+        if let Some(n) = arg.strip_prefix("--bench=") {
+            return n
+                .parse()
+                .map(Invocation::Bench)
+                .map_err(|_| format!("--bench=<n> takes a number of runs, not {}", n));
+        }
+        let flag = FLAGS.iter().find(|f| {
+            arg.strip_prefix("--") == Some(f.long)
+                || f.short.is_some_and(|s| *arg == format!("-{}", s))
+        });
+        match flag.map(|f| f.long) {
+            Some("read-only") => options.read_only = true,
+            Some("audit") => options.audit = true,
+            Some("help") => return Ok(Invocation::Help),
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+    Ok(Invocation::Run(options))
+}
+
+fn flag_spelling(flag: &Flag) -> String {
+    match flag.short {
+        Some(s) => format!("-{}, --{}", s, flag.long),
+        None => format!("--{}", flag.long),
+    }
+}
+
+fn subcommand_spelling(sub: &Subcommand) -> String {
+    match sub.arg {
+        Some(arg) => format!("{} <{}>", sub.name, arg),
+        None => sub.name.to_string(),
+    }
+}
+
+pub fn usage() -> String {
+    let flags: Vec<String> = FLAGS
+        .iter()
+        .filter(|f| f.long != "help")
+        .map(|f| format!("[--{}]", f.long))
+        .collect();
+    let mut lines = vec![format!("usage: {} {}", NAME, flags.join(" "))];
+    lines.extend(
+        SUBCOMMANDS
+            .iter()
+            .map(|s| format!("       {} {}", NAME, subcommand_spelling(s))),
+    );
+    lines.join("\n")
+}
+
+pub fn help() -> String {
+    let rows = |items: Vec<(String, &str)>| {
+        let width = items.iter().map(|(s, _)| s.len()).max().unwrap_or(0);
+        items
+            .into_iter()
+            .map(|(s, help)| format!("  {:width$}  {}", s, help, width = width))
+            .collect::<Vec<_>>()
+    };
+    let mut lines = vec![
+        format!("{} {}", NAME, env!("CARGO_PKG_VERSION")),
+        "A vim-oriented git TUI; run it inside a repository.".to_string(),
+        String::new(),
+        usage(),
+        String::new(),
+        "options:".to_string(),
+    ];
+    lines.extend(rows(
+        FLAGS.iter().map(|f| (flag_spelling(f), f.help)).collect(),
+    ));
+    lines.push(String::new());
+    lines.push("commands:".to_string());
+    lines.extend(rows(
+        SUBCOMMANDS
+            .iter()
+            .map(|s| (subcommand_spelling(s), s.help))
+            .collect(),
+    ));
+    lines.join("\n")
+}
+
+/// Escapes text for roff: backslashes and hyphens, and a leading dot or quote.
+fn roff(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+    if text.starts_with(['.', '\'']) {
+        format!("\\&{}", text)
+    } else {
+        text
+    }
+}
+
+pub fn man_page() -> String {
+    let mut out = vec![
+        format!(
+            ".TH {} 1 \"\" \"{} {}\" \"User Commands\"",
+            roff(&NAME.to_uppercase()),
+            roff(NAME),
+            env!("CARGO_PKG_VERSION")
+        ),
+        ".SH NAME".to_string(),
+        format!("{} \\- a vim\\-oriented git TUI", roff(NAME)),
+        ".SH SYNOPSIS".to_string(),
+        format!(".B {}", roff(NAME)),
+    ];
+    for f in FLAGS.iter().filter(|f| f.long != "help") {
+        out.push(format!("[\\fB\\-\\-{}\\fR]", roff(f.long)));
+    }
+    for s in SUBCOMMANDS {
+        out.push(".br".to_string());
+        out.push(format!(".B {} {}", roff(NAME), roff(s.name)));
+        if let Some(arg) = s.arg {
+            out.push(format!("\\fI{}\\fR", roff(arg)));
+        }
+    }
+    out.extend([
+        ".SH DESCRIPTION".to_string(),
+        "Run inside a git repository. Everything happens through keys and the".to_string(),
+        ".B :".to_string(),
+        "command line; each action runs git and shows its output.".to_string(),
+        ".SH OPTIONS".to_string(),
+    ]);
+    for f in FLAGS {
+        out.push(".TP".to_string());
+        out.push(match f.short {
+            Some(s) => format!("\\fB\\-{}\\fR, \\fB\\-\\-{}\\fR", s, roff(f.long)),
+            None => format!("\\fB\\-\\-{}\\fR", roff(f.long)),
+        });
+        out.push(roff(f.help));
+    }
+    out.push(".SH COMMANDS".to_string());
+    for s in SUBCOMMANDS {
+        out.push(".TP".to_string());
+        out.push(match s.arg {
+            Some(arg) => format!("\\fB{}\\fR \\fI{}\\fR", roff(s.name), roff(arg)),
+            None => format!("\\fB{}\\fR", roff(s.name)),
+        });
+        out.push(roff(s.help));
+    }
+    out.extend([
+        ".SH FILES".to_string(),
+        ".TP".to_string(),
+        "\\fI~/.config/simple\\-git\\-tui/config.toml\\fR".to_string(),
+        "The configuration, written with defaults on first start (the platform's config"
+            .to_string(),
+        "directory outside Linux).".to_string(),
+    ]);
+    out.join("\n") + "\n"
+}
+
+pub fn completions(shell: Shell) -> String {
+    let shells: Vec<&str> = Shell::ALL.iter().map(|s| s.name()).collect();
+    match shell {
+        Shell::Bash => {
+            let mut words: Vec<String> = SUBCOMMANDS.iter().map(|s| s.name.to_string()).collect();
+            let flags: Vec<String> = FLAGS
+                .iter()
+                .flat_map(|f| {
+                    f.short
+                        .map(|s| format!("-{}", s))
+                        .into_iter()
+                        .chain([format!("--{}", f.long)])
+                })
+                .collect();
+            words.extend(flags.iter().cloned());
+            format!(
+                r#"_simple_git_tui() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    if [ "$COMP_CWORD" -eq 2 ] && [ "${{COMP_WORDS[1]}}" = completions ]; then
+        COMPREPLY=($(compgen -W "{shells}" -- "$cur"))
+    elif [ "$COMP_CWORD" -eq 1 ]; then
+        COMPREPLY=($(compgen -W "{words}" -- "$cur"))
+    else
+        COMPREPLY=($(compgen -W "{flags}" -- "$cur"))
+    fi
+}}
+complete -F _simple_git_tui {name}
+"#,
+                shells = shells.join(" "),
+                words = words.join(" "),
+                flags = flags.join(" "),
+                name = NAME,
+            )
+        }
+        Shell::Zsh => {
+            let quote = |s: &str| s.replace('\'', "'\\''");
+            let mut out = vec![format!("#compdef {}", NAME), String::new()];
+            out.push("_arguments \\".to_string());
+            for f in FLAGS {
+                let help = quote(f.help);
+                out.push(match f.short {
+                    Some(s) => format!("  '(-)'{{-{},--{}}}'[{}]' \\", s, f.long, help),
+                    None => format!("  '--{}[{}]' \\", f.long, help),
+                });
+            }
+            let subs: Vec<String> = SUBCOMMANDS
+                .iter()
+                .map(|s| format!("{}\\:\"{}\"", s.name, quote(s.help)))
+                .collect();
+            out.push(format!("  '1::command:(({}))' \\", subs.join(" ")));
+            out.push(format!("  '2::shell:({})'", shells.join(" ")));
+            out.join("\n") + "\n"
+        }
+        Shell::Fish => {
+            let quote = |s: &str| s.replace('\\', "\\\\").replace('\'', "\\'");
+            let mut out = vec![format!("complete -c {} -f", NAME)];
+            for f in FLAGS {
+                let short = f.short.map(|s| format!(" -s {}", s)).unwrap_or_default();
+                out.push(format!(
+                    "complete -c {}{} -l {} -d '{}'",
+                    NAME,
+                    short,
+                    f.long,
+                    quote(f.help)
+                ));
+            }
+            for s in SUBCOMMANDS {
+                out.push(format!(
+                    "complete -c {} -n __fish_use_subcommand -a {} -d '{}'",
+                    NAME,
+                    s.name,
+                    quote(s.help)
+                ));
+            }
+            out.push(format!(
+                "complete -c {} -n '__fish_seen_subcommand_from completions' -a '{}'",
+                NAME,
+                shells.join(" ")
+            ));
+            out.join("\n") + "\n"
+        }
+    }
+}
@@ -1,43 +1,429 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
-use std::{fs, path::PathBuf};
+use std::{collections::BTreeMap, fs, path::PathBuf, sync::OnceLock};
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table, value};
 
 use crate::define::DEFAULT_CONFIG;
+use crate::git::{LfsMode, LfsOptions, parse_lfs_mode};
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Config {
     pub git_path: String,
+    /// Built-in color preset; `[colors]` entries override it.
+    #[serde(default)]
+    pub theme: Option<String>,
     #[serde(default)]
     pub colors: ColorConfig,
     #[serde(default)]
     pub layout: LayoutConfig,
+    /// Named pane arrangements from `[layouts.<name>]`, next to the built-in presets.
+    #[serde(default)]
+    pub layouts: BTreeMap<String, LayoutPreset>,
+    #[serde(default)]
+    pub focus: FocusRules,
     #[serde(default)]
     pub files_diff_cmd: Option<String>,
+    /// Shows file diffs side by side, old on the left and new on the right.
+    #[serde(default)]
+    pub files_diff_split: bool,
+    /// Groups FILES into a collapsible tree of directories.
+    #[serde(default)]
+    pub files_tree: bool,
+    #[serde(default)]
+    pub editor: Option<String>,
+    /// Only browse: commands that change the repository, its files or remotes are refused.
+    #[serde(default)]
+    pub read_only: bool,
+    /// Every command that could change something waits for `y` after showing its argv,
+    /// and everything run is appended to the session transcript.
+    #[serde(default)]
+    pub audit: bool,
+    /// Shows the git command behind each action above the status bar and keeps today's
+    /// list for `:today`.
+    #[serde(default)]
+    pub teaching: bool,
+    #[serde(default)]
+    pub commit_preview: CommitPreviewConfig,
+    #[serde(default)]
+    pub issues: IssueConfig,
+    #[serde(default)]
+    pub links: LinksConfig,
+    #[serde(default)]
+    pub submodules: SubmoduleConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub branches: BranchConfig,
+    #[serde(default)]
+    pub decorations: DecorationConfig,
+    #[serde(default)]
+    pub codeowners: CodeOwnersConfig,
+    #[serde(default)]
+    pub updates: UpdateConfig,
     pub commands: Vec<CommandConfig>,
+    #[serde(default)]
+    pub flows: Vec<FlowConfig>,
+    #[serde(default)]
+    pub events: EventHooks,
+    #[serde(default)]
+    pub keys: KeyConfig,
 }
 
-#[derive(Deserialize, Debug, Default)]
+/// Commands run in the background when something happens in the UI.
+/// `{branch}`, `{repo}` and `{default_branch}` are substituted as quoted words; `!` runs
+/// a shell command, otherwise git.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct EventHooks {
+    pub on_repo_open: Option<String>,
+    pub on_branch_change: Option<String>,
+    pub on_commit: Option<String>,
+}
+
+/// `[keys]`: the keys of an action, replacing its built-in ones when set.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct KeyConfig {
+    pub focus_left: Option<Vec<String>>,
+    pub focus_right: Option<Vec<String>>,
+    pub up: Option<Vec<String>>,
+    pub down: Option<Vec<String>>,
+    pub stage: Option<Vec<String>>,
+    pub diff: Option<Vec<String>>,
+    pub discard: Option<Vec<String>>,
+    pub mark: Option<Vec<String>>,
+    pub command_line: Option<Vec<String>>,
+    pub quit: Option<Vec<String>>,
+    /// `[keys.actions]`: keys that run a named action from `:actions` in any pane.
+    pub actions: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
 pub struct ColorConfig {
     pub accent: Option<String>,
     pub error: Option<String>,
     pub background: Option<String>,
+    /// `auto`, `none`, `16`, `256` or `truecolor`; `auto` honours `NO_COLOR`, `COLORTERM` and `TERM`.
+    pub mode: Option<String>,
+    /// Language-aware colors in diffs; on unless set to false.
+    pub syntax: Option<bool>,
+    pub keyword: Option<String>,
+    pub string: Option<String>,
+    pub comment: Option<String>,
+    pub number: Option<String>,
+    pub staged: Option<String>,
+    pub unstaged: Option<String>,
+    pub untracked: Option<String>,
+    pub conflicted: Option<String>,
+    /// Background of the selected row in lists.
+    pub selection: Option<String>,
+    /// Borders of unfocused panes.
+    pub border: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct CommandConfig {
     pub name: String,
+    /// `{default_branch}`, `{branch}`, `{file}` and `{files}` are filled in, quoted;
+    /// each `{input:prompt}` is asked for before the command runs.
     pub cmd: String,
+    /// LFS step run after the command: `fetch`, `pull` or `push`.
     #[serde(default)]
     pub lfs: Option<String>,
+    /// Remote for the LFS step instead of the one git-lfs would pick.
+    #[serde(default)]
+    pub lfs_remote: Option<String>,
+    /// Ref to fetch or push objects for, instead of `--all` (fetch) or `HEAD` (push).
+    #[serde(default)]
+    pub lfs_ref: Option<String>,
+    /// Comma-separated path patterns for `lfs fetch/pull --include` and `--exclude`.
+    #[serde(default)]
+    pub lfs_include: Option<String>,
+    #[serde(default)]
+    pub lfs_exclude: Option<String>,
     #[serde(default)]
     pub interactive: bool,
+    #[serde(default)]
+    pub scope: CommandScope,
+    /// Key that runs this command while the CMD pane is focused; not one CMD keeps for
+    /// itself (`Keymap::reserves_command_key`).
+    #[serde(default)]
+    pub key: Option<char>,
+    /// Run after the command exits successfully: a git command, or a shell command prefixed with `!`.
+    #[serde(default)]
+    pub on_success: Option<String>,
+    #[serde(default)]
+    pub on_failure: Option<String>,
+    /// Reruns of a network command that could not reach the remote, waiting 2s, 4s, 8s, ...
+    #[serde(default)]
+    pub retries: u32,
+    /// Asks before running: `true`, or the question to ask.
+    #[serde(default)]
+    pub confirm: Option<CommandConfirm>,
+}
+
+/// `confirm = true` or `confirm = "question"` on a command.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum CommandConfirm {
+    Ask(bool),
+    Question(String),
+}
+
+impl CommandConfig {
+    /// The question asked before the command runs, if it asks one.
+    pub fn confirm_question(&self) -> Option<String> {
+        match &self.confirm {
+            Some(CommandConfirm::Ask(true)) => Some(format!("Run \"{}\"?", self.name)),
+            Some(CommandConfirm::Question(question)) => Some(question.clone()),
+            Some(CommandConfirm::Ask(false)) | None => None,
+        }
+    }
+
+    pub fn lfs_mode(&self) -> LfsMode {
+        parse_lfs_mode(
+            self.lfs.as_ref(),
+            LfsOptions {
+                remote: self.lfs_remote.clone(),
+                refspec: self.lfs_ref.clone(),
+                include: self.lfs_include.clone(),
+                exclude: self.lfs_exclude.clone(),
+            },
+        )
+    }
+}
+
+/// Which pathspec a configured command runs against.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandScope {
+    /// Run as written (the `:scope` subtree still applies).
+    #[default]
+    Repo,
+    /// Append the file selected in the FILES pane.
+    Selected,
+    /// Open the command line prefilled so the pathspec can be edited first.
+    Ask,
+}
+
+/// A guided sequence of prompts and commands started with `:flow <name>`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct FlowConfig {
+    pub name: String,
+    pub steps: Vec<FlowStep>,
+}
+
+/// One flow step: either asks for a value (`prompt`) or runs a command (`run`).
+#[derive(Deserialize, Debug, Clone)]
+pub struct FlowStep {
+    /// Question shown in the status bar; the answer is stored as `{var}`.
+    #[serde(default)]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub var: Option<String>,
+    #[serde(default)]
+    pub default: Option<String>,
+    /// Git command, or shell command prefixed with `!`; `{var}` placeholders are filled in.
+    #[serde(default)]
+    pub run: Option<String>,
+    #[serde(default)]
+    pub when: StepCondition,
+}
+
+/// Which outcome of the last command a flow step runs after.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StepCondition {
+    #[default]
+    Success,
+    Failure,
+    Always,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CommitPreviewConfig {
+    pub enabled: bool,
+    pub large_file_kb: u64,
+    /// Shell command run before committing; a non-zero exit is reported as a warning.
+    pub lint: Option<String>,
+}
+
+impl Default for CommitPreviewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            large_file_kb: 1024,
+            lint: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct IssueConfig {
+    /// URL template such as `https://jira.example.com/browse/{id}`.
+    pub url: Option<String>,
+    /// Prefill editor commits with the issue ID found in the branch name.
+    pub insert_in_commit: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct BranchConfig {
+    /// Name built by `:new-branch`; each `{field}` is prompted for and `{slug}` is
+    /// lowercased and hyphenated.
+    pub template: String,
+}
+
+impl Default for BranchConfig {
+    fn default() -> Self {
+        Self {
+            template: "{type}/{issue}-{slug}".into(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct SubmoduleConfig {
+    /// Show dirty/ahead submodule counts in the status bar, refreshed in the background.
+    pub summary: bool,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct DecorationConfig {
+    /// Shell command given the FILES paths on stdin, one per line; each `path<TAB>text` line
+    /// it prints is shown next to that file, e.g. code owners or lint status.
+    pub command: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CodeOwnersConfig {
+    /// Before a push, list the CODEOWNERS whose review the branch's changes need.
+    pub push_summary: bool,
+}
+
+impl Default for CodeOwnersConfig {
+    fn default() -> Self {
+        Self { push_summary: true }
+    }
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Queue network commands that fail while offline and rerun them after the next
+    /// network command succeeds.
+    pub retry_offline: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct UpdateConfig {
+    /// Ask GitHub for newer releases at startup and note one in the status bar; nothing
+    /// is ever downloaded.
+    pub check_on_start: bool,
+    /// The `curl` the check asks GitHub through; without one `:check-update` reports
+    /// that it cannot run it.
+    pub curl_path: String,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            check_on_start: false,
+            curl_path: "curl".into(),
+        }
+    }
+}
+
+/// Clickable hashes, paths and issue IDs in the LOG and R panes.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct LinksConfig {
+    pub mode: LinkMode,
+    /// Base URL for commit links; derived from the `origin` remote when unset.
+    pub web_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkMode {
+    /// Only in terminals known to support OSC 8 hyperlinks.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Deserialize, Debug, Clone)]
 pub struct LayoutConfig {
     pub cmd_width: u16,
     pub files_height: u16,
     pub result_height: u16,
+    #[serde(default = "default_true")]
+    pub minimap: bool,
+    #[serde(default)]
+    pub cmd_sort: CommandSort,
+    /// Layout preset to start with; `L` cycles through them at runtime.
+    #[serde(default)]
+    pub preset: Option<String>,
+}
+
+/// Which panes a layout shows, in what order and at what size. Sizes left out come
+/// from `[layout]`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct LayoutPreset {
+    /// The column holding the first of these is drawn on the left, and `h`/`l` move
+    /// along this order. LOG is always shown.
+    #[serde(default = "default_panes")]
+    pub panes: Vec<Pane>,
+    #[serde(default)]
+    pub cmd_width: Option<u16>,
+    #[serde(default)]
+    pub files_height: Option<u16>,
+    #[serde(default)]
+    pub result_height: Option<u16>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Pane {
+    Cmd,
+    /// The list slot: FILES, COMMITS or MATCHES.
+    Files,
+    Log,
+    Result,
+}
+
+/// Where focus moves once a command or diff finishes; unset rules leave it in place.
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct FocusRules {
+    /// A diff shown with `d` or by a command; takes precedence over `after_success`.
+    #[serde(default)]
+    pub after_diff: Option<Pane>,
+    #[serde(default)]
+    pub after_success: Option<Pane>,
+    #[serde(default)]
+    pub after_failure: Option<Pane>,
+}
+
+fn default_panes() -> Vec<Pane> {
+    vec![Pane::Cmd, Pane::Files, Pane::Log, Pane::Result]
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandSort {
+    /// Keep the order from config.toml (rearranged with J/K).
+    #[default]
+    Config,
+    /// List the most recently run commands first; the order is kept in
+    /// `recent_commands` next to config.toml, which is left as is.
+    Recent,
 }
 
 impl Default for LayoutConfig {
@@ -46,10 +432,17 @@ impl Default for LayoutConfig {
             cmd_width: 32,
             files_height: 7,
             result_height: 5,
+            minimap: true,
+            cmd_sort: CommandSort::Config,
+            preset: None,
         }
     }
 }
 
+fn default_true() -> bool {
+    true
+}
+
 pub fn load_config() -> Result<Config> {
     let path = ensure_config_file()?;
     let text = fs::read_to_string(&path)
@@ -79,6 +472,115 @@ fn ensure_config_file() -> Result<PathBuf> {
     Ok(legacy)
 }
 
+/// Path of the config file in use, whether or not it exists yet.
+pub fn config_file_path() -> PathBuf {
+    preferred_config_path().unwrap_or_else(|| PathBuf::from("config.toml"))
+}
+
+/// Applies `edit` to the `[[commands]]` array in config.toml, keeping comments and layout.
+fn edit_commands(edit: impl FnOnce(&mut ArrayOfTables) -> Result<()>) -> Result<()> {
+    let path = config_file_path();
+    let text = fs::read_to_string(&path)
+        .with_context(|| format!("cannot read config file: {}", path.display()))?;
+    let mut doc: DocumentMut = text.parse().context("invalid config.toml")?;
+    let commands = doc
+        .entry("commands")
+        .or_insert(Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .context("`commands` in config.toml is not an array of tables")?;
+    edit(commands)?;
+    fs::write(&path, doc.to_string())
+        .with_context(|| format!("cannot write config file: {}", path.display()))
+}
+
+/// Writes `cmd` over the command at `index`, or appends it when `index` is `None`.
+pub fn save_command(index: Option<usize>, cmd: &CommandConfig) -> Result<()> {
+    edit_commands(|commands| {
+        let table = match index {
+            Some(i) => commands
+                .get_mut(i)
+                .with_context(|| format!("no command #{} in config.toml", i + 1))?,
+            None => {
+                commands.push(Table::new());
+                commands.get_mut(commands.len() - 1).expect("just pushed")
+            }
+        };
+        table["name"] = value(&cmd.name);
+        table["cmd"] = value(&cmd.cmd);
+        match &cmd.lfs {
+            Some(lfs) => table["lfs"] = value(lfs),
+            None => {
+                table.remove("lfs");
+            }
+        }
+        if cmd.interactive {
+            table["interactive"] = value(true);
+        } else {
+            table.remove("interactive");
+        }
+        match cmd.key {
+            Some(key) => table["key"] = value(key.to_string()),
+            None => {
+                table.remove("key");
+            }
+        }
+        Ok(())
+    })
+}
+
+pub fn remove_command(index: usize) -> Result<()> {
+    edit_commands(|commands| {
+        if index >= commands.len() {
+            anyhow::bail!("no command #{} in config.toml", index + 1);
+        }
+        commands.remove(index);
+        Ok(())
+    })
+}
+
+/// Moves the command at `from` to `to`, shifting the ones in between.
+pub fn move_command(from: usize, to: usize) -> Result<()> {
+    edit_commands(|commands| {
+        let mut tables: Vec<Table> = commands.iter().cloned().collect();
+        if from >= tables.len() || to >= tables.len() {
+            anyhow::bail!("no command #{} in config.toml", from.max(to) + 1);
+        }
+        // Tables render in document-position order, so each slot keeps its position.
+        let positions: Vec<Option<usize>> = tables.iter().map(|t| t.position()).collect();
+        let table = tables.remove(from);
+        tables.insert(to, table);
+        commands.clear();
+        for (mut table, position) in tables.into_iter().zip(positions) {
+            if let Some(position) = position {
+                table.set_position(position);
+            }
+            commands.push(table);
+        }
+        Ok(())
+    })
+}
+
 fn preferred_config_path() -> Option<PathBuf> {
-    dirs_next::config_dir().map(|dir| dir.join("simple-git-tui").join("config.toml"))
+    app_dir().map(|dir| dir.join("config.toml"))
+}
+
+static APP_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Redirects the config file and persisted state, e.g. into a temporary directory under test.
+/// Only the first call has an effect.
+pub fn set_app_dir(dir: PathBuf) {
+    let _ = APP_DIR.set(dir);
+}
+
+/// Per-user directory holding the config file and other persisted state.
+pub fn app_dir() -> Option<PathBuf> {
+    APP_DIR
+        .get()
+        .cloned()
+        .or_else(|| dirs_next::config_dir().map(|dir| dir.join("simple-git-tui")))
+}
+
+/// The built-in configuration written to config.toml on first start.
+pub fn default_config() -> Config {
+    toml::from_str(DEFAULT_CONFIG).expect("built-in config is valid")
 }
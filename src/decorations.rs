@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::Stdio;
+use std::thread;
+
+use crate::shell::shell_command;
+
+/// Runs the `[decorations] command` with `paths` on stdin, one per line, and reads back
+/// `path<TAB>annotation` lines. Other output lines are ignored.
+pub fn run_decorator(
+    command: &str,
+    repo: &Path,
+    paths: &[String],
+) -> Result<HashMap<String, String>, String> {
+    let mut child = shell_command(command)
+        .current_dir(repo)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("cannot run {}: {}", command, e))?;
+    // Fed from another thread so a decorator that answers before reading everything
+    // cannot fill its stdout pipe while we are still writing.
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input: String = paths.iter().map(|p| format!("{}\n", p)).collect();
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("{}: {}", command, e))?;
+    let _ = writer.join();
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "{} exited with code {}: {}",
+            command,
+            output.status.code().unwrap_or(-1),
+            stderr.lines().next().unwrap_or("")
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(path, note)| (path.to_string(), note.trim().to_string()))
+        .filter(|(_, note)| !note.is_empty())
+        .collect())
+}
@@ -1,17 +1,132 @@
-pub const DEFAULT_CONFIG: &str = r#"
+pub const DEFAULT_CONFIG: &str = r##"
 git_path = "git"
 
+# Color preset: "gruvbox", "catppuccin", "solarized-dark", "solarized-light" or "nord";
+# entries in [colors] below override it
+# theme = "gruvbox"
+
 [colors]
-accent = "cyan"
-error = "red"
-background = "black"
+# Colors are names ("cyan", "dark_gray", "light_blue", ...), "#rrggbb" or "ansi:<0-255>"
+# accent = "cyan"
+# error = "red"
+# background = "black"
+# Color depth: "auto" (detect; honours NO_COLOR), "none", "16", "256" or "truecolor"
+mode = "auto"
+# Language-aware colors in diffs; false for slow terminals
+syntax = true
+# keyword = "magenta"
+# string = "yellow"
+# comment = "gray"
+# number = "cyan"
+# FILES entries; staged defaults to accent and conflicted to error
+# staged = "cyan"
+# unstaged = "#d7875f"
+# untracked = "ansi:244"
+# conflicted = "red"
+# Background of the selected row in lists, and borders of unfocused panes
+# selection = "ansi:236"
+# border = "dark_gray"
 
 [layout]
 cmd_width = 32
 files_height = 7
 result_height = 5
+minimap = true
+# "config" keeps this file's order; "recent" lists the last run first
+cmd_sort = "config"
+# Start with a layout preset: "default", "wide-diff", "review", "minimal" or one of
+# [layouts.<name>]; L cycles through them
+# preset = "review"
+
+# [layouts.diff-left]
+# panes = ["log", "result", "files"]   # order sets the side and h/l order; LOG always shows
+# cmd_width = 40
+# result_height = 8
+
+[focus]
+# Move focus when a command or diff finishes: "cmd", "files", "log" or "result"
+# after_diff = "log"
+# after_failure = "result"
+# after_success = "files"
 
 files_diff_cmd = "diff HEAD --"
+# Show diffs side by side (old | new) instead of unified; "|" toggles it
+files_diff_split = false
+# Group FILES into a collapsible directory tree instead of a flat list; "T" toggles it
+files_tree = false
+# Browse without staging, committing or pushing (same as launching with --read-only)
+read_only = false
+# Confirm each mutating command's exact argv and log every run (same as --audit)
+audit = false
+# Show the git command behind every action and keep a list for ":today"
+teaching = false
+
+[commit_preview]
+enabled = true
+large_file_kb = 1024
+# lint = "cargo clippy -- -D warnings"
+# Repositories with a DCO policy: `git config simple-git-tui.requireSignoff true`
+# adds --signoff to every commit and blocks commits that cannot be signed off.
+
+[issues]
+# url = "https://jira.example.com/browse/{id}"
+insert_in_commit = false
+
+[branches]
+# Name for ":new-branch"; asks for each {field}, {slug} becomes e.g. "fix-login-crash"
+template = "{type}/{issue}-{slug}"
+
+[submodules]
+# Show e.g. "submodules: 2 dirty" in the status bar (one extra git status per submodule)
+summary = false
+
+[decorations]
+# Annotate FILES entries: gets the paths on stdin, prints "path<TAB>text" lines
+# command = "scripts/owners-for.sh"
+
+[codeowners]
+# With a CODEOWNERS file, confirm pushes after listing the owners who must review
+push_summary = true
+
+[updates]
+# Ask GitHub for newer releases at startup (":check-update" asks any time); needs curl
+check_on_start = false
+# curl_path = "curl"
+
+[network]
+# Rerun fetch/pull/push that failed while offline once a network command succeeds
+retry_offline = false
+
+[links]
+# OSC 8 hyperlinks on commit hashes, file paths and issue IDs: "auto", "always" or "never"
+mode = "auto"
+# web_url = "https://github.com/owner/repo"
+
+[events]
+# on_repo_open = "!echo {repo} >> ~/.recent-repos"
+# on_branch_change = "!echo {branch} > .git/tui-branch"
+# on_commit = "!notify-send committed"
+
+[keys]
+# Keys for these actions, replacing the built-in ones: a character, a name (space, enter,
+# tab, esc, backspace, delete, up, down, left, right, home, end, pageup, pagedown, f1-f12),
+# optionally after ctrl- or alt-. A key taken from another action needs that action
+# bound elsewhere too; a key a pane already uses (n in LOG) keeps its meaning there
+# focus_left = ["h", "left"]
+# focus_right = ["l", "right"]
+# up = ["k", "up"]
+# down = ["j", "down"]
+# stage = ["s"]          # FILES
+# diff = ["d"]           # FILES
+# discard = ["x"]        # FILES
+# mark = ["space"]       # FILES
+# command_line = [":"]
+# quit = ["q"]
+
+[keys.actions]
+# Keys that run a named action (:actions lists them) in any pane
+# toggle-diff-mode = ["D"]
+# branches = ["ctrl-b"]
 
 [[commands]]
 name = "Status"
@@ -19,20 +134,68 @@ cmd  = "status -sb"
 
 [[commands]]
 name = "Graph"
-cmd  = "log --oneline --graph --decorate --all --color=always"
+cmd  = ":commits --all"   # browse in COMMITS; Enter shows a commit's diff in LOG
+
+[[commands]]
+name = "Log for file"
+cmd  = "log --oneline --follow --color=always"
+scope = "selected"
 
 [[commands]]
 name = "Pull"
 cmd  = "pull"
 lfs  = "pull"
+# lfs_remote = "origin"        # LFS step options: remote, ref (fetch/push),
+# lfs_include = "assets/**"    # and comma-separated include/exclude patterns
+# on_success = "!cargo test"   # "!" runs a shell command, otherwise git
+# on_failure = "status -sb"
+# retries = 3                  # rerun when the remote is unreachable, with backoff
 
 [[commands]]
 name = "Push"
 cmd = "push"
 lfs = "push"
 
+# `confirm = true` asks before running; a string is the question to ask.
+# [[commands]]
+# name = "Force push"
+# cmd = "push --force-with-lease"
+# confirm = "Overwrite the remote branch with yours?"
+
+# On slow connections, skip LFS downloads on checkout and fetch only the assets you
+# need: ":lfs-pointers" lists missing content, ":hydrate <path>" (H in FILES) pulls it.
+# [[commands]]
+# name = "Skip LFS downloads"
+# cmd = "lfs install --local --skip-smudge"
+
+# "{default_branch}" is the remote's default branch (origin/HEAD), e.g.
+# [[commands]]
+# name = "Rebase on default"
+# cmd = "rebase origin/{default_branch}"
+
+# "{branch}" is the current branch, "{file}" the selected file and "{files}" the
+# marked ones; "{input:Question}" asks in the status bar before running.
+# [[commands]]
+# name = "Move file"
+# cmd = "mv {file} {input:New path}"
+
+[[commands]]
+name = "Stage all"
+cmd = ":stage-all"   # ":" runs a built-in action, see :actions
+
 [[commands]]
 name = "Commit"
-cmd = "commit"
-interactive = true
-"#;
+cmd = "commit"   # writes the message in a popup; `interactive = true` uses $EDITOR
+
+# Guided flows run with ":flow <name>". Steps either prompt for a {var} or run a
+# command; `when = "success" | "failure" | "always"` checks the last command's exit code.
+# [[flows]]
+# name = "Release"
+# steps = [
+#   { prompt = "Version", var = "version" },
+#   { run = "tag v{version}" },
+#   { run = "push origin v{version}" },
+#   { run = "!gh release create v{version} --generate-notes" },
+#   { run = "!echo release failed", when = "failure" },
+# ]
+"##;
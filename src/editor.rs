@@ -0,0 +1,53 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+use crate::git::parse_args_line;
+
+/// Builds the editor invocation for `path` at `line`.
+///
+/// `template` comes from the `editor` config key and may use `{file}` and `{line}`;
+/// without it `$VISUAL`/`$EDITOR` is launched with the common `+<line> <file>` form.
+pub fn editor_command(template: Option<&str>, path: &Path, line: u32) -> Command {
+    let file = path.to_string_lossy();
+    let raw = match template {
+        Some(t) if !t.trim().is_empty() => t.to_string(),
+        _ => default_template(),
+    };
+
+    let mut parts: Vec<String> = parse_args_line(&raw)
+        .into_iter()
+        .map(|arg| {
+            arg.replace("{file}", &file)
+                .replace("{line}", &line.to_string())
+        })
+        .collect();
+    if !raw.contains("{file}") {
+        parts.push(file.into_owned());
+    }
+
+    let program = if parts.is_empty() {
+        fallback_editor().to_string()
+    } else {
+        parts.remove(0)
+    };
+    let mut cmd = Command::new(program);
+    cmd.args(parts);
+    cmd
+}
+
+fn default_template() -> String {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty());
+    match editor {
+        Some(e) => format!("{} +{{line}} {{file}}", e),
+        None if cfg!(windows) => format!("{} {{file}}", fallback_editor()),
+        None => format!("{} +{{line}} {{file}}", fallback_editor()),
+    }
+}
+
+fn fallback_editor() -> &'static str {
+    if cfg!(windows) { "notepad" } else { "vi" }
+}
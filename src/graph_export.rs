@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+
+use crate::config::app_dir;
+use crate::git::{Commit, RefKind};
+use crate::notes::sanitize;
+
+/// Where `:export-graph` writes when it is not given a path.
+pub fn export_path(repo_root: &Path, secs: u64, extension: &str) -> Option<PathBuf> {
+    let repo_key = sanitize(&repo_root.to_string_lossy());
+    Some(
+        app_dir()?
+            .join("graphs")
+            .join(repo_key)
+            .join(format!("graph-{}.{}", secs, extension)),
+    )
+}
+
+/// A graphviz digraph of `commits`, newest at the top, each pointing at those of its
+/// parents that are listed too. Tagged commits are filled yellow and branch heads green.
+pub fn dot(commits: &[Commit]) -> String {
+    let mut out = vec![
+        "digraph commits {".to_string(),
+        "  rankdir=TB;".to_string(),
+        "  node [shape=box, fontname=\"monospace\"];".to_string(),
+    ];
+    for c in commits {
+        let mut label = format!("{} {}", c.short, c.subject);
+        if !c.refs.is_empty() {
+            let names: Vec<String> = c
+                .refs
+                .iter()
+                .map(|r| match r.kind {
+                    RefKind::Head if r.name != "HEAD" => format!("HEAD -> {}", r.name),
+                    RefKind::Tag => format!("tag: {}", r.name),
+                    _ => r.name.clone(),
+                })
+                .collect();
+            label.push_str(&format!("\n({})", names.join(", ")));
+        }
+        let fill = if c.refs.iter().any(|r| r.kind == RefKind::Tag) {
+            ", style=filled, fillcolor=\"khaki\""
+        } else if c
+            .refs
+            .iter()
+            .any(|r| matches!(r.kind, RefKind::Head | RefKind::Branch))
+        {
+            ", style=filled, fillcolor=\"palegreen\""
+        } else {
+            ""
+        };
+        out.push(format!(
+            "  \"{}\" [label=\"{}\"{}];",
+            c.short,
+            escape(&label),
+            fill
+        ));
+    }
+    for c in commits {
+        for parent in &c.parents {
+            if let Some(p) = commits.iter().find(|p| p.hash == *parent) {
+                out.push(format!("  \"{}\" -> \"{}\";", c.short, p.short));
+            }
+        }
+    }
+    out.push("}".to_string());
+    out.join("\n") + "\n"
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
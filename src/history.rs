@@ -0,0 +1,61 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::app_dir;
+
+/// Lines kept in the history file; older ones are dropped as new ones come in.
+const MAX_ENTRIES: usize = 500;
+
+/// `:` lines run from the command line, oldest first, one per line.
+fn history_path() -> Option<PathBuf> {
+    Some(app_dir()?.join("cmdline_history"))
+}
+
+pub fn load() -> Vec<String> {
+    history_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .map(|raw| raw.lines().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Moves `line` to the end of `history`, dropping an earlier copy, and saves it.
+pub fn record(history: &mut Vec<String>, line: &str) -> std::io::Result<()> {
+    history.retain(|l| l != line);
+    history.push(line.replace('\n', " "));
+    let excess = history.len().saturating_sub(MAX_ENTRIES);
+    history.drain(..excess);
+    save(history_path(), history)
+}
+
+/// Names of the configured commands in the order they were last run, most recent
+/// first; `cmd_sort = "recent"` lists them so without touching config.toml.
+fn recent_commands_path() -> Option<PathBuf> {
+    Some(app_dir()?.join("recent_commands"))
+}
+
+pub fn load_recent_commands() -> Vec<String> {
+    recent_commands_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .map(|raw| raw.lines().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Moves `name` to the front of `recent`, dropping an earlier copy, and saves it.
+pub fn record_recent_command(recent: &mut Vec<String>, name: &str) -> std::io::Result<()> {
+    recent.retain(|n| n != name);
+    recent.insert(0, name.replace('\n', " "));
+    recent.truncate(MAX_ENTRIES);
+    save(recent_commands_path(), recent)
+}
+
+fn save(path: Option<PathBuf>, lines: &[String]) -> std::io::Result<()> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut text = lines.join("\n");
+    text.push('\n');
+    fs::write(path, text)
+}
@@ -0,0 +1,89 @@
+/// Finds issue references in `text`: Jira-style keys (`ABC-123`) and `#123`.
+pub fn find_issue_ids(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut ids = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let boundary = i == 0 || !chars[i - 1].is_alphanumeric();
+        if boundary && chars[i] == '#' {
+            let digits = take_while(&chars[i + 1..], |c| c.is_ascii_digit());
+            if digits > 0 {
+                ids.push(chars[i..i + 1 + digits].iter().collect());
+                i += 1 + digits;
+                continue;
+            }
+        }
+        if boundary && chars[i].is_ascii_uppercase() {
+            let key = take_while(&chars[i..], |c| {
+                c.is_ascii_uppercase() || c.is_ascii_digit()
+            });
+            if key >= 2 && chars.get(i + key) == Some(&'-') {
+                let digits = take_while(&chars[i + key + 1..], |c| c.is_ascii_digit());
+                let end = i + key + 1 + digits;
+                let at_end = chars.get(end).is_none_or(|c| !c.is_alphanumeric());
+                if digits > 0 && at_end {
+                    ids.push(chars[i..end].iter().collect());
+                    i = end;
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+    ids
+}
+
+/// The issue a branch such as `feature/ABC-123-login` or `fix/42-crash` is for. Jira keys
+/// are matched as written and a number only where a path segment starts with it, so
+/// `release-2` names no issue.
+pub fn branch_issue_id(branch: &str) -> Option<String> {
+    find_issue_ids(branch)
+        .into_iter()
+        .find(|id| !id.starts_with('#'))
+        .or_else(|| {
+            branch
+                .split('/')
+                .filter_map(|segment| segment.split(['-', '_']).next())
+                .find(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+                .map(|n| format!("#{}", n))
+        })
+}
+
+pub fn issue_url(template: &str, id: &str) -> String {
+    template.replace("{id}", id.trim_start_matches('#'))
+}
+
+fn take_while(chars: &[char], pred: impl Fn(char) -> bool) -> usize {
+    chars.iter().take_while(|c| pred(**c)).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_keys_and_numbers() {
+        assert_eq!(
+            find_issue_ids("ABC-123: fix (#45), see X2Y-7 and abc-9, not #x or AB-12a"),
+            ["ABC-123", "#45", "X2Y-7"]
+        );
+        assert!(find_issue_ids("A-1 x#3 ABC-").is_empty());
+    }
+
+    #[test]
+    fn branch_keys_are_case_sensitive() {
+        let cases = [
+            ("feature/ABC-123-login", Some("ABC-123")),
+            ("ABC-123", Some("ABC-123")),
+            ("fix/42-crash", Some("#42")),
+            ("123_login", Some("#123")),
+            ("feature/fix-1", None),
+            ("release-2", None),
+            ("feature/abc-login", None),
+            ("main", None),
+        ];
+        for (branch, expected) in cases {
+            assert_eq!(branch_issue_id(branch).as_deref(), expected, "{:?}", branch);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::app_dir;
+use crate::git::parse_args_line;
+use crate::notes::sanitize;
+
+/// A destructive command written down before it runs and removed once it ends.
+pub struct JournalEntry {
+    pub started: u64,
+    pub command: String,
+    /// HEAD before the command ran.
+    pub head: Option<String>,
+    /// `git stash create` snapshot of tracked changes before the command ran.
+    pub backup: Option<String>,
+}
+
+impl JournalEntry {
+    pub fn new(command: &str, head: Option<String>, backup: Option<String>) -> Self {
+        Self {
+            started: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            command: command.to_string(),
+            head,
+            backup,
+        }
+    }
+
+    /// What the user can run to get back to the state before the command.
+    pub fn recovery_hints(&self) -> Vec<String> {
+        let subcmd = parse_args_line(&self.command)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        let mut hints = Vec::new();
+        if subcmd == "rebase" {
+            hints.push("git status  (then git rebase --continue or git rebase --abort)".into());
+        }
+        if let Some(head) = &self.head {
+            hints.push(format!(
+                "git reset --hard {}  (HEAD before the command)",
+                head
+            ));
+        }
+        if let Some(backup) = &self.backup {
+            hints.push(format!("git stash apply {}  (uncommitted changes)", backup));
+        }
+        if subcmd == "clean" {
+            hints.push("untracked files removed by clean cannot be restored by git".into());
+        }
+        hints.push("git reflog  (every position HEAD has been at)".into());
+        hints
+    }
+}
+
+/// Commands that can lose work if interrupted or run by mistake.
+pub fn is_destructive(args_str: &str) -> bool {
+    let args = parse_args_line(args_str);
+    let Some(subcmd) = args.first() else {
+        return false;
+    };
+    let has = |flags: &[&str]| args.iter().any(|a| flags.contains(&a.as_str()));
+    match subcmd.as_str() {
+        "reset" | "restore" | "clean" | "rebase" => true,
+        "checkout" => has(&["--", "-f", "--force", "."]),
+        "stash" => has(&["drop", "clear", "pop"]),
+        "branch" => has(&["-D", "--force"]),
+        "push" => has(&["-f", "--force", "--force-with-lease"]),
+        _ => false,
+    }
+}
+
+fn journal_path(repo_root: &Path) -> Option<PathBuf> {
+    Some(
+        app_dir()?
+            .join("journal")
+            .join(format!("{}.txt", sanitize(&repo_root.to_string_lossy()))),
+    )
+}
+
+pub fn write(repo_root: &Path, entry: &JournalEntry) -> io::Result<()> {
+    let Some(path) = journal_path(repo_root) else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut text = format!("started: {}\ncommand: {}\n", entry.started, entry.command);
+    if let Some(head) = &entry.head {
+        text.push_str(&format!("head: {}\n", head));
+    }
+    if let Some(backup) = &entry.backup {
+        text.push_str(&format!("backup: {}\n", backup));
+    }
+    // Write-then-rename so a crash never leaves a half-written journal behind.
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, text)?;
+    fs::rename(tmp, path)
+}
+
+pub fn clear(repo_root: &Path) {
+    if let Some(path) = journal_path(repo_root) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Reads and removes the entry a previous session left behind.
+pub fn take(repo_root: &Path) -> Option<JournalEntry> {
+    let path = journal_path(repo_root)?;
+    let text = fs::read_to_string(&path).ok()?;
+    let _ = fs::remove_file(&path);
+
+    let mut entry = JournalEntry {
+        started: 0,
+        command: String::new(),
+        head: None,
+        backup: None,
+    };
+    for line in text.lines() {
+        match line.split_once(": ") {
+            Some(("started", v)) => entry.started = v.parse().unwrap_or(0),
+            Some(("command", v)) => entry.command = v.to_string(),
+            Some(("head", v)) => entry.head = Some(v.to_string()),
+            Some(("backup", v)) => entry.backup = Some(v.to_string()),
+            _ => {}
+        }
+    }
+    (!entry.command.is_empty()).then_some(entry)
+}
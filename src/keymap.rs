@@ -0,0 +1,573 @@
+//! Rebindable keys from `[keys]`. A key resolves to the action it is bound to, or to the
+//! action whose built-in key it is unless `[keys]` rebound that action; handlers dispatch
+//! on the action after the keys of their own pane, which keep their meaning there.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::config::KeyConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyAction {
+    FocusLeft,
+    FocusRight,
+    Up,
+    Down,
+    Stage,
+    Diff,
+    Discard,
+    Mark,
+    CommandLine,
+    Quit,
+}
+
+impl KeyAction {
+    const ALL: [KeyAction; 10] = [
+        KeyAction::FocusLeft,
+        KeyAction::FocusRight,
+        KeyAction::Up,
+        KeyAction::Down,
+        KeyAction::Stage,
+        KeyAction::Diff,
+        KeyAction::Discard,
+        KeyAction::Mark,
+        KeyAction::CommandLine,
+        KeyAction::Quit,
+    ];
+
+    /// The key the action has unless `[keys]` rebinds it.
+    fn builtin(self) -> char {
+        match self {
+            KeyAction::FocusLeft => 'h',
+            KeyAction::FocusRight => 'l',
+            KeyAction::Up => 'k',
+            KeyAction::Down => 'j',
+            KeyAction::Stage => 's',
+            KeyAction::Diff => 'd',
+            KeyAction::Discard => 'x',
+            KeyAction::Mark => ' ',
+            KeyAction::CommandLine => ':',
+            KeyAction::Quit => 'q',
+        }
+    }
+
+    fn chord(self) -> Chord {
+        Chord::of(&KeyEvent::from(KeyCode::Char(self.builtin())))
+    }
+
+    /// The built-in key as the help overlay shows it.
+    fn builtin_label(self) -> String {
+        match self.builtin() {
+            ' ' => "Space".to_string(),
+            c => c.to_string(),
+        }
+    }
+
+    /// Stage, diff, discard and mark are FILES keys; elsewhere their keys mean other
+    /// things.
+    fn files_only(self) -> bool {
+        matches!(
+            self,
+            KeyAction::Stage | KeyAction::Diff | KeyAction::Discard | KeyAction::Mark
+        )
+    }
+
+    /// Its name in `[keys]`.
+    fn name(self) -> &'static str {
+        match self {
+            KeyAction::FocusLeft => "focus_left",
+            KeyAction::FocusRight => "focus_right",
+            KeyAction::Up => "up",
+            KeyAction::Down => "down",
+            KeyAction::Stage => "stage",
+            KeyAction::Diff => "diff",
+            KeyAction::Discard => "discard",
+            KeyAction::Mark => "mark",
+            KeyAction::CommandLine => "command_line",
+            KeyAction::Quit => "quit",
+        }
+    }
+
+    fn configured(self, cfg: &KeyConfig) -> Option<&Vec<String>> {
+        match self {
+            KeyAction::FocusLeft => cfg.focus_left.as_ref(),
+            KeyAction::FocusRight => cfg.focus_right.as_ref(),
+            KeyAction::Up => cfg.up.as_ref(),
+            KeyAction::Down => cfg.down.as_ref(),
+            KeyAction::Stage => cfg.stage.as_ref(),
+            KeyAction::Diff => cfg.diff.as_ref(),
+            KeyAction::Discard => cfg.discard.as_ref(),
+            KeyAction::Mark => cfg.mark.as_ref(),
+            KeyAction::CommandLine => cfg.command_line.as_ref(),
+            KeyAction::Quit => cfg.quit.as_ref(),
+        }
+    }
+}
+
+/// Keys the whole UI handles before any rebindable action but `command_line` and
+/// `quit`, with what they do.
+const GLOBAL_KEYS: [(&str, &str); 7] = [
+    ("ctrl-c", "cancel"),
+    ("ctrl-t", "the file finder"),
+    ("?", "help"),
+    ("{", "the previous tab"),
+    ("}", "the next tab"),
+    ("L", "the layout"),
+    ("|", "the side-by-side diff"),
+];
+
+/// Keys some panes handle before any rebindable action, everywhere but FILES.
+const PANE_KEYS: [(&str, &str); 2] = [
+    ("v", "text selection in LOG and RESULT"),
+    ("tab", "cycling the list panel"),
+];
+
+/// Keys FILES handles before any rebindable action.
+const FILES_KEYS: [(&str, &str); 11] = [
+    ("o", "taking ours"),
+    ("t", "taking theirs"),
+    ("m", "the mergetool"),
+    ("r", "marking resolved"),
+    ("v", "the visual range"),
+    ("T", "the file tree"),
+    ("W", "the function context"),
+    ("H", "hydrating"),
+    ("f", "the file history"),
+    ("enter", "the hunk view"),
+    ("esc", "clearing marks"),
+];
+
+/// Keys CMD keeps for moving commands, besides the global keys and those of actions.
+const CMD_KEYS: &str = "JK";
+
+/// What `chord` does when it is one of `keys`.
+fn reserved(keys: &[(&str, &'static str)], chord: Chord) -> Option<&'static str> {
+    keys.iter()
+        .find(|(key, _)| Chord::parse(key) == Some(chord))
+        .map(|(_, what)| *what)
+}
+
+/// A key with its Ctrl and Alt modifiers; Shift is part of the character.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Chord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Chord {
+    fn of(key: &KeyEvent) -> Self {
+        Chord {
+            code: key.code,
+            modifiers: key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        loop {
+            if let Some(r) = rest.strip_prefix("ctrl-") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("alt-") {
+                modifiers |= KeyModifiers::ALT;
+                rest = r;
+            } else {
+                break;
+            }
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next()?, chars.next()) {
+            (c, None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "enter" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "esc" => KeyCode::Esc,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => KeyCode::F(name.strip_prefix('f')?.parse().ok()?),
+            },
+        };
+        Some(Chord { code, modifiers })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    /// Each chord with its action and the text it was written as.
+    bindings: Vec<(Chord, KeyAction, String)>,
+    /// Actions whose built-in key was replaced by `[keys]`.
+    rebound: Vec<KeyAction>,
+    /// `[keys.actions]`: each chord with the name of the action it runs and its text.
+    named: Vec<(Chord, String, String)>,
+}
+
+impl Keymap {
+    /// The keymap for `[keys]`, and a message for each key it could not read.
+    pub fn new(cfg: &KeyConfig) -> (Self, Vec<String>) {
+        let mut keymap = Keymap {
+            bindings: Vec::new(),
+            rebound: Vec::new(),
+            named: Vec::new(),
+        };
+        let mut errors = Vec::new();
+        for action in KeyAction::ALL {
+            let Some(keys) = action.configured(cfg) else {
+                continue;
+            };
+            keymap.rebound.push(action);
+            for key in keys {
+                match Chord::parse(key) {
+                    Some(chord) => keymap.bindings.push((chord, action, key.clone())),
+                    None => errors.push(format!("[keys] {}: unknown key {:?}", action.name(), key)),
+                }
+            }
+        }
+        for (name, keys) in &cfg.actions {
+            for key in keys {
+                match Chord::parse(key) {
+                    Some(chord) => keymap.named.push((chord, name.clone(), key.clone())),
+                    None => errors.push(format!("[keys.actions] {}: unknown key {:?}", name, key)),
+                }
+            }
+        }
+        errors.extend(keymap.clashes());
+        errors.extend(keymap.named_clashes());
+        (keymap, errors)
+    }
+
+    /// A message for each key `[keys]` gave an action while another action keeps it,
+    /// which would leave that other action without a key: `stage = ["space"]` needs
+    /// `mark` bound elsewhere. Keys the UI or FILES handle first never reach an
+    /// action, so binding one is reported too.
+    fn clashes(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (i, (chord, action, text)) in self.bindings.iter().enumerate() {
+            if let Some(what) =
+                reserved(&GLOBAL_KEYS, *chord).or_else(|| reserved(&PANE_KEYS, *chord))
+            {
+                errors.push(format!(
+                    "[keys] {}: {:?} is the key for {}; pick another key",
+                    action.name(),
+                    text,
+                    what
+                ));
+                continue;
+            }
+            if let Some(what) = reserved(&FILES_KEYS, *chord) {
+                errors.push(format!(
+                    "[keys] {}: {:?} is the FILES key for {}; pick another key",
+                    action.name(),
+                    text,
+                    what
+                ));
+                continue;
+            }
+            let taken = self.bindings[..i]
+                .iter()
+                .find(|(c, a, _)| c == chord && a != action)
+                .map(|(_, a, _)| *a)
+                .or_else(|| {
+                    KeyAction::ALL.into_iter().find(|other| {
+                        other != action && !self.rebound.contains(other) && *chord == other.chord()
+                    })
+                });
+            if let Some(other) = taken {
+                errors.push(format!(
+                    "[keys] {}: {:?} is also the key of {}; bind {} to other keys",
+                    action.name(),
+                    text,
+                    other.name(),
+                    other.name()
+                ));
+            }
+        }
+        errors
+    }
+
+    /// A message for each `[keys.actions]` key that another key already means; named
+    /// actions run in every pane, so FILES keys and the built-in keys of actions count.
+    fn named_clashes(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (i, (chord, name, text)) in self.named.iter().enumerate() {
+            let key = KeyEvent::new(chord.code, chord.modifiers);
+            let taken = reserved(&GLOBAL_KEYS, *chord)
+                .or_else(|| reserved(&PANE_KEYS, *chord))
+                .or_else(|| reserved(&FILES_KEYS, *chord))
+                .map(String::from)
+                .or_else(|| self.action(&key, true).map(|a| a.name().to_string()))
+                .or_else(|| {
+                    self.named[..i]
+                        .iter()
+                        .find(|(c, n, _)| c == chord && n != name)
+                        .map(|(_, n, _)| n.clone())
+                });
+            if let Some(what) = taken {
+                errors.push(format!(
+                    "[keys.actions] {}: {:?} is already the key for {}; pick another key",
+                    name, text, what
+                ));
+            }
+        }
+        errors
+    }
+
+    /// The name of the `[keys.actions]` action `key` runs, if any.
+    pub fn named_action(&self, key: &KeyEvent) -> Option<&str> {
+        let chord = Chord::of(key);
+        self.named
+            .iter()
+            .find(|(c, _, _)| *c == chord)
+            .map(|(_, name, _)| name.as_str())
+    }
+
+    /// The `[keys.actions]` bindings as `(keys, action name)`, for the help overlay.
+    pub fn named_keys(&self) -> Vec<(String, &str)> {
+        let mut keys: Vec<(String, &str)> = Vec::new();
+        for (_, name, text) in &self.named {
+            match keys.iter_mut().find(|(_, n)| n == name) {
+                Some((texts, _)) => {
+                    texts.push('/');
+                    texts.push_str(text);
+                }
+                None => keys.push((text.clone(), name)),
+            }
+        }
+        keys
+    }
+
+    /// The action `key` does, if any; `in_files` enables the FILES-only actions.
+    pub fn action(&self, key: &KeyEvent, in_files: bool) -> Option<KeyAction> {
+        let applies = |action: &KeyAction| in_files || !action.files_only();
+        let chord = Chord::of(key);
+        if let Some((_, action, _)) = self
+            .bindings
+            .iter()
+            .find(|(c, action, _)| *c == chord && applies(action))
+        {
+            return Some(*action);
+        }
+        KeyAction::ALL.into_iter().find(|action| {
+            applies(action) && !self.rebound.contains(action) && chord == action.chord()
+        })
+    }
+
+    /// Whether `key` does what the built-in key `c` does: the same action when `c` is
+    /// the built-in key of one, otherwise `c` itself.
+    pub fn means(&self, key: &KeyEvent, c: char, in_files: bool) -> bool {
+        match KeyAction::ALL
+            .into_iter()
+            .find(|action| (in_files || !action.files_only()) && action.builtin() == c)
+        {
+            Some(action) => self.action(key, in_files) == Some(action),
+            None => Chord::of(key) == Chord::of(&KeyEvent::from(KeyCode::Char(c))),
+        }
+    }
+
+    /// Whether CMD keeps `c` for itself, so a `[[commands]]` entry cannot use it: the
+    /// keys of actions outside FILES and of `[keys.actions]`, `J`/`K` and the global keys.
+    pub fn reserves_command_key(&self, c: char) -> bool {
+        let key = KeyEvent::from(KeyCode::Char(c));
+        CMD_KEYS.contains(c)
+            || reserved(&GLOBAL_KEYS, Chord::of(&key)).is_some()
+            || self.action(&key, false).is_some()
+            || self.named_action(&key).is_some()
+    }
+
+    /// The keys that do `action`, as `[keys]` spells them, for the help overlay; `None`
+    /// when `[keys]` left it without any.
+    pub fn keys(&self, action: KeyAction) -> Option<String> {
+        if !self.rebound.contains(&action) {
+            return Some(action.builtin_label());
+        }
+        let keys: Vec<&str> = self
+            .bindings
+            .iter()
+            .filter(|(_, a, _)| *a == action)
+            .map(|(_, _, text)| text.as_str())
+            .collect();
+        (!keys.is_empty()).then(|| keys.join("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(code: KeyCode, modifiers: KeyModifiers) -> Option<Chord> {
+        Some(Chord { code, modifiers })
+    }
+
+    #[test]
+    fn parses_chords() {
+        let none = KeyModifiers::NONE;
+        let cases = [
+            ("j", chord(KeyCode::Char('j'), none)),
+            ("J", chord(KeyCode::Char('J'), none)),
+            ("space", chord(KeyCode::Char(' '), none)),
+            ("Enter", chord(KeyCode::Enter, none)),
+            ("pagedown", chord(KeyCode::PageDown, none)),
+            ("f5", chord(KeyCode::F(5), none)),
+            ("ctrl-n", chord(KeyCode::Char('n'), KeyModifiers::CONTROL)),
+            (
+                "ctrl-alt-up",
+                chord(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::ALT),
+            ),
+            ("alt--", chord(KeyCode::Char('-'), KeyModifiers::ALT)),
+            ("", None),
+            ("ctrl-", None),
+            ("spacebar", None),
+            ("fx", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(Chord::parse(text), expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn chords_ignore_shift() {
+        let key = KeyEvent::new(KeyCode::Char('J'), KeyModifiers::SHIFT);
+        assert_eq!(Some(Chord::of(&key)), Chord::parse("J"));
+    }
+
+    fn keymap(cfg: KeyConfig) -> Keymap {
+        let (keymap, errors) = Keymap::new(&cfg);
+        assert!(errors.is_empty(), "{:?}", errors);
+        keymap
+    }
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::from(KeyCode::Char(c))
+    }
+
+    #[test]
+    fn resolves_built_in_keys() {
+        let keymap = keymap(KeyConfig::default());
+        assert_eq!(keymap.action(&key('j'), false), Some(KeyAction::Down));
+        assert_eq!(keymap.action(&key('s'), true), Some(KeyAction::Stage));
+        // FILES keys mean other things elsewhere.
+        assert_eq!(keymap.action(&key('s'), false), None);
+        assert_eq!(keymap.action(&key('n'), false), None);
+    }
+
+    #[test]
+    fn rebinding_frees_the_built_in_key() {
+        let keymap = keymap(KeyConfig {
+            down: Some(vec!["n".into(), "ctrl-n".into()]),
+            ..KeyConfig::default()
+        });
+        assert_eq!(keymap.action(&key('n'), false), Some(KeyAction::Down));
+        let ctrl_n = KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL);
+        assert_eq!(keymap.action(&ctrl_n, false), Some(KeyAction::Down));
+        assert_eq!(keymap.action(&key('j'), false), None);
+        assert_eq!(keymap.keys(KeyAction::Down).as_deref(), Some("n/ctrl-n"));
+    }
+
+    #[test]
+    fn again_follows_a_rebound_action() {
+        let keymap = keymap(KeyConfig {
+            discard: Some(vec!["z".into()]),
+            ..KeyConfig::default()
+        });
+        assert!(keymap.means(&key('z'), 'x', true));
+        assert!(!keymap.means(&key('x'), 'x', true));
+        assert!(keymap.means(&key('D'), 'D', false));
+    }
+
+    #[test]
+    fn reports_keys_taken_from_another_action() {
+        let (_, errors) = Keymap::new(&KeyConfig {
+            stage: Some(vec!["space".into()]),
+            ..KeyConfig::default()
+        });
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("mark"), "{}", errors[0]);
+        let (_, errors) = Keymap::new(&KeyConfig {
+            stage: Some(vec!["space".into()]),
+            mark: Some(vec!["M".into()]),
+            ..KeyConfig::default()
+        });
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn reports_keys_handled_before_actions() {
+        let cases = [
+            (
+                KeyConfig {
+                    down: Some(vec!["f".into()]),
+                    ..KeyConfig::default()
+                },
+                "file history",
+            ),
+            (
+                KeyConfig {
+                    stage: Some(vec!["L".into()]),
+                    ..KeyConfig::default()
+                },
+                "layout",
+            ),
+            (
+                KeyConfig {
+                    up: Some(vec!["tab".into()]),
+                    ..KeyConfig::default()
+                },
+                "list panel",
+            ),
+        ];
+        for (cfg, what) in cases {
+            let (_, errors) = Keymap::new(&cfg);
+            assert_eq!(errors.len(), 1, "{:?}", errors);
+            assert!(errors[0].contains(what), "{}", errors[0]);
+        }
+    }
+
+    #[test]
+    fn binds_named_actions() {
+        let named = |pairs: &[(&str, &str)]| KeyConfig {
+            actions: pairs
+                .iter()
+                .map(|(name, key)| (name.to_string(), vec![key.to_string()]))
+                .collect(),
+            ..KeyConfig::default()
+        };
+        let keymap = keymap(named(&[("toggle-diff-mode", "D"), ("branches", "ctrl-b")]));
+        assert_eq!(keymap.named_action(&key('D')), Some("toggle-diff-mode"));
+        let ctrl_b = KeyEvent::new(KeyCode::Char('b'), KeyModifiers::CONTROL);
+        assert_eq!(keymap.named_action(&ctrl_b), Some("branches"));
+        assert!(keymap.reserves_command_key('D'));
+
+        for taken in ["s", "f", "?", "j"] {
+            let (_, errors) = Keymap::new(&named(&[("refresh", taken)]));
+            assert_eq!(errors.len(), 1, "{:?}: {:?}", taken, errors);
+        }
+    }
+
+    #[test]
+    fn command_keys_follow_rebound_actions() {
+        let keymap = keymap(KeyConfig {
+            down: Some(vec!["n".into()]),
+            ..KeyConfig::default()
+        });
+        let cases = [
+            ('n', true),
+            ('j', false),
+            ('k', true),
+            ('J', true),
+            ('?', true),
+            ('s', false),
+            ('v', false),
+        ];
+        for (c, reserved) in cases {
+            assert_eq!(keymap.reserves_command_key(c), reserved, "{:?}", c);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! The simple-git-tui application as a library, so integration tests can drive it.
+
+pub mod app;
+pub mod cli;
+mod clipboard;
+pub mod config;
+pub mod crash;
+mod decorations;
+mod define;
+mod editor;
+mod graph_export;
+mod history;
+mod issues;
+mod journal;
+mod keymap;
+mod links;
+mod notes;
+mod pull_request;
+pub mod theme;
+mod timings;
+mod updates;
+
+use simple_git_tui_core::{
+    codeowners, diff, doctor, git, jobs, preview, range_diff, shell, syntax,
+};
@@ -0,0 +1,214 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::{Attribute, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor},
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+};
+
+use crate::config::LinkMode;
+use crate::issues::{find_issue_ids, issue_url};
+
+/// A run of cells printed again inside an OSC 8 hyperlink after the frame is drawn.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Hyperlink {
+    pub x: u16,
+    pub y: u16,
+    pub url: String,
+    pub cells: Vec<(String, Style)>,
+}
+
+/// What text can link to; built per frame by the view model.
+pub struct LinkTargets<'a> {
+    /// Web page of the repository, e.g. `https://github.com/owner/repo`.
+    pub web: Option<&'a str>,
+    pub repo_root: &'a Path,
+    /// Repository-relative paths shown in the UI.
+    pub files: Vec<&'a str>,
+    pub issue_url: Option<&'a str>,
+}
+
+impl LinkTargets<'_> {
+    fn url_for(&self, token: &str) -> Option<String> {
+        if let Some(web) = self.web
+            && is_commit_hash(token)
+        {
+            return Some(format!("{}/commit/{}", web, token));
+        }
+        if let Some(template) = self.issue_url
+            && find_issue_ids(token).first().is_some_and(|id| id == token)
+        {
+            return Some(issue_url(template, token));
+        }
+        // `a/src/main.rs` in diff headers, `src/main.rs:12:` in grep output.
+        let path = token.split(':').next().unwrap_or(token);
+        let path = ["a/", "b/"]
+            .iter()
+            .find_map(|p| {
+                path.strip_prefix(p)
+                    .filter(|rest| self.files.contains(rest))
+            })
+            .unwrap_or(path);
+        if self.files.contains(&path) {
+            return Some(file_url(&self.repo_root.join(path)));
+        }
+        None
+    }
+}
+
+/// Whether hyperlinks are written, given `links.mode` from the config.
+pub fn enabled(mode: LinkMode) -> bool {
+    match mode {
+        LinkMode::Always => true,
+        LinkMode::Never => false,
+        LinkMode::Auto => terminal_supports_links(),
+    }
+}
+
+/// Terminals known to render OSC 8; others may print the sequence as garbage.
+fn terminal_supports_links() -> bool {
+    let var = |name| std::env::var(name).unwrap_or_default();
+    let program = var("TERM_PROGRAM");
+    let term = var("TERM");
+    ["iTerm.app", "WezTerm", "vscode", "ghostty", "Hyper"].contains(&program.as_str())
+        || !var("WT_SESSION").is_empty()
+        || !var("KITTY_WINDOW_ID").is_empty()
+        || var("VTE_VERSION").parse::<u32>().is_ok_and(|v| v >= 5000)
+        || ["kitty", "alacritty", "foot", "wezterm"]
+            .iter()
+            .any(|t| term.contains(t))
+}
+
+/// Web page for a remote URL in any of the forms git accepts:
+/// `git@host:owner/repo.git`, `ssh://git@host:22/owner/repo.git`, `https://host/owner/repo.git`.
+pub fn web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim().trim_end_matches('/');
+    let remote = remote.strip_suffix(".git").unwrap_or(remote);
+    let (host, path) = if let Some((scheme, rest)) = remote.split_once("://") {
+        if !matches!(scheme, "https" | "http" | "ssh" | "git") {
+            return None;
+        }
+        let (authority, path) = rest.split_once('/')?;
+        let host = authority.rsplit('@').next()?;
+        let host = if scheme == "ssh" || scheme == "git" {
+            host.split(':').next()?
+        } else {
+            host
+        };
+        (host, path)
+    } else {
+        let (authority, path) = remote.split_once(':')?;
+        (authority.rsplit('@').next()?, path)
+    };
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+    Some(format!("https://{}/{}", host, path.trim_start_matches('/')))
+}
+
+fn file_url(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut url = String::from("file://");
+    if !path.starts_with('/') {
+        url.push('/');
+    }
+    for c in path.chars() {
+        match c {
+            ' ' => url.push_str("%20"),
+            '#' => url.push_str("%23"),
+            '%' => url.push_str("%25"),
+            '?' => url.push_str("%3F"),
+            _ => url.push(c),
+        }
+    }
+    url
+}
+
+fn is_commit_hash(token: &str) -> bool {
+    (7..=40).contains(&token.len())
+        && token.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+        // Rules out words such as `defaced`.
+        && token.bytes().any(|b| b.is_ascii_digit())
+}
+
+/// Scans the drawn text of `area` for hashes, paths and issue IDs.
+pub fn find_links(buf: &Buffer, area: Rect, targets: &LinkTargets) -> Vec<Hyperlink> {
+    let area = area.intersection(buf.area);
+    let mut links = Vec::new();
+    for y in area.top()..area.bottom() {
+        let cells: Vec<&ratatui::buffer::Cell> =
+            (area.left()..area.right()).map(|x| &buf[(x, y)]).collect();
+        let mut start = 0;
+        while start < cells.len() {
+            if cells[start].symbol().trim().is_empty() {
+                start += 1;
+                continue;
+            }
+            let end = (start..cells.len())
+                .find(|&i| cells[i].symbol().trim().is_empty())
+                .unwrap_or(cells.len());
+            let word: String = cells[start..end].iter().map(|c| c.symbol()).collect();
+            // Every cell of a token is one ASCII character, so char and cell offsets agree.
+            if word.is_ascii() && word.len() == end - start {
+                let lead = word.len() - word.trim_start_matches(['(', '[', '<', '\'', '"']).len();
+                let token =
+                    word[lead..].trim_end_matches([',', '.', ')', ']', '>', '\'', '"', ';']);
+                if let Some(url) = targets.url_for(token) {
+                    let from = start + lead;
+                    links.push(Hyperlink {
+                        x: area.x + from as u16,
+                        y,
+                        url,
+                        cells: cells[from..from + token.len()]
+                            .iter()
+                            .map(|c| (c.symbol().to_string(), c.style()))
+                            .collect(),
+                    });
+                }
+            }
+            start = end;
+        }
+    }
+    links
+}
+
+/// Prints `links` over what is already on screen, wrapped in OSC 8 sequences.
+/// Ratatui's diffing cannot carry the escape codes, so this runs after each draw.
+pub fn write_links(out: &mut impl Write, links: &[Hyperlink]) -> io::Result<()> {
+    for link in links {
+        queue!(
+            out,
+            MoveTo(link.x, link.y),
+            Print(format!("\x1b]8;;{}\x1b\\", link.url))
+        )?;
+        for (symbol, style) in &link.cells {
+            queue!(
+                out,
+                SetAttribute(Attribute::Reset),
+                SetForegroundColor(style.fg.unwrap_or_default().into()),
+                SetBackgroundColor(style.bg.unwrap_or_default().into()),
+            )?;
+            for (modifier, attribute) in [
+                (Modifier::BOLD, Attribute::Bold),
+                (Modifier::DIM, Attribute::Dim),
+                (Modifier::ITALIC, Attribute::Italic),
+                (Modifier::UNDERLINED, Attribute::Underlined),
+                (Modifier::REVERSED, Attribute::Reverse),
+            ] {
+                if style.add_modifier.contains(modifier) {
+                    queue!(out, SetAttribute(attribute))?;
+                }
+            }
+            queue!(out, Print(symbol))?;
+        }
+        queue!(out, Print("\x1b]8;;\x1b\\"))?;
+    }
+    queue!(out, SetAttribute(Attribute::Reset), ResetColor)?;
+    out.flush()
+}
@@ -1,22 +1,44 @@
-mod app;
-mod config;
-mod define;
-mod git;
-mod theme;
-
-use std::sync::{Arc, atomic::AtomicBool, mpsc};
-
-use app::App;
-use config::load_config;
-use theme::Theme;
+use simple_git_tui::app::App;
+use simple_git_tui::cli::{self, Invocation};
+use simple_git_tui::config::load_config;
+use simple_git_tui::crash;
+use simple_git_tui::theme::Theme;
 
 fn main() -> anyhow::Result<()> {
-    let cfg = load_config()?;
-    let theme = Theme::from_config(&cfg.colors);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match cli::parse(&args) {
+        Ok(Invocation::Run(options)) => options,
+        Ok(Invocation::Help) => {
+            println!("{}", cli::help());
+            return Ok(());
+        }
+        Ok(Invocation::Man) => {
+            print!("{}", cli::man_page());
+            return Ok(());
+        }
+        Ok(Invocation::Completions(shell)) => {
+            print!("{}", cli::completions(shell));
+            return Ok(());
+        }
+        Ok(Invocation::Bench(runs)) => {
+            let cfg = load_config()?;
+            let theme = Theme::from_config(cfg.theme.as_deref(), &cfg.colors);
+            println!("{}", App::new(cfg, theme).bench(runs)?);
+            return Ok(());
+        }
+        Err(e) => anyhow::bail!("{}\n{}", e, cli::usage()),
+    };
+    let mut cfg = load_config()?;
+    cfg.read_only |= options.read_only;
+    cfg.audit |= options.audit;
+    let theme = Theme::from_config(cfg.theme.as_deref(), &cfg.colors);
 
-    let (tx, rx) = mpsc::channel();
-    let cancel_flag = Arc::new(AtomicBool::new(false));
-    let app = App::new(cfg, theme, tx, rx, cancel_flag);
+    crash::install_panic_hook();
+    let app = App::new(cfg, theme);
 
-    app.run()
+    let res = app.run();
+    if let Err(e) = &res {
+        crash::print_report_path(crash::write_report(&format!("error: {:#}", e)).as_deref());
+    }
+    res
 }
@@ -0,0 +1,46 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::app_dir;
+
+/// Location of the scratchpad for `branch` in the repository at `repo_root`.
+pub fn note_path(repo_root: &Path, branch: &str) -> Option<PathBuf> {
+    let repo_key = sanitize(&repo_root.to_string_lossy());
+    Some(
+        app_dir()?
+            .join("notes")
+            .join(repo_key)
+            .join(format!("{}.md", sanitize(branch))),
+    )
+}
+
+pub fn first_line(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()?
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(|l| l.to_string())
+}
+
+pub fn append_line(path: &Path, text: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", text)
+}
+
+/// File-name-safe form of a path or branch name.
+pub fn sanitize(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
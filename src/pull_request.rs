@@ -0,0 +1,151 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::app_dir;
+use crate::git::CommandResult;
+use crate::notes::sanitize;
+use crate::shell::hide_console;
+
+/// Where GitHub looks for a pull request template, in order.
+const TEMPLATES: [&str; 4] = [
+    ".github/PULL_REQUEST_TEMPLATE.md",
+    ".github/pull_request_template.md",
+    "PULL_REQUEST_TEMPLATE.md",
+    "docs/pull_request_template.md",
+];
+
+/// Headings whose section receives the commit messages.
+const SUMMARY_HEADINGS: [&str; 5] = ["summary", "description", "changes", "what", "overview"];
+
+/// The first pull request template found in `repo`.
+pub fn load_template(repo: &Path) -> Option<(PathBuf, String)> {
+    TEMPLATES.iter().find_map(|location| {
+        let path = repo.join(location);
+        let text = fs::read_to_string(&path).ok()?;
+        Some((path, text))
+    })
+}
+
+/// Location of the description being written for `branch` in the repository at `repo_root`.
+pub fn draft_path(repo_root: &Path, branch: &str) -> Option<PathBuf> {
+    let repo_key = sanitize(&repo_root.to_string_lossy());
+    Some(
+        app_dir()?
+            .join("pr")
+            .join(repo_key)
+            .join(format!("{}.md", sanitize(branch))),
+    )
+}
+
+/// A title line, a blank line and the body, like `gh pr create --fill`: one commit gives
+/// its own message, several give the branch name and a list of their subjects. The body
+/// goes under the template's summary-like heading, or above the template without one.
+pub fn draft(branch: &str, messages: &[String], template: Option<&str>) -> String {
+    let (title, fill) = match messages {
+        [message] => {
+            let (subject, body) = message.split_once('\n').unwrap_or((message, ""));
+            let body = body.trim();
+            (
+                subject.trim().to_string(),
+                if body.is_empty() {
+                    subject.trim()
+                } else {
+                    body
+                }
+                .to_string(),
+            )
+        }
+        _ => (
+            branch.replace(['-', '_', '/'], " "),
+            messages
+                .iter()
+                .filter_map(|m| m.lines().next())
+                .map(|subject| format!("- {}", subject.trim()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+    };
+    let body = match template {
+        None => fill,
+        Some(template) => {
+            let mut lines: Vec<String> = template.lines().map(str::to_string).collect();
+            let heading = lines.iter().position(|l| {
+                let name = l.trim_start_matches('#').trim().to_lowercase();
+                l.starts_with('#') && SUMMARY_HEADINGS.iter().any(|h| name.starts_with(h))
+            });
+            match heading {
+                Some(i) => {
+                    lines.insert(i + 1, String::new());
+                    lines.insert(i + 2, fill);
+                    lines.join("\n")
+                }
+                None => format!("{}\n\n{}", fill, template.trim_end()),
+            }
+        }
+    };
+    format!("{}\n\n{}\n", title, body.trim_end())
+}
+
+/// Splits an edited draft into its title and body; `None` when the title is empty.
+pub fn parse_draft(text: &str) -> Option<(String, String)> {
+    let (title, body) = text.split_once('\n').unwrap_or((text, ""));
+    let title = title.trim();
+    (!title.is_empty()).then(|| (title.to_string(), body.trim().to_string()))
+}
+
+/// The `gh` invocation that opens the pull request; the body is fed on stdin.
+pub fn gh_pr_create(title: &str) -> Command {
+    let mut cmd = Command::new("gh");
+    hide_console(&mut cmd).args(["pr", "create", "--title", title, "--body-file", "-"]);
+    cmd
+}
+
+/// Runs `cmd` in `repo` with `body` on stdin and collects its output the way git
+/// commands report theirs.
+pub fn run_gh(mut cmd: Command, repo: &Path, body: &str) -> CommandResult {
+    let mut res = CommandResult {
+        log_lines: Vec::new(),
+        result_lines: vec!["$ gh pr create".into()],
+        success: false,
+    };
+    let output = cmd
+        .current_dir(repo)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            // gh reads the whole body before it talks to GitHub, so this cannot block.
+            child
+                .stdin
+                .take()
+                .expect("stdin is piped")
+                .write_all(body.as_bytes())?;
+            child.wait_with_output()
+        });
+    match output {
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            res.log_lines.extend(stdout.lines().map(str::to_string));
+            // gh prints the new pull request's URL.
+            res.result_lines.extend(
+                stdout
+                    .lines()
+                    .filter(|l| l.starts_with("https://"))
+                    .map(str::to_string),
+            );
+            res.result_lines
+                .push(format!("exit code: {}", output.status.code().unwrap_or(-1)));
+            res.result_lines.extend(stderr.lines().map(str::to_string));
+            res.success = output.status.success();
+        }
+        Err(e) => res.result_lines.push(format!(
+            "ERROR: failed to run gh ({}); install GitHub CLI from https://cli.github.com",
+            e
+        )),
+    }
+    res
+}
@@ -1,5 +1,7 @@
 use crate::config::ColorConfig;
-use ratatui::style::Color;
+use crate::syntax::TokenKind;
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier, Style};
 
 #[derive(Clone)]
 pub struct Theme {
@@ -8,37 +10,409 @@ pub struct Theme {
     pub error: Color,
     #[allow(dead_code)]
     pub background: Color,
+    /// FILES entries by state: staged, changed in the worktree only, untracked, and
+    /// left unmerged.
+    pub staged: Color,
+    pub unstaged: Color,
+    pub untracked: Color,
+    pub conflicted: Color,
+    /// Background of the selected row of a list; `Reset` leaves it to bold text.
+    pub selection: Color,
+    /// Borders of the panes without focus; the focused one takes the accent.
+    pub border: Color,
+    pub support: ColorSupport,
+    /// Colors of source tokens in diffs; `None` when `[colors] syntax = false`.
+    pub syntax: Option<SyntaxColors>,
 }
 
-impl Theme {
-    pub fn from_config(cfg: &ColorConfig) -> Self {
-        fn parse_color(s: &str) -> Color {
-            match s.to_lowercase().as_str() {
-                "black" => Color::Black,
-                "white" => Color::White,
-                "red" => Color::Red,
-                "green" => Color::Green,
-                "blue" => Color::Blue,
-                "yellow" => Color::Yellow,
-                "magenta" => Color::Magenta,
-                "cyan" => Color::Cyan,
-                "gray" | "grey" => Color::Gray,
-                _ => Color::Reset,
+#[derive(Clone, Copy)]
+pub struct SyntaxColors {
+    pub keyword: Color,
+    pub string: Color,
+    pub comment: Color,
+    pub number: Color,
+}
+
+impl SyntaxColors {
+    pub fn of(&self, kind: TokenKind) -> Color {
+        match kind {
+            TokenKind::Keyword => self.keyword,
+            TokenKind::String => self.string,
+            TokenKind::Comment => self.comment,
+            TokenKind::Number => self.number,
+        }
+    }
+}
+
+/// How many colors the terminal can show; richer colors are converted down to fit.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum ColorSupport {
+    /// `NO_COLOR` or a dumb terminal: attributes only.
+    None,
+    Basic,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// `mode` is `colors.mode` from the config; `auto` (or unset) inspects the environment.
+    pub fn detect(mode: Option<&str>) -> Self {
+        match mode.map(str::to_lowercase).as_deref() {
+            Some("none") => return ColorSupport::None,
+            Some("16") => return ColorSupport::Basic,
+            Some("256") => return ColorSupport::Ansi256,
+            Some("truecolor") => return ColorSupport::TrueColor,
+            _ => {}
+        }
+        let var = |name| std::env::var(name).unwrap_or_default();
+        // https://no-color.org: any non-empty value disables color.
+        if !var("NO_COLOR").is_empty() {
+            return ColorSupport::None;
+        }
+        let colorterm = var("COLORTERM");
+        let term = var("TERM");
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorSupport::TrueColor
+        } else if term.contains("256color") {
+            ColorSupport::Ansi256
+        } else if term == "dumb" {
+            ColorSupport::None
+        } else if cfg!(windows) && term.is_empty() {
+            // Windows Terminal and recent conhost render 24-bit color without setting TERM.
+            ColorSupport::TrueColor
+        } else {
+            ColorSupport::Basic
+        }
+    }
+
+    pub fn convert(self, color: Color) -> Color {
+        match (self, color) {
+            (_, Color::Reset) | (ColorSupport::TrueColor, _) => color,
+            (ColorSupport::None, _) => Color::Reset,
+            (ColorSupport::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(rgb_to_256(r, g, b)),
+            (ColorSupport::Ansi256, _) => color,
+            (ColorSupport::Basic, Color::Rgb(r, g, b)) => nearest_basic(r, g, b),
+            (ColorSupport::Basic, Color::Indexed(i)) => {
+                let (r, g, b) = indexed_to_rgb(i);
+                nearest_basic(r, g, b)
             }
+            (ColorSupport::Basic, _) => color,
         }
+    }
+
+    /// Converts every cell of a drawn frame, covering colors that do not come from the theme
+    /// (diff highlighting, ANSI output of git).
+    pub fn apply(self, buf: &mut Buffer) {
+        if self == ColorSupport::TrueColor {
+            return;
+        }
+        for cell in buf.content.iter_mut() {
+            cell.fg = self.convert(cell.fg);
+            cell.bg = self.convert(cell.bg);
+        }
+    }
+}
+
+/// The 16 ANSI colors with xterm's default RGB values.
+const BASIC_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
 
+fn nearest_basic(r: u8, g: u8, b: u8) -> Color {
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(r, cr) + d(g, cg) + d(b, cb)
+    };
+    BASIC_COLORS
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+/// Levels of one channel in the 6x6x6 cube of the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    if r == g && g == b {
+        return match r {
+            0..8 => 16,
+            249.. => 231,
+            _ => 232 + ((r as u16 - 8) * 24 / 241) as u8,
+        };
+    }
+    let level = |v: u8| match v {
+        0..48 => 0,
+        48..115 => 1,
+        _ => (v - 35) / 40,
+    };
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+fn indexed_to_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..16 => BASIC_COLORS[i as usize].1,
+        16..232 => {
+            let i = i - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let v = 8 + (i - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+/// A color name, `#rrggbb`, or `ansi:<n>` for entry `n` of the 256-color palette;
+/// anything else is the terminal's default color.
+fn parse_color(s: &str) -> Color {
+    let s = s.trim().to_lowercase();
+    if let Some(hex) = s.strip_prefix('#') {
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+        };
+        return match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Color::Rgb(r, g, b),
+            _ => Color::Reset,
+        };
+    }
+    if let Some(index) = s.strip_prefix("ansi:") {
+        return index.trim().parse().map_or(Color::Reset, Color::Indexed);
+    }
+    match s.replace(['_', '-', ' '], "").as_str() {
+        "black" => Color::Black,
+        "white" => Color::White,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "blue" => Color::Blue,
+        "yellow" => Color::Yellow,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        _ => Color::Reset,
+    }
+}
+
+/// The colors a theme starts from before `[colors]` overrides them.
+struct Palette {
+    accent: Color,
+    error: Color,
+    background: Color,
+    /// `None` follows the accent.
+    staged: Option<Color>,
+    unstaged: Color,
+    untracked: Color,
+    /// `None` follows the error color.
+    conflicted: Option<Color>,
+    selection: Color,
+    border: Color,
+    keyword: Color,
+    string: Color,
+    comment: Color,
+    number: Color,
+}
+
+const fn hex(rgb: u32) -> Color {
+    Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+}
+
+const DEFAULT_PALETTE: Palette = Palette {
+    accent: Color::Cyan,
+    error: Color::Red,
+    background: Color::Black,
+    staged: None,
+    unstaged: Color::Reset,
+    untracked: Color::Reset,
+    conflicted: None,
+    selection: Color::Reset,
+    border: Color::Reset,
+    keyword: Color::Magenta,
+    string: Color::Yellow,
+    comment: Color::DarkGray,
+    number: Color::Cyan,
+};
+
+/// Built-in palettes for `theme = "<name>"`.
+const PRESETS: [(&str, Palette); 5] = [
+    (
+        "gruvbox",
+        Palette {
+            accent: hex(0x8ec07c),
+            error: hex(0xfb4934),
+            background: hex(0x282828),
+            staged: Some(hex(0xb8bb26)),
+            unstaged: hex(0xfe8019),
+            untracked: hex(0x928374),
+            conflicted: Some(hex(0xfb4934)),
+            selection: hex(0x3c3836),
+            border: hex(0x504945),
+            keyword: hex(0xfb4934),
+            string: hex(0xb8bb26),
+            comment: hex(0x928374),
+            number: hex(0xd3869b),
+        },
+    ),
+    (
+        "catppuccin",
+        Palette {
+            accent: hex(0xcba6f7),
+            error: hex(0xf38ba8),
+            background: hex(0x1e1e2e),
+            staged: Some(hex(0xa6e3a1)),
+            unstaged: hex(0xfab387),
+            untracked: hex(0x6c7086),
+            conflicted: Some(hex(0xf38ba8)),
+            selection: hex(0x313244),
+            border: hex(0x45475a),
+            keyword: hex(0xcba6f7),
+            string: hex(0xa6e3a1),
+            comment: hex(0x6c7086),
+            number: hex(0xfab387),
+        },
+    ),
+    (
+        "solarized-dark",
+        Palette {
+            accent: hex(0x268bd2),
+            error: hex(0xdc322f),
+            background: hex(0x002b36),
+            staged: Some(hex(0x859900)),
+            unstaged: hex(0xcb4b16),
+            untracked: hex(0x586e75),
+            conflicted: Some(hex(0xdc322f)),
+            selection: hex(0x073642),
+            border: hex(0x586e75),
+            keyword: hex(0x859900),
+            string: hex(0x2aa198),
+            comment: hex(0x586e75),
+            number: hex(0xd33682),
+        },
+    ),
+    (
+        "solarized-light",
+        Palette {
+            accent: hex(0x268bd2),
+            error: hex(0xdc322f),
+            background: hex(0xfdf6e3),
+            staged: Some(hex(0x859900)),
+            unstaged: hex(0xcb4b16),
+            untracked: hex(0x93a1a1),
+            conflicted: Some(hex(0xdc322f)),
+            selection: hex(0xeee8d5),
+            border: hex(0x93a1a1),
+            keyword: hex(0x859900),
+            string: hex(0x2aa198),
+            comment: hex(0x93a1a1),
+            number: hex(0xd33682),
+        },
+    ),
+    (
+        "nord",
+        Palette {
+            accent: hex(0x88c0d0),
+            error: hex(0xbf616a),
+            background: hex(0x2e3440),
+            staged: Some(hex(0xa3be8c)),
+            unstaged: hex(0xd08770),
+            untracked: hex(0x616e88),
+            conflicted: Some(hex(0xbf616a)),
+            selection: hex(0x3b4252),
+            border: hex(0x4c566a),
+            keyword: hex(0x81a1c1),
+            string: hex(0xa3be8c),
+            comment: hex(0x616e88),
+            number: hex(0xb48ead),
+        },
+    ),
+];
+
+/// Names `theme` accepts.
+pub fn preset_names() -> impl Iterator<Item = &'static str> {
+    PRESETS.iter().map(|(name, _)| *name)
+}
+
+fn preset(name: &str) -> Option<&'static Palette> {
+    PRESETS
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, palette)| palette)
+}
+
+/// Whether `theme = "<name>"` names a built-in preset.
+pub fn is_preset(name: &str) -> bool {
+    preset(name).is_some()
+}
+
+impl Theme {
+    /// The preset `theme` names (the default colors when it is unset or unknown), with
+    /// the entries of `[colors]` on top.
+    pub fn from_config(theme: Option<&str>, cfg: &ColorConfig) -> Self {
+        let palette = theme.and_then(preset).unwrap_or(&DEFAULT_PALETTE);
+        let support = ColorSupport::detect(cfg.mode.as_deref());
+        let color = |value: &Option<String>, default| {
+            support.convert(value.as_deref().map(parse_color).unwrap_or(default))
+        };
+        let accent = color(&cfg.accent, palette.accent);
+        let error = color(&cfg.error, palette.error);
         Theme {
-            accent: cfg
-                .accent
-                .as_deref()
-                .map(parse_color)
-                .unwrap_or(Color::Cyan),
-            error: cfg.error.as_deref().map(parse_color).unwrap_or(Color::Red),
-            background: cfg
-                .background
-                .as_deref()
-                .map(parse_color)
-                .unwrap_or(Color::Black),
+            accent,
+            error,
+            background: color(&cfg.background, palette.background),
+            staged: color(&cfg.staged, palette.staged.unwrap_or(accent)),
+            unstaged: color(&cfg.unstaged, palette.unstaged),
+            untracked: color(&cfg.untracked, palette.untracked),
+            conflicted: color(&cfg.conflicted, palette.conflicted.unwrap_or(error)),
+            selection: color(&cfg.selection, palette.selection),
+            border: color(&cfg.border, palette.border),
+            support,
+            syntax: cfg.syntax.unwrap_or(true).then(|| SyntaxColors {
+                keyword: color(&cfg.keyword, palette.keyword),
+                string: color(&cfg.string, palette.string),
+                comment: color(&cfg.comment, palette.comment),
+                number: color(&cfg.number, palette.number),
+            }),
+        }
+    }
+
+    /// Border of a pane, in the accent while it has the focus.
+    pub fn border(&self, focused: bool) -> Style {
+        Style::default().fg(if focused { self.accent } else { self.border })
+    }
+
+    /// `style` for the selected row of a list.
+    pub fn selected(&self, style: Style) -> Style {
+        let style = style.add_modifier(Modifier::BOLD);
+        if self.selection == Color::Reset {
+            style
+        } else {
+            style.bg(self.selection)
         }
     }
 }
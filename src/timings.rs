@@ -0,0 +1,128 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::app_dir;
+
+/// Durations of one command line across its recorded runs.
+pub struct TimingStats {
+    pub command: String,
+    pub runs: usize,
+    pub average: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub max: Duration,
+    pub last: Duration,
+    /// Transfer summary of the most recent run that moved data.
+    pub last_transfer: Option<String>,
+}
+
+/// Tab-separated run log: unix time, milliseconds, exit status, repository, command and,
+/// for network commands, a transfer summary.
+fn timings_path() -> Option<PathBuf> {
+    Some(app_dir()?.join("timings.tsv"))
+}
+
+pub fn record(
+    repo: &Path,
+    command: &str,
+    elapsed: Duration,
+    success: bool,
+    transfer: Option<&str>,
+) -> std::io::Result<()> {
+    let Some(path) = timings_path() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let transfer = transfer
+        .map(|t| format!("\t{}", t.replace(['\t', '\n'], " ")))
+        .unwrap_or_default();
+    writeln!(
+        file,
+        "{}\t{}\t{}\t{}\t{}{}",
+        now,
+        elapsed.as_millis(),
+        if success { "ok" } else { "failed" },
+        repo.to_string_lossy().replace('\t', " "),
+        command.replace(['\t', '\n'], " "),
+        transfer
+    )
+}
+
+/// Per-command statistics for `repo` (every repository when `None`), slowest average first.
+pub fn load_stats(repo: Option<&Path>) -> Vec<TimingStats> {
+    let Some(raw) = timings_path().and_then(|p| fs::read_to_string(p).ok()) else {
+        return Vec::new();
+    };
+    let repo = repo.map(|r| r.to_string_lossy().into_owned());
+
+    // Runs keep file order, so the last entry of each group is the most recent.
+    let mut groups: Vec<(String, Vec<Duration>, Option<String>)> = Vec::new();
+    for line in raw.lines() {
+        let fields: Vec<&str> = line.splitn(6, '\t').collect();
+        let [_, millis, _, run_repo, command] = fields[..fields.len().min(5)] else {
+            continue;
+        };
+        let transfer = fields.get(5).map(|t| t.to_string());
+        if repo.as_deref().is_some_and(|r| r != run_repo) {
+            continue;
+        }
+        let Ok(millis) = millis.parse() else {
+            continue;
+        };
+        let elapsed = Duration::from_millis(millis);
+        match groups.iter_mut().find(|(c, _, _)| c == command) {
+            Some((_, runs, last_transfer)) => {
+                runs.push(elapsed);
+                if transfer.is_some() {
+                    *last_transfer = transfer;
+                }
+            }
+            None => groups.push((command.to_string(), vec![elapsed], transfer)),
+        }
+    }
+
+    let mut stats: Vec<TimingStats> = groups
+        .into_iter()
+        .map(|(command, runs, last_transfer)| {
+            let last = *runs.last().unwrap_or(&Duration::ZERO);
+            let total: Duration = runs.iter().sum();
+            let mut sorted = runs;
+            sorted.sort();
+            TimingStats {
+                command,
+                runs: sorted.len(),
+                average: total / sorted.len() as u32,
+                p50: percentile(&sorted, 50),
+                p90: percentile(&sorted, 90),
+                max: *sorted.last().unwrap_or(&Duration::ZERO),
+                last,
+                last_transfer,
+            }
+        })
+        .collect();
+    stats.sort_by_key(|s| std::cmp::Reverse(s.average));
+    stats
+}
+
+/// Nearest-rank percentile of an ascending, non-empty slice.
+pub fn percentile(sorted: &[Duration], pct: usize) -> Duration {
+    let rank = (sorted.len() * pct).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+pub fn format_duration(d: Duration) -> String {
+    if d < Duration::from_secs(1) {
+        format!("{}ms", d.as_millis())
+    } else {
+        format!("{:.2}s", d.as_secs_f64())
+    }
+}
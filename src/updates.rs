@@ -0,0 +1,135 @@
+use std::process::Command;
+
+use serde::Deserialize;
+
+use crate::shell::hide_console;
+
+/// Published releases, most recently created first, so a backport release can come
+/// before a newer version. Drafts and pre-releases are left out.
+const RELEASES_URL: &str =
+    "https://api.github.com/repos/siroio/simple-git-tui/releases?per_page=30";
+
+/// A release as the GitHub releases API describes it; other fields are ignored.
+#[derive(Deserialize)]
+pub struct Release {
+    #[serde(rename = "tag_name")]
+    pub tag: String,
+    #[serde(default, deserialize_with = "or_empty")]
+    pub name: String,
+    #[serde(rename = "html_url", default)]
+    pub url: String,
+    /// The changelog, as written on the release page.
+    #[serde(default, deserialize_with = "or_empty")]
+    pub body: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+}
+
+/// `name` and `body` are `null` on releases made without them.
+fn or_empty<'de, D: serde::Deserializer<'de>>(de: D) -> Result<String, D::Error> {
+    Ok(Option::<String>::deserialize(de)?.unwrap_or_default())
+}
+
+/// Asks the GitHub releases API which releases exist, through the `curl` at `curl`:
+/// nothing is downloaded but the list.
+pub fn fetch_releases(curl: &str) -> Result<Vec<Release>, String> {
+    let mut cmd = Command::new(curl);
+    hide_console(&mut cmd).args([
+        "--silent",
+        "--show-error",
+        "--fail",
+        "--location",
+        "--max-time",
+        "15",
+        "--header",
+        "Accept: application/vnd.github+json",
+        "--user-agent",
+        concat!("simple-git-tui/", env!("CARGO_PKG_VERSION")),
+        RELEASES_URL,
+    ]);
+    let output = cmd
+        .output()
+        .map_err(|e| format!("cannot run {}: {}", curl, e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    parse_releases(&output.stdout)
+}
+
+fn parse_releases(json: &[u8]) -> Result<Vec<Release>, String> {
+    let releases: Vec<Release> = serde_json::from_slice(json)
+        .map_err(|e| format!("unexpected reply from the releases API: {}", e))?;
+    Ok(releases
+        .into_iter()
+        .filter(|r| !r.draft && !r.prerelease)
+        .collect())
+}
+
+/// The releases newer than this build, highest version first.
+pub fn newer_releases(releases: Vec<Release>) -> Vec<Release> {
+    let current = version_key(env!("CARGO_PKG_VERSION"));
+    let mut newer: Vec<Release> = releases
+        .into_iter()
+        .filter(|r| version_key(&r.tag) > current)
+        .collect();
+    newer.sort_by_key(|r| std::cmp::Reverse(version_key(&r.tag)));
+    newer
+}
+
+/// `v1.10.2` as `[1, 10, 2]`, so versions compare numerically.
+fn version_key(tag: &str) -> Vec<u64> {
+    tag.trim_start_matches(['v', 'V'])
+        .split(['.', '-', '+'])
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_published_releases() {
+        let json = r#"[
+            {"tag_name": "v0.3.0-rc1", "name": "RC", "html_url": "u3", "body": "", "draft": false, "prerelease": true, "id": 3},
+            {"tag_name": "v0.2.0", "name": null, "html_url": "https://x/v0.2.0", "body": "* été \ud83c\udf89\n* fix", "draft": false, "prerelease": false, "assets": [{"size": 1.5e3}]},
+            {"tag_name": "v0.1.9", "name": "Draft", "html_url": "u1", "body": null, "draft": true, "prerelease": false}
+        ]"#;
+        let releases = parse_releases(json.as_bytes()).unwrap();
+        assert_eq!(releases.len(), 1);
+        let release = &releases[0];
+        assert_eq!(release.tag, "v0.2.0");
+        assert_eq!(release.name, "");
+        assert_eq!(release.url, "https://x/v0.2.0");
+        assert_eq!(release.body, "* été 🎉\n* fix");
+    }
+
+    #[test]
+    fn rejects_other_replies() {
+        assert!(parse_releases(br#"{"message": "API rate limit exceeded"}"#).is_err());
+        assert!(parse_releases(b"<html>").is_err());
+        assert!(parse_releases(&[b'['; 100_000]).is_err());
+    }
+
+    #[test]
+    fn lists_newer_releases_by_version() {
+        let json = r#"[
+            {"tag_name": "v900.1.5", "html_url": "backport"},
+            {"tag_name": "v900.10.0", "html_url": "latest"},
+            {"tag_name": "v0.0.1", "html_url": "old"},
+            {"tag_name": "v900.2.0", "html_url": "middle"}
+        ]"#;
+        let releases = newer_releases(parse_releases(json.as_bytes()).unwrap());
+        let tags: Vec<&str> = releases.iter().map(|r| r.tag.as_str()).collect();
+        assert_eq!(tags, ["v900.10.0", "v900.2.0", "v900.1.5"]);
+    }
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert!(version_key("v0.10.0") > version_key("0.9.9"));
+        assert!(version_key("V1.2.0-beta") == version_key("1.2.0"));
+        assert!(version_key("nightly").is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+use crossterm::event::KeyCode;
+use simple_git_tui::app::harness::{Harness, TempRepo};
+use simple_git_tui::config::default_config;
+
+fn modified_repo() -> TempRepo {
+    let repo = TempRepo::new();
+    repo.commit("a.txt", "one\n", "initial");
+    repo.write("a.txt", "two\n");
+    repo
+}
+
+#[test]
+fn stage_all_stages_worktree_changes() {
+    let repo = modified_repo();
+    let mut app = Harness::new(default_config(), repo.path());
+    assert!(app.screen().contains("[ M] a.txt"));
+
+    app.cmdline("stage-all");
+
+    assert_eq!(repo.git(&["diff", "--cached", "--name-only"]), "a.txt\n");
+    assert!(app.screen().contains("[M ] a.txt"));
+}
+
+#[test]
+fn discard_asks_before_restoring() {
+    let repo = modified_repo();
+    let mut app = Harness::new(default_config(), repo.path());
+
+    app.keys("lx");
+    assert!(app.screen().contains("Discard changes to \"a.txt\"?"));
+    assert_eq!(repo.read("a.txt"), "two\n");
+
+    app.press(KeyCode::Char('x'));
+    assert_eq!(repo.read("a.txt"), "one\n");
+}
+
+/// `main` and `topic` both changed `a.txt`; merging `topic` leaves it conflicted.
+fn conflicted_repo() -> TempRepo {
+    let repo = TempRepo::new();
+    repo.commit("a.txt", "base\n", "initial");
+    repo.git(&["switch", "-q", "-c", "topic"]);
+    repo.commit("a.txt", "theirs\n", "topic change");
+    repo.git(&["switch", "-q", "main"]);
+    repo.commit("a.txt", "ours\n", "main change");
+    let merge = std::process::Command::new("git")
+        .args(["merge", "-q", "topic"])
+        .current_dir(repo.path())
+        .output()
+        .unwrap();
+    assert!(!merge.status.success(), "merge should conflict");
+    repo
+}
+
+#[test]
+fn conflicts_take_a_side_and_resolve() {
+    let repo = conflicted_repo();
+    let mut app = Harness::new(default_config(), repo.path());
+    assert!(app.screen().contains("[UU] a.txt"));
+    assert_eq!(app.view_model().conflict_count(), 1);
+
+    app.keys("lt");
+    assert_eq!(repo.read("a.txt"), "theirs\n");
+    assert_eq!(app.view_model().conflict_count(), 1);
+
+    app.keys("s");
+    assert_eq!(app.view_model().conflict_count(), 0);
+    assert_eq!(repo.git(&["diff", "--cached", "--name-only"]), "a.txt\n");
+    assert!(app.screen().contains("[M ] a.txt"));
+}
+
+#[cfg(unix)]
+#[test]
+fn commands_go_through_git_path() {
+    use simple_git_tui::app::harness::FakeGit;
+
+    let repo = modified_repo();
+    let git = FakeGit::new().unwrap();
+    git.respond("log", "scripted history\n", 0).unwrap();
+    let mut config = default_config();
+    config.git_path = git.path();
+    let mut app = Harness::new(config, repo.path());
+
+    app.cmdline("log --oneline");
+
+    assert!(app.screen().contains("scripted history"));
+    assert!(git.calls().iter().any(|call| call == "log --oneline"));
+}
+
+#[test]
+fn status_bar_follows_upstream_changes() {
+    let repo = TempRepo::new();
+    repo.commit("a.txt", "one\n", "initial");
+    repo.git(&["switch", "-q", "-c", "topic"]);
+    repo.commit("a.txt", "two\n", "topic change");
+    let mut app = Harness::new(default_config(), repo.path());
+    assert!(app.screen().contains("[topic] "));
+
+    // Only the config changes; no ref the status bar knew about moves.
+    app.cmdline("branch -u main");
+    assert!(app.screen().contains("[topic...main ↑1 ↓0]"));
+
+    app.cmdline("branch --unset-upstream");
+    assert!(app.screen().contains("[topic] "));
+}
+
+#[test]
+fn closing_a_review_with_changes_asks_first() {
+    let repo = TempRepo::new();
+    repo.commit("a.txt", "one\n", "initial");
+    repo.git(&["branch", "review-me"]);
+    let mut app = Harness::new(default_config(), repo.path());
+
+    app.cmdline("review review-me");
+    let path = app.view_model().review().expect("review tab").path.clone();
+    std::fs::write(path.join("a.txt"), "edited\n").unwrap();
+
+    app.keys("q");
+    assert!(app.screen().contains("Close the review of review-me?"));
+    app.press(KeyCode::Esc);
+    assert!(app.view_model().review().is_some());
+    assert!(path.exists());
+
+    app.keys("q").keys("y");
+    assert!(app.view_model().review().is_none());
+    assert!(!path.exists());
+}